@@ -0,0 +1,151 @@
+//! # Diagnostics
+//!
+//! Problems the engine worked around instead of failing: a font that
+//! couldn't be decoded, an image drawn as a placeholder, SVG elements that
+//! were skipped, a link with no target, content clipped at the page edge.
+//!
+//! [`render_with_diagnostics`](crate::render_with_diagnostics) returns them
+//! alongside the PDF. In strict mode any diagnostic fails the render with
+//! [`FormeError::Diagnostics`](crate::FormeError::Diagnostics) instead.
+
+use std::fmt;
+
+use serde::Serialize;
+
+use crate::model::{Node, SourceLocation};
+
+/// How much of the document a diagnostic affects.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    /// The content was rendered, but not as written.
+    Warning,
+    /// The content couldn't be rendered and was replaced or left out.
+    Error,
+}
+
+/// What went wrong. Stable across releases, for filtering and alerting.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum DiagnosticCode {
+    /// A font's `src` isn't valid inline data and couldn't be resolved.
+    FontDecodeFailed,
+    /// A font's bytes aren't a font the engine can read.
+    FontParseFailed,
+    /// An image couldn't be loaded or resolved and was drawn as a placeholder.
+    ImageLoadFailed,
+    /// An SVG contains elements the engine doesn't draw.
+    UnsupportedSvgElement,
+    /// A `#name` link matches no node id or bookmark.
+    MissingLinkTarget,
+    /// A `{{pageRef:id}}` matches no node id.
+    MissingPageRefTarget,
+    /// Page references or table of contents page numbers were still moving
+    /// after the last layout pass, so some may be out of date.
+    UnsettledPageRefs,
+    /// Content that can't break is taller than a page and was clipped.
+    ContentOverflow,
+    /// A table cell's number is written with a different decimal mark from
+    /// the rest of its column, so it was left out of the footer sums.
+    InconsistentNumberFormat,
+}
+
+impl DiagnosticCode {
+    /// The severity diagnostics with this code are reported at.
+    pub fn severity(self) -> Severity {
+        match self {
+            DiagnosticCode::FontDecodeFailed
+            | DiagnosticCode::FontParseFailed
+            | DiagnosticCode::ImageLoadFailed => Severity::Error,
+            DiagnosticCode::UnsupportedSvgElement
+            | DiagnosticCode::MissingLinkTarget
+            | DiagnosticCode::MissingPageRefTarget
+            | DiagnosticCode::UnsettledPageRefs
+            | DiagnosticCode::ContentOverflow
+            | DiagnosticCode::InconsistentNumberFormat => Severity::Warning,
+        }
+    }
+}
+
+/// One problem found while rendering a document.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub code: DiagnosticCode,
+    pub message: String,
+    /// The `id` of the offending node, when it has one.
+    pub node_id: Option<String>,
+    /// Where the offending node was written, when known.
+    pub source_location: Option<SourceLocation>,
+}
+
+impl Diagnostic {
+    /// A diagnostic that isn't tied to a node, such as a bad font entry.
+    pub fn new(code: DiagnosticCode, message: impl Into<String>) -> Self {
+        Self {
+            severity: code.severity(),
+            code,
+            message: message.into(),
+            node_id: None,
+            source_location: None,
+        }
+    }
+
+    /// A diagnostic about `node`, carrying its id and source location.
+    pub fn for_node(code: DiagnosticCode, message: impl Into<String>, node: &Node) -> Self {
+        Self {
+            node_id: node.id.clone(),
+            source_location: node.source_location.clone(),
+            ..Self::new(code, message)
+        }
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let severity = match self.severity {
+            Severity::Warning => "warning",
+            Severity::Error => "error",
+        };
+        write!(f, "{} [{:?}]: {}", severity, self.code, self.message)?;
+        if let Some(id) = &self.node_id {
+            write!(f, " (node \"{}\")", id)?;
+        }
+        if let Some(loc) = &self.source_location {
+            write!(f, " at {}:{}:{}", loc.file, loc.line, loc.column)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::style::Style;
+
+    #[test]
+    fn test_for_node_carries_id_and_location() {
+        let mut node = Node::view(Style::default(), vec![]);
+        node.id = Some("logo".to_string());
+        node.source_location = Some(SourceLocation {
+            file: "Invoice.tsx".to_string(),
+            line: 12,
+            column: 4,
+        });
+        let d = Diagnostic::for_node(DiagnosticCode::ImageLoadFailed, "bad image", &node);
+        assert_eq!(d.severity, Severity::Error);
+        assert_eq!(
+            d.to_string(),
+            "error [ImageLoadFailed]: bad image (node \"logo\") at Invoice.tsx:12:4"
+        );
+    }
+
+    #[test]
+    fn test_serializes_camel_case() {
+        let d = Diagnostic::new(DiagnosticCode::MissingLinkTarget, "no target");
+        let json = serde_json::to_value(&d).unwrap();
+        assert_eq!(json["severity"], "warning");
+        assert_eq!(json["code"], "MissingLinkTarget");
+        assert!(json["nodeId"].is_null());
+    }
+}
@@ -1,10 +1,13 @@
 //! Structured error types for the Forme rendering engine.
 //!
-//! Three variants cover the real error sources: JSON parsing, font loading,
-//! and layout/PDF generation failures.
+//! The variants cover the real error sources: JSON parsing, font loading,
+//! layout/PDF generation and template failures, plus diagnostics that a
+//! strict render refuses to work around.
 
 use std::fmt;
 
+use crate::diagnostics::Diagnostic;
+
 /// The unified error type returned by all public Forme API functions.
 #[derive(Debug)]
 pub enum FormeError {
@@ -19,6 +22,8 @@ pub enum FormeError {
     RenderError(String),
     /// Template expression evaluation failed.
     TemplateError(String),
+    /// A strict render found problems it would otherwise have worked around.
+    Diagnostics(Vec<Diagnostic>),
 }
 
 impl fmt::Display for FormeError {
@@ -34,6 +39,17 @@ impl fmt::Display for FormeError {
             FormeError::FontError(msg) => write!(f, "Font error: {}", msg),
             FormeError::RenderError(msg) => write!(f, "Render error: {}", msg),
             FormeError::TemplateError(msg) => write!(f, "Template error: {}", msg),
+            FormeError::Diagnostics(diagnostics) => {
+                write!(
+                    f,
+                    "Strict render failed with {} problem(s):",
+                    diagnostics.len()
+                )?;
+                for d in diagnostics {
+                    write!(f, "\n  {}", d)?;
+                }
+                Ok(())
+            }
         }
     }
 }
@@ -0,0 +1,997 @@
+//! # CFF Outlines
+//!
+//! OpenType fonts with PostScript outlines (`.otf`) keep their glyphs in a
+//! `CFF ` table of Type 2 charstrings instead of `glyf`. This module builds
+//! the `CFF ` table of a subset font, for [`subset_font`](super::subset::subset_font)
+//! to wrap in an OpenType file.
+//!
+//! [`subset_cff`] copies the charstrings of the used glyphs, in their new
+//! order, and rebuilds the charset (and the FDSelect of a CID-keyed font)
+//! to match. A CID-keyed subset gets an identity charset, each glyph's CID
+//! being its new glyph ID, since that's the CID the content stream shows. Subroutines are copied whole: charstrings call them by index,
+//! so dropping unused ones would mean rewriting every call.
+//!
+//! [`cff_from_outlines`] instead draws each glyph and writes it as a new
+//! charstring. It serves `CFF2` fonts and instances of variable fonts, whose
+//! charstrings only make sense together with the variation data, and any
+//! `CFF ` table [`subset_cff`] can't read.
+
+use std::collections::BTreeSet;
+
+use ttf_parser::{GlyphId, OutlineBuilder};
+
+// Top DICT operators (two-byte operators are 1200 + the second byte)
+const CHARSET: u16 = 15;
+const ENCODING: u16 = 16;
+const CHAR_STRINGS: u16 = 17;
+const PRIVATE: u16 = 18;
+const FONT_MATRIX: u16 = 1207;
+const ROS: u16 = 1230;
+const FD_ARRAY: u16 = 1236;
+const FD_SELECT: u16 = 1237;
+// Private DICT operators
+const SUBRS: u16 = 19;
+const DEFAULT_WIDTH_X: u16 = 20;
+const NOMINAL_WIDTH_X: u16 = 21;
+
+/// The first string ID after the 391 standard strings.
+const FIRST_CUSTOM_SID: u16 = 391;
+
+/// Subset a `CFF ` table to `needed_gids`, which must include glyph 0.
+/// Glyphs are renumbered in ascending order of their old IDs, as
+/// [`subset_ttf`](super::subset::subset_ttf) does.
+pub fn subset_cff(cff: &[u8], needed_gids: &BTreeSet<u16>) -> Result<Vec<u8>, String> {
+    let header_size = *cff.get(2).ok_or("CFF table is truncated")? as usize;
+    let names = read_index(cff, header_size)?;
+    let top_dicts = read_index(cff, names.end)?;
+    let strings = read_index(cff, top_dicts.end)?;
+    let global_subrs = read_index(cff, strings.end)?;
+    let mut top = parse_dict(top_dicts.items.first().ok_or("CFF has no Top DICT")?)?;
+
+    let char_strings = read_index(cff, offset_operand(&top, CHAR_STRINGS)?)?;
+    let num_glyphs = char_strings.items.len();
+    let charset = read_charset(cff, dict_int(&top, CHARSET).unwrap_or(0), num_glyphs)?;
+    let gids: Vec<usize> = needed_gids
+        .iter()
+        .map(|&gid| gid as usize)
+        .filter(|&gid| gid < num_glyphs)
+        .collect();
+
+    let is_cid = top.iter().any(|(op, _)| *op == ROS);
+    let new_char_strings = write_index(gids.iter().map(|&gid| char_strings.items[gid]));
+    let mut new_charset = vec![0u8];
+    if is_cid && gids.len() > 1 {
+        // The content stream shows each glyph by its new ID as its CID, and
+        // a CIDFontType0 has no CIDToGIDMap: give glyph i CID i, as a single
+        // format 2 range
+        new_charset = vec![2u8];
+        new_charset.extend_from_slice(&1u16.to_be_bytes());
+        new_charset.extend_from_slice(&(gids.len() as u16 - 2).to_be_bytes());
+    } else if !is_cid {
+        for &gid in gids.iter().skip(1) {
+            new_charset.extend_from_slice(&charset[gid].to_be_bytes());
+        }
+    }
+
+    // The encoding maps character codes, which a CIDFont doesn't use
+    top.retain(|(op, _)| *op != ENCODING);
+    set_offset(&mut top, CHARSET, 0);
+    set_offset(&mut top, CHAR_STRINGS, 0);
+
+    let mut new_fd_select = Vec::new();
+    let mut font_dicts = Vec::new();
+    let mut privates = Vec::new();
+    if is_cid {
+        let fd_select = read_fd_select(cff, offset_operand(&top, FD_SELECT)?, num_glyphs)?;
+        new_fd_select.push(0); // format 0: one FD index per glyph
+        new_fd_select.extend(gids.iter().map(|&gid| fd_select[gid]));
+        for item in read_index(cff, offset_operand(&top, FD_ARRAY)?)?.items {
+            let mut font_dict = parse_dict(item)?;
+            privates.push(read_private(cff, &font_dict)?);
+            set_private(&mut font_dict, 0, 0);
+            font_dicts.push(font_dict);
+        }
+        set_offset(&mut top, FD_SELECT, 0);
+        set_offset(&mut top, FD_ARRAY, 0);
+    } else {
+        privates.push(read_private(cff, &top)?);
+        set_private(&mut top, 0, 0);
+    }
+
+    // Offset operands are written at a fixed size, so every length can be
+    // measured before the offsets are known
+    let top_index_len = write_index([encode_dict(&top).as_slice()]).len();
+    let mut pos = header_size
+        + (names.end - names.start)
+        + top_index_len
+        + (strings.end - strings.start)
+        + (global_subrs.end - global_subrs.start);
+    set_offset(&mut top, CHARSET, pos);
+    pos += new_charset.len();
+    if is_cid {
+        set_offset(&mut top, FD_SELECT, pos);
+        pos += new_fd_select.len();
+    }
+    set_offset(&mut top, CHAR_STRINGS, pos);
+    pos += new_char_strings.len();
+
+    let mut tail = Vec::new();
+    if is_cid {
+        let fd_array_len = write_index(
+            font_dicts
+                .iter()
+                .map(encode_dict)
+                .collect::<Vec<_>>()
+                .iter(),
+        )
+        .len();
+        set_offset(&mut top, FD_ARRAY, pos);
+        let mut private_pos = pos + fd_array_len;
+        let mut private_data = Vec::new();
+        for (font_dict, private) in font_dicts.iter_mut().zip(&privates) {
+            let encoded = private.encode();
+            set_private(font_dict, private.dict_len(), private_pos);
+            private_pos += encoded.len();
+            private_data.extend_from_slice(&encoded);
+        }
+        tail.extend(write_index(
+            font_dicts
+                .iter()
+                .map(encode_dict)
+                .collect::<Vec<_>>()
+                .iter(),
+        ));
+        tail.extend(private_data);
+    } else {
+        set_private(&mut top, privates[0].dict_len(), pos);
+        tail.extend(privates[0].encode());
+    }
+
+    let mut out = cff[..header_size].to_vec();
+    out.extend_from_slice(&cff[names.start..names.end]);
+    out.extend(write_index([encode_dict(&top).as_slice()]));
+    out.extend_from_slice(&cff[strings.start..global_subrs.end]);
+    out.extend(new_charset);
+    out.extend(new_fd_select);
+    out.extend(new_char_strings);
+    out.extend(tail);
+    Ok(out)
+}
+
+/// Build a name-keyed `CFF ` table holding `needed_gids` (including glyph
+/// 0) drawn from `face` at its current variation coordinates. Glyphs are
+/// renumbered in ascending order and named `g1`, `g2`, and so on.
+pub fn cff_from_outlines(
+    face: &ttf_parser::Face,
+    needed_gids: &BTreeSet<u16>,
+    name: &str,
+) -> Vec<u8> {
+    let char_strings: Vec<Vec<u8>> = needed_gids
+        .iter()
+        .map(|&gid| {
+            let mut builder =
+                CharStringBuilder::new(face.glyph_hor_advance(GlyphId(gid)).unwrap_or(0));
+            face.outline_glyph(GlyphId(gid), &mut builder);
+            builder.finish()
+        })
+        .collect();
+    let glyph_names: Vec<String> = (1..char_strings.len()).map(|i| format!("g{}", i)).collect();
+
+    let mut charset = vec![0u8];
+    for i in 0..glyph_names.len() {
+        charset.extend_from_slice(&(FIRST_CUSTOM_SID + i as u16).to_be_bytes());
+    }
+    let private = Private {
+        dict: vec![
+            (DEFAULT_WIDTH_X, vec![Operand::Int(0)]),
+            (NOMINAL_WIDTH_X, vec![Operand::Int(0)]),
+        ],
+        subrs: None,
+    };
+
+    let mut top = Vec::new();
+    let units_per_em = face.units_per_em();
+    if units_per_em != 1000 {
+        let scale = Operand::Real(encode_real(1.0 / units_per_em as f64));
+        let zero = Operand::Int(0);
+        top.push((
+            FONT_MATRIX,
+            vec![
+                scale.clone(),
+                zero.clone(),
+                zero.clone(),
+                scale,
+                zero.clone(),
+                zero,
+            ],
+        ));
+    }
+    set_offset(&mut top, CHARSET, 0);
+    set_offset(&mut top, CHAR_STRINGS, 0);
+    set_private(&mut top, 0, 0);
+
+    let header = [1u8, 0, 4, 4];
+    let names = write_index([sanitize_name(name).as_bytes()]);
+    let strings = write_index(glyph_names.iter().map(|n| n.as_bytes()));
+    let global_subrs = write_index(std::iter::empty::<&[u8]>());
+    let char_strings = write_index(char_strings.iter());
+
+    let top_index_len = write_index([encode_dict(&top).as_slice()]).len();
+    let mut pos = header.len() + names.len() + top_index_len + strings.len() + global_subrs.len();
+    set_offset(&mut top, CHARSET, pos);
+    pos += charset.len();
+    set_offset(&mut top, CHAR_STRINGS, pos);
+    pos += char_strings.len();
+    set_private(&mut top, private.dict_len(), pos);
+
+    let mut out = header.to_vec();
+    out.extend(names);
+    out.extend(write_index([encode_dict(&top).as_slice()]));
+    out.extend(strings);
+    out.extend(global_subrs);
+    out.extend(charset);
+    out.extend(char_strings);
+    out.extend(private.encode());
+    out
+}
+
+/// Font names in CFF are PostScript names: printable ASCII without spaces
+/// or delimiters.
+fn sanitize_name(name: &str) -> String {
+    let name: String = name
+        .chars()
+        .filter(|c| c.is_ascii_graphic() && !"[](){}<>/%".contains(*c))
+        .collect();
+    if name.is_empty() {
+        "Subset".to_string()
+    } else {
+        name
+    }
+}
+
+// ─── INDEX ──────────────────────────────────────────────────────
+
+/// An INDEX: a counted list of byte strings, spanning `start..end`.
+struct Index<'a> {
+    items: Vec<&'a [u8]>,
+    start: usize,
+    end: usize,
+}
+
+fn read_index(data: &[u8], start: usize) -> Result<Index<'_>, String> {
+    let truncated = || "CFF INDEX is truncated".to_string();
+    let count = read_uint(data, start, 2).ok_or_else(truncated)?;
+    if count == 0 {
+        return Ok(Index {
+            items: Vec::new(),
+            start,
+            end: start + 2,
+        });
+    }
+    let off_size = *data.get(start + 2).ok_or_else(truncated)? as usize;
+    if !(1..=4).contains(&off_size) {
+        return Err(format!("Invalid CFF INDEX offset size {}", off_size));
+    }
+    let offsets_start = start + 3;
+    // Offsets are 1-based, from the byte before the object data
+    let data_start = offsets_start + (count + 1) * off_size - 1;
+    let mut offsets = Vec::with_capacity(count + 1);
+    for i in 0..=count {
+        let offset =
+            read_uint(data, offsets_start + i * off_size, off_size).ok_or_else(truncated)?;
+        offsets.push(data_start + offset);
+    }
+    let end = *offsets.last().unwrap_or(&data_start);
+    if end > data.len() || offsets.windows(2).any(|w| w[0] > w[1]) {
+        return Err(truncated());
+    }
+    Ok(Index {
+        items: offsets.windows(2).map(|w| &data[w[0]..w[1]]).collect(),
+        start,
+        end,
+    })
+}
+
+fn write_index<T: AsRef<[u8]>>(items: impl IntoIterator<Item = T>) -> Vec<u8> {
+    let items: Vec<T> = items.into_iter().collect();
+    let mut out = (items.len() as u16).to_be_bytes().to_vec();
+    if items.is_empty() {
+        return out;
+    }
+    let total: usize = items.iter().map(|item| item.as_ref().len()).sum();
+    let off_size = match total + 1 {
+        0..=0xFF => 1,
+        0x100..=0xFFFF => 2,
+        0x10000..=0xFF_FFFF => 3,
+        _ => 4,
+    };
+    out.push(off_size as u8);
+    let mut offset = 1usize;
+    for item in items.iter().map(|item| item.as_ref().len()).chain([0]) {
+        out.extend_from_slice(&(offset as u32).to_be_bytes()[4 - off_size..]);
+        offset += item;
+    }
+    for item in &items {
+        out.extend_from_slice(item.as_ref());
+    }
+    out
+}
+
+fn read_uint(data: &[u8], pos: usize, size: usize) -> Option<usize> {
+    let bytes = data.get(pos..pos + size)?;
+    Some(bytes.iter().fold(0, |acc, &b| (acc << 8) | b as usize))
+}
+
+// ─── DICT ───────────────────────────────────────────────────────
+
+#[derive(Debug, Clone, PartialEq)]
+enum Operand {
+    Int(i32),
+    /// An offset, always written as a 5-byte integer so that a DICT's
+    /// length doesn't depend on where things end up.
+    Offset(usize),
+    /// A real number, kept in its packed BCD form.
+    Real(Vec<u8>),
+}
+
+type Dict = Vec<(u16, Vec<Operand>)>;
+
+fn parse_dict(data: &[u8]) -> Result<Dict, String> {
+    let truncated = || "CFF DICT is truncated".to_string();
+    let byte = |pos: usize| data.get(pos).copied().ok_or_else(truncated);
+    let mut dict = Vec::new();
+    let mut operands = Vec::new();
+    let mut pos = 0;
+    while pos < data.len() {
+        let b0 = data[pos];
+        match b0 {
+            0..=11 | 13..=21 => {
+                dict.push((b0 as u16, std::mem::take(&mut operands)));
+                pos += 1;
+            }
+            12 => {
+                dict.push((1200 + byte(pos + 1)? as u16, std::mem::take(&mut operands)));
+                pos += 2;
+            }
+            28 => {
+                operands.push(Operand::Int(
+                    i16::from_be_bytes([byte(pos + 1)?, byte(pos + 2)?]) as i32,
+                ));
+                pos += 3;
+            }
+            29 => {
+                let bytes = data.get(pos + 1..pos + 5).ok_or_else(truncated)?;
+                operands.push(Operand::Int(i32::from_be_bytes(bytes.try_into().unwrap())));
+                pos += 5;
+            }
+            30 => {
+                let start = pos + 1;
+                pos = start;
+                // Nibbles end at 0xf, in either half of a byte
+                loop {
+                    let b = byte(pos)?;
+                    pos += 1;
+                    if b & 0x0F == 0x0F || b >> 4 == 0x0F {
+                        break;
+                    }
+                }
+                operands.push(Operand::Real(data[start..pos].to_vec()));
+            }
+            32..=246 => {
+                operands.push(Operand::Int(b0 as i32 - 139));
+                pos += 1;
+            }
+            247..=250 => {
+                operands.push(Operand::Int(
+                    (b0 as i32 - 247) * 256 + byte(pos + 1)? as i32 + 108,
+                ));
+                pos += 2;
+            }
+            251..=254 => {
+                operands.push(Operand::Int(
+                    -(b0 as i32 - 251) * 256 - byte(pos + 1)? as i32 - 108,
+                ));
+                pos += 2;
+            }
+            _ => return Err(format!("Invalid CFF DICT byte {}", b0)),
+        }
+    }
+    Ok(dict)
+}
+
+fn encode_dict(dict: &Dict) -> Vec<u8> {
+    let mut out = Vec::new();
+    for (op, operands) in dict {
+        for operand in operands {
+            match operand {
+                Operand::Int(value) => encode_dict_int(*value, &mut out),
+                Operand::Offset(value) => {
+                    out.push(29);
+                    out.extend_from_slice(&(*value as i32).to_be_bytes());
+                }
+                Operand::Real(bytes) => {
+                    out.push(30);
+                    out.extend_from_slice(bytes);
+                }
+            }
+        }
+        if *op >= 1200 {
+            out.extend_from_slice(&[12, (op - 1200) as u8]);
+        } else {
+            out.push(*op as u8);
+        }
+    }
+    out
+}
+
+fn encode_dict_int(value: i32, out: &mut Vec<u8>) {
+    match value {
+        -107..=107 => out.push((value + 139) as u8),
+        108..=1131 => {
+            let v = value - 108;
+            out.extend_from_slice(&[(v / 256 + 247) as u8, (v % 256) as u8]);
+        }
+        -1131..=-108 => {
+            let v = -value - 108;
+            out.extend_from_slice(&[(v / 256 + 251) as u8, (v % 256) as u8]);
+        }
+        -32768..=32767 => {
+            out.push(28);
+            out.extend_from_slice(&(value as i16).to_be_bytes());
+        }
+        _ => {
+            out.push(29);
+            out.extend_from_slice(&value.to_be_bytes());
+        }
+    }
+}
+
+/// Pack a real number as BCD nibbles, ending with 0xf.
+fn encode_real(value: f64) -> Vec<u8> {
+    let mut nibbles: Vec<u8> = Vec::new();
+    for c in format!("{}", value).chars() {
+        nibbles.push(match c {
+            '0'..='9' => c as u8 - b'0',
+            '.' => 0xA,
+            '-' => 0xE,
+            _ => continue,
+        });
+    }
+    nibbles.push(0xF);
+    if nibbles.len() % 2 == 1 {
+        nibbles.push(0xF);
+    }
+    nibbles
+        .chunks(2)
+        .map(|pair| (pair[0] << 4) | pair[1])
+        .collect()
+}
+
+fn dict_int(dict: &Dict, op: u16) -> Option<usize> {
+    match dict.iter().find(|(o, _)| *o == op)?.1.last()? {
+        Operand::Int(value) if *value >= 0 => Some(*value as usize),
+        Operand::Offset(value) => Some(*value),
+        _ => None,
+    }
+}
+
+fn offset_operand(dict: &Dict, op: u16) -> Result<usize, String> {
+    dict_int(dict, op).ok_or_else(|| format!("CFF DICT is missing operator {}", op))
+}
+
+fn set_operands(dict: &mut Dict, op: u16, operands: Vec<Operand>) {
+    match dict.iter_mut().find(|(o, _)| *o == op) {
+        Some(entry) => entry.1 = operands,
+        None => dict.push((op, operands)),
+    }
+}
+
+fn set_offset(dict: &mut Dict, op: u16, offset: usize) {
+    set_operands(dict, op, vec![Operand::Offset(offset)]);
+}
+
+fn set_private(dict: &mut Dict, size: usize, offset: usize) {
+    set_operands(
+        dict,
+        PRIVATE,
+        vec![Operand::Offset(size), Operand::Offset(offset)],
+    );
+}
+
+// ─── Private DICT ───────────────────────────────────────────────
+
+/// A Private DICT and the local subroutines it points to.
+struct Private {
+    dict: Dict,
+    /// The local Subrs INDEX, verbatim.
+    subrs: Option<Vec<u8>>,
+}
+
+impl Private {
+    /// The length of the encoded DICT, without the subroutines after it.
+    fn dict_len(&self) -> usize {
+        encode_dict(&self.dict).len()
+    }
+
+    /// The DICT followed by its subroutines, which it points to by an
+    /// offset from its own start.
+    fn encode(&self) -> Vec<u8> {
+        let mut out = encode_dict(&self.dict);
+        if let Some(subrs) = &self.subrs {
+            out.extend_from_slice(subrs);
+        }
+        out
+    }
+}
+
+/// Read the Private DICT a Top DICT or Font DICT points to.
+fn read_private(cff: &[u8], dict: &Dict) -> Result<Private, String> {
+    let (size, offset) = match dict.iter().find(|(op, _)| *op == PRIVATE) {
+        Some((_, operands)) => match operands.as_slice() {
+            [Operand::Int(size), Operand::Int(offset)] if *size >= 0 && *offset >= 0 => {
+                (*size as usize, *offset as usize)
+            }
+            _ => return Err("Invalid CFF Private operator".to_string()),
+        },
+        None => {
+            return Ok(Private {
+                dict: Vec::new(),
+                subrs: None,
+            })
+        }
+    };
+    let data = cff
+        .get(offset..offset + size)
+        .ok_or("CFF Private DICT is truncated")?;
+    let mut private = parse_dict(data)?;
+    let subrs = match dict_int(&private, SUBRS) {
+        Some(subrs_offset) => {
+            let index = read_index(cff, offset + subrs_offset)?;
+            Some(cff[index.start..index.end].to_vec())
+        }
+        None => None,
+    };
+    if subrs.is_some() {
+        let len = {
+            set_offset(&mut private, SUBRS, 0);
+            encode_dict(&private).len()
+        };
+        set_offset(&mut private, SUBRS, len);
+    }
+    Ok(Private {
+        dict: private,
+        subrs,
+    })
+}
+
+// ─── charset and FDSelect ───────────────────────────────────────
+
+/// The SID (or CID, in a CID-keyed font) of every glyph.
+fn read_charset(cff: &[u8], offset: usize, num_glyphs: usize) -> Result<Vec<u16>, String> {
+    match offset {
+        // ISOAdobe: glyph i has SID i
+        0 => return Ok((0..num_glyphs as u16).collect()),
+        1 | 2 => return Err("Expert charsets are not supported".to_string()),
+        _ => {}
+    }
+    let truncated = || "CFF charset is truncated".to_string();
+    let u16_at = |pos: usize| {
+        read_uint(cff, pos, 2)
+            .map(|v| v as u16)
+            .ok_or_else(truncated)
+    };
+    let mut ids = vec![0u16];
+    let format = *cff.get(offset).ok_or_else(truncated)?;
+    let mut pos = offset + 1;
+    while ids.len() < num_glyphs {
+        match format {
+            0 => {
+                ids.push(u16_at(pos)?);
+                pos += 2;
+            }
+            1 | 2 => {
+                let first = u16_at(pos)?;
+                let left = if format == 1 {
+                    *cff.get(pos + 2).ok_or_else(truncated)? as u16
+                } else {
+                    u16_at(pos + 2)?
+                };
+                pos += if format == 1 { 3 } else { 4 };
+                for i in 0..=left {
+                    ids.push(first.wrapping_add(i));
+                }
+            }
+            _ => return Err(format!("Invalid CFF charset format {}", format)),
+        }
+    }
+    ids.truncate(num_glyphs);
+    Ok(ids)
+}
+
+/// The Font DICT index of every glyph.
+fn read_fd_select(cff: &[u8], offset: usize, num_glyphs: usize) -> Result<Vec<u8>, String> {
+    let truncated = || "CFF FDSelect is truncated".to_string();
+    match cff.get(offset) {
+        Some(0) => cff
+            .get(offset + 1..offset + 1 + num_glyphs)
+            .map(|fds| fds.to_vec())
+            .ok_or_else(truncated),
+        Some(3) => {
+            let num_ranges = read_uint(cff, offset + 1, 2).ok_or_else(truncated)?;
+            let mut fds = vec![0u8; num_glyphs];
+            for i in 0..num_ranges {
+                let range = offset + 3 + i * 3;
+                let first = read_uint(cff, range, 2).ok_or_else(truncated)?;
+                let fd = *cff.get(range + 2).ok_or_else(truncated)?;
+                // The next range's first glyph, or the sentinel
+                let end = read_uint(cff, range + 3, 2).ok_or_else(truncated)?;
+                for slot in fds.iter_mut().take(end.min(num_glyphs)).skip(first) {
+                    *slot = fd;
+                }
+            }
+            Ok(fds)
+        }
+        Some(format) => Err(format!("Invalid CFF FDSelect format {}", format)),
+        None => Err(truncated()),
+    }
+}
+
+// ─── Charstrings ────────────────────────────────────────────────
+
+/// Writes an outline as a Type 2 charstring, with rounded coordinates.
+struct CharStringBuilder {
+    data: Vec<u8>,
+    /// The advance width, written before the first operator.
+    width: Option<i32>,
+    x: i32,
+    y: i32,
+}
+
+impl CharStringBuilder {
+    fn new(width: u16) -> Self {
+        Self {
+            data: Vec::new(),
+            width: Some(width as i32),
+            x: 0,
+            y: 0,
+        }
+    }
+
+    fn number(&mut self, value: i32) {
+        match value {
+            -107..=107 => self.data.push((value + 139) as u8),
+            108..=1131 => {
+                let v = value - 108;
+                self.data
+                    .extend_from_slice(&[(v / 256 + 247) as u8, (v % 256) as u8]);
+            }
+            -1131..=-108 => {
+                let v = -value - 108;
+                self.data
+                    .extend_from_slice(&[(v / 256 + 251) as u8, (v % 256) as u8]);
+            }
+            _ => {
+                self.data.push(28);
+                self.data
+                    .extend_from_slice(&(value.clamp(-32768, 32767) as i16).to_be_bytes());
+            }
+        }
+    }
+
+    /// Write the points as deltas from the current point, then `op`.
+    fn op(&mut self, points: &[(f32, f32)], op: u8) {
+        if let Some(width) = self.width.take() {
+            self.number(width);
+        }
+        for &(x, y) in points {
+            let (x, y) = (x.round() as i32, y.round() as i32);
+            self.number(x - self.x);
+            self.number(y - self.y);
+            self.x = x;
+            self.y = y;
+        }
+        self.data.push(op);
+    }
+
+    fn finish(mut self) -> Vec<u8> {
+        self.op(&[], 14); // endchar
+        self.data
+    }
+}
+
+impl OutlineBuilder for CharStringBuilder {
+    fn move_to(&mut self, x: f32, y: f32) {
+        self.op(&[(x, y)], 21); // rmoveto
+    }
+
+    fn line_to(&mut self, x: f32, y: f32) {
+        self.op(&[(x, y)], 5); // rlineto
+    }
+
+    fn quad_to(&mut self, x1: f32, y1: f32, x: f32, y: f32) {
+        // The cubic with the same curve as the quadratic
+        let (x0, y0) = (self.x as f32, self.y as f32);
+        self.curve_to(
+            x0 + 2.0 / 3.0 * (x1 - x0),
+            y0 + 2.0 / 3.0 * (y1 - y0),
+            x + 2.0 / 3.0 * (x1 - x),
+            y + 2.0 / 3.0 * (y1 - y),
+            x,
+            y,
+        );
+    }
+
+    fn curve_to(&mut self, x1: f32, y1: f32, x2: f32, y2: f32, x: f32, y: f32) {
+        self.op(&[(x1, y1), (x2, y2), (x, y)], 8); // rrcurveto
+    }
+
+    fn close(&mut self) {
+        // Type 2 contours close implicitly
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::font::subset::tests::{add_tables, remove_tables};
+    use crate::font::subset::{subset_font, FontFormat};
+    use std::collections::HashSet;
+    use ttf_parser::Face;
+
+    const REGULAR: &[u8] = include_bytes!("../../fonts/NotoSans-Regular.ttf");
+
+    /// Noto Sans with its outlines moved from `glyf` into a `CFF ` table.
+    /// The name-keyed table has one charstring per glyph. The CID-keyed one
+    /// has two Font DICTs, one for each half of the glyphs, and keeps each
+    /// outline in a local subroutine its charstring calls.
+    pub(crate) fn make_otf(cid_keyed: bool) -> Vec<u8> {
+        let face = Face::parse(REGULAR, 0).unwrap();
+        let cff = if cid_keyed {
+            make_cid_cff(&face)
+        } else {
+            let all: BTreeSet<u16> = (0..face.number_of_glyphs()).collect();
+            cff_from_outlines(&face, &all, "NotoSans-Regular")
+        };
+        let mut maxp = 0x00005000u32.to_be_bytes().to_vec();
+        maxp.extend_from_slice(&face.number_of_glyphs().to_be_bytes());
+        let font = add_tables(REGULAR, vec![(*b"CFF ", cff), (*b"maxp", maxp)]);
+        remove_tables(
+            &font,
+            &[b"glyf", b"loca", b"cvt ", b"fpgm", b"prep", b"gasp"],
+            true,
+        )
+    }
+
+    fn make_cid_cff(face: &Face) -> Vec<u8> {
+        let num_glyphs = face.number_of_glyphs() as usize;
+        let half = num_glyphs / 2;
+        let bias = |count: usize| if count < 1240 { 107 } else { 1131 };
+        let biases = [bias(half), bias(num_glyphs - half)];
+
+        let mut subrs: [Vec<Vec<u8>>; 2] = Default::default();
+        let mut char_strings = Vec::new();
+        for gid in 0..num_glyphs {
+            let fd = (gid >= half) as usize;
+            let glyph = GlyphId(gid as u16);
+            let mut outline = CharStringBuilder::new(face.glyph_hor_advance(glyph).unwrap());
+            face.outline_glyph(glyph, &mut outline);
+            let mut call = CharStringBuilder::new(0);
+            call.width = None;
+            call.number(subrs[fd].len() as i32 - biases[fd]);
+            call.data.push(10); // callsubr
+            subrs[fd].push(outline.finish());
+            char_strings.push(call.data);
+        }
+
+        let privates: Vec<Private> = subrs
+            .iter()
+            .map(|subrs| {
+                let mut private = Private {
+                    dict: vec![(SUBRS, vec![Operand::Offset(0)])],
+                    subrs: Some(write_index(subrs.iter())),
+                };
+                let len = private.dict_len();
+                set_offset(&mut private.dict, SUBRS, len);
+                private
+            })
+            .collect();
+        let mut charset = vec![2u8];
+        charset.extend_from_slice(&1u16.to_be_bytes());
+        charset.extend_from_slice(&(num_glyphs as u16 - 2).to_be_bytes());
+        let mut fd_select = vec![3u8, 0, 2];
+        for (first, fd) in [(0u16, 0u8), (half as u16, 1)] {
+            fd_select.extend_from_slice(&first.to_be_bytes());
+            fd_select.push(fd);
+        }
+        fd_select.extend_from_slice(&(num_glyphs as u16).to_be_bytes());
+
+        // ROS: the strings "Adobe" and "Identity", supplement 0
+        let mut top: Dict = vec![(
+            ROS,
+            vec![Operand::Int(391), Operand::Int(392), Operand::Int(0)],
+        )];
+        for op in [CHARSET, FD_SELECT, CHAR_STRINGS, FD_ARRAY] {
+            set_offset(&mut top, op, 0);
+        }
+        let mut font_dicts: Vec<Dict> = vec![Vec::new(), Vec::new()];
+        for font_dict in &mut font_dicts {
+            set_private(font_dict, 0, 0);
+        }
+
+        let header = [1u8, 0, 4, 4];
+        let names = write_index([b"NotoSansCID".as_slice()]);
+        let strings = write_index([b"Adobe".as_slice(), b"Identity"]);
+        let global_subrs = write_index(std::iter::empty::<&[u8]>());
+        let char_strings = write_index(char_strings.iter());
+        let top_len = write_index([encode_dict(&top).as_slice()]).len();
+        let fd_array_len =
+            write_index(font_dicts.iter().map(encode_dict).collect::<Vec<_>>()).len();
+
+        let mut pos = header.len() + names.len() + top_len + strings.len() + global_subrs.len();
+        set_offset(&mut top, CHARSET, pos);
+        pos += charset.len();
+        set_offset(&mut top, FD_SELECT, pos);
+        pos += fd_select.len();
+        set_offset(&mut top, CHAR_STRINGS, pos);
+        pos += char_strings.len();
+        set_offset(&mut top, FD_ARRAY, pos);
+        pos += fd_array_len;
+        for (font_dict, private) in font_dicts.iter_mut().zip(&privates) {
+            set_private(font_dict, private.dict_len(), pos);
+            pos += private.encode().len();
+        }
+
+        let mut out = header.to_vec();
+        out.extend(names);
+        out.extend(write_index([encode_dict(&top).as_slice()]));
+        out.extend(strings);
+        out.extend(global_subrs);
+        out.extend(charset);
+        out.extend(fd_select);
+        out.extend(char_strings);
+        out.extend(write_index(
+            font_dicts.iter().map(encode_dict).collect::<Vec<_>>(),
+        ));
+        for private in &privates {
+            out.extend(private.encode());
+        }
+        out
+    }
+
+    /// Drawing commands, for comparing outlines.
+    #[derive(Default)]
+    struct Recorder(Vec<String>);
+
+    impl OutlineBuilder for Recorder {
+        fn move_to(&mut self, x: f32, y: f32) {
+            self.0.push(format!("M {} {}", x, y));
+        }
+        fn line_to(&mut self, x: f32, y: f32) {
+            self.0.push(format!("L {} {}", x, y));
+        }
+        fn quad_to(&mut self, x1: f32, y1: f32, x: f32, y: f32) {
+            self.0.push(format!("Q {} {} {} {}", x1, y1, x, y));
+        }
+        fn curve_to(&mut self, x1: f32, y1: f32, x2: f32, y2: f32, x: f32, y: f32) {
+            self.0
+                .push(format!("C {} {} {} {} {} {}", x1, y1, x2, y2, x, y));
+        }
+        fn close(&mut self) {
+            self.0.push("Z".to_string());
+        }
+    }
+
+    fn outline(font: &[u8], gid: u16) -> (Vec<String>, Option<u16>) {
+        let face = Face::parse(font, 0).unwrap();
+        let mut recorder = Recorder::default();
+        face.outline_glyph(GlyphId(gid), &mut recorder);
+        (recorder.0, face.glyph_hor_advance(GlyphId(gid)))
+    }
+
+    /// Subset `font` to a few glyphs, including the last one, and check
+    /// each keeps its outline, advance and character.
+    fn check_subset(font: &[u8]) {
+        let face = Face::parse(font, 0).unwrap();
+        let chars: Vec<(char, u16)> = "Hé€"
+            .chars()
+            .map(|ch| (ch, face.glyph_index(ch).unwrap().0))
+            .collect();
+        let last = face.number_of_glyphs() - 1;
+        let mut used: HashSet<u16> = chars.iter().map(|&(_, gid)| gid).collect();
+        used.insert(last);
+
+        let table = face
+            .raw_face()
+            .table(ttf_parser::Tag::from_bytes(b"CFF "))
+            .unwrap();
+        let needed: BTreeSet<u16> = used.iter().copied().chain([0]).collect();
+        assert!(subset_cff(table, &needed).is_ok());
+
+        let subset = subset_font(font, &used, &[]).unwrap();
+        assert_eq!(subset.format, FontFormat::OpenTypeCff);
+        let subset_face = Face::parse(&subset.ttf_data, 0).unwrap();
+        assert!(subset_face.tables().cff.is_some());
+        assert_eq!(subset_face.number_of_glyphs(), used.len() as u16 + 1);
+        for &(ch, gid) in &chars {
+            let new_gid = subset.gid_remap[&gid];
+            assert_eq!(subset_face.glyph_index(ch), Some(GlyphId(new_gid)));
+            let (commands, advance) = outline(&subset.ttf_data, new_gid);
+            assert!(!commands.is_empty());
+            assert_eq!((commands, advance), outline(font, gid), "{}", ch);
+        }
+        assert_eq!(
+            outline(&subset.ttf_data, subset.gid_remap[&last]),
+            outline(font, last)
+        );
+    }
+
+    #[test]
+    fn test_subset_name_keyed_cff() {
+        check_subset(&make_otf(false));
+    }
+
+    #[test]
+    fn test_subset_cid_keyed_cff() {
+        check_subset(&make_otf(true));
+    }
+
+    #[test]
+    fn test_cid_keyed_subset_maps_cids_to_new_glyph_ids() {
+        let font = make_otf(true);
+        let face = Face::parse(&font, 0).unwrap();
+        let used: HashSet<u16> = "Hé€"
+            .chars()
+            .map(|ch| face.glyph_index(ch).unwrap().0)
+            .collect();
+        let subset = subset_font(&font, &used, &[]).unwrap();
+        let subset_face = Face::parse(&subset.ttf_data, 0).unwrap();
+        let table = subset_face
+            .raw_face()
+            .table(ttf_parser::Tag::from_bytes(b"CFF "))
+            .unwrap();
+        let header_size = table[2] as usize;
+        let names = read_index(table, header_size).unwrap();
+        let top = parse_dict(read_index(table, names.end).unwrap().items[0]).unwrap();
+        let num_glyphs = subset_face.number_of_glyphs() as usize;
+        let charset = read_charset(table, dict_int(&top, CHARSET).unwrap(), num_glyphs).unwrap();
+
+        // A viewer finds the glyph a content-stream CID names through the
+        // charset; the content stream writes each glyph's new ID
+        for &gid in &used {
+            let cid = subset.gid_remap[&gid];
+            let found = charset.iter().position(|&c| c == cid).unwrap() as u16;
+            assert_eq!(outline(&subset.ttf_data, found), outline(&font, gid));
+        }
+    }
+
+    #[test]
+    fn test_outlines_keep_their_shape() {
+        let ttf = Face::parse(REGULAR, 0).unwrap();
+        let otf = make_otf(false);
+        let otf = Face::parse(&otf, 0).unwrap();
+        for ch in ['H', 'o', 'g', '@'] {
+            let gid = ttf.glyph_index(ch).unwrap();
+            let expected = ttf.glyph_bounding_box(gid).unwrap();
+            let actual = otf.glyph_bounding_box(gid).unwrap();
+            for (a, b) in [
+                (actual.x_min, expected.x_min),
+                (actual.y_min, expected.y_min),
+                (actual.x_max, expected.x_max),
+                (actual.y_max, expected.y_max),
+            ] {
+                assert!((a - b).abs() <= 1, "{}: {} vs {}", ch, a, b);
+            }
+        }
+    }
+
+    #[test]
+    fn test_dict_round_trip() {
+        let dict: Dict = vec![
+            (CHARSET, vec![Operand::Int(-1200)]),
+            (FONT_MATRIX, vec![Operand::Real(encode_real(0.00048828125))]),
+            (PRIVATE, vec![Operand::Int(45), Operand::Int(70000)]),
+        ];
+        assert_eq!(parse_dict(&encode_dict(&dict)).unwrap(), dict);
+        assert_eq!(encode_real(-2.25), [0xE2, 0xA2, 0x5F]);
+    }
+}
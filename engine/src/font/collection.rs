@@ -0,0 +1,141 @@
+//! Font collections (`.ttc` / `.otc`).
+//!
+//! A collection stores several faces that share tables. The rest of the
+//! engine parses fonts as a single face at index 0, so a face is cut out of
+//! its collection into a standalone font when it's registered: its table
+//! directory is rewritten with the tables copied after it.
+
+/// Number of faces in `data`: the collection's count, or 1 for a single font.
+pub fn face_count(data: &[u8]) -> u32 {
+    ttf_parser::fonts_in_collection(data).unwrap_or(1)
+}
+
+/// The font data for face `index` of `data`, as a standalone font. Single
+/// fonts are returned unchanged for index 0.
+pub fn standalone_face(data: Vec<u8>, index: u32) -> Result<Vec<u8>, String> {
+    let count = face_count(&data);
+    if index >= count {
+        return Err(format!(
+            "Face index {} is out of range: the font has {} face(s)",
+            index, count
+        ));
+    }
+    if ttf_parser::fonts_in_collection(&data).is_none() {
+        return Ok(data);
+    }
+    extract_face(&data, index)
+}
+
+/// Cut face `index` out of the collection in `data` as a standalone font.
+pub(crate) fn extract_face(data: &[u8], index: u32) -> Result<Vec<u8>, String> {
+    let truncated = || "Font collection is truncated".to_string();
+    let u16_at = |pos: usize| -> Result<u16, String> {
+        data.get(pos..pos + 2)
+            .map(|b| u16::from_be_bytes([b[0], b[1]]))
+            .ok_or_else(truncated)
+    };
+    let u32_at = |pos: usize| -> Result<u32, String> {
+        data.get(pos..pos + 4)
+            .map(|b| u32::from_be_bytes([b[0], b[1], b[2], b[3]]))
+            .ok_or_else(truncated)
+    };
+
+    // TTC header: tag, version, numFonts, then one offset per face
+    let face_offset = u32_at(12 + 4 * index as usize)? as usize;
+    let sfnt_version = u32_at(face_offset)?;
+    let num_tables = u16_at(face_offset + 4)?;
+
+    let mut records = Vec::with_capacity(num_tables as usize);
+    for i in 0..num_tables as usize {
+        let record = face_offset + 12 + 16 * i;
+        let tag = u32_at(record)?;
+        let checksum = u32_at(record + 4)?;
+        let offset = u32_at(record + 8)? as usize;
+        let length = u32_at(record + 12)? as usize;
+        let table = data.get(offset..offset + length).ok_or_else(truncated)?;
+        records.push((tag, checksum, table));
+    }
+
+    let mut output = Vec::new();
+    output.extend_from_slice(&sfnt_version.to_be_bytes());
+    output.extend_from_slice(&num_tables.to_be_bytes());
+    // searchRange, entrySelector and rangeShift only depend on numTables
+    output.extend_from_slice(
+        data.get(face_offset + 6..face_offset + 12)
+            .ok_or_else(truncated)?,
+    );
+
+    let mut table_offset = 12 + 16 * records.len();
+    for (tag, checksum, table) in &records {
+        output.extend_from_slice(&tag.to_be_bytes());
+        output.extend_from_slice(&checksum.to_be_bytes());
+        output.extend_from_slice(&(table_offset as u32).to_be_bytes());
+        output.extend_from_slice(&(table.len() as u32).to_be_bytes());
+        table_offset += (table.len() + 3) & !3;
+    }
+    for (_, _, table) in &records {
+        output.extend_from_slice(table);
+        while output.len() % 4 != 0 {
+            output.push(0);
+        }
+    }
+    Ok(output)
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    const REGULAR: &[u8] = include_bytes!("../../fonts/NotoSans-Regular.ttf");
+    const BOLD: &[u8] = include_bytes!("../../fonts/NotoSans-Bold.ttf");
+
+    /// Pack standalone fonts into a collection, without sharing tables.
+    pub(crate) fn make_collection(fonts: &[&[u8]]) -> Vec<u8> {
+        let header_len = 12 + 4 * fonts.len();
+        let mut out = Vec::new();
+        out.extend_from_slice(b"ttcf");
+        out.extend_from_slice(&0x0001_0000u32.to_be_bytes());
+        out.extend_from_slice(&(fonts.len() as u32).to_be_bytes());
+        let mut offset = header_len;
+        for font in fonts {
+            out.extend_from_slice(&(offset as u32).to_be_bytes());
+            offset += (font.len() + 3) & !3;
+        }
+        for font in fonts {
+            // Table offsets in a collection are from the start of the file
+            let base = out.len();
+            let mut font = font.to_vec();
+            let num_tables = u16::from_be_bytes([font[4], font[5]]) as usize;
+            for i in 0..num_tables {
+                let pos = 12 + 16 * i + 8;
+                let table = u32::from_be_bytes(font[pos..pos + 4].try_into().unwrap());
+                font[pos..pos + 4].copy_from_slice(&(table + base as u32).to_be_bytes());
+            }
+            out.extend_from_slice(&font);
+            while out.len() % 4 != 0 {
+                out.push(0);
+            }
+        }
+        out
+    }
+
+    #[test]
+    fn test_single_font_passes_through() {
+        assert_eq!(face_count(REGULAR), 1);
+        assert_eq!(standalone_face(REGULAR.to_vec(), 0).unwrap(), REGULAR);
+        assert!(standalone_face(REGULAR.to_vec(), 1).is_err());
+    }
+
+    #[test]
+    fn test_extract_faces_from_collection() {
+        let collection = make_collection(&[REGULAR, BOLD]);
+        assert_eq!(face_count(&collection), 2);
+        for (index, expected_weight) in [(0, 400), (1, 700)] {
+            let face_data = standalone_face(collection.clone(), index).unwrap();
+            let face = ttf_parser::Face::parse(&face_data, 0).unwrap();
+            assert_eq!(face.weight().to_number(), expected_weight);
+            assert!(face.glyph_index('A').is_some());
+        }
+        assert!(standalone_face(collection, 2).is_err());
+    }
+}
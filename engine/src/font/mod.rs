@@ -6,37 +6,109 @@
 //! which don't require embedding. Custom font support via ttf-parser comes next.
 
 pub mod builtin;
+pub mod cff;
+pub mod collection;
 pub mod fallback;
 pub mod metrics;
 pub mod subset;
+pub mod variable;
 
 pub use metrics::{unicode_to_winansi, StandardFontMetrics};
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock};
+pub use ttf_parser::Variation;
+pub use variable::VariableFont;
 
-/// A font registry that maps font family + weight + style to font data.
+/// File extensions `FontRegistry::scan_directory` loads.
+const FONT_EXTENSIONS: &[&str] = &["ttf", "otf", "ttc", "otc"];
+
+/// A font registry that maps font family + weight + width + style to font data.
+#[derive(Clone)]
 pub struct FontRegistry {
     fonts: HashMap<FontKey, FontData>,
+    /// Variable fonts by family and style. Each serves every weight and
+    /// width along its axes.
+    variable_fonts: HashMap<(String, bool), FontData>,
 }
 
 #[derive(Debug, Clone, Hash, PartialEq, Eq)]
 pub struct FontKey {
     pub family: String,
     pub weight: u32,
+    /// Width as a percentage of normal, as in CSS `font-stretch`.
+    pub stretch: u32,
     pub italic: bool,
 }
 
+/// Which styles may be faked when a family has no face for them: bold by
+/// stroking the glyph outlines as well as filling them, and italic by
+/// slanting them. Both are allowed by default.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FontSynthesis {
+    pub weight: bool,
+    pub style: bool,
+}
+
+impl Default for FontSynthesis {
+    fn default() -> Self {
+        Self {
+            weight: true,
+            style: true,
+        }
+    }
+}
+
+impl FontSynthesis {
+    /// No synthetic styles: text is drawn with the closest face as it is.
+    pub const NONE: Self = Self {
+        weight: false,
+        style: false,
+    };
+
+    /// The styles to fake when text asking for `requested` is drawn with
+    /// the face registered under `drawn`.
+    pub fn synthesize(self, requested: &FontKey, drawn: &FontKey) -> SyntheticStyle {
+        SyntheticStyle {
+            bold: self.weight && requested.weight >= 600 && drawn.weight < 600,
+            oblique: self.style && requested.italic && !drawn.italic,
+        }
+    }
+}
+
+/// The styles faked for a run of text.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SyntheticStyle {
+    pub bold: bool,
+    pub oblique: bool,
+}
+
+/// Stroke width of synthetic bold as a fraction of the font size. The
+/// stroke widens each glyph by this much, so its advance grows to match.
+pub const SYNTHETIC_BOLD_STROKE: f64 = 0.03;
+
+/// Horizontal shear of synthetic oblique text: tan(12°).
+pub const SYNTHETIC_OBLIQUE_SKEW: f64 = 0.2126;
+
+/// A registered font. Font bytes and parsed metrics are shared, so
+/// cloning a registry for a render doesn't copy them.
 #[derive(Debug, Clone)]
 pub enum FontData {
     /// One of the 14 standard PDF fonts. No embedding needed.
     Standard(StandardFont),
     /// A TrueType/OpenType font that needs to be embedded.
     Custom {
-        data: Vec<u8>,
+        data: Arc<[u8]>,
         /// Glyph IDs that are actually used (for subsetting).
         used_glyphs: Vec<u16>,
         /// Parsed metrics from ttf-parser, if available.
-        metrics: Option<CustomFontMetrics>,
+        metrics: Option<Arc<CustomFontMetrics>>,
     },
+    /// A variable font, embedded as a static instance for each
+    /// weight and width it's used at.
+    Variable(Arc<VariableFont>),
 }
 
 /// Parsed metrics from a TrueType/OpenType font via ttf-parser.
@@ -64,7 +136,16 @@ impl CustomFontMetrics {
 
     /// Parse metrics from font data using ttf-parser.
     pub fn from_font_data(data: &[u8]) -> Option<Self> {
-        let face = ttf_parser::Face::parse(data, 0).ok()?;
+        Self::from_instance(data, &[])
+    }
+
+    /// Parse metrics for an instance of a variable font, with `variations`
+    /// set on its axes. Advance widths are those of the instance.
+    pub fn from_instance(data: &[u8], variations: &[Variation]) -> Option<Self> {
+        let mut face = ttf_parser::Face::parse(data, 0).ok()?;
+        for variation in variations {
+            face.set_variation(variation.axis, variation.value)?;
+        }
         let units_per_em = face.units_per_em();
         let ascender = face.ascender();
         let descender = face.descender();
@@ -129,6 +210,7 @@ impl FontData {
                 metrics: Some(m), ..
             } => m.glyph_ids.contains_key(&ch),
             FontData::Custom { metrics: None, .. } => false,
+            FontData::Variable(font) => font.metrics.glyph_ids.contains_key(&ch),
             FontData::Standard(_) => {
                 unicode_to_winansi(ch).is_some() || (ch as u32) >= 32 && (ch as u32) <= 255
             }
@@ -188,13 +270,17 @@ impl FontRegistry {
                 FontKey {
                     family: family.to_string(),
                     weight,
+                    stretch: 100,
                     italic,
                 },
                 FontData::Standard(font),
             );
         }
 
-        let mut registry = Self { fonts };
+        let mut registry = Self {
+            fonts,
+            variable_fonts: HashMap::new(),
+        };
         builtin::register_builtin_fonts(&mut registry);
         registry
     }
@@ -204,62 +290,24 @@ impl FontRegistry {
     ///
     /// Supports CSS-style font family lists: `"Inter, Helvetica"` tries Inter
     /// first, then Helvetica. Quoted families are unquoted automatically.
-    pub fn resolve(&self, families: &str, weight: u32, italic: bool) -> &FontData {
-        let snapped_weight = if weight >= 600 { 700 } else { 400 };
-
-        for family in families.split(',') {
-            let family = family.trim().trim_matches('"').trim_matches('\'');
-            if family.is_empty() {
-                continue;
-            }
-
-            // Try exact weight
-            let key = FontKey {
-                family: family.to_string(),
-                weight,
-                italic,
-            };
-            if let Some(font) = self.fonts.get(&key) {
-                return font;
-            }
-
-            // Try with normalized weight (snap to 400 or 700)
-            let key = FontKey {
-                family: family.to_string(),
-                weight: snapped_weight,
-                italic,
-            };
-            if let Some(font) = self.fonts.get(&key) {
-                return font;
-            }
+    pub fn resolve(&self, families: &str, weight: u32, stretch: u32, italic: bool) -> &FontData {
+        self.font(&self.resolve_key(families, weight, stretch, italic))
+    }
 
-            // Try opposite weight (400 if bold requested, 700 if regular requested)
-            let opposite_weight = if snapped_weight == 700 { 400 } else { 700 };
-            let key = FontKey {
-                family: family.to_string(),
-                weight: opposite_weight,
-                italic,
-            };
-            if let Some(font) = self.fonts.get(&key) {
-                return font;
+    /// The key of the font `resolve` returns. For a variable font this is
+    /// the instance serving the request, so two requests share a key exactly
+    /// when they're drawn with the same font.
+    pub fn resolve_key(&self, families: &str, weight: u32, stretch: u32, italic: bool) -> FontKey {
+        for family in split_families(families) {
+            if let Some(key) = self
+                .candidate_keys(family, weight, stretch, italic)
+                .into_iter()
+                .find(|key| self.get(key).is_some())
+            {
+                return key;
             }
         }
-
-        // Final fallback: Helvetica
-        let key = FontKey {
-            family: "Helvetica".to_string(),
-            weight: snapped_weight,
-            italic,
-        };
-        self.fonts.get(&key).unwrap_or_else(|| {
-            self.fonts
-                .get(&FontKey {
-                    family: "Helvetica".to_string(),
-                    weight: 400,
-                    italic: false,
-                })
-                .expect("Helvetica must be registered")
-        })
+        Self::helvetica_key(weight, italic)
     }
 
     /// Resolve a font for a specific character from a comma-separated fallback chain.
@@ -272,50 +320,15 @@ impl FontRegistry {
         families: &str,
         ch: char,
         weight: u32,
+        stretch: u32,
         italic: bool,
     ) -> (&FontData, String) {
-        let snapped_weight = if weight >= 600 { 700 } else { 400 };
-
-        for family in families.split(',') {
-            let family = family.trim().trim_matches('"').trim_matches('\'');
-            if family.is_empty() {
-                continue;
-            }
-
-            // Try exact weight
-            let key = FontKey {
-                family: family.to_string(),
-                weight,
-                italic,
-            };
-            if let Some(font) = self.fonts.get(&key) {
-                if font.has_char(ch) {
-                    return (font, family.to_string());
-                }
-            }
-
-            // Try with normalized weight
-            let key = FontKey {
-                family: family.to_string(),
-                weight: snapped_weight,
-                italic,
-            };
-            if let Some(font) = self.fonts.get(&key) {
-                if font.has_char(ch) {
-                    return (font, family.to_string());
-                }
-            }
-
-            // Try opposite weight (400 if bold requested, 700 if regular requested)
-            let opposite_weight = if snapped_weight == 700 { 400 } else { 700 };
-            let key = FontKey {
-                family: family.to_string(),
-                weight: opposite_weight,
-                italic,
-            };
-            if let Some(font) = self.fonts.get(&key) {
-                if font.has_char(ch) {
-                    return (font, family.to_string());
+        for family in split_families(families) {
+            for key in self.candidate_keys(family, weight, stretch, italic) {
+                if let Some(font) = self.get(&key) {
+                    if font.has_char(ch) {
+                        return (font, family.to_string());
+                    }
                 }
             }
         }
@@ -323,60 +336,288 @@ impl FontRegistry {
         // Try builtin Unicode font (Noto Sans) before Helvetica
         let builtin_key = FontKey {
             family: "Noto Sans".to_string(),
-            weight: snapped_weight,
+            weight: if weight >= 600 { 700 } else { 400 },
+            stretch: 100,
             italic: false,
         };
-        if let Some(font) = self.fonts.get(&builtin_key) {
+        if let Some(font) = self.get(&builtin_key) {
             if font.has_char(ch) {
                 return (font, "Noto Sans".to_string());
             }
         }
 
         // Final fallback: Helvetica
-        let key = FontKey {
-            family: "Helvetica".to_string(),
-            weight: snapped_weight,
-            italic,
-        };
-        let font = self.fonts.get(&key).unwrap_or_else(|| {
-            self.fonts
-                .get(&FontKey {
-                    family: "Helvetica".to_string(),
-                    weight: 400,
-                    italic: false,
+        (
+            self.font(&Self::helvetica_key(weight, italic)),
+            "Helvetica".to_string(),
+        )
+    }
+
+    /// The keys that can serve a request for one family, best first: the
+    /// exact weight and width, an instance of a variable font, then the
+    /// exact, snapped (400 or 700) and opposite weight, at the requested
+    /// width before the normal width. An italic request then tries the
+    /// family's upright faces the same way, which can be slanted instead.
+    fn candidate_keys(
+        &self,
+        family: &str,
+        weight: u32,
+        stretch: u32,
+        italic: bool,
+    ) -> Vec<FontKey> {
+        let mut keys = Vec::new();
+        let styles: &[bool] = if italic { &[true, false] } else { &[false] };
+        for &italic in styles {
+            let key = |weight, stretch| FontKey {
+                family: family.to_string(),
+                weight,
+                stretch,
+                italic,
+            };
+            keys.push(key(weight, stretch));
+            if let Some(FontData::Variable(font)) =
+                self.variable_fonts.get(&(family.to_string(), italic))
+            {
+                let (weight, stretch) = font.instance(weight, stretch);
+                keys.push(key(weight, stretch));
+            }
+            let snapped_weight = if weight >= 600 { 700 } else { 400 };
+            let opposite_weight = if snapped_weight == 700 { 400 } else { 700 };
+            for stretch in [stretch, 100] {
+                for weight in [weight, snapped_weight, opposite_weight] {
+                    keys.push(key(weight, stretch));
+                }
+            }
+        }
+        keys
+    }
+
+    /// The font registered under `key`, or the variable font whose instance
+    /// it names.
+    pub fn get(&self, key: &FontKey) -> Option<&FontData> {
+        self.fonts.get(key).or_else(|| {
+            self.variable_fonts
+                .get(&(key.family.clone(), key.italic))
+                .filter(|font| match font {
+                    FontData::Variable(font) => {
+                        font.instance(key.weight, key.stretch) == (key.weight, key.stretch)
+                    }
+                    _ => false,
                 })
+        })
+    }
+
+    fn font(&self, key: &FontKey) -> &FontData {
+        self.get(key).unwrap_or_else(|| {
+            self.fonts
+                .get(&Self::helvetica_key(400, false))
                 .expect("Helvetica must be registered")
-        });
-        (font, "Helvetica".to_string())
+        })
+    }
+
+    fn helvetica_key(weight: u32, italic: bool) -> FontKey {
+        FontKey {
+            family: "Helvetica".to_string(),
+            weight: if weight >= 600 { 700 } else { 400 },
+            stretch: 100,
+            italic,
+        }
     }
 
     /// Register a custom font.
+    ///
+    /// A variable font is registered once for its family and style and
+    /// serves every weight and width along its axes; `weight` is ignored.
     pub fn register(&mut self, family: &str, weight: u32, italic: bool, data: Vec<u8>) {
-        let metrics = CustomFontMetrics::from_font_data(&data);
-        self.fonts.insert(
+        if let Some(font) = VariableFont::parse(&data) {
+            self.variable_fonts.insert(
+                (family.to_string(), italic),
+                FontData::Variable(Arc::new(font)),
+            );
+            return;
+        }
+        self.register_static(
             FontKey {
                 family: family.to_string(),
                 weight,
+                stretch: 100,
                 italic,
             },
+            data,
+        );
+    }
+
+    fn register_static(&mut self, key: FontKey, data: Vec<u8>) {
+        let metrics = CustomFontMetrics::from_font_data(&data).map(Arc::new);
+        self.fonts.insert(
+            key,
             FontData::Custom {
-                data,
+                data: data.into(),
                 used_glyphs: Vec::new(),
                 metrics,
             },
         );
     }
 
-    /// Iterate over all registered fonts.
+    /// Register face `face_index` of a font file (0 for a single font)
+    /// under the family, weight, width and style its own tables give: the
+    /// typographic family name, falling back to the family name, and the
+    /// OS/2 weight class, width class and italic flag. A variable font is
+    /// registered for every weight and width along its axes, and the key
+    /// returned is its default instance.
+    pub fn register_face(&mut self, data: Vec<u8>, face_index: u32) -> Result<FontKey, String> {
+        let data = collection::standalone_face(data, face_index)?;
+        let face = ttf_parser::Face::parse(&data, 0)
+            .map_err(|e| format!("Failed to parse font: {}", e))?;
+        let tables = face.tables();
+        if tables.glyf.is_none() && tables.cff.is_none() && tables.cff2.is_none() {
+            return Err("Only fonts with TrueType or CFF outlines can be embedded".to_string());
+        }
+        let family = face_name(&face, ttf_parser::name_id::TYPOGRAPHIC_FAMILY)
+            .or_else(|| face_name(&face, ttf_parser::name_id::FAMILY))
+            .ok_or_else(|| "Font has no family name".to_string())?;
+        let italic = face.is_italic() || face.is_oblique();
+        if let Some(font) = VariableFont::parse(&data) {
+            let (weight, stretch) = font.default_instance();
+            self.variable_fonts
+                .insert((family.clone(), italic), FontData::Variable(Arc::new(font)));
+            return Ok(FontKey {
+                family,
+                weight,
+                stretch,
+                italic,
+            });
+        }
+        let key = FontKey {
+            family,
+            weight: face.weight().to_number() as u32,
+            stretch: stretch_of(face.width()),
+            italic,
+        };
+        self.register_static(key.clone(), data);
+        Ok(key)
+    }
+
+    /// Register every face of every font file (`.ttf`, `.otf`, `.ttc`,
+    /// `.otc`) in `dir` and its subdirectories with `register_face`.
+    ///
+    /// Files are visited in path order, so when two faces share a family,
+    /// weight and style the later one wins. Faces that can't be registered
+    /// are returned with the reason rather than failing the scan.
+    pub fn scan_directory(&mut self, dir: impl AsRef<Path>) -> Result<FontScan, String> {
+        let mut files = Vec::new();
+        collect_font_files(dir.as_ref(), &mut files)?;
+        files.sort();
+
+        let mut scan = FontScan::default();
+        for path in files {
+            let data = match std::fs::read(&path) {
+                Ok(data) => data,
+                Err(e) => {
+                    scan.skipped.push((path, e.to_string()));
+                    continue;
+                }
+            };
+            let faces = collection::face_count(&data);
+            if faces == 1 {
+                match self.register_face(data, 0) {
+                    Ok(key) => scan.registered.push(key),
+                    Err(e) => scan.skipped.push((path, e)),
+                }
+                continue;
+            }
+            for index in 0..faces {
+                let face = collection::extract_face(&data, index);
+                match face.and_then(|face| self.register_face(face, 0)) {
+                    Ok(key) => scan.registered.push(key),
+                    Err(e) => scan.skipped.push((path.clone(), e)),
+                }
+            }
+        }
+        Ok(scan)
+    }
+
+    /// Iterate over all registered static fonts.
     pub fn iter(&self) -> impl Iterator<Item = (&FontKey, &FontData)> {
         self.fonts.iter()
     }
 }
 
+/// The outcome of `FontRegistry::scan_directory`.
+#[derive(Debug, Default)]
+pub struct FontScan {
+    /// The faces registered, in the order they were found.
+    pub registered: Vec<FontKey>,
+    /// Files or faces that couldn't be registered, with the reason.
+    pub skipped: Vec<(PathBuf, String)>,
+}
+
+fn collect_font_files(dir: &Path, files: &mut Vec<PathBuf>) -> Result<(), String> {
+    let entries = std::fs::read_dir(dir)
+        .map_err(|e| format!("Failed to read font directory '{}': {}", dir.display(), e))?;
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.is_dir() {
+            collect_font_files(&path, files)?;
+        } else if path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .is_some_and(|ext| FONT_EXTENSIONS.contains(&ext.to_ascii_lowercase().as_str()))
+        {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
+
+/// Split a CSS-style family list into unquoted family names.
+fn split_families(families: &str) -> impl Iterator<Item = &str> {
+    families
+        .split(',')
+        .map(|family| family.trim().trim_matches('"').trim_matches('\''))
+        .filter(|family| !family.is_empty())
+}
+
+/// The CSS `font-stretch` percentage of an OS/2 width class.
+pub(crate) fn stretch_of(width: ttf_parser::Width) -> u32 {
+    match width {
+        ttf_parser::Width::UltraCondensed => 50,
+        ttf_parser::Width::ExtraCondensed => 62,
+        ttf_parser::Width::Condensed => 75,
+        ttf_parser::Width::SemiCondensed => 87,
+        ttf_parser::Width::Normal => 100,
+        ttf_parser::Width::SemiExpanded => 112,
+        ttf_parser::Width::Expanded => 125,
+        ttf_parser::Width::ExtraExpanded => 150,
+        ttf_parser::Width::UltraExpanded => 200,
+    }
+}
+
+/// A name from the face's name table, preferring US English.
+fn face_name(face: &ttf_parser::Face, name_id: u16) -> Option<String> {
+    let mut names = face
+        .names()
+        .into_iter()
+        .filter(|name| name.name_id == name_id && name.is_unicode());
+    let first = names.clone().find_map(|name| name.to_string());
+    names
+        .find(|name| name.language_id == 0x0409)
+        .and_then(|name| name.to_string())
+        .or(first)
+        .filter(|name| !name.is_empty())
+}
+
+/// Synthetic styles by family, then by weight, width and style.
+type SyntheticStyles = HashMap<String, HashMap<(u32, u32, bool), SyntheticStyle>>;
+
 /// Shared font context used by layout and PDF serialization.
 /// Provides text measurement with real glyph metrics.
 pub struct FontContext {
     registry: FontRegistry,
+    synthesis: FontSynthesis,
+    /// Synthetic styles already worked out, by family and then weight,
+    /// width and style. Measurement asks for every character.
+    synthetic_styles: RwLock<SyntheticStyles>,
 }
 
 impl Default for FontContext {
@@ -387,8 +628,88 @@ impl Default for FontContext {
 
 impl FontContext {
     pub fn new() -> Self {
+        Self::with_registry(FontRegistry::new())
+    }
+
+    /// A context starting from an existing registry, such as one filled by
+    /// `FontRegistry::scan_directory`.
+    pub fn with_registry(registry: FontRegistry) -> Self {
         Self {
-            registry: FontRegistry::new(),
+            registry,
+            synthesis: FontSynthesis::default(),
+            synthetic_styles: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// The styles that may be faked for families missing a face.
+    pub fn font_synthesis(&self) -> FontSynthesis {
+        self.synthesis
+    }
+
+    /// Set the styles that may be faked for families missing a face.
+    pub fn set_font_synthesis(&mut self, synthesis: FontSynthesis) {
+        self.synthesis = synthesis;
+        self.synthetic_styles
+            .get_mut()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .clear();
+    }
+
+    /// The styles faked for text in `family` at this weight, width and
+    /// style, given the face that draws it.
+    pub fn synthetic_style(
+        &self,
+        family: &str,
+        weight: u32,
+        stretch: u32,
+        italic: bool,
+    ) -> SyntheticStyle {
+        let variant = (weight, stretch, italic);
+        if let Some(style) = self
+            .synthetic_styles
+            .read()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .get(family)
+            .and_then(|styles| styles.get(&variant))
+        {
+            return *style;
+        }
+        let requested = FontKey {
+            family: family.to_string(),
+            weight,
+            stretch,
+            italic,
+        };
+        let drawn = self.registry.resolve_key(family, weight, stretch, italic);
+        let style = self.synthesis.synthesize(&requested, &drawn);
+        self.synthetic_styles
+            .write()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .entry(family.to_string())
+            .or_default()
+            .insert(variant, style);
+        style
+    }
+
+    /// The extra advance synthetic bold gives each glyph of text in
+    /// `family`, in points. Zero when the family has a bold face or
+    /// synthetic bold is turned off.
+    pub fn synthetic_bold_width(
+        &self,
+        family: &str,
+        weight: u32,
+        stretch: u32,
+        italic: bool,
+        font_size: f64,
+    ) -> f64 {
+        // Cheap checks first: this runs for every measured character
+        if !self.synthesis.weight || weight < 600 {
+            return 0.0;
+        }
+        if self.synthetic_style(family, weight, stretch, italic).bold {
+            font_size * SYNTHETIC_BOLD_STROKE
+        } else {
+            0.0
         }
     }
 
@@ -401,24 +722,29 @@ impl FontContext {
         ch: char,
         family: &str,
         weight: u32,
+        stretch: u32,
         italic: bool,
         font_size: f64,
     ) -> f64 {
         // Fast path: single font family — try primary font first,
         // fall back to per-char resolution only when the char isn't covered
-        let font_data = if !family.contains(',') {
-            let primary = self.registry.resolve(family, weight, italic);
+        let (font_data, drawn_family) = if !family.contains(',') {
+            let primary = self.registry.resolve(family, weight, stretch, italic);
             if ch.is_whitespace() || primary.has_char(ch) {
-                primary
+                (primary, None)
             } else {
-                let (data, _) = self.registry.resolve_for_char(family, ch, weight, italic);
-                data
+                let (data, family) = self
+                    .registry
+                    .resolve_for_char(family, ch, weight, stretch, italic);
+                (data, Some(family))
             }
         } else {
-            let (data, _) = self.registry.resolve_for_char(family, ch, weight, italic);
-            data
+            let (data, family) = self
+                .registry
+                .resolve_for_char(family, ch, weight, stretch, italic);
+            (data, Some(family))
         };
-        match font_data {
+        let width = match font_data {
             FontData::Standard(std_font) => std_font.metrics().char_width(ch, font_size),
             FontData::Custom {
                 metrics: Some(m), ..
@@ -426,20 +752,37 @@ impl FontContext {
             FontData::Custom { metrics: None, .. } => {
                 StandardFont::Helvetica.metrics().char_width(ch, font_size)
             }
-        }
+            FontData::Variable(font) => {
+                font.with_instance(weight, stretch, |m| m.char_width(ch, font_size))
+            }
+        };
+        let drawn_family = drawn_family.as_deref().unwrap_or(family);
+        width + self.synthetic_bold_width(drawn_family, weight, stretch, italic, font_size)
     }
 
     /// Measure the width of a string in points.
+    #[allow(clippy::too_many_arguments)]
     pub fn measure_string(
         &self,
         text: &str,
         family: &str,
         weight: u32,
+        stretch: u32,
         italic: bool,
         font_size: f64,
         letter_spacing: f64,
     ) -> f64 {
-        let font_data = self.registry.resolve(family, weight, italic);
+        let font_data = self.registry.resolve(family, weight, stretch, italic);
+        // Synthetic bold widens every glyph, like extra letter spacing
+        let letter_spacing =
+            letter_spacing + self.synthetic_bold_width(family, weight, stretch, italic, font_size);
+        let measure = |m: &CustomFontMetrics| {
+            let mut width = 0.0;
+            for ch in text.chars() {
+                width += m.char_width(ch, font_size) + letter_spacing;
+            }
+            width
+        };
         match font_data {
             FontData::Standard(std_font) => {
                 std_font
@@ -448,22 +791,17 @@ impl FontContext {
             }
             FontData::Custom {
                 metrics: Some(m), ..
-            } => {
-                let mut width = 0.0;
-                for ch in text.chars() {
-                    width += m.char_width(ch, font_size) + letter_spacing;
-                }
-                width
-            }
+            } => measure(m),
             FontData::Custom { metrics: None, .. } => StandardFont::Helvetica
                 .metrics()
                 .measure_string(text, font_size, letter_spacing),
+            FontData::Variable(font) => font.with_instance(weight, stretch, measure),
         }
     }
 
     /// Resolve a font key to its font data.
-    pub fn resolve(&self, family: &str, weight: u32, italic: bool) -> &FontData {
-        self.registry.resolve(family, weight, italic)
+    pub fn resolve(&self, family: &str, weight: u32, stretch: u32, italic: bool) -> &FontData {
+        self.registry.resolve(family, weight, stretch, italic)
     }
 
     /// Access the underlying font registry.
@@ -473,27 +811,55 @@ impl FontContext {
 
     /// Access the underlying font registry mutably.
     pub fn registry_mut(&mut self) -> &mut FontRegistry {
+        // Registering a face can change which face draws a family
+        self.synthetic_styles
+            .get_mut()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .clear();
         &mut self.registry
     }
 
     /// Get the raw font data bytes for a custom font.
     /// Returns `None` for standard fonts or if the font isn't found.
-    pub fn font_data(&self, family: &str, weight: u32, italic: bool) -> Option<&[u8]> {
-        let font_data = self.registry.resolve(family, weight, italic);
+    pub fn font_data(
+        &self,
+        family: &str,
+        weight: u32,
+        stretch: u32,
+        italic: bool,
+    ) -> Option<&[u8]> {
+        let font_data = self.registry.resolve(family, weight, stretch, italic);
         match font_data {
-            FontData::Custom { data, .. } => Some(data),
+            FontData::Custom { data, .. } => Some(data.as_ref()),
+            FontData::Variable(font) => Some(&font.data),
             FontData::Standard(_) => None,
         }
     }
 
+    /// The axis coordinates to shape a variable font's text with. Empty for
+    /// static fonts.
+    pub fn variations(
+        &self,
+        family: &str,
+        weight: u32,
+        stretch: u32,
+        italic: bool,
+    ) -> Vec<Variation> {
+        match self.registry.resolve(family, weight, stretch, italic) {
+            FontData::Variable(font) => font.variations(weight, stretch),
+            _ => Vec::new(),
+        }
+    }
+
     /// Get the units-per-em for a font. Returns 1000 for standard fonts.
-    pub fn units_per_em(&self, family: &str, weight: u32, italic: bool) -> u16 {
-        let font_data = self.registry.resolve(family, weight, italic);
+    pub fn units_per_em(&self, family: &str, weight: u32, stretch: u32, italic: bool) -> u16 {
+        let font_data = self.registry.resolve(family, weight, stretch, italic);
         match font_data {
             FontData::Custom {
                 metrics: Some(m), ..
             } => m.units_per_em,
             FontData::Custom { metrics: None, .. } => 1000,
+            FontData::Variable(font) => font.metrics.units_per_em,
             FontData::Standard(_) => 1000,
         }
     }
@@ -506,54 +872,54 @@ mod tests {
     #[test]
     fn test_font_context_helvetica() {
         let ctx = FontContext::new();
-        let w = ctx.char_width(' ', "Helvetica", 400, false, 12.0);
+        let w = ctx.char_width(' ', "Helvetica", 400, 100, false, 12.0);
         assert!((w - 3.336).abs() < 0.001);
     }
 
     #[test]
     fn test_font_context_bold_wider() {
         let ctx = FontContext::new();
-        let regular = ctx.char_width('A', "Helvetica", 400, false, 12.0);
-        let bold = ctx.char_width('A', "Helvetica", 700, false, 12.0);
+        let regular = ctx.char_width('A', "Helvetica", 400, 100, false, 12.0);
+        let bold = ctx.char_width('A', "Helvetica", 700, 100, false, 12.0);
         assert!(bold > regular, "Bold A should be wider than regular A");
     }
 
     #[test]
     fn test_font_context_measure_string() {
         let ctx = FontContext::new();
-        let w = ctx.measure_string("Hello", "Helvetica", 400, false, 12.0, 0.0);
+        let w = ctx.measure_string("Hello", "Helvetica", 400, 100, false, 12.0, 0.0);
         assert!(w > 0.0);
     }
 
     #[test]
     fn test_font_context_fallback() {
         let ctx = FontContext::new();
-        let w1 = ctx.char_width('A', "Helvetica", 400, false, 12.0);
-        let w2 = ctx.char_width('A', "UnknownFont", 400, false, 12.0);
+        let w1 = ctx.char_width('A', "Helvetica", 400, 100, false, 12.0);
+        let w2 = ctx.char_width('A', "UnknownFont", 400, 100, false, 12.0);
         assert!((w1 - w2).abs() < 0.001);
     }
 
     #[test]
     fn test_font_context_weight_resolution() {
         let ctx = FontContext::new();
-        let w700 = ctx.char_width('A', "Helvetica", 700, false, 12.0);
-        let w800 = ctx.char_width('A', "Helvetica", 800, false, 12.0);
+        let w700 = ctx.char_width('A', "Helvetica", 700, 100, false, 12.0);
+        let w800 = ctx.char_width('A', "Helvetica", 800, 100, false, 12.0);
         assert!((w700 - w800).abs() < 0.001);
     }
 
     #[test]
     fn test_font_fallback_chain_first_match() {
         let ctx = FontContext::new();
-        let w1 = ctx.char_width('A', "Times", 400, false, 12.0);
-        let w2 = ctx.char_width('A', "Times, Helvetica", 400, false, 12.0);
+        let w1 = ctx.char_width('A', "Times", 400, 100, false, 12.0);
+        let w2 = ctx.char_width('A', "Times, Helvetica", 400, 100, false, 12.0);
         assert!((w1 - w2).abs() < 0.001, "Should use Times (first in chain)");
     }
 
     #[test]
     fn test_font_fallback_chain_second_match() {
         let ctx = FontContext::new();
-        let w1 = ctx.char_width('A', "Helvetica", 400, false, 12.0);
-        let w2 = ctx.char_width('A', "Missing, Helvetica", 400, false, 12.0);
+        let w1 = ctx.char_width('A', "Helvetica", 400, 100, false, 12.0);
+        let w2 = ctx.char_width('A', "Missing, Helvetica", 400, 100, false, 12.0);
         assert!((w1 - w2).abs() < 0.001, "Should fall back to Helvetica");
     }
 
@@ -563,22 +929,22 @@ mod tests {
         // When all specified families are missing, resolve_for_char tries
         // builtin Noto Sans first, then Helvetica. 'A' is in Noto Sans,
         // so we get Noto Sans metrics (not Helvetica).
-        let w = ctx.char_width('A', "Missing, AlsoMissing", 400, false, 12.0);
+        let w = ctx.char_width('A', "Missing, AlsoMissing", 400, 100, false, 12.0);
         assert!(w > 0.0, "Should still produce a valid width from fallback");
     }
 
     #[test]
     fn test_font_fallback_chain_quoted_families() {
         let ctx = FontContext::new();
-        let w1 = ctx.char_width('A', "Times", 400, false, 12.0);
-        let w2 = ctx.char_width('A', "'Times', \"Helvetica\"", 400, false, 12.0);
+        let w1 = ctx.char_width('A', "Times", 400, 100, false, 12.0);
+        let w2 = ctx.char_width('A', "'Times', \"Helvetica\"", 400, 100, false, 12.0);
         assert!((w1 - w2).abs() < 0.001, "Should strip quotes and use Times");
     }
 
     #[test]
     fn test_builtin_noto_sans_registered() {
         let registry = FontRegistry::new();
-        let font = registry.resolve("Noto Sans", 400, false);
+        let font = registry.resolve("Noto Sans", 400, 100, false);
         assert!(
             matches!(font, FontData::Custom { .. }),
             "Noto Sans should be registered as a custom font"
@@ -593,7 +959,7 @@ mod tests {
     #[test]
     fn test_builtin_noto_sans_fallback_for_cyrillic() {
         let registry = FontRegistry::new();
-        let (font, family) = registry.resolve_for_char("Helvetica", '\u{041F}', 400, false);
+        let (font, family) = registry.resolve_for_char("Helvetica", '\u{041F}', 400, 100, false);
         assert_eq!(
             family, "Noto Sans",
             "Cyrillic should fall back to Noto Sans"
@@ -601,11 +967,169 @@ mod tests {
         assert!(matches!(font, FontData::Custom { .. }));
     }
 
+    #[test]
+    fn test_register_face_uses_name_table() {
+        let mut registry = FontRegistry::new();
+        let collection = collection::tests::make_collection(&[
+            include_bytes!("../../fonts/NotoSans-Regular.ttf"),
+            include_bytes!("../../fonts/NotoSans-Bold.ttf"),
+        ]);
+        let key = registry.register_face(collection, 1).unwrap();
+        assert_eq!(
+            key,
+            FontKey {
+                family: "Noto Sans".to_string(),
+                weight: 700,
+                stretch: 100,
+                italic: false,
+            }
+        );
+        assert!(registry.register_face(b"not a font".to_vec(), 0).is_err());
+    }
+
+    #[test]
+    fn test_cloned_registry_shares_font_data() {
+        let mut registry = FontRegistry::new();
+        registry.register("Flex", 400, false, variable::tests::make_variable_font());
+        let cloned = registry.clone();
+        for family in ["Noto Sans", "Flex"] {
+            match (
+                registry.resolve(family, 400, 100, false),
+                cloned.resolve(family, 400, 100, false),
+            ) {
+                (FontData::Custom { data: a, .. }, FontData::Custom { data: b, .. }) => {
+                    assert!(Arc::ptr_eq(a, b))
+                }
+                (FontData::Variable(a), FontData::Variable(b)) => assert!(Arc::ptr_eq(a, b)),
+                _ => panic!("{} should resolve to the same kind of font", family),
+            }
+        }
+    }
+
+    #[test]
+    fn test_font_context_can_be_shared_between_threads() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<FontRegistry>();
+        assert_send_sync::<FontContext>();
+
+        let mut registry = FontRegistry::new();
+        registry.register("Flex", 400, false, variable::tests::make_variable_font());
+        let ctx = FontContext::with_registry(registry);
+        let ctx = &ctx;
+        let (regular, black) = std::thread::scope(|scope| {
+            let width =
+                |weight| scope.spawn(move || ctx.char_width('H', "Flex", weight, 100, false, 10.0));
+            let (regular, black) = (width(400), width(900));
+            (regular.join().unwrap(), black.join().unwrap())
+        });
+        assert!(black > regular);
+    }
+
+    #[test]
+    fn test_variable_font_serves_every_weight_and_width() {
+        let mut registry = FontRegistry::new();
+        registry.register("Flex", 400, false, variable::tests::make_variable_font());
+        let key = |weight, stretch| FontKey {
+            family: "Flex".to_string(),
+            weight,
+            stretch,
+            italic: false,
+        };
+        assert_eq!(registry.resolve_key("Flex", 550, 100, false), key(550, 100));
+        assert_eq!(registry.resolve_key("Flex", 950, 30, false), key(900, 50));
+        assert!(matches!(
+            registry.resolve("Flex", 300, 125, false),
+            FontData::Variable(_)
+        ));
+        let (font, family) = registry.resolve_for_char("Flex", 'H', 650, 100, false);
+        assert_eq!(family, "Flex");
+        assert!(matches!(font, FontData::Variable(_)));
+
+        let ctx = FontContext::with_registry(registry);
+        let regular = ctx.char_width('H', "Flex", 400, 100, false, 10.0);
+        let black = ctx.char_width('H', "Flex", 900, 100, false, 10.0);
+        let expected = 10.0 * 2.0 * variable::tests::WEIGHT_SHIFT as f64 / 1000.0;
+        assert!((black - regular - expected).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_static_font_stretch_falls_back_to_normal_width() {
+        let registry = FontRegistry::new();
+        let key = registry.resolve_key("Noto Sans", 700, 75, false);
+        assert_eq!(
+            (key.family.as_str(), key.weight, key.stretch),
+            ("Noto Sans", 700, 100)
+        );
+    }
+
+    #[test]
+    fn test_italic_falls_back_to_upright_face_of_family() {
+        let registry = FontRegistry::new();
+        let key = registry.resolve_key("Noto Sans", 700, 100, true);
+        assert_eq!(
+            (key.family.as_str(), key.weight, key.italic),
+            ("Noto Sans", 700, false)
+        );
+        // Families with an italic face still use it
+        assert!(registry.resolve_key("Times", 400, 100, true).italic);
+    }
+
+    #[test]
+    fn test_synthetic_styles_fill_in_missing_faces() {
+        let mut ctx = FontContext::new();
+        let regular = include_bytes!("../../fonts/NotoSans-Regular.ttf");
+        ctx.registry_mut()
+            .register("Body", 400, false, regular.to_vec());
+        assert_eq!(
+            ctx.synthetic_style("Body", 700, 100, true),
+            SyntheticStyle {
+                bold: true,
+                oblique: true
+            }
+        );
+        assert_eq!(
+            ctx.synthetic_style("Body", 500, 100, false),
+            SyntheticStyle::default()
+        );
+        assert_eq!(
+            ctx.synthetic_style("Noto Sans", 700, 100, false),
+            SyntheticStyle::default()
+        );
+
+        let plain = ctx.char_width('A', "Body", 400, 100, false, 10.0);
+        let bold = ctx.char_width('A', "Body", 700, 100, false, 10.0);
+        assert!((bold - plain - 10.0 * SYNTHETIC_BOLD_STROKE).abs() < 1e-9);
+        let word = ctx.measure_string("AA", "Body", 700, 100, false, 10.0, 0.0);
+        assert!((word - 2.0 * bold).abs() < 1e-9);
+
+        ctx.set_font_synthesis(FontSynthesis::NONE);
+        assert_eq!(
+            ctx.synthetic_style("Body", 700, 100, true),
+            SyntheticStyle::default()
+        );
+        assert_eq!(ctx.char_width('A', "Body", 700, 100, false, 10.0), plain);
+    }
+
+    #[test]
+    fn test_synthetic_styles_follow_registered_faces() {
+        let mut ctx = FontContext::new();
+        let regular = include_bytes!("../../fonts/NotoSans-Regular.ttf");
+        let bold = include_bytes!("../../fonts/NotoSans-Bold.ttf");
+        ctx.registry_mut()
+            .register("Body", 400, false, regular.to_vec());
+        assert!(ctx.synthetic_style("Body", 700, 100, false).bold);
+        // Worked out once, then a bold face arrives
+        ctx.registry_mut()
+            .register("Body", 700, false, bold.to_vec());
+        assert!(!ctx.synthetic_style("Body", 700, 100, false).bold);
+        assert_eq!(ctx.synthetic_bold_width("Body", 700, 100, false, 10.0), 0.0);
+    }
+
     #[test]
     fn test_font_fallback_single_family_unchanged() {
         let ctx = FontContext::new();
-        let w1 = ctx.char_width('A', "Courier", 400, false, 12.0);
-        let w2 = ctx.char_width('A', "Courier", 400, false, 12.0);
+        let w1 = ctx.char_width('A', "Courier", 400, 100, false, 12.0);
+        let w2 = ctx.char_width('A', "Courier", 400, 100, false, 12.0);
         assert!(
             (w1 - w2).abs() < 0.001,
             "Single family should work as before"
@@ -14,15 +14,70 @@
 //! 2. Remap old GIDs to new contiguous GIDs
 //! 3. Rebuild required TrueType tables (glyf, loca, hmtx, cmap, etc.)
 //! 4. Write a valid TrueType file with correct checksums and alignment
+//!
+//! ## Variable Fonts
+//!
+//! PDF has no notion of variation axes, so an instance of a variable font is
+//! embedded as a static font: each glyph's outline is drawn at the
+//! instance's axis coordinates and written back as a simple glyph, with
+//! advances measured the same way. Hinting is dropped, since the original
+//! instructions were written for the default outlines.
+//!
+//! ## PostScript Outlines
+//!
+//! Fonts with a `CFF ` or `CFF2` table are subset by [`subset_font`] into an
+//! OpenType file with a `CFF ` table (see [`cff`](super::cff)) and the same
+//! cmap, metrics and naming tables as a TrueType subset.
 
 use std::collections::{BTreeSet, HashMap};
 
+use ttf_parser::{OutlineBuilder, Variation};
+
+use super::cff;
+
+/// How a subset font stores its outlines, which decides how it's embedded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FontFormat {
+    /// `glyf` outlines: a `CIDFontType2` with a `FontFile2`.
+    TrueType,
+    /// `CFF ` outlines in an OpenType file: a `CIDFontType0` with a
+    /// `FontFile3` of subtype `OpenType`.
+    OpenTypeCff,
+}
+
+impl FontFormat {
+    /// The format of a font file, by the outline table it has.
+    pub fn of(face: &ttf_parser::Face) -> Self {
+        if face.tables().cff.is_some() || face.tables().cff2.is_some() {
+            FontFormat::OpenTypeCff
+        } else {
+            FontFormat::TrueType
+        }
+    }
+}
+
 /// Result of subsetting a font.
 pub struct SubsetResult {
     /// The subset TrueType file bytes.
     pub ttf_data: Vec<u8>,
     /// Maps original glyph IDs to new contiguous glyph IDs.
     pub gid_remap: HashMap<u16, u16>,
+    pub format: FontFormat,
+}
+
+/// Subset a TrueType or OpenType font, or an instance of a variable font,
+/// to the given glyph IDs.
+pub fn subset_font(
+    font_data: &[u8],
+    used_gids: &std::collections::HashSet<u16>,
+    variations: &[Variation],
+) -> Result<SubsetResult, String> {
+    let face = ttf_parser::Face::parse(font_data, 0)
+        .map_err(|e| format!("Failed to parse font: {:?}", e))?;
+    match FontFormat::of(&face) {
+        FontFormat::TrueType => subset_ttf_instance(font_data, used_gids, variations),
+        FontFormat::OpenTypeCff => subset_otf(font_data, used_gids, variations),
+    }
 }
 
 /// Subset a TrueType font to only include the given glyph IDs.
@@ -30,8 +85,22 @@ pub fn subset_ttf(
     ttf_data: &[u8],
     used_gids: &std::collections::HashSet<u16>,
 ) -> Result<SubsetResult, String> {
-    let face = ttf_parser::Face::parse(ttf_data, 0)
+    subset_ttf_instance(ttf_data, used_gids, &[])
+}
+
+/// Subset one instance of a variable font, at the given axis coordinates,
+/// into a static font. With no variations this is [`subset_ttf`].
+pub fn subset_ttf_instance(
+    ttf_data: &[u8],
+    used_gids: &std::collections::HashSet<u16>,
+    variations: &[Variation],
+) -> Result<SubsetResult, String> {
+    let mut face = ttf_parser::Face::parse(ttf_data, 0)
         .map_err(|e| format!("Failed to parse TTF: {:?}", e))?;
+    for variation in variations {
+        face.set_variation(variation.axis, variation.value);
+    }
+    let instanced = !variations.is_empty();
 
     // Always include glyph 0 (.notdef)
     let mut needed_gids: BTreeSet<u16> = BTreeSet::new();
@@ -63,9 +132,14 @@ pub fn subset_ttf(
 
     let new_num_glyphs = needed_gids.len() as u16;
 
-    // Rebuild glyf table with remapped composite references
-    let (new_glyf, new_loca_offsets) =
-        rebuild_glyf(raw_glyf, &loca_offsets, &needed_gids, &gid_remap);
+    // Rebuild glyf table with remapped composite references, or draw the
+    // instance's outlines
+    let (new_glyf, new_loca_offsets, x_mins) = if instanced {
+        instance_glyf(&face, &needed_gids)
+    } else {
+        let (glyf, offsets) = rebuild_glyf(raw_glyf, &loca_offsets, &needed_gids, &gid_remap);
+        (glyf, offsets, Vec::new())
+    };
 
     // Determine loca format based on glyf size
     let new_loca_format: i16 = if new_glyf.len() > 0x1FFFE { 1 } else { 0 };
@@ -75,31 +149,14 @@ pub fn subset_ttf(
     let raw_hmtx = find_table(ttf_data, b"hmtx").ok_or("Missing hmtx table")?;
     let raw_hhea = find_table(ttf_data, b"hhea").ok_or("Missing hhea table")?;
     let num_h_metrics = read_u16(raw_hhea, 34) as usize;
-    let new_hmtx = rebuild_hmtx(raw_hmtx, &needed_gids, num_h_metrics);
+    let new_hmtx = if instanced {
+        instance_hmtx(&face, &needed_gids, &x_mins)
+    } else {
+        rebuild_hmtx(raw_hmtx, &needed_gids, num_h_metrics)
+    };
 
     // Build minimal cmap (Format 4)
-    // We need the original char→gid mapping — invert through the face
-    let mut char_to_new_gid: Vec<(u16, u16)> = Vec::new();
-    for &old_gid in &needed_gids {
-        if old_gid == 0 {
-            continue;
-        }
-        // Search for Unicode codepoint that maps to this GID
-        // This is O(n) per glyph but subset sizes are small
-        for code in 0u32..=0xFFFF {
-            if let Some(ch) = char::from_u32(code) {
-                if let Some(gid) = face.glyph_index(ch) {
-                    if gid.0 == old_gid {
-                        if let Some(&new_gid) = gid_remap.get(&old_gid) {
-                            char_to_new_gid.push((code as u16, new_gid));
-                        }
-                        break;
-                    }
-                }
-            }
-        }
-    }
-    let new_cmap = build_cmap_format4(&char_to_new_gid);
+    let new_cmap = build_subset_cmap(&face, &needed_gids, &gid_remap);
 
     // Copy or rebuild remaining required tables
     let new_head = rebuild_head(head, new_loca_format);
@@ -115,13 +172,18 @@ pub fn subset_ttf(
         .map(|t| t.to_vec())
         .unwrap_or_else(|| build_minimal_name(&face));
 
-    // Copy OS/2 table verbatim if present
-    let new_os2 = find_table(ttf_data, b"OS/2").map(|t| t.to_vec());
+    // Copy OS/2 table verbatim if present, with an instance's weight
+    let new_os2 = find_table(ttf_data, b"OS/2").map(|t| instance_os2(t, variations));
 
-    // Copy hinting tables verbatim if present
-    let cvt_data = find_table(ttf_data, b"cvt ").map(|t| t.to_vec());
-    let fpgm_data = find_table(ttf_data, b"fpgm").map(|t| t.to_vec());
-    let prep_data = find_table(ttf_data, b"prep").map(|t| t.to_vec());
+    // Copy hinting tables verbatim if present (not for instances)
+    let hinting = |tag: &[u8; 4]| {
+        find_table(ttf_data, tag)
+            .filter(|_| !instanced)
+            .map(|t| t.to_vec())
+    };
+    let cvt_data = hinting(b"cvt ");
+    let fpgm_data = hinting(b"fpgm");
+    let prep_data = hinting(b"prep");
 
     // Assemble the final TrueType file
     let mut tables: Vec<(u32, Vec<u8>)> = Vec::new();
@@ -155,9 +217,142 @@ pub fn subset_ttf(
     Ok(SubsetResult {
         ttf_data: output,
         gid_remap,
+        format: FontFormat::TrueType,
+    })
+}
+
+/// Subset a font with PostScript outlines into an OpenType file with a
+/// `CFF ` table.
+fn subset_otf(
+    font_data: &[u8],
+    used_gids: &std::collections::HashSet<u16>,
+    variations: &[Variation],
+) -> Result<SubsetResult, String> {
+    let mut face = ttf_parser::Face::parse(font_data, 0)
+        .map_err(|e| format!("Failed to parse font: {:?}", e))?;
+    for variation in variations {
+        face.set_variation(variation.axis, variation.value);
+    }
+
+    let num_glyphs = face.number_of_glyphs();
+    let mut needed_gids: BTreeSet<u16> = BTreeSet::new();
+    needed_gids.insert(0);
+    needed_gids.extend(used_gids.iter().filter(|&&gid| gid < num_glyphs));
+    let gid_remap: HashMap<u16, u16> = needed_gids
+        .iter()
+        .enumerate()
+        .map(|(new_gid, &old_gid)| (old_gid, new_gid as u16))
+        .collect();
+    let new_num_glyphs = needed_gids.len() as u16;
+
+    // CFF charstrings are copied; CFF2 charstrings and instances are drawn
+    let name = face_postscript_name(&face);
+    let new_cff = match find_table(font_data, b"CFF ") {
+        Some(table) if variations.is_empty() => cff::subset_cff(table, &needed_gids)
+            .unwrap_or_else(|_| cff::cff_from_outlines(&face, &needed_gids, &name)),
+        _ => cff::cff_from_outlines(&face, &needed_gids, &name),
+    };
+
+    let head = find_table(font_data, b"head").ok_or("Missing head table")?;
+    let raw_hhea = find_table(font_data, b"hhea").ok_or("Missing hhea table")?;
+    let x_mins: Vec<i16> = needed_gids
+        .iter()
+        .map(|&gid| {
+            face.glyph_bounding_box(ttf_parser::GlyphId(gid))
+                .map_or(0, |bbox| bbox.x_min)
+        })
+        .collect();
+
+    // maxp version 0.5: just the glyph count, as CFF fonts use
+    let mut new_maxp = vec![0u8; 6];
+    write_u32(&mut new_maxp, 0, 0x00005000);
+    write_u16(&mut new_maxp, 4, new_num_glyphs);
+
+    let mut tables: Vec<(u32, Vec<u8>)> = vec![
+        (tag_u32(b"CFF "), new_cff),
+        (
+            tag_u32(b"cmap"),
+            build_subset_cmap(&face, &needed_gids, &gid_remap),
+        ),
+        (tag_u32(b"head"), rebuild_head(head, 0)),
+        (tag_u32(b"hhea"), rebuild_hhea(raw_hhea, new_num_glyphs)),
+        (
+            tag_u32(b"hmtx"),
+            instance_hmtx(&face, &needed_gids, &x_mins),
+        ),
+        (tag_u32(b"maxp"), new_maxp),
+        (
+            tag_u32(b"name"),
+            find_table(font_data, b"name")
+                .map(|t| t.to_vec())
+                .unwrap_or_else(|| build_minimal_name(&face)),
+        ),
+        (tag_u32(b"post"), build_post_format3()),
+    ];
+    if let Some(os2) = find_table(font_data, b"OS/2") {
+        tables.push((tag_u32(b"OS/2"), instance_os2(os2, variations)));
+    }
+    tables.sort_by_key(|(tag, _)| *tag);
+
+    Ok(SubsetResult {
+        ttf_data: write_sfnt(tag_u32(b"OTTO"), &mut tables),
+        gid_remap,
+        format: FontFormat::OpenTypeCff,
     })
 }
 
+/// The font's PostScript name, for the subset's `CFF ` table.
+fn face_postscript_name(face: &ttf_parser::Face) -> String {
+    face.names()
+        .into_iter()
+        .find(|n| n.name_id == ttf_parser::name_id::POST_SCRIPT_NAME)
+        .and_then(|n| n.to_string())
+        .unwrap_or_else(|| "Subset".to_string())
+}
+
+/// A Format 4 cmap mapping the subset's glyphs from the characters the
+/// original font maps to them.
+fn build_subset_cmap(
+    face: &ttf_parser::Face,
+    needed_gids: &BTreeSet<u16>,
+    gid_remap: &HashMap<u16, u16>,
+) -> Vec<u8> {
+    // We need the original char→gid mapping — invert through the face
+    let mut char_to_new_gid: Vec<(u16, u16)> = Vec::new();
+    for &old_gid in needed_gids {
+        if old_gid == 0 {
+            continue;
+        }
+        // Search for Unicode codepoint that maps to this GID
+        // This is O(n) per glyph but subset sizes are small
+        for code in 0u32..=0xFFFF {
+            if let Some(ch) = char::from_u32(code) {
+                if let Some(gid) = face.glyph_index(ch) {
+                    if gid.0 == old_gid {
+                        if let Some(&new_gid) = gid_remap.get(&old_gid) {
+                            char_to_new_gid.push((code as u16, new_gid));
+                        }
+                        break;
+                    }
+                }
+            }
+        }
+    }
+    build_cmap_format4(&char_to_new_gid)
+}
+
+/// A copy of an OS/2 table with an instance's weight class.
+fn instance_os2(os2: &[u8], variations: &[Variation]) -> Vec<u8> {
+    let mut os2 = os2.to_vec();
+    let weight = variations
+        .iter()
+        .find(|v| v.axis == ttf_parser::Tag::from_bytes(b"wght"));
+    if let (Some(weight), true) = (weight, os2.len() >= 6) {
+        write_u16(&mut os2, 4, weight.value.round() as u16);
+    }
+    os2
+}
+
 // ─── Table Locating ─────────────────────────────────────────────
 
 fn find_table<'a>(data: &'a [u8], tag: &[u8; 4]) -> Option<&'a [u8]> {
@@ -326,6 +521,131 @@ fn rebuild_glyf(
     (new_glyf, new_offsets)
 }
 
+/// Draw each glyph at the face's variation coordinates and encode it as a
+/// simple glyph. Also returns each glyph's xMin, its left side bearing.
+fn instance_glyf(
+    face: &ttf_parser::Face,
+    needed_gids: &BTreeSet<u16>,
+) -> (Vec<u8>, Vec<u32>, Vec<i16>) {
+    let mut new_glyf: Vec<u8> = Vec::new();
+    let mut new_offsets: Vec<u32> = Vec::new();
+    let mut x_mins: Vec<i16> = Vec::new();
+
+    for &old_gid in needed_gids {
+        new_offsets.push(new_glyf.len() as u32);
+
+        let mut outline = GlyphOutline::default();
+        face.outline_glyph(ttf_parser::GlyphId(old_gid), &mut outline);
+        outline.close();
+        if outline.contours.is_empty() {
+            // Empty glyph
+            x_mins.push(0);
+            continue;
+        }
+
+        let (glyph, x_min) = outline.encode();
+        new_glyf.extend_from_slice(&glyph);
+        x_mins.push(x_min);
+
+        while !new_glyf.len().is_multiple_of(4) {
+            new_glyf.push(0);
+        }
+    }
+
+    new_offsets.push(new_glyf.len() as u32);
+
+    (new_glyf, new_offsets, x_mins)
+}
+
+/// The contours of one glyph as TrueType points: (x, y, on curve).
+#[derive(Default)]
+struct GlyphOutline {
+    contours: Vec<Vec<(i16, i16, bool)>>,
+    current: Vec<(i16, i16, bool)>,
+}
+
+impl GlyphOutline {
+    fn point(&mut self, x: f32, y: f32, on_curve: bool) {
+        self.current
+            .push((x.round() as i16, y.round() as i16, on_curve));
+    }
+
+    fn last(&self) -> (f32, f32) {
+        self.current
+            .last()
+            .map_or((0.0, 0.0), |&(x, y, _)| (x as f32, y as f32))
+    }
+
+    /// Encode as a simple glyph without instructions, with every
+    /// coordinate written as a word. Returns the glyph and its xMin.
+    fn encode(&self) -> (Vec<u8>, i16) {
+        let points: Vec<&(i16, i16, bool)> = self.contours.iter().flatten().collect();
+        let x_min = points.iter().map(|p| p.0).min().unwrap_or(0);
+        let y_min = points.iter().map(|p| p.1).min().unwrap_or(0);
+        let x_max = points.iter().map(|p| p.0).max().unwrap_or(0);
+        let y_max = points.iter().map(|p| p.1).max().unwrap_or(0);
+
+        let mut data = Vec::new();
+        data.extend_from_slice(&(self.contours.len() as i16).to_be_bytes());
+        for value in [x_min, y_min, x_max, y_max] {
+            data.extend_from_slice(&value.to_be_bytes());
+        }
+        let mut end = 0;
+        for contour in &self.contours {
+            end += contour.len();
+            data.extend_from_slice(&((end - 1) as u16).to_be_bytes());
+        }
+        data.extend_from_slice(&0u16.to_be_bytes()); // instructionLength
+        for &&(_, _, on_curve) in &points {
+            data.push(on_curve as u8);
+        }
+        let (mut prev_x, mut prev_y) = (0i16, 0i16);
+        for &&(x, _, _) in &points {
+            data.extend_from_slice(&x.wrapping_sub(prev_x).to_be_bytes());
+            prev_x = x;
+        }
+        for &&(_, y, _) in &points {
+            data.extend_from_slice(&y.wrapping_sub(prev_y).to_be_bytes());
+            prev_y = y;
+        }
+        (data, x_min)
+    }
+}
+
+impl OutlineBuilder for GlyphOutline {
+    fn move_to(&mut self, x: f32, y: f32) {
+        self.close();
+        self.point(x, y, true);
+    }
+
+    fn line_to(&mut self, x: f32, y: f32) {
+        self.point(x, y, true);
+    }
+
+    fn quad_to(&mut self, x1: f32, y1: f32, x: f32, y: f32) {
+        self.point(x1, y1, false);
+        self.point(x, y, true);
+    }
+
+    fn curve_to(&mut self, x1: f32, y1: f32, x2: f32, y2: f32, x: f32, y: f32) {
+        // glyf outlines are quadratic; approximate any cubic with one quad
+        let (x0, y0) = self.last();
+        let cx = (3.0 * (x1 + x2) - x0 - x) / 4.0;
+        let cy = (3.0 * (y1 + y2) - y0 - y) / 4.0;
+        self.quad_to(cx, cy, x, y);
+    }
+
+    fn close(&mut self) {
+        // The closing point repeats the first one, which glyf leaves implicit
+        if self.current.len() > 1 && self.current.first() == self.current.last() {
+            self.current.pop();
+        }
+        if !self.current.is_empty() {
+            self.contours.push(std::mem::take(&mut self.current));
+        }
+    }
+}
+
 fn rewrite_composite_gids(glyph_data: &mut [u8], gid_remap: &HashMap<u16, u16>) {
     let mut pos = 10; // skip header
 
@@ -412,6 +732,19 @@ fn rebuild_hmtx(hmtx: &[u8], needed_gids: &BTreeSet<u16>, num_h_metrics: usize)
     data
 }
 
+/// Horizontal metrics at the face's variation coordinates.
+fn instance_hmtx(face: &ttf_parser::Face, needed_gids: &BTreeSet<u16>, x_mins: &[i16]) -> Vec<u8> {
+    let mut data = Vec::new();
+    for (&gid, &x_min) in needed_gids.iter().zip(x_mins) {
+        let advance = face
+            .glyph_hor_advance(ttf_parser::GlyphId(gid))
+            .unwrap_or(0);
+        data.extend_from_slice(&advance.to_be_bytes());
+        data.extend_from_slice(&x_min.to_be_bytes());
+    }
+    data
+}
+
 fn build_cmap_format4(char_to_gid: &[(u16, u16)]) -> Vec<u8> {
     // Build a cmap table with a single Format 4 subtable
     // Platform 3 (Windows), Encoding 1 (Unicode BMP)
@@ -611,6 +944,12 @@ fn build_minimal_name(face: &ttf_parser::Face) -> Vec<u8> {
 // ─── TrueType File Writer ───────────────────────────────────────
 
 fn write_ttf_file(tables: &mut [(u32, Vec<u8>)]) -> Vec<u8> {
+    write_sfnt(0x00010000, tables)
+}
+
+/// Write a font file: `sfnt_version` is 0x00010000 for TrueType outlines,
+/// 'OTTO' for CFF.
+fn write_sfnt(sfnt_version: u32, tables: &mut [(u32, Vec<u8>)]) -> Vec<u8> {
     let num_tables = tables.len() as u16;
     let entry_selector = if num_tables > 0 {
         (num_tables as f64).log2().floor() as u16
@@ -622,7 +961,7 @@ fn write_ttf_file(tables: &mut [(u32, Vec<u8>)]) -> Vec<u8> {
 
     // Offset table (12 bytes)
     let mut output: Vec<u8> = Vec::new();
-    output.extend_from_slice(&0x00010000u32.to_be_bytes()); // sfVersion (TrueType)
+    output.extend_from_slice(&sfnt_version.to_be_bytes());
     output.extend_from_slice(&num_tables.to_be_bytes());
     output.extend_from_slice(&search_range.to_be_bytes());
     output.extend_from_slice(&entry_selector.to_be_bytes());
@@ -761,9 +1100,41 @@ fn tag_u32(tag: &[u8; 4]) -> u32 {
 // ─── Tests ──────────────────────────────────────────────────────
 
 #[cfg(test)]
-mod tests {
+pub(crate) mod tests {
     use super::*;
 
+    fn read_tables(font: &[u8]) -> Vec<(u32, Vec<u8>)> {
+        (0..read_u16(font, 4) as usize)
+            .map(|i| {
+                let record = 12 + 16 * i;
+                let offset = read_u32(font, record + 8) as usize;
+                let length = read_u32(font, record + 12) as usize;
+                (
+                    read_u32(font, record),
+                    font[offset..offset + length].to_vec(),
+                )
+            })
+            .collect()
+    }
+
+    /// Copy `font` with extra tables added, replacing any with the same tag.
+    pub(crate) fn add_tables(font: &[u8], extra: Vec<([u8; 4], Vec<u8>)>) -> Vec<u8> {
+        let mut tables = read_tables(font);
+        tables.retain(|(tag, _)| !extra.iter().any(|(t, _)| tag_u32(t) == *tag));
+        tables.extend(extra.into_iter().map(|(tag, data)| (tag_u32(&tag), data)));
+        tables.sort_by_key(|(tag, _)| *tag);
+        write_ttf_file(&mut tables)
+    }
+
+    /// Copy `font` without the given tables, as an OpenType file with CFF
+    /// outlines when `otf` is set.
+    pub(crate) fn remove_tables(font: &[u8], remove: &[&[u8; 4]], otf: bool) -> Vec<u8> {
+        let mut tables = read_tables(font);
+        tables.retain(|(tag, _)| !remove.iter().any(|t| tag_u32(t) == *tag));
+        let sfnt_version = if otf { tag_u32(b"OTTO") } else { 0x00010000 };
+        write_sfnt(sfnt_version, &mut tables)
+    }
+
     #[test]
     fn test_tag_u32() {
         assert_eq!(tag_u32(b"glyf"), 0x676C7966);
@@ -831,4 +1202,36 @@ mod tests {
         let subtable_offset = read_u32(&cmap, 8) as usize;
         assert_eq!(read_u16(&cmap, subtable_offset), 4);
     }
+
+    #[test]
+    fn test_subset_instance_draws_varied_outlines() {
+        use crate::font::variable::tests::{make_variable_font, WEIGHT_SHIFT};
+
+        let font = make_variable_font();
+        let face = ttf_parser::Face::parse(&font, 0).unwrap();
+        let h = face.glyph_index('H').unwrap();
+        let used: std::collections::HashSet<u16> = [h.0].into_iter().collect();
+        let bold = [Variation {
+            axis: ttf_parser::Tag::from_bytes(b"wght"),
+            value: 900.0,
+        }];
+
+        let regular = subset_ttf_instance(&font, &used, &[]).unwrap();
+        let instance = subset_ttf_instance(&font, &used, &bold).unwrap();
+        let regular_face = ttf_parser::Face::parse(&regular.ttf_data, 0).unwrap();
+        let instance_face = ttf_parser::Face::parse(&instance.ttf_data, 0).unwrap();
+        assert!(!instance_face.is_variable());
+
+        let new_gid = ttf_parser::GlyphId(instance.gid_remap[&h.0]);
+        assert_eq!(
+            instance_face.glyph_hor_advance(new_gid).unwrap(),
+            regular_face.glyph_hor_advance(new_gid).unwrap() + 2 * WEIGHT_SHIFT as u16
+        );
+        let regular_box = regular_face.glyph_bounding_box(new_gid).unwrap();
+        let instance_box = instance_face.glyph_bounding_box(new_gid).unwrap();
+        assert_eq!(instance_box.x_min, regular_box.x_min + WEIGHT_SHIFT);
+        assert_eq!(instance_box.y_max, regular_box.y_max);
+        assert_eq!(instance_face.weight().to_number(), 900);
+        assert!(find_table(&instance.ttf_data, b"fpgm").is_none());
+    }
 }
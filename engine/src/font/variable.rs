@@ -0,0 +1,338 @@
+//! # Variable Fonts
+//!
+//! A variable font stores one set of outlines plus deltas (in `gvar`, or
+//! inside `CFF2` charstrings) that move them along the axes its `fvar`
+//! table declares, so a single file covers a whole range of weights and
+//! widths. A registered variable
+//! font serves every `font_weight` and `font_stretch` its `wght` and `wdth`
+//! axes reach; requests outside an axis are clamped to it.
+//!
+//! Each weight and width a document uses is an instance of the font. Its
+//! metrics are measured the first time it's used, its text is shaped with
+//! the axes set, and the PDF embeds a static copy of its outlines (see
+//! [`subset_font`](super::subset::subset_font)).
+
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock, RwLockReadGuard};
+
+use ttf_parser::{Tag, Variation};
+
+use super::{stretch_of, CustomFontMetrics};
+
+const WEIGHT_AXIS: Tag = Tag::from_bytes(b"wght");
+const WIDTH_AXIS: Tag = Tag::from_bytes(b"wdth");
+
+/// The range of one variation axis.
+#[derive(Debug, Clone, Copy)]
+struct AxisRange {
+    min: f32,
+    max: f32,
+}
+
+impl AxisRange {
+    fn clamp(&self, value: u32) -> u32 {
+        (value as f32).clamp(self.min, self.max).round() as u32
+    }
+}
+
+/// A variable font and the instances of it measured so far.
+#[derive(Debug)]
+pub struct VariableFont {
+    pub data: Arc<[u8]>,
+    /// Metrics of the default instance. Glyph coverage and units per em
+    /// are the same for every instance.
+    pub metrics: CustomFontMetrics,
+    weight_axis: Option<AxisRange>,
+    width_axis: Option<AxisRange>,
+    /// The weight and width of the default instance, used for a request
+    /// along an axis the font doesn't have.
+    default_weight: u32,
+    default_stretch: u32,
+    /// Metrics of each instance measured so far, by weight and width.
+    /// Behind a lock so a registry can be shared between threads.
+    instances: RwLock<HashMap<(u32, u32), CustomFontMetrics>>,
+}
+
+impl Clone for VariableFont {
+    fn clone(&self) -> Self {
+        Self {
+            data: self.data.clone(),
+            metrics: self.metrics.clone(),
+            weight_axis: self.weight_axis,
+            width_axis: self.width_axis,
+            default_weight: self.default_weight,
+            default_stretch: self.default_stretch,
+            instances: RwLock::new(self.read_instances().clone()),
+        }
+    }
+}
+
+impl VariableFont {
+    /// Parse `data` as a variable font: TrueType outlines with `gvar`
+    /// deltas, or `CFF2` outlines. Returns `None` for static fonts.
+    pub fn parse(data: &[u8]) -> Option<Self> {
+        let face = ttf_parser::Face::parse(data, 0).ok()?;
+        let tables = face.tables();
+        let varies = (tables.glyf.is_some() && tables.gvar.is_some()) || tables.cff2.is_some();
+        if !face.is_variable() || !varies {
+            return None;
+        }
+        let axis = |tag: Tag| {
+            face.variation_axes()
+                .into_iter()
+                .find(|axis| axis.tag == tag)
+                .map(|axis| {
+                    (
+                        axis.def_value,
+                        AxisRange {
+                            min: axis.min_value,
+                            max: axis.max_value,
+                        },
+                    )
+                })
+        };
+        let weight = axis(WEIGHT_AXIS);
+        let width = axis(WIDTH_AXIS);
+        Some(Self {
+            data: data.into(),
+            metrics: CustomFontMetrics::from_font_data(data)?,
+            weight_axis: weight.map(|(_, range)| range),
+            width_axis: width.map(|(_, range)| range),
+            default_weight: weight
+                .map(|(default, _)| default.round() as u32)
+                .unwrap_or(face.weight().to_number() as u32),
+            default_stretch: width
+                .map(|(default, _)| default.round() as u32)
+                .unwrap_or_else(|| stretch_of(face.width())),
+            instances: RwLock::new(HashMap::new()),
+        })
+    }
+
+    /// The weight and width of the default instance.
+    pub fn default_instance(&self) -> (u32, u32) {
+        (self.default_weight, self.default_stretch)
+    }
+
+    /// The weight and width of the instance that serves a request: each
+    /// clamped to its axis, or the default instance's along a missing axis.
+    pub fn instance(&self, weight: u32, stretch: u32) -> (u32, u32) {
+        (
+            self.weight_axis
+                .map_or(self.default_weight, |axis| axis.clamp(weight)),
+            self.width_axis
+                .map_or(self.default_stretch, |axis| axis.clamp(stretch)),
+        )
+    }
+
+    /// The axis coordinates of the instance that serves a request.
+    pub fn variations(&self, weight: u32, stretch: u32) -> Vec<Variation> {
+        let (weight, stretch) = self.instance(weight, stretch);
+        let mut variations = Vec::new();
+        if self.weight_axis.is_some() {
+            variations.push(Variation {
+                axis: WEIGHT_AXIS,
+                value: weight as f32,
+            });
+        }
+        if self.width_axis.is_some() {
+            variations.push(Variation {
+                axis: WIDTH_AXIS,
+                value: stretch as f32,
+            });
+        }
+        variations
+    }
+
+    /// Run `f` with the metrics of the instance that serves a request,
+    /// measuring the instance first if it hasn't been used yet.
+    pub fn with_instance<R>(
+        &self,
+        weight: u32,
+        stretch: u32,
+        f: impl FnOnce(&CustomFontMetrics) -> R,
+    ) -> R {
+        let instance = self.instance(weight, stretch);
+        if instance == self.default_instance() {
+            return f(&self.metrics);
+        }
+        if let Some(metrics) = self.read_instances().get(&instance) {
+            return f(metrics);
+        }
+        let measured =
+            CustomFontMetrics::from_instance(&self.data, &self.variations(weight, stretch))
+                .unwrap_or_else(|| self.metrics.clone());
+        let mut instances = self
+            .instances
+            .write()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        f(instances.entry(instance).or_insert(measured))
+    }
+
+    fn read_instances(&self) -> RwLockReadGuard<'_, HashMap<(u32, u32), CustomFontMetrics>> {
+        self.instances
+            .read()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    const REGULAR: &[u8] = include_bytes!("../../fonts/NotoSans-Regular.ttf");
+
+    /// How far the `wght` axis moves outlines at weight 900, in font units.
+    /// Advances grow by twice as much.
+    pub(crate) const WEIGHT_SHIFT: i16 = 40;
+    /// How far the `wdth` axis moves outlines at 200% width.
+    pub(crate) const WIDTH_SHIFT: i16 = 60;
+
+    /// Noto Sans with a `wght` axis (100-900, default 400) and a `wdth` axis
+    /// (50-200, default 100). Raising either axis moves the outlines of the
+    /// printable ASCII glyphs right and widens their advances by twice as
+    /// much; narrowing `wdth` does the opposite at half the rate. Composite
+    /// glyphs don't vary.
+    pub(crate) fn make_variable_font() -> Vec<u8> {
+        let face = ttf_parser::Face::parse(REGULAR, 0).unwrap();
+        let num_glyphs = face.number_of_glyphs();
+        let mut varied = vec![None; num_glyphs as usize];
+        for ch in ' '..='~' {
+            if let Some(gid) = face.glyph_index(ch) {
+                varied[gid.0 as usize] = simple_glyph_points(&face, gid.0);
+            }
+        }
+
+        let mut fvar = Vec::new();
+        for value in [1u16, 0, 16, 2, 2, 20, 0, 12] {
+            fvar.extend_from_slice(&value.to_be_bytes());
+        }
+        for (tag, min, default, max) in [(b"wght", 100, 400, 900), (b"wdth", 50, 100, 200)] {
+            fvar.extend_from_slice(tag);
+            for value in [min, default, max] {
+                fvar.extend_from_slice(&((value as u32) << 16).to_be_bytes());
+            }
+            fvar.extend_from_slice(&[0, 0, 1, 0]);
+        }
+
+        // One tuple per axis direction: (peak coordinates, outline shift)
+        let tuples: [([i16; 2], i16); 3] = [
+            ([0x4000, 0], WEIGHT_SHIFT),
+            ([0, 0x4000], WIDTH_SHIFT),
+            ([0, -0x4000], -WIDTH_SHIFT / 2),
+        ];
+        let mut glyph_data = Vec::new();
+        let mut offsets = vec![0u32];
+        for gid in 0..num_glyphs {
+            if let Some(points) = varied[gid as usize] {
+                let mut serialized = Vec::new();
+                let mut headers = Vec::new();
+                for (peak, shift) in tuples {
+                    let start = serialized.len();
+                    // Private point numbers: all points
+                    serialized.push(0);
+                    let mut dx = vec![shift; points];
+                    // Phantom points: origin, advance, then two vertical ones
+                    dx.extend_from_slice(&[0, 2 * shift, 0, 0]);
+                    pack_deltas(&dx, &mut serialized);
+                    pack_deltas(&vec![0; points + 4], &mut serialized);
+                    headers.extend_from_slice(&((serialized.len() - start) as u16).to_be_bytes());
+                    headers.extend_from_slice(&0xA000u16.to_be_bytes());
+                    for coord in peak {
+                        headers.extend_from_slice(&coord.to_be_bytes());
+                    }
+                }
+                glyph_data.extend_from_slice(&(tuples.len() as u16).to_be_bytes());
+                glyph_data.extend_from_slice(&((4 + headers.len()) as u16).to_be_bytes());
+                glyph_data.extend_from_slice(&headers);
+                glyph_data.extend_from_slice(&serialized);
+            }
+            offsets.push(glyph_data.len() as u32);
+        }
+
+        let data_offset = 20 + 4 * offsets.len() as u32;
+        let mut gvar = Vec::new();
+        gvar.extend_from_slice(&[0, 1, 0, 0, 0, 2, 0, 0]);
+        gvar.extend_from_slice(&data_offset.to_be_bytes());
+        gvar.extend_from_slice(&num_glyphs.to_be_bytes());
+        gvar.extend_from_slice(&1u16.to_be_bytes());
+        gvar.extend_from_slice(&data_offset.to_be_bytes());
+        for offset in offsets {
+            gvar.extend_from_slice(&offset.to_be_bytes());
+        }
+        gvar.extend_from_slice(&glyph_data);
+
+        super::super::subset::tests::add_tables(REGULAR, vec![(*b"fvar", fvar), (*b"gvar", gvar)])
+    }
+
+    /// The number of points in a glyph's outline, or `None` for a
+    /// composite glyph, which has no points of its own.
+    fn simple_glyph_points(face: &ttf_parser::Face, gid: u16) -> Option<usize> {
+        let table = |tag: &[u8; 4]| face.raw_face().table(Tag::from_bytes(tag)).unwrap();
+        let (glyf, loca) = (table(b"glyf"), table(b"loca"));
+        let long = table(b"head")[51] == 1;
+        let offset = |i: usize| {
+            if long {
+                u32::from_be_bytes(loca[i * 4..i * 4 + 4].try_into().unwrap()) as usize
+            } else {
+                u16::from_be_bytes(loca[i * 2..i * 2 + 2].try_into().unwrap()) as usize * 2
+            }
+        };
+        let (start, end) = (offset(gid as usize), offset(gid as usize + 1));
+        if start == end {
+            return Some(0);
+        }
+        let contours = i16::from_be_bytes([glyf[start], glyf[start + 1]]);
+        if contours < 0 {
+            return None;
+        }
+        let last_end = start + 10 + 2 * (contours as usize - 1);
+        Some(u16::from_be_bytes([glyf[last_end], glyf[last_end + 1]]) as usize + 1)
+    }
+
+    fn pack_deltas(deltas: &[i16], out: &mut Vec<u8>) {
+        for run in deltas.chunks(64) {
+            if run.iter().all(|&d| d == 0) {
+                out.push(0x80 | (run.len() - 1) as u8);
+            } else {
+                out.push(0x40 | (run.len() - 1) as u8);
+                for d in run {
+                    out.extend_from_slice(&d.to_be_bytes());
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_static_font_is_not_variable() {
+        assert!(VariableFont::parse(REGULAR).is_none());
+    }
+
+    #[test]
+    fn test_instances_clamp_to_axes() {
+        let font = VariableFont::parse(&make_variable_font()).unwrap();
+        assert_eq!(font.default_instance(), (400, 100));
+        assert_eq!(font.instance(650, 100), (650, 100));
+        assert_eq!(font.instance(1000, 20), (900, 50));
+        let variations = font.variations(550, 125);
+        assert_eq!(variations.len(), 2);
+        assert_eq!(variations[0].value, 550.0);
+        assert_eq!(variations[1].value, 125.0);
+    }
+
+    #[test]
+    fn test_instance_metrics_follow_axes() {
+        let font = VariableFont::parse(&make_variable_font()).unwrap();
+        let advance =
+            |weight, stretch| font.with_instance(weight, stretch, |m| m.advance_widths[&'H']);
+        let regular = advance(400, 100);
+        assert_eq!(advance(900, 100), regular + 2 * WEIGHT_SHIFT as u16);
+        assert_eq!(advance(650, 100), regular + WEIGHT_SHIFT as u16);
+        assert_eq!(advance(400, 200), regular + 2 * WIDTH_SHIFT as u16);
+        assert_eq!(advance(400, 50), regular - WIDTH_SHIFT as u16);
+        // Glyphs outside the varied range keep their advance
+        assert_eq!(
+            font.with_instance(900, 100, |m| m.advance_widths[&'é']),
+            font.metrics.advance_widths[&'é']
+        );
+    }
+}
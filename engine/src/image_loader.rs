@@ -1,14 +1,32 @@
 //! # Image Loading and Decoding
 //!
-//! Loads images from file paths, data URIs, or raw base64 strings and prepares
-//! them for PDF embedding. JPEG images pass through without re-encoding
-//! (the PDF spec supports DCTDecode natively). PNG images are decoded to RGB
-//! pixels with a separate alpha channel for SMask transparency.
+//! Loads images from data URIs, raw base64 strings, or references read
+//! through a [`ResourceResolver`], and prepares them for PDF embedding. JPEG
+//! images (RGB, grayscale or CMYK) pass through without re-encoding (the PDF
+//! spec supports DCTDecode natively). Palette PNGs keep their palette and
+//! are embedded as indexed images. Other PNGs, WebP, GIF (the first frame),
+//! BMP and TIFF images are decoded to RGB pixels with a separate alpha
+//! channel for SMask transparency.
+//!
+//! An [`ImagePolicy`] can shrink images before embedding: images with more
+//! pixels than the size they're drawn at needs are resampled down to a
+//! maximum resolution (except CMYK JPEGs, which keep their pixels), and
+//! opaque photographs can be recompressed as JPEG. Images with few colors,
+//! such as logos, charts and screenshots, stay lossless, since JPEG blurs
+//! their edges and seldom makes them smaller.
 
+use std::borrow::Cow;
+use std::collections::HashSet;
 use std::io::Cursor;
 
+use image::imageops::FilterType;
+use image::{ColorType, DynamicImage, GrayImage, RgbImage};
+use serde::{Deserialize, Serialize};
+
+use crate::resource::{read_source, ResourceResolver};
+
 /// A fully decoded/loaded image ready for PDF embedding.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct LoadedImage {
     pub pixel_data: ImagePixelData,
     pub width_px: u32,
@@ -16,7 +34,7 @@ pub struct LoadedImage {
 }
 
 /// The pixel data in a format the PDF serializer can consume directly.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum ImagePixelData {
     /// Raw JPEG bytes — embed directly with DCTDecode.
     Jpeg {
@@ -30,30 +48,78 @@ pub enum ImagePixelData {
         /// width * height bytes (grayscale alpha). None if fully opaque.
         alpha: Option<Vec<u8>>,
     },
+    /// Palette indices, as read from a palette PNG — embed as an /Indexed
+    /// image.
+    Indexed {
+        /// RGB triplets, up to 256 of them
+        palette: Vec<u8>,
+        /// Bits per index: 1, 2, 4 or 8
+        bits_per_component: u8,
+        /// Indices packed `bits_per_component` to an index, each row
+        /// starting on a byte boundary
+        indices: Vec<u8>,
+        /// width * height bytes (grayscale alpha), from the palette's
+        /// transparency. None if fully opaque.
+        alpha: Option<Vec<u8>>,
+    },
 }
 
 /// JPEG color space for the PDF /ColorSpace entry.
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum JpegColorSpace {
     DeviceRGB,
     DeviceGray,
+    /// Four-component CMYK. Adobe's encoder (marked by an APP14 "Adobe"
+    /// segment) stores the values inverted, so they're drawn through an
+    /// inverted /Decode array.
+    DeviceCMYK {
+        inverted: bool,
+    },
+}
+
+/// How images are resampled and compressed when they're embedded. The
+/// default embeds every image at full resolution, JPEGs as they are and
+/// everything else losslessly.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct ImagePolicy {
+    /// Highest resolution, in pixels per inch at the largest size the image
+    /// is drawn, to embed an image at. Images with more pixels than that are
+    /// resampled down to it.
+    pub max_dpi: Option<f64>,
+    /// JPEG quality (1-100) to recompress opaque photographs with: images
+    /// with more than [`PHOTO_MIN_COLORS`] distinct colors. Images with
+    /// transparency and flat graphics stay lossless. Without it, only JPEGs
+    /// that are resampled are re-encoded, at [`DEFAULT_JPEG_QUALITY`].
+    pub jpeg_quality: Option<u8>,
 }
 
+/// Images with more distinct colors than this are taken for photographs,
+/// which `ImagePolicy::jpeg_quality` recompresses. Fewer are flat graphics.
+pub const PHOTO_MIN_COLORS: usize = 1024;
+
+/// Quality JPEGs are re-encoded at after resampling, unless the policy
+/// sets one.
+pub const DEFAULT_JPEG_QUALITY: u8 = 85;
+
 /// Load an image from a source string.
 ///
 /// Supported `src` formats:
 /// - `data:image/...;base64,...` — data URI
-/// - File path (absolute or relative) — reads from disk
 /// - Raw base64-encoded image data
-pub fn load_image(src: &str) -> Result<LoadedImage, String> {
-    let raw_bytes = read_source_bytes(src)?;
+/// - Anything else (a file path, URL or asset name) — read by `resolver`
+pub fn load_image(src: &str, resolver: &dyn ResourceResolver) -> Result<LoadedImage, String> {
+    let raw_bytes = read_source(src, resolver)?;
     decode_image_bytes(&raw_bytes)
 }
 
 /// Read only the pixel dimensions from an image source without decoding pixels.
 /// Returns (width, height) in pixels. Much cheaper than `load_image`.
-pub fn load_image_dimensions(src: &str) -> Result<(u32, u32), String> {
-    let raw_bytes = read_source_bytes(src)?;
+pub fn load_image_dimensions(
+    src: &str,
+    resolver: &dyn ResourceResolver,
+) -> Result<(u32, u32), String> {
+    let raw_bytes = read_source(src, resolver)?;
     let reader = image::io::Reader::new(Cursor::new(&raw_bytes))
         .with_guessed_format()
         .map_err(|e| format!("Image format detection error: {}", e))?;
@@ -62,46 +128,6 @@ pub fn load_image_dimensions(src: &str) -> Result<(u32, u32), String> {
         .map_err(|e| format!("Failed to read image dimensions: {}", e))
 }
 
-/// Resolve the source string to raw image bytes.
-fn read_source_bytes(src: &str) -> Result<Vec<u8>, String> {
-    // Data URI: data:image/png;base64,iVBOR...
-    if src.starts_with("data:image/") {
-        let comma_pos = src
-            .find(',')
-            .ok_or_else(|| "Invalid data URI: missing comma".to_string())?;
-        let b64_data = &src[comma_pos + 1..];
-        return base64_decode(b64_data);
-    }
-
-    // File path — try reading from disk (not available in WASM)
-    // Only match explicit path prefixes to avoid treating base64 strings
-    // (which contain '/') as file paths.
-    if src.starts_with('/') || src.starts_with("./") || src.starts_with("../") {
-        #[cfg(not(target_arch = "wasm32"))]
-        {
-            return std::fs::read(src)
-                .map_err(|e| format!("Failed to read image file '{}': {}", src, e));
-        }
-        #[cfg(target_arch = "wasm32")]
-        {
-            return Err(format!(
-                "File path images not supported in WASM: '{}'. Use data URIs or base64.",
-                src
-            ));
-        }
-    }
-
-    // Try raw base64
-    base64_decode(src)
-}
-
-fn base64_decode(input: &str) -> Result<Vec<u8>, String> {
-    use base64::Engine;
-    base64::engine::general_purpose::STANDARD
-        .decode(input)
-        .map_err(|e| format!("Base64 decode error: {}", e))
-}
-
 /// Detect image format from magic bytes and decode accordingly.
 fn decode_image_bytes(data: &[u8]) -> Result<LoadedImage, String> {
     if data.len() < 4 {
@@ -113,9 +139,15 @@ fn decode_image_bytes(data: &[u8]) -> Result<LoadedImage, String> {
     } else if is_png(data) {
         decode_png(data)
     } else if is_webp(data) {
-        decode_webp(data)
+        decode_raster(data, "WebP")
+    } else if is_gif(data) {
+        decode_raster(data, "GIF")
+    } else if is_bmp(data) {
+        decode_raster(data, "BMP")
+    } else if is_tiff(data) {
+        decode_raster(data, "TIFF")
     } else {
-        Err("Unsupported image format (expected JPEG, PNG, or WebP)".to_string())
+        Err("Unsupported image format (expected JPEG, PNG, WebP, GIF, BMP, or TIFF)".to_string())
     }
 }
 
@@ -131,6 +163,18 @@ fn is_webp(data: &[u8]) -> bool {
     data.len() >= 12 && &data[0..4] == b"RIFF" && &data[8..12] == b"WEBP"
 }
 
+fn is_gif(data: &[u8]) -> bool {
+    data.starts_with(b"GIF87a") || data.starts_with(b"GIF89a")
+}
+
+fn is_bmp(data: &[u8]) -> bool {
+    data.starts_with(b"BM")
+}
+
+fn is_tiff(data: &[u8]) -> bool {
+    data.starts_with(b"II*\0") || data.starts_with(b"MM\0*")
+}
+
 /// JPEG: read dimensions and color space without decoding pixels.
 /// The raw JPEG bytes are passed through to the PDF (DCTDecode).
 fn decode_jpeg(data: &[u8]) -> Result<LoadedImage, String> {
@@ -157,24 +201,32 @@ fn decode_jpeg(data: &[u8]) -> Result<LoadedImage, String> {
 }
 
 /// Scan JPEG markers to find the SOF (Start of Frame) segment and read
-/// the number of components to determine color space.
+/// the number of components to determine color space. An APP14 "Adobe"
+/// segment before it marks CMYK values as inverted.
 fn detect_jpeg_color_space(data: &[u8]) -> JpegColorSpace {
+    let mut adobe = false;
     let mut i = 2; // skip SOI marker (FF D8)
     while i + 1 < data.len() {
         if data[i] != 0xFF {
             break;
         }
         let marker = data[i + 1];
+        if marker == 0xEE
+            && data
+                .get(i + 4..)
+                .is_some_and(|seg| seg.starts_with(b"Adobe"))
+        {
+            adobe = true;
+        }
         // SOF markers: C0-C3, C5-C7, C9-CB, CD-CF
         let is_sof = matches!(marker, 0xC0..=0xC3 | 0xC5..=0xC7 | 0xC9..=0xCB | 0xCD..=0xCF);
         if is_sof {
             // SOF segment: length(2) + precision(1) + height(2) + width(2) + num_components(1)
             if i + 9 < data.len() {
-                let num_components = data[i + 9];
-                return if num_components == 1 {
-                    JpegColorSpace::DeviceGray
-                } else {
-                    JpegColorSpace::DeviceRGB
+                return match data[i + 9] {
+                    1 => JpegColorSpace::DeviceGray,
+                    4 => JpegColorSpace::DeviceCMYK { inverted: adobe },
+                    _ => JpegColorSpace::DeviceRGB,
                 };
             }
         }
@@ -190,15 +242,16 @@ fn detect_jpeg_color_space(data: &[u8]) -> JpegColorSpace {
     JpegColorSpace::DeviceRGB
 }
 
-/// WebP: decode to RGBA, split into RGB + alpha (same pipeline as PNG).
-fn decode_webp(data: &[u8]) -> Result<LoadedImage, String> {
+/// WebP, GIF (the first frame), BMP and TIFF: decode to RGBA, split into
+/// RGB + alpha (same pipeline as PNG). `format` names the format in errors.
+fn decode_raster(data: &[u8], format: &str) -> Result<LoadedImage, String> {
     let reader = image::io::Reader::new(Cursor::new(data))
         .with_guessed_format()
-        .map_err(|e| format!("WebP format detection error: {}", e))?;
+        .map_err(|e| format!("{} format detection error: {}", format, e))?;
 
     let img = reader
         .decode()
-        .map_err(|e| format!("Failed to decode WebP: {}", e))?;
+        .map_err(|e| format!("Failed to decode {}: {}", format, e))?;
 
     let rgba = img.to_rgba8();
     let width = rgba.width();
@@ -230,8 +283,13 @@ fn decode_webp(data: &[u8]) -> Result<LoadedImage, String> {
     })
 }
 
-/// PNG: decode to RGBA, split into RGB + alpha.
+/// PNG: keep a palette PNG's indices, else decode to RGBA, split into
+/// RGB + alpha.
 fn decode_png(data: &[u8]) -> Result<LoadedImage, String> {
+    if let Some(indexed) = decode_indexed_png(data) {
+        return Ok(indexed);
+    }
+
     let reader = image::io::Reader::new(Cursor::new(data))
         .with_guessed_format()
         .map_err(|e| format!("PNG format detection error: {}", e))?;
@@ -270,6 +328,234 @@ fn decode_png(data: &[u8]) -> Result<LoadedImage, String> {
     })
 }
 
+/// A palette PNG with its palette and packed indices as they are, or None
+/// when `data` isn't one (or can't be read this way).
+fn decode_indexed_png(data: &[u8]) -> Option<LoadedImage> {
+    let mut decoder = png::Decoder::new(data);
+    decoder.set_transformations(png::Transformations::IDENTITY);
+    let mut reader = decoder.read_info().ok()?;
+    let info = reader.info();
+    if info.color_type != png::ColorType::Indexed {
+        return None;
+    }
+    let palette = info.palette.as_ref()?.to_vec();
+    let transparency = info.trns.as_ref().map(|trns| trns.to_vec());
+    let mut indices = vec![0; reader.output_buffer_size()];
+    let frame = reader.next_frame(&mut indices).ok()?;
+    indices.truncate(frame.buffer_size());
+    let bits_per_component = frame.bit_depth as u8;
+    let (width, height) = (frame.width, frame.height);
+
+    // Per-pixel alpha from the palette entries' transparency
+    let alpha = transparency
+        .filter(|trns| trns.iter().any(|&a| a != 255))
+        .map(|trns| {
+            indexed_pixels(&indices, bits_per_component, width, height)
+                .map(|index| trns.get(index).copied().unwrap_or(255))
+                .collect()
+        });
+
+    Some(LoadedImage {
+        pixel_data: ImagePixelData::Indexed {
+            palette,
+            bits_per_component,
+            indices,
+            alpha,
+        },
+        width_px: width,
+        height_px: height,
+    })
+}
+
+/// The palette index of each pixel of an image `width` × `height` whose
+/// `indices` are packed `bits` to an index, rows starting on byte boundaries.
+fn indexed_pixels(
+    indices: &[u8],
+    bits: u8,
+    width: u32,
+    height: u32,
+) -> impl Iterator<Item = usize> + '_ {
+    let bits = bits as usize;
+    let row_len = (width as usize * bits).div_ceil(8);
+    let per_byte = 8 / bits;
+    let mask = (1u16 << bits) as u8 - 1;
+    (0..height as usize).flat_map(move |y| {
+        let row = &indices[y * row_len..(y + 1) * row_len];
+        (0..width as usize).map(move |x| {
+            let shift = 8 - bits * (x % per_byte + 1);
+            ((row[x / per_byte] >> shift) & mask) as usize
+        })
+    })
+}
+
+/// The RGB pixels of an indexed image.
+fn indexed_to_rgb(image: &LoadedImage) -> Option<LoadedImage> {
+    let ImagePixelData::Indexed {
+        palette,
+        bits_per_component,
+        indices,
+        alpha,
+    } = &image.pixel_data
+    else {
+        return None;
+    };
+    let mut rgb = Vec::with_capacity((image.width_px * image.height_px) as usize * 3);
+    for index in indexed_pixels(
+        indices,
+        *bits_per_component,
+        image.width_px,
+        image.height_px,
+    ) {
+        rgb.extend_from_slice(palette.get(index * 3..index * 3 + 3).unwrap_or(&[0, 0, 0]));
+    }
+    Some(LoadedImage {
+        pixel_data: ImagePixelData::Decoded {
+            rgb,
+            alpha: alpha.clone(),
+        },
+        width_px: image.width_px,
+        height_px: image.height_px,
+    })
+}
+
+/// The image to embed for `image` drawn at most `width` × `height` points,
+/// following `policy`. Returns `image` itself when the policy leaves it as
+/// it is, or when it can't be re-encoded.
+pub fn prepare_for_embedding<'a>(
+    image: &'a LoadedImage,
+    width: f64,
+    height: f64,
+    policy: &ImagePolicy,
+) -> Cow<'a, LoadedImage> {
+    let (width_px, height_px) = target_size(image, width, height, policy.max_dpi);
+    let resample = (width_px, height_px) != (image.width_px, image.height_px);
+    let prepared = match &image.pixel_data {
+        // Decoding would convert CMYK to RGB without the color management
+        // the source expects, so CMYK JPEGs keep their pixels
+        ImagePixelData::Jpeg {
+            color_space: JpegColorSpace::DeviceCMYK { .. },
+            ..
+        } => None,
+        ImagePixelData::Jpeg { data, .. } if resample => {
+            image::load_from_memory_with_format(data, image::ImageFormat::Jpeg)
+                .ok()
+                .and_then(|decoded| {
+                    let resized = decoded.resize_exact(width_px, height_px, FilterType::CatmullRom);
+                    let quality = policy.jpeg_quality.unwrap_or(DEFAULT_JPEG_QUALITY);
+                    encode_jpeg(&resized, quality)
+                })
+        }
+        ImagePixelData::Jpeg { .. } => None,
+        // A palette image is a flat graphic: resampled as its RGB pixels
+        // would be, but never recompressed
+        ImagePixelData::Indexed { .. } => {
+            if !resample {
+                return Cow::Borrowed(image);
+            }
+            let policy = ImagePolicy {
+                jpeg_quality: None,
+                ..*policy
+            };
+            indexed_to_rgb(image)
+                .map(|rgb| prepare_for_embedding(&rgb, width, height, &policy).into_owned())
+        }
+        ImagePixelData::Decoded { rgb, alpha } => {
+            let jpeg_quality = policy
+                .jpeg_quality
+                .filter(|_| alpha.is_none() && is_photographic(rgb));
+            if !resample && jpeg_quality.is_none() {
+                return Cow::Borrowed(image);
+            }
+            let rgb = RgbImage::from_raw(image.width_px, image.height_px, rgb.clone());
+            rgb.and_then(|rgb| {
+                let rgb =
+                    image::imageops::resize(&rgb, width_px, height_px, FilterType::CatmullRom);
+                if let Some(quality) = jpeg_quality {
+                    return encode_jpeg(&DynamicImage::ImageRgb8(rgb), quality);
+                }
+                let alpha = match alpha {
+                    Some(alpha) => {
+                        let alpha =
+                            GrayImage::from_raw(image.width_px, image.height_px, alpha.clone())?;
+                        let alpha = image::imageops::resize(
+                            &alpha,
+                            width_px,
+                            height_px,
+                            FilterType::CatmullRom,
+                        );
+                        Some(alpha.into_raw())
+                    }
+                    None => None,
+                };
+                Some(LoadedImage {
+                    pixel_data: ImagePixelData::Decoded {
+                        rgb: rgb.into_raw(),
+                        alpha,
+                    },
+                    width_px,
+                    height_px,
+                })
+            })
+        }
+    };
+    prepared.map_or(Cow::Borrowed(image), Cow::Owned)
+}
+
+/// The pixel size to embed `image` at when drawn `width` × `height` points:
+/// scaled down evenly, if it's over `max_dpi` on both axes, until one of
+/// them is at `max_dpi`.
+fn target_size(image: &LoadedImage, width: f64, height: f64, max_dpi: Option<f64>) -> (u32, u32) {
+    let full = (image.width_px, image.height_px);
+    let Some(max_dpi) = max_dpi.filter(|dpi| *dpi > 0.0) else {
+        return full;
+    };
+    if width <= 0.0 || height <= 0.0 || image.width_px == 0 || image.height_px == 0 {
+        return full;
+    }
+    let scale_x = width / 72.0 * max_dpi / image.width_px as f64;
+    let scale_y = height / 72.0 * max_dpi / image.height_px as f64;
+    let scale = scale_x.max(scale_y);
+    if scale >= 1.0 {
+        return full;
+    }
+    let scaled = |px: u32| ((px as f64 * scale).ceil() as u32).max(1);
+    (scaled(image.width_px), scaled(image.height_px))
+}
+
+/// Whether RGB pixels have more than [`PHOTO_MIN_COLORS`] distinct colors.
+fn is_photographic(rgb: &[u8]) -> bool {
+    let mut colors = HashSet::new();
+    rgb.chunks_exact(3).any(|pixel| {
+        colors.insert([pixel[0], pixel[1], pixel[2]]) && colors.len() > PHOTO_MIN_COLORS
+    })
+}
+
+/// Encode `image` as a baseline JPEG: grayscale images in one channel,
+/// everything else as RGB.
+fn encode_jpeg(image: &DynamicImage, quality: u8) -> Option<LoadedImage> {
+    let (pixels, color_type, color_space) = match image {
+        DynamicImage::ImageLuma8(gray) => (
+            Cow::Borrowed(gray.as_raw()),
+            ColorType::L8,
+            JpegColorSpace::DeviceGray,
+        ),
+        _ => (
+            Cow::Owned(image.to_rgb8().into_raw()),
+            ColorType::Rgb8,
+            JpegColorSpace::DeviceRGB,
+        ),
+    };
+    let mut data = Vec::new();
+    image::codecs::jpeg::JpegEncoder::new_with_quality(&mut data, quality.clamp(1, 100))
+        .encode(&pixels, image.width(), image.height(), color_type)
+        .ok()?;
+    Some(LoadedImage {
+        pixel_data: ImagePixelData::Jpeg { data, color_space },
+        width_px: image.width(),
+        height_px: image.height(),
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -290,7 +576,7 @@ mod tests {
 
     #[test]
     fn test_invalid_data_uri() {
-        let result = load_image("data:image/png;base64");
+        let result = load_image("data:image/png;base64", &crate::resource::DenyAllResolver);
         assert!(result.is_err());
     }
 
@@ -451,8 +737,294 @@ mod tests {
         let b64 = base64::engine::general_purpose::STANDARD.encode(&buf);
         let data_uri = format!("data:image/png;base64,{}", b64);
 
-        let loaded = load_image(&data_uri).unwrap();
+        let loaded = load_image(&data_uri, &crate::resource::DenyAllResolver).unwrap();
         assert_eq!(loaded.width_px, 1);
         assert_eq!(loaded.height_px, 1);
     }
+
+    fn decoded(width: u32, height: u32, alpha: bool) -> LoadedImage {
+        let pixels = (width * height) as usize;
+        LoadedImage {
+            pixel_data: ImagePixelData::Decoded {
+                rgb: (0..pixels * 3).map(|i| (i % 251) as u8).collect(),
+                alpha: alpha.then(|| vec![128; pixels]),
+            },
+            width_px: width,
+            height_px: height,
+        }
+    }
+
+    #[test]
+    fn test_prepare_resamples_to_max_dpi() {
+        let image = decoded(400, 200, true);
+        let policy = ImagePolicy {
+            max_dpi: Some(144.0),
+            jpeg_quality: Some(70),
+        };
+        // 100pt at 144dpi is 200px; with transparency it stays lossless
+        let prepared = prepare_for_embedding(&image, 100.0, 50.0, &policy);
+        assert_eq!((prepared.width_px, prepared.height_px), (200, 100));
+        match &prepared.pixel_data {
+            ImagePixelData::Decoded { rgb, alpha } => {
+                assert_eq!(rgb.len(), 200 * 100 * 3);
+                assert_eq!(alpha.as_ref().unwrap().len(), 200 * 100);
+            }
+            _ => panic!("Image with alpha should stay lossless"),
+        }
+
+        // Drawn large enough, the image keeps all its pixels
+        let prepared = prepare_for_embedding(&image, 300.0, 150.0, &policy);
+        assert_eq!((prepared.width_px, prepared.height_px), (400, 200));
+    }
+
+    /// An opaque image shaded smoothly across, with a color per pixel.
+    fn photo(width: u32, height: u32) -> LoadedImage {
+        let rgb = (0..height)
+            .flat_map(|y| (0..width).flat_map(move |x| [x as u8 * 4, y as u8 * 4, (x + y) as u8]))
+            .collect();
+        LoadedImage {
+            pixel_data: ImagePixelData::Decoded { rgb, alpha: None },
+            width_px: width,
+            height_px: height,
+        }
+    }
+
+    #[test]
+    fn test_prepare_recompresses_opaque_images_as_jpeg() {
+        let image = photo(64, 64);
+        let policy = ImagePolicy {
+            max_dpi: None,
+            jpeg_quality: Some(60),
+        };
+        let prepared = prepare_for_embedding(&image, 64.0, 64.0, &policy);
+        assert_eq!((prepared.width_px, prepared.height_px), (64, 64));
+        assert!(matches!(
+            prepared.pixel_data,
+            ImagePixelData::Jpeg {
+                color_space: JpegColorSpace::DeviceRGB,
+                ..
+            }
+        ));
+
+        // The default policy leaves every image as it is
+        let prepared = prepare_for_embedding(&image, 10.0, 10.0, &ImagePolicy::default());
+        assert!(matches!(prepared, Cow::Borrowed(_)));
+    }
+
+    #[test]
+    fn test_prepare_keeps_flat_graphics_lossless() {
+        let policy = ImagePolicy {
+            max_dpi: Some(72.0),
+            jpeg_quality: Some(60),
+        };
+        // 251 colors: left as it is, or resampled without recompressing
+        let graphic = decoded(64, 64, false);
+        let prepared = prepare_for_embedding(&graphic, 64.0, 64.0, &policy);
+        assert!(matches!(prepared, Cow::Borrowed(_)));
+        let prepared = prepare_for_embedding(&graphic, 32.0, 32.0, &policy);
+        assert_eq!((prepared.width_px, prepared.height_px), (32, 32));
+        assert!(matches!(
+            prepared.pixel_data,
+            ImagePixelData::Decoded { .. }
+        ));
+
+        // Palette images are never recompressed
+        let palette = decode_image_bytes(&palette_png(400, false)).unwrap();
+        let prepared = prepare_for_embedding(&palette, 400.0, 2.0, &policy);
+        assert!(matches!(prepared, Cow::Borrowed(_)));
+        let prepared = prepare_for_embedding(&palette, 100.0, 0.5, &policy);
+        assert!(matches!(
+            prepared.pixel_data,
+            ImagePixelData::Decoded { .. }
+        ));
+
+        assert!(is_photographic(&rgb_of(&photo(64, 64))));
+        assert!(!is_photographic(&rgb_of(&graphic)));
+    }
+
+    fn rgb_of(image: &LoadedImage) -> Vec<u8> {
+        match &image.pixel_data {
+            ImagePixelData::Decoded { rgb, .. } => rgb.clone(),
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn test_prepare_resamples_jpeg() {
+        let img = image::GrayImage::from_fn(300, 300, |x, y| image::Luma([(x ^ y) as u8]));
+        let mut buf = Vec::new();
+        let encoder = image::codecs::jpeg::JpegEncoder::new(&mut buf);
+        image::ImageEncoder::write_image(encoder, img.as_raw(), 300, 300, image::ColorType::L8)
+            .unwrap();
+        let loaded = decode_image_bytes(&buf).unwrap();
+        let policy = ImagePolicy {
+            max_dpi: Some(72.0),
+            jpeg_quality: None,
+        };
+        let prepared = prepare_for_embedding(&loaded, 100.0, 100.0, &policy);
+        assert_eq!((prepared.width_px, prepared.height_px), (100, 100));
+        match &prepared.pixel_data {
+            ImagePixelData::Jpeg { data, color_space } => {
+                assert!(data.len() < buf.len());
+                assert!(matches!(color_space, JpegColorSpace::DeviceGray));
+            }
+            _ => panic!("JPEG should stay as Jpeg variant"),
+        }
+        // Not over the limit: passed through untouched
+        let prepared = prepare_for_embedding(&loaded, 300.0, 300.0, &policy);
+        assert!(matches!(prepared, Cow::Borrowed(_)));
+    }
+
+    #[test]
+    fn test_prepare_leaves_cmyk_jpeg_as_it_is() {
+        let image = LoadedImage {
+            pixel_data: ImagePixelData::Jpeg {
+                data: vec![0xFF, 0xD8],
+                color_space: JpegColorSpace::DeviceCMYK { inverted: true },
+            },
+            width_px: 300,
+            height_px: 300,
+        };
+        let policy = ImagePolicy {
+            max_dpi: Some(72.0),
+            jpeg_quality: Some(60),
+        };
+        let prepared = prepare_for_embedding(&image, 100.0, 100.0, &policy);
+        assert!(matches!(prepared, Cow::Borrowed(_)));
+    }
+
+    #[test]
+    fn test_is_gif_bmp_tiff() {
+        assert!(is_gif(b"GIF89a\x01\x00"));
+        assert!(is_gif(b"GIF87a\x01\x00"));
+        assert!(!is_gif(b"GIF9"));
+        assert!(is_bmp(b"BM\x00\x00"));
+        assert!(is_tiff(b"II*\x00"));
+        assert!(is_tiff(b"MM\x00*"));
+        assert!(!is_tiff(b"II\x00*"));
+    }
+
+    #[test]
+    fn test_decode_gif_bmp_tiff() {
+        let img = DynamicImage::ImageRgba8(image::RgbaImage::from_fn(3, 2, |x, _| {
+            image::Rgba([0, 0, 255, if x == 0 { 0 } else { 255 }])
+        }));
+        for format in [
+            image::ImageFormat::Gif,
+            image::ImageFormat::Bmp,
+            image::ImageFormat::Tiff,
+        ] {
+            let mut buf = Vec::new();
+            img.write_to(&mut Cursor::new(&mut buf), format).unwrap();
+            let loaded = decode_image_bytes(&buf).unwrap();
+            assert_eq!((loaded.width_px, loaded.height_px), (3, 2), "{:?}", format);
+            match &loaded.pixel_data {
+                ImagePixelData::Decoded { rgb, alpha } => {
+                    assert_eq!(&rgb[3..6], &[0, 0, 255], "{:?}", format);
+                    assert_eq!(alpha.as_ref().unwrap()[0], 0, "{:?}", format);
+                }
+                _ => panic!("{:?} should decode to Decoded variant", format),
+            }
+        }
+    }
+
+    #[test]
+    fn test_detect_cmyk_jpeg() {
+        // SOI, then a SOF0 segment for an 8x8 image with 4 components
+        let sof = [
+            0xFF, 0xC0, 0x00, 0x14, 8, 0, 8, 0, 8, 4, 1, 0x11, 0, 2, 0x11, 0, 3, 0x11, 0, 4, 0x11,
+            0,
+        ];
+        let app14 = [
+            0xFF, 0xEE, 0x00, 0x0E, b'A', b'd', b'o', b'b', b'e', 0, 100, 0, 0, 0, 0, 0,
+        ];
+        let plain = [&[0xFF, 0xD8][..], &sof].concat();
+        let adobe = [&[0xFF, 0xD8][..], &app14, &sof].concat();
+        assert_eq!(
+            detect_jpeg_color_space(&plain),
+            JpegColorSpace::DeviceCMYK { inverted: false }
+        );
+        assert_eq!(
+            detect_jpeg_color_space(&adobe),
+            JpegColorSpace::DeviceCMYK { inverted: true }
+        );
+    }
+
+    /// A 2-bit palette PNG, `width` pixels wide and 2 high, cycling through
+    /// four colors; the first is half transparent when `transparent`.
+    fn palette_png(width: u32, transparent: bool) -> Vec<u8> {
+        let mut buf = Vec::new();
+        let mut encoder = png::Encoder::new(&mut buf, width, 2);
+        encoder.set_color(png::ColorType::Indexed);
+        encoder.set_depth(png::BitDepth::Two);
+        encoder.set_palette(vec![255, 0, 0, 0, 255, 0, 0, 0, 255, 255, 255, 255]);
+        if transparent {
+            encoder.set_trns(vec![128]);
+        }
+        let row_len = (width as usize * 2).div_ceil(8);
+        let mut indices = vec![0; row_len * 2];
+        for y in 0..2 {
+            for x in 0..width as usize {
+                indices[y * row_len + x / 4] |= ((x % 4) as u8) << (6 - 2 * (x % 4));
+            }
+        }
+        let mut writer = encoder.write_header().unwrap();
+        writer.write_image_data(&indices).unwrap();
+        drop(writer);
+        buf
+    }
+
+    #[test]
+    fn test_decode_palette_png_keeps_indices() {
+        let loaded = decode_image_bytes(&palette_png(5, true)).unwrap();
+        assert_eq!((loaded.width_px, loaded.height_px), (5, 2));
+        match &loaded.pixel_data {
+            ImagePixelData::Indexed {
+                palette,
+                bits_per_component,
+                indices,
+                alpha,
+            } => {
+                assert_eq!(palette.len(), 12);
+                assert_eq!(*bits_per_component, 2);
+                assert_eq!(indices.len(), 4);
+                assert_eq!(
+                    alpha.as_ref().unwrap(),
+                    &[128, 255, 255, 255, 128].repeat(2)
+                );
+            }
+            _ => panic!("Palette PNG should decode to Indexed variant"),
+        }
+
+        let opaque = decode_image_bytes(&palette_png(5, false)).unwrap();
+        assert!(matches!(
+            opaque.pixel_data,
+            ImagePixelData::Indexed { alpha: None, .. }
+        ));
+        let rgb = indexed_to_rgb(&opaque).unwrap();
+        match &rgb.pixel_data {
+            ImagePixelData::Decoded { rgb, .. } => {
+                assert_eq!(&rgb[..6], &[255, 0, 0, 0, 255, 0]);
+                assert_eq!(&rgb[12..15], &[255, 0, 0]);
+            }
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn test_prepare_resamples_palette_png_as_rgb() {
+        let loaded = decode_image_bytes(&palette_png(400, false)).unwrap();
+        let policy = ImagePolicy {
+            max_dpi: Some(72.0),
+            jpeg_quality: None,
+        };
+        let prepared = prepare_for_embedding(&loaded, 100.0, 0.5, &policy);
+        assert_eq!((prepared.width_px, prepared.height_px), (100, 1));
+        assert!(matches!(
+            prepared.pixel_data,
+            ImagePixelData::Decoded { .. }
+        ));
+        let prepared = prepare_for_embedding(&loaded, 400.0, 2.0, &policy);
+        assert!(matches!(prepared, Cow::Borrowed(_)));
+    }
 }
@@ -0,0 +1,306 @@
+//! # Backgrounds
+//!
+//! A node's `background_image` is drawn over its background color, clipped
+//! to its box and the box's rounded corners. Gradients fill the box, and
+//! the PDF writer draws them as axial and radial shadings. A background
+//! image is sized and placed as in CSS `background-size` and
+//! `background-position`, then tiled across the box unless it's
+//! `NoRepeat`.
+//!
+//! This module resolves gradient stops and does the geometry, in
+//! coordinates from the box's top-left corner.
+
+use std::sync::Arc;
+
+use super::object_fit::{self, ImagePlacement};
+use crate::image_loader::LoadedImage;
+use crate::style::{
+    BackgroundRepeat, BackgroundSize, Color, ColorStop, Dimension, ObjectFit, ObjectPosition,
+};
+
+/// Most tiles drawn for one background, so a tiny image tiled across a
+/// large box can't blow up the content stream.
+const MAX_TILES: usize = 10_000;
+
+/// A background ready to draw.
+#[derive(Debug, Clone)]
+pub enum Background {
+    /// A linear gradient running at `angle` degrees clockwise from "to top".
+    LinearGradient {
+        angle: f64,
+        stops: Vec<GradientStop>,
+    },
+    /// A radial gradient from the box's center to its farthest corner.
+    RadialGradient { stops: Vec<GradientStop> },
+    /// An image, sized, positioned and tiled across the box.
+    Image {
+        image_data: Arc<LoadedImage>,
+        size: BackgroundSize,
+        repeat: BackgroundRepeat,
+        position: ObjectPosition,
+    },
+}
+
+/// A gradient color stop at `offset` along the gradient, from 0 to 1.
+#[derive(Debug, Clone, Copy)]
+pub struct GradientStop {
+    pub offset: f64,
+    pub color: Color,
+}
+
+/// The stops of a gradient with every offset set, in order: the first and
+/// last default to the ends, stops without one are spread evenly between
+/// their neighbors, and a stop before an earlier one moves up to it. A
+/// single stop is a solid color, so it's drawn at both ends.
+pub fn gradient_stops(stops: &[ColorStop]) -> Vec<GradientStop> {
+    let mut offsets: Vec<Option<f64>> = stops
+        .iter()
+        .map(|stop| stop.offset.map(|offset| offset.clamp(0.0, 1.0)))
+        .collect();
+    if let Some(first) = offsets.first_mut() {
+        first.get_or_insert(0.0);
+    }
+    if let Some(last) = offsets.last_mut() {
+        last.get_or_insert(1.0);
+    }
+    let mut previous = 0.0f64;
+    for offset in offsets.iter_mut().flatten() {
+        *offset = offset.max(previous);
+        previous = *offset;
+    }
+
+    let mut resolved: Vec<GradientStop> = Vec::with_capacity(stops.len() + 1);
+    let mut i = 0;
+    while i < stops.len() {
+        if let Some(offset) = offsets[i] {
+            resolved.push(GradientStop {
+                offset,
+                color: stops[i].color,
+            });
+            i += 1;
+            continue;
+        }
+        // A run of stops without offsets, between two that have them (the
+        // first and last always do)
+        let start = resolved.last().map_or(0.0, |stop| stop.offset);
+        let next = (i..stops.len())
+            .find(|&j| offsets[j].is_some())
+            .unwrap_or(stops.len() - 1);
+        let end = offsets[next].unwrap_or(1.0);
+        let steps = (next - i + 1) as f64;
+        for (k, stop) in stops[i..next].iter().enumerate() {
+            resolved.push(GradientStop {
+                offset: start + (end - start) * (k + 1) as f64 / steps,
+                color: stop.color,
+            });
+        }
+        i = next;
+    }
+    if let [only] = resolved[..] {
+        resolved.push(GradientStop {
+            offset: 1.0,
+            ..only
+        });
+    }
+    resolved
+}
+
+/// The start and end of a linear gradient's line in a box `width` ×
+/// `height`: through the box's center at `angle`, long enough that the
+/// corners get the end colors, as in CSS.
+pub fn linear_gradient_line(angle: f64, width: f64, height: f64) -> ((f64, f64), (f64, f64)) {
+    let (sin, cos) = angle.to_radians().sin_cos();
+    // Zero degrees points up, toward smaller y
+    let (dx, dy) = (sin, -cos);
+    let half = ((width * sin).abs() + (height * cos).abs()) / 2.0;
+    let (cx, cy) = (width / 2.0, height / 2.0);
+    (
+        (cx - dx * half, cy - dy * half),
+        (cx + dx * half, cy + dy * half),
+    )
+}
+
+/// The radius of a radial gradient in a box `width` × `height`: from its
+/// center to a corner.
+pub fn radial_gradient_radius(width: f64, height: f64) -> f64 {
+    (width / 2.0).hypot(height / 2.0)
+}
+
+/// Where each tile of a background image `image_width` × `image_height`
+/// pixels is drawn in a box `width` × `height`. Tiles cover the box along
+/// each axis `repeat` tiles in, lined up with the positioned first tile.
+pub fn tiles(
+    image_width: f64,
+    image_height: f64,
+    size: BackgroundSize,
+    repeat: BackgroundRepeat,
+    position: ObjectPosition,
+    width: f64,
+    height: f64,
+) -> Vec<ImagePlacement> {
+    if image_width <= 0.0 || image_height <= 0.0 {
+        return Vec::new();
+    }
+    let (tile_width, tile_height) = match size {
+        BackgroundSize::Auto => (image_width, image_height),
+        BackgroundSize::Cover | BackgroundSize::Contain => {
+            let fit = match size {
+                BackgroundSize::Cover => ObjectFit::Cover,
+                _ => ObjectFit::Contain,
+            };
+            let fitted = object_fit::place(fit, position, image_width, image_height, width, height);
+            (fitted.width, fitted.height)
+        }
+        BackgroundSize::Size {
+            width: tile_width,
+            height: tile_height,
+        } => {
+            let resolve = |dimension: Dimension, of: f64| match dimension {
+                Dimension::Pt(value) => Some(value),
+                Dimension::Percent(percent) => Some(of * percent / 100.0),
+                Dimension::Auto => None,
+            };
+            match (resolve(tile_width, width), resolve(tile_height, height)) {
+                (Some(w), Some(h)) => (w, h),
+                (Some(w), None) => (w, w * image_height / image_width),
+                (None, Some(h)) => (h * image_width / image_height, h),
+                (None, None) => (image_width, image_height),
+            }
+        }
+    };
+    if tile_width <= 0.0 || tile_height <= 0.0 {
+        return Vec::new();
+    }
+
+    let first = object_fit::position_in(position, tile_width, tile_height, width, height);
+    let repeat_x = matches!(repeat, BackgroundRepeat::Repeat | BackgroundRepeat::RepeatX);
+    let repeat_y = matches!(repeat, BackgroundRepeat::Repeat | BackgroundRepeat::RepeatY);
+    let xs = starts(first.x, tile_width, width, repeat_x);
+    let ys = starts(first.y, tile_height, height, repeat_y);
+    ys.iter()
+        .flat_map(|&y| {
+            xs.iter().map(move |&x| ImagePlacement {
+                x,
+                y,
+                width: tile_width,
+                height: tile_height,
+            })
+        })
+        .take(MAX_TILES)
+        .collect()
+}
+
+/// Where tiles `size` long start along an axis `length` long: at `first`
+/// alone, or stepping across the whole axis from where tiling back from
+/// `first` reaches its start.
+fn starts(first: f64, size: f64, length: f64, repeat: bool) -> Vec<f64> {
+    if !repeat {
+        return vec![first];
+    }
+    let mut start = first - (first / size).ceil() * size;
+    let mut starts = Vec::new();
+    while start < length && starts.len() < MAX_TILES {
+        starts.push(start);
+        start += size;
+    }
+    starts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stop(offset: Option<f64>) -> ColorStop {
+        ColorStop {
+            color: Color::BLACK,
+            offset,
+        }
+    }
+
+    #[test]
+    fn test_gradient_stops_fill_in_offsets() {
+        let offsets = |stops: &[ColorStop]| -> Vec<f64> {
+            gradient_stops(stops).iter().map(|s| s.offset).collect()
+        };
+        assert_eq!(
+            offsets(&[stop(None), stop(None), stop(None)]),
+            [0.0, 0.5, 1.0]
+        );
+        assert_eq!(
+            offsets(&[
+                stop(None),
+                stop(Some(0.2)),
+                stop(None),
+                stop(None),
+                stop(Some(0.8))
+            ]),
+            [0.0, 0.2, 0.4, 0.6000000000000001, 0.8]
+        );
+        // Out of order: moved up to the stop before
+        assert_eq!(offsets(&[stop(Some(0.6)), stop(Some(0.3))]), [0.6, 0.6]);
+        assert_eq!(offsets(&[stop(None)]), [0.0, 1.0]);
+    }
+
+    #[test]
+    fn test_linear_gradient_line_reaches_corners() {
+        let close = |(x, y): (f64, f64), (ex, ey): (f64, f64)| {
+            (x - ex).abs() < 1e-9 && (y - ey).abs() < 1e-9
+        };
+        // Top to bottom
+        let (start, end) = linear_gradient_line(180.0, 200.0, 100.0);
+        assert!(close(start, (100.0, 0.0)) && close(end, (100.0, 100.0)));
+        // 45 degrees: from bottom-left toward top-right, through the center
+        let (start, end) = linear_gradient_line(45.0, 100.0, 100.0);
+        assert!(close(start, (0.0, 100.0)) && close(end, (100.0, 0.0)));
+    }
+
+    #[test]
+    fn test_tiles_cover_box_from_position() {
+        let centered = ObjectPosition::default();
+        let tiles_x = tiles(
+            40.0,
+            20.0,
+            BackgroundSize::Auto,
+            BackgroundRepeat::RepeatX,
+            centered,
+            100.0,
+            50.0,
+        );
+        // Centered at 30, tiled back to -10 and across to 90
+        let xs: Vec<f64> = tiles_x.iter().map(|t| t.x).collect();
+        assert_eq!(xs, [-10.0, 30.0, 70.0]);
+        assert!(tiles_x.iter().all(|t| t.y == 15.0));
+
+        let sized = BackgroundSize::Size {
+            width: Dimension::Percent(50.0),
+            height: Dimension::Auto,
+        };
+        let single = tiles(
+            40.0,
+            20.0,
+            sized,
+            BackgroundRepeat::NoRepeat,
+            centered,
+            100.0,
+            50.0,
+        );
+        assert_eq!(single.len(), 1);
+        assert_eq!((single[0].width, single[0].height), (50.0, 25.0));
+
+        let cover = tiles(
+            40.0,
+            20.0,
+            BackgroundSize::Cover,
+            BackgroundRepeat::Repeat,
+            centered,
+            100.0,
+            100.0,
+        );
+        // One tile covers the box
+        assert_eq!(cover.len(), 1);
+        assert_eq!(
+            (cover[0].x, cover[0].width, cover[0].height),
+            (-50.0, 200.0, 100.0)
+        );
+    }
+}
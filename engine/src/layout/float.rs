@@ -0,0 +1,175 @@
+//! # Floats
+//!
+//! A child with `float: Left` or `Right` is taken out of its column
+//! container's flow and placed against that side of the container. It
+//! doesn't push the siblings after it down: text beside it wraps around it,
+//! each line getting the width the floats leave free at its height, and
+//! other content is narrowed to sit beside it. Content that can't get its
+//! min-content width beside the floats moves down below them. The container
+//! grows to enclose its floats.
+//!
+//! This module only does the geometry, so layout and measurement place
+//! floats and wrap text the same way. Floats are positioned in the flow's
+//! coordinates: x as the content is laid out, y relative to the page's
+//! content area (the cursor's y).
+
+use crate::style::Float;
+use crate::text::LineWidths;
+
+/// A placed float's margin box.
+#[derive(Debug, Clone)]
+pub struct PlacedFloat {
+    pub side: Float,
+    pub left: f64,
+    pub right: f64,
+    pub top: f64,
+    pub bottom: f64,
+}
+
+/// How far the floats reach into a box spanning `x` to `x + width`, over
+/// the band from `top` to `bottom`: `(from the left edge, from the right
+/// edge)`.
+pub fn insets(floats: &[PlacedFloat], x: f64, width: f64, top: f64, bottom: f64) -> (f64, f64) {
+    let mut left = 0.0f64;
+    let mut right = 0.0f64;
+    for float in floats.iter().filter(|f| f.top < bottom && f.bottom > top) {
+        match float.side {
+            Float::Left => left = left.max(float.right - x),
+            Float::Right => right = right.max(x + width - float.left),
+            Float::None => {}
+        }
+    }
+    (left.min(width), right.min(width))
+}
+
+/// The lowest bottom edge of the floats that reach below `y`, if any.
+fn next_bottom(floats: &[PlacedFloat], y: f64) -> Option<f64> {
+    floats
+        .iter()
+        .map(|f| f.bottom)
+        .filter(|&bottom| bottom > y)
+        .min_by(f64::total_cmp)
+}
+
+/// The bottom of the lowest float.
+pub fn clearance(floats: &[PlacedFloat]) -> Option<f64> {
+    floats.iter().map(|f| f.bottom).max_by(f64::total_cmp)
+}
+
+/// The first y at or below `y` where content at least `min_width` wide
+/// fits beside every float that reaches below that y, in a box spanning
+/// `x` to `x + width`.
+pub fn fit_below(floats: &[PlacedFloat], x: f64, width: f64, y: f64, min_width: f64) -> f64 {
+    let mut y = y;
+    loop {
+        let (left, right) = insets(floats, x, width, y, f64::INFINITY);
+        if width - left - right >= min_width {
+            return y;
+        }
+        match next_bottom(floats, y) {
+            Some(bottom) => y = bottom,
+            None => return y,
+        }
+    }
+}
+
+/// Place a float `width` × `height` (its margin box) against `side` of a
+/// box spanning `x` to `x + container_width`, as high as possible at or
+/// below `y`: beside earlier floats when there's room, else below them.
+pub fn place(
+    floats: &[PlacedFloat],
+    side: Float,
+    x: f64,
+    container_width: f64,
+    y: f64,
+    width: f64,
+    height: f64,
+) -> PlacedFloat {
+    let mut top = y;
+    loop {
+        let (left, right) = insets(floats, x, container_width, top, top + height);
+        let fits = container_width - left - right >= width;
+        match next_bottom(floats, top) {
+            Some(bottom) if !fits => top = bottom,
+            _ => {
+                let float_x = match side {
+                    Float::Right => x + container_width - right - width,
+                    _ => x + left,
+                };
+                return PlacedFloat {
+                    side,
+                    left: float_x,
+                    right: float_x + width,
+                    top,
+                    bottom: top + height,
+                };
+            }
+        }
+    }
+}
+
+/// The width of each line of text `line_height` tall, starting at `top`,
+/// in a box spanning `x` to `x + width`: narrowed beside the floats, and
+/// the full width once past them.
+pub fn line_widths(
+    floats: &[PlacedFloat],
+    x: f64,
+    width: f64,
+    top: f64,
+    line_height: f64,
+) -> LineWidths {
+    let mut first = Vec::new();
+    if line_height > 0.0 {
+        let end = clearance(floats).unwrap_or(top);
+        let mut y = top;
+        while y < end {
+            let (left, right) = insets(floats, x, width, y, y + line_height);
+            first.push((width - left - right).max(0.0));
+            y += line_height;
+        }
+    }
+    LineWidths { first, rest: width }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_floats_stack_side_by_side_then_below() {
+        let mut floats = vec![place(&[], Float::Left, 0.0, 300.0, 0.0, 100.0, 50.0)];
+        floats.push(place(&floats, Float::Right, 0.0, 300.0, 0.0, 100.0, 80.0));
+        assert_eq!((floats[1].left, floats[1].top), (200.0, 0.0));
+        // A third float doesn't fit between them, so it goes below the first
+        let third = place(&floats, Float::Left, 0.0, 300.0, 10.0, 150.0, 20.0);
+        assert_eq!((third.left, third.top), (0.0, 50.0));
+    }
+
+    #[test]
+    fn test_lines_narrow_beside_floats() {
+        let floats = vec![PlacedFloat {
+            side: Float::Left,
+            left: 10.0,
+            right: 70.0,
+            top: 0.0,
+            bottom: 25.0,
+        }];
+        let widths = line_widths(&floats, 10.0, 200.0, 0.0, 10.0);
+        assert_eq!(widths.first, vec![140.0, 140.0, 140.0]);
+        assert_eq!(widths.get(3), 200.0);
+        assert_eq!(insets(&floats, 10.0, 200.0, 25.0, 35.0), (0.0, 0.0));
+    }
+
+    #[test]
+    fn test_fit_below_skips_past_narrow_gaps() {
+        let floats = vec![PlacedFloat {
+            side: Float::Right,
+            left: 50.0,
+            right: 100.0,
+            top: 0.0,
+            bottom: 40.0,
+        }];
+        assert_eq!(fit_below(&floats, 0.0, 100.0, 5.0, 30.0), 5.0);
+        assert_eq!(fit_below(&floats, 0.0, 100.0, 5.0, 60.0), 40.0);
+    }
+}
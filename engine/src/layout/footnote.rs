@@ -0,0 +1,244 @@
+//! # Footnotes
+//!
+//! A text run with a `footnote` is a reference mark: it's drawn as the
+//! footnote's number in superscript, and the footnote's body goes in the
+//! footnote area at the bottom of the page the mark lands on, above any
+//! fixed footer. The area grows upward as footnotes are added and the
+//! page's remaining height shrinks with it, so the page content stops above
+//! it. A body that doesn't fit continues in the next page's area.
+//!
+//! This module numbers the marks and builds and places the area's content;
+//! the layout engine decides which page each footnote goes on.
+
+use std::borrow::Cow;
+
+use super::{offset_element_y, DrawCommand, LayoutElement};
+use crate::model::{Node, NodeKind, TextRun};
+use crate::style::{
+    Color, CornerValues, EdgeValues, Overflow, ResolvedStyle, Style, VerticalAlign,
+};
+
+/// Space between the page content and the first footnote, with the
+/// separator rule in the middle.
+pub const SEPARATOR_SPACE: f64 = 12.0;
+
+/// The separator rule spans this fraction of the content width.
+const SEPARATOR_LENGTH: f64 = 1.0 / 3.0;
+
+const SEPARATOR_THICKNESS: f64 = 0.5;
+
+/// Reference marks are this much smaller than their text, unless the run
+/// sets a size.
+const MARK_SCALE: f64 = 0.7;
+
+/// A footnote body, or the part of one that goes on one page, laid out for
+/// the footnote area: its elements, laid out from the top of the page's
+/// content area, and their height.
+#[derive(Debug, Clone)]
+pub struct FootnoteBlock {
+    pub elements: Vec<LayoutElement>,
+    pub height: f64,
+}
+
+/// The printed number of the `number`th footnote.
+pub fn label(number: usize) -> String {
+    number.to_string()
+}
+
+/// The style of a reference mark in text styled `text`: the run's own
+/// style, raised as a superscript and smaller unless the run sets its own.
+pub fn mark_style(run: &Style, text: &ResolvedStyle) -> Style {
+    Style {
+        font_size: run.font_size.or(Some(text.font_size * MARK_SCALE)),
+        vertical_align: run.vertical_align.or(Some(VerticalAlign::Super)),
+        ..run.clone()
+    }
+}
+
+/// The runs of text styled `text` with each footnote reference's content
+/// replaced by its mark, numbered from `first`.
+pub fn number_runs<'a>(
+    runs: &'a [TextRun],
+    first: usize,
+    text: &ResolvedStyle,
+) -> Cow<'a, [TextRun]> {
+    if runs.iter().all(|run| run.footnote.is_none()) {
+        return Cow::Borrowed(runs);
+    }
+    let mut number = first;
+    let numbered = runs
+        .iter()
+        .map(|run| {
+            if run.footnote.is_none() {
+                return run.clone();
+            }
+            let mark = TextRun {
+                content: label(number),
+                style: mark_style(&run.style, text),
+                ..run.clone()
+            };
+            number += 1;
+            mark
+        })
+        .collect();
+    Cow::Owned(numbered)
+}
+
+/// A footnote's body with its number in front: at the start of the first
+/// paragraph when the body starts with text, else on a line of its own.
+/// `text` is the style of the referencing text, which the body inherits.
+pub fn numbered_body(body: &[Node], label: &str, text: &ResolvedStyle, width: f64) -> Vec<Node> {
+    let mut nodes = body.to_vec();
+    // Sized to the body's text, not the reference's
+    let body_style = match nodes.first() {
+        Some(first) => first.style.resolve(Some(text), width),
+        None => text.clone(),
+    };
+    let mark = TextRun {
+        content: label.to_string(),
+        style: mark_style(&Style::default(), &body_style),
+        href: None,
+        footnote: None,
+    };
+    match nodes.first_mut() {
+        Some(Node {
+            kind: NodeKind::Text { content, runs, .. },
+            ..
+        }) => {
+            if runs.is_empty() {
+                runs.push(TextRun {
+                    content: std::mem::take(content),
+                    style: Style::default(),
+                    href: None,
+                    footnote: None,
+                });
+            }
+            let space = TextRun {
+                content: " ".to_string(),
+                style: Style::default(),
+                href: None,
+                footnote: None,
+            };
+            runs.splice(0..0, [mark, space]);
+        }
+        _ => {
+            let mut line = Node::text("", Style::default());
+            if let NodeKind::Text { runs, .. } = &mut line.kind {
+                runs.push(mark);
+            }
+            nodes.insert(0, line);
+        }
+    }
+    nodes
+}
+
+/// Height of a footnote area holding `blocks`.
+pub fn area_height(blocks: &[FootnoteBlock]) -> f64 {
+    if blocks.is_empty() {
+        return 0.0;
+    }
+    SEPARATOR_SPACE + blocks.iter().map(|block| block.height).sum::<f64>()
+}
+
+/// The elements of a footnote area `top` below the top of a content area at
+/// `(x, content_y)`, `width` wide: the separator rule, then the blocks.
+pub fn place(
+    blocks: &[FootnoteBlock],
+    x: f64,
+    content_y: f64,
+    width: f64,
+    top: f64,
+) -> Vec<LayoutElement> {
+    if blocks.is_empty() {
+        return Vec::new();
+    }
+    let mut elements = vec![LayoutElement {
+        x,
+        y: content_y + top + (SEPARATOR_SPACE - SEPARATOR_THICKNESS) / 2.0,
+        width: width * SEPARATOR_LENGTH,
+        height: SEPARATOR_THICKNESS,
+        draw: DrawCommand::Rect {
+            background: Some(Color::BLACK),
+            background_image: None,
+            border_width: crate::model::Edges::uniform(0.0),
+            border_color: EdgeValues::uniform(Color::BLACK),
+            border_radius: CornerValues::uniform(0.0),
+            opacity: 1.0,
+        },
+        children: vec![],
+        node_type: None,
+        resolved_style: None,
+        source_location: None,
+        href: None,
+        bookmark: None,
+        bookmark_level: 0,
+        node_id: None,
+        alt: None,
+        is_header_row: false,
+        overflow: Overflow::default(),
+    }];
+    let mut y = top + SEPARATOR_SPACE;
+    for block in blocks {
+        for el in &block.elements {
+            let mut el = el.clone();
+            offset_element_y(&mut el, y);
+            elements.push(el);
+        }
+        y += block.height;
+    }
+    elements
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(content: &str, footnote: Option<Vec<Node>>) -> TextRun {
+        TextRun {
+            content: content.to_string(),
+            style: Style::default(),
+            href: None,
+            footnote,
+        }
+    }
+
+    #[test]
+    fn test_number_runs_replaces_marks_in_order() {
+        let text = Style::default().resolve(None, 500.0);
+        let body = vec![Node::text("Note", Style::default())];
+        let runs = vec![
+            run("Claim", None),
+            run("", Some(body.clone())),
+            run(" and another", None),
+            run("*", Some(body)),
+        ];
+        let numbered = number_runs(&runs, 4, &text);
+        let contents: Vec<&str> = numbered.iter().map(|r| r.content.as_str()).collect();
+        assert_eq!(contents, ["Claim", "4", " and another", "5"]);
+        assert_eq!(numbered[1].style.vertical_align, Some(VerticalAlign::Super));
+        assert!(numbered[1].style.font_size.unwrap() < text.font_size);
+        assert!(matches!(
+            number_runs(&runs[..1], 1, &text),
+            Cow::Borrowed(_)
+        ));
+    }
+
+    #[test]
+    fn test_numbered_body_starts_with_its_label() {
+        let text = Style::default().resolve(None, 500.0);
+        let body = numbered_body(
+            &[Node::text("Source.", Style::default())],
+            "3",
+            &text,
+            500.0,
+        );
+        let NodeKind::Text { runs, .. } = &body[0].kind else {
+            panic!("expected text");
+        };
+        let contents: Vec<&str> = runs.iter().map(|r| r.content.as_str()).collect();
+        assert_eq!(contents, ["3", " ", "Source."]);
+
+        let body = numbered_body(&[Node::view(Style::default(), vec![])], "3", &text, 500.0);
+        assert_eq!(body.len(), 2);
+    }
+}
@@ -31,21 +31,32 @@
 //! container and then slices, so the flex calculations are wrong on both
 //! halves. We run flex AFTER splitting.
 
+pub mod background;
 pub mod flex;
+pub mod float;
+pub mod footnote;
 pub mod grid;
+pub mod object_fit;
 pub mod page_break;
+pub mod page_number;
+pub mod table;
+pub mod toc;
 
+use std::borrow::Cow;
 use std::cell::RefCell;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
 
 use serde::Serialize;
 
+use crate::diagnostics::{Diagnostic, DiagnosticCode};
 use crate::font::FontContext;
 use crate::model::*;
+use crate::resource::{ResourceResolver, CURRENT_DIR_RESOLVER};
 use crate::style::*;
 use crate::text::bidi;
 use crate::text::shaping;
-use crate::text::{BrokenLine, RunBrokenLine, StyledChar, TextLayout};
+use crate::text::{is_cjk, BrokenLine, LineWidths, RunBrokenLine, StyledChar, TextLayout};
 
 /// A bookmark entry collected during layout.
 #[derive(Debug, Clone, Serialize)]
@@ -412,6 +423,10 @@ pub struct LayoutPage {
     pub(crate) watermarks: Vec<Node>,
     /// Page config needed for fixed element layout (internal use).
     pub(crate) config: PageConfig,
+    /// Numbering section started on this page by a Page node (internal use).
+    pub(crate) numbering: Option<PageNumbering>,
+    /// Footnotes to place at the bottom of the page (internal use).
+    pub(crate) footnotes: Vec<footnote::FootnoteBlock>,
 }
 
 /// A positioned element on a page.
@@ -437,6 +452,12 @@ pub struct LayoutElement {
     pub href: Option<String>,
     /// Optional bookmark title for PDF outline entries.
     pub bookmark: Option<String>,
+    /// Nesting depth of `bookmark` in the outline: the number of bookmarked
+    /// nodes enclosing the node it came from.
+    pub bookmark_level: u32,
+    /// The `id` of the node whose content starts with this element. Set on
+    /// the first element a node produces, like `bookmark`.
+    pub node_id: Option<String>,
     /// Optional alt text for images and SVGs (accessibility).
     pub alt: Option<String>,
     /// Whether this is a table header row (for tagged PDF: TH vs TD).
@@ -466,6 +487,7 @@ fn node_kind_name(kind: &NodeKind) -> &'static str {
         NodeKind::Canvas { .. } => "Canvas",
         NodeKind::QrCode { .. } => "QrCode",
         NodeKind::Watermark { .. } => "Watermark",
+        NodeKind::TableOfContents { .. } => "TableOfContents",
     }
 }
 
@@ -477,6 +499,9 @@ pub enum DrawCommand {
     /// Draw a rectangle (background, border).
     Rect {
         background: Option<Color>,
+        /// Drawn over `background`, clipped to the rectangle and its
+        /// rounded corners.
+        background_image: Option<Box<background::Background>>,
         border_width: Edges,
         border_color: EdgeValues<Color>,
         border_radius: CornerValues,
@@ -489,9 +514,13 @@ pub enum DrawCommand {
         text_decoration: TextDecoration,
         opacity: f64,
     },
-    /// Draw an image.
+    /// Draw an image. Every element drawing the same image source shares
+    /// its decoded data.
     Image {
-        image_data: crate::image_loader::LoadedImage,
+        image_data: Arc<crate::image_loader::LoadedImage>,
+        /// Where the image is drawn in the element's box, following
+        /// `object_fit`; what falls outside the box is clipped.
+        placement: object_fit::ImagePlacement,
     },
     /// Draw a grey placeholder rectangle (fallback when image loading fails).
     ImagePlaceholder,
@@ -529,6 +558,13 @@ pub struct TextLine {
     pub height: f64,
     /// Extra width added to each space character for justification (PDF `Tw` operator).
     pub word_spacing: f64,
+    /// Extra width added after each glyph for inter-character justification
+    /// of CJK text (added to the PDF `Tc` operator).
+    pub char_spacing: f64,
+    /// A column of vertical text (`WritingMode::VerticalRl`): glyphs run
+    /// down from `y`, centered on `x`, and each glyph's `x_offset` is its
+    /// distance down the column.
+    pub vertical: bool,
 }
 
 #[derive(Debug, Clone)]
@@ -545,7 +581,11 @@ pub struct PositionedGlyph {
     pub font_size: f64,
     pub font_family: String,
     pub font_weight: u32,
+    pub font_stretch: u32,
     pub font_style: FontStyle,
+    /// OpenType features the glyph was shaped with, shared with the rest
+    /// of its run.
+    pub font_features: Arc<[FontFeature]>,
     /// The character this glyph represents. For ligatures, the first char of the cluster.
     pub char_value: char,
     /// Per-glyph color (for text runs with different colors).
@@ -559,6 +599,9 @@ pub struct PositionedGlyph {
     /// For ligature glyphs, the full cluster text (e.g., "fi" for an fi ligature).
     /// `None` for 1:1 char-to-glyph mappings.
     pub cluster_text: Option<String>,
+    /// How far the glyph is raised above the baseline, in points (negative:
+    /// lowered), for superscripts and subscripts.
+    pub baseline_shift: f64,
 }
 
 /// Shift a layout element and all its nested content (children, text lines)
@@ -575,8 +618,28 @@ fn offset_element_y(el: &mut LayoutElement, dy: f64) {
     }
 }
 
+/// Whether text wraps line by line around floats, rather than being
+/// narrowed as a whole to fit beside them.
+fn wraps_around_floats(node: &Node, style: &ResolvedStyle) -> bool {
+    matches!(node.kind, NodeKind::Text { .. })
+        && !matches!(style.writing_mode, WritingMode::VerticalRl)
+}
+
+/// Where the next line of text goes in a box from `x` to `x + width`, as
+/// `(left edge, width)`: beside the floats level with it.
+fn line_beside_floats(cursor: &PageCursor, x: f64, width: f64, line_height: f64) -> (f64, f64) {
+    let (left, right) = float::insets(&cursor.floats, x, width, cursor.y, cursor.y + line_height);
+    (x + left, width - left - right)
+}
+
+/// Whether a view flows its children through newspaper-style columns.
+fn is_multi_column(style: &ResolvedStyle) -> bool {
+    style.column_count > 1
+        && !matches!(style.display, Display::Grid)
+        && matches!(style.flex_direction, FlexDirection::Column)
+}
+
 /// Shift a layout element and all its nested content horizontally by `dx` points.
-#[allow(dead_code)]
 fn offset_element_x(el: &mut LayoutElement, dx: f64) {
     el.x += dx;
     if let DrawCommand::Text { ref mut lines, .. } = el.draw {
@@ -589,6 +652,509 @@ fn offset_element_x(el: &mut LayoutElement, dx: f64) {
     }
 }
 
+/// A table's children sorted into header, body and footer rows, each in
+/// document order.
+type TableRowGroups<'a> = (Vec<&'a Node>, Vec<&'a Node>, Vec<(&'a Node, TableFooter)>);
+
+/// Split a table's children into header rows, body rows and footer rows.
+fn partition_table_rows(node: &Node) -> TableRowGroups<'_> {
+    let mut header = Vec::new();
+    let mut body = Vec::new();
+    let mut footers = Vec::new();
+    for child in &node.children {
+        match child.kind {
+            NodeKind::TableRow {
+                footer: Some(mode), ..
+            } => footers.push((child, mode)),
+            NodeKind::TableRow {
+                is_header: true, ..
+            } => header.push(child),
+            _ => body.push(child),
+        }
+    }
+    (header, body, footers)
+}
+
+/// The `(col_span, row_span)` of each cell, row by row. Children that
+/// aren't `TableCell`s occupy a single slot.
+fn table_cell_spans(rows: &[&Node]) -> Vec<Vec<(u32, u32)>> {
+    rows.iter()
+        .map(|row| {
+            row.children
+                .iter()
+                .map(|cell| match cell.kind {
+                    NodeKind::TableCell { col_span, row_span } => (col_span, row_span),
+                    _ => (1, 1),
+                })
+                .collect()
+        })
+        .collect()
+}
+
+/// All text content inside a node, concatenated in document order.
+fn node_text(node: &Node) -> String {
+    let mut text = String::new();
+    collect_node_text(node, &mut text);
+    text
+}
+
+fn collect_node_text(node: &Node, text: &mut String) {
+    if let NodeKind::Text { content, runs, .. } = &node.kind {
+        if runs.is_empty() {
+            text.push_str(content);
+        } else {
+            for run in runs {
+                text.push_str(&run.content);
+                for body in run.footnote.iter().flatten() {
+                    collect_node_text(body, text);
+                }
+            }
+        }
+    }
+    for child in &node.children {
+        collect_node_text(child, text);
+    }
+}
+
+/// Clone a node, rewriting the content of every text node inside it.
+fn map_node_text(node: &Node, f: &dyn Fn(&str) -> String) -> Node {
+    let mut mapped = node.clone();
+    if let NodeKind::Text { content, runs, .. } = &mut mapped.kind {
+        *content = f(content);
+        for run in runs.iter_mut() {
+            run.content = f(&run.content);
+            if let Some(body) = &mut run.footnote {
+                *body = body.iter().map(|node| map_node_text(node, f)).collect();
+            }
+        }
+    }
+    mapped.children = node
+        .children
+        .iter()
+        .map(|child| map_node_text(child, f))
+        .collect();
+    mapped
+}
+
+/// Rows of one table section (header or body) placed on the column grid.
+struct TableSection<'a> {
+    rows: Vec<&'a Node>,
+    placements: Vec<Vec<table::CellPlacement>>,
+    /// Measured row heights, filled in once column widths are known.
+    heights: Vec<f64>,
+}
+
+impl<'a> TableSection<'a> {
+    fn new(rows: Vec<&'a Node>) -> Self {
+        let placements = table::place_cells(&table_cell_spans(&rows));
+        Self {
+            rows,
+            placements,
+            heights: Vec::new(),
+        }
+    }
+
+    fn height(&self) -> f64 {
+        self.heights.iter().sum()
+    }
+}
+
+/// A table ready for layout: header and body measured against resolved
+/// column widths. Footer rows are measured when they're drawn, since their
+/// sum tokens change from fragment to fragment.
+struct PreparedTable<'a> {
+    header: TableSection<'a>,
+    body: TableSection<'a>,
+    footers: Vec<(&'a Node, TableFooter)>,
+    col_widths: Vec<f64>,
+}
+
+impl<'a> PreparedTable<'a> {
+    /// Footer rows drawn at the end of a fragment, in document order.
+    fn footer_rows(&self, is_last_fragment: bool) -> Vec<&'a Node> {
+        self.footers
+            .iter()
+            .filter(|(_, mode)| match mode {
+                TableFooter::Repeat => true,
+                TableFooter::Continued => !is_last_fragment,
+                TableFooter::Final => is_last_fragment,
+            })
+            .map(|(row, _)| *row)
+            .collect()
+    }
+}
+
+/// State threaded through a table's layout while it's split into
+/// page fragments.
+struct TableContext<'a> {
+    table: PreparedTable<'a>,
+    style: &'a ResolvedStyle,
+    /// Left edge of the first column.
+    cell_x: f64,
+    /// The cursor's `reserved_bottom` outside this table; restored while
+    /// footer rows are drawn into the space reserved for them.
+    outer_reserved_bottom: f64,
+    sums: table::ColumnSums,
+}
+
+impl TableContext<'_> {
+    /// Count a body row's numeric cells toward the footer sums, returning
+    /// a diagnostic for each number its column couldn't read.
+    fn add_row_sums(&mut self, row_idx: usize) -> Vec<Diagnostic> {
+        let row = self.table.body.rows[row_idx];
+        let mut skipped = Vec::new();
+        for placement in &self.table.body.placements[row_idx] {
+            let Some(cell) = row.children.get(placement.cell_index) else {
+                continue;
+            };
+            let text = node_text(cell);
+            if !self.sums.add(placement.col_start, &text) {
+                skipped.push(Diagnostic::for_node(
+                    DiagnosticCode::InconsistentNumberFormat,
+                    format!(
+                        "\"{}\" uses a different decimal mark from column {} and was left out of its sums",
+                        text.trim(),
+                        placement.col_start
+                    ),
+                    cell,
+                ));
+            }
+        }
+        skipped
+    }
+}
+
+/// Layout passes made at most while cross-referenced pages settle.
+const MAX_LAYOUT_PASSES: usize = 5;
+
+/// Page numbers printed by `{{pageRef:id}}` tokens and tables of contents,
+/// as of one layout pass.
+#[derive(Debug, PartialEq)]
+struct CrossReferences {
+    /// Page label of every node `id`.
+    page_refs: HashMap<String, String>,
+    /// Rows for every table of contents.
+    toc: Vec<toc::TocEntry>,
+}
+
+impl CrossReferences {
+    /// Whether the document prints any cross-referenced page numbers.
+    fn needed(document: &Document) -> bool {
+        document.children.iter().any(|node| {
+            toc::has_table_of_contents(node) || page_number::has_page_refs(&node_text(node))
+        })
+    }
+
+    /// A guess before any layout: the table of contents rows without their
+    /// page numbers, and no page references.
+    fn estimate(document: &Document) -> Self {
+        Self {
+            page_refs: HashMap::new(),
+            toc: toc::estimate_entries(&document.children),
+        }
+    }
+
+    /// The page numbers on a laid-out document.
+    fn collect(pages: &[LayoutPage]) -> Self {
+        let numbers = number_layout_pages(pages);
+        Self {
+            page_refs: page_ref_labels(pages, &numbers),
+            toc: toc::collect_entries(pages, &numbers),
+        }
+    }
+
+    /// The document with page references substituted and tables of
+    /// contents filled in.
+    fn apply(&self, document: &Document) -> Document {
+        Document {
+            children: document
+                .children
+                .iter()
+                .map(|node| {
+                    let node = map_node_text(node, &|text| {
+                        page_number::substitute_page_refs(text, &self.page_refs)
+                    });
+                    toc::fill(&node, &self.toc)
+                })
+                .collect(),
+            ..document.clone()
+        }
+    }
+}
+
+/// Mark the first element laid out since `start_len` elements into page
+/// `start_page` with a node's `id`. The element may have landed on that
+/// page, a later finished page, or the cursor's current page. Elements
+/// already carrying a descendant's id (nodes that don't wrap their
+/// children, like Page) are skipped.
+fn tag_first_element(
+    pages: &mut [LayoutPage],
+    cursor: &mut PageCursor,
+    start_page: usize,
+    start_len: usize,
+    id: &str,
+) {
+    let finished = pages.iter_mut().skip(start_page).map(|p| &mut p.elements);
+    for (i, elements) in finished
+        .chain(std::iter::once(&mut cursor.elements))
+        .enumerate()
+    {
+        let from = if i == 0 { start_len } else { 0 };
+        if let Some(el) = elements
+            .iter_mut()
+            .skip(from)
+            .find(|el| el.node_id.is_none())
+        {
+            el.node_id = Some(id.to_string());
+            return;
+        }
+    }
+}
+
+/// Lines a text of `lines` broken lines shows: `Ellipsis` and `Clip`
+/// truncate it to a single line.
+fn visible_line_count(lines: usize, style: &ResolvedStyle) -> usize {
+    match style.text_overflow {
+        TextOverflow::Wrap => lines,
+        TextOverflow::Ellipsis | TextOverflow::Clip => lines.min(1),
+    }
+}
+
+/// Nest the bookmarks laid out since `start_len` elements into page
+/// `start_page` under a node's own bookmark `title`. The first bookmark with
+/// that title is the node's own; every other one came from a descendant and
+/// moves one level deeper.
+fn nest_bookmarks(
+    pages: &mut [LayoutPage],
+    cursor: &mut PageCursor,
+    start_page: usize,
+    start_len: usize,
+    title: &str,
+) {
+    fn nest(elements: &mut [LayoutElement], title: &str, own_found: &mut bool) {
+        for el in elements {
+            if let Some(bookmark) = &el.bookmark {
+                if !*own_found && bookmark == title {
+                    *own_found = true;
+                } else {
+                    el.bookmark_level += 1;
+                }
+            }
+            nest(&mut el.children, title, own_found);
+        }
+    }
+    let mut own_found = false;
+    let finished = pages.iter_mut().skip(start_page).map(|p| &mut p.elements);
+    for (i, elements) in finished
+        .chain(std::iter::once(&mut cursor.elements))
+        .enumerate()
+    {
+        let from = if i == 0 { start_len } else { 0 };
+        if let Some(elements) = elements.get_mut(from..) {
+            nest(elements, title, &mut own_found);
+        }
+    }
+}
+
+/// A diagnostic for every `#name` link whose target is neither a node `id`
+/// nor a bookmark title. Each missing target is reported once.
+fn missing_link_targets(pages: &[LayoutPage]) -> Vec<Diagnostic> {
+    fn collect<'a>(
+        elements: &'a [LayoutElement],
+        targets: &mut HashSet<&'a str>,
+        links: &mut Vec<&'a LayoutElement>,
+    ) {
+        for el in elements {
+            if let Some(id) = &el.node_id {
+                targets.insert(id);
+            }
+            if let Some(title) = &el.bookmark {
+                targets.insert(title);
+            }
+            if el.href.as_deref().is_some_and(|h| h.starts_with('#')) {
+                links.push(el);
+            }
+            collect(&el.children, targets, links);
+        }
+    }
+    let mut targets = HashSet::new();
+    let mut links = Vec::new();
+    for page in pages {
+        collect(&page.elements, &mut targets, &mut links);
+    }
+
+    let mut reported = HashSet::new();
+    let mut diagnostics = Vec::new();
+    for el in links {
+        let href = el.href.as_deref().unwrap_or_default();
+        if !targets.contains(&href[1..]) && reported.insert(href) {
+            diagnostics.push(Diagnostic {
+                node_id: el.node_id.clone(),
+                source_location: el.source_location.clone(),
+                ..Diagnostic::new(
+                    DiagnosticCode::MissingLinkTarget,
+                    format!("Link target \"{href}\" matches no node id or bookmark"),
+                )
+            });
+        }
+    }
+    diagnostics
+}
+
+/// A diagnostic for every `{{pageRef:id}}` whose id isn't in `labels`,
+/// against the text node it's in. Each missing id is reported once.
+fn missing_page_refs(document: &Document, labels: &HashMap<String, String>) -> Vec<Diagnostic> {
+    fn collect(
+        node: &Node,
+        labels: &HashMap<String, String>,
+        reported: &mut HashSet<String>,
+        diagnostics: &mut Vec<Diagnostic>,
+    ) {
+        if let NodeKind::Text { content, runs, .. } = &node.kind {
+            let texts = std::iter::once(content).chain(runs.iter().map(|run| &run.content));
+            for id in texts.flat_map(|text| page_number::page_ref_ids(text)) {
+                if !labels.contains_key(id) && reported.insert(id.to_string()) {
+                    diagnostics.push(Diagnostic::for_node(
+                        DiagnosticCode::MissingPageRefTarget,
+                        format!(
+                            "Page reference \"{id}\" matches no node id, printing \"{}\"",
+                            page_number::MISSING_PAGE_REF
+                        ),
+                        node,
+                    ));
+                }
+            }
+            for body in runs.iter().filter_map(|run| run.footnote.as_ref()) {
+                for node in body {
+                    collect(node, labels, reported, diagnostics);
+                }
+            }
+        }
+        for child in &node.children {
+            collect(child, labels, reported, diagnostics);
+        }
+    }
+    let mut reported = HashSet::new();
+    let mut diagnostics = Vec::new();
+    for node in &document.children {
+        collect(node, labels, &mut reported, &mut diagnostics);
+    }
+    diagnostics
+}
+
+/// Page numbers for laid-out pages, following their numbering sections.
+fn number_layout_pages(pages: &[LayoutPage]) -> Vec<page_number::PageNumbers> {
+    let section_starts: Vec<Option<PageNumbering>> =
+        pages.iter().map(|page| page.numbering.clone()).collect();
+    page_number::number_pages(&section_starts)
+}
+
+/// The printed page number of every node `id` on the pages, keyed by id.
+/// The first page an id appears on wins.
+fn page_ref_labels(
+    pages: &[LayoutPage],
+    numbers: &[page_number::PageNumbers],
+) -> HashMap<String, String> {
+    fn collect(elements: &[LayoutElement], label: &str, labels: &mut HashMap<String, String>) {
+        for el in elements {
+            if let Some(id) = &el.node_id {
+                labels
+                    .entry(id.clone())
+                    .or_insert_with(|| label.to_string());
+            }
+            collect(&el.children, label, labels);
+        }
+    }
+    let mut labels = HashMap::new();
+    for (page, numbers) in pages.iter().zip(numbers) {
+        collect(&page.elements, &numbers.label(), &mut labels);
+    }
+    labels
+}
+
+/// The characters of a text node's runs, each with its run's resolved style.
+fn styled_chars_from_runs(
+    runs: &[TextRun],
+    parent_href: Option<&str>,
+    style: &ResolvedStyle,
+    text_width: f64,
+) -> Vec<StyledChar> {
+    let mut styled_chars: Vec<StyledChar> = Vec::new();
+    for (run_idx, run) in runs.iter().enumerate() {
+        let run_style = run.style.resolve(Some(style), text_width);
+        let run_href = run.href.as_deref().or(parent_href);
+        let transform = run_style.text_transform;
+        let baseline_shift = run_style.vertical_align.baseline_shift(style.font_size);
+        let footnote = run.footnote.is_some().then_some(run_idx);
+        let mut prev_is_whitespace = true;
+        for ch in run.content.chars() {
+            let transformed_ch = apply_char_transform(ch, transform, prev_is_whitespace);
+            prev_is_whitespace = ch.is_whitespace();
+            styled_chars.push(StyledChar {
+                ch: transformed_ch,
+                font_family: run_style.font_family.clone(),
+                font_size: run_style.font_size,
+                font_weight: run_style.font_weight,
+                font_stretch: run_style.font_stretch,
+                font_style: run_style.font_style,
+                font_features: run_style.font_features.clone(),
+                color: run_style.color,
+                href: run_href.map(|s| s.to_string()),
+                text_decoration: run_style.text_decoration,
+                letter_spacing: run_style.letter_spacing,
+                baseline_shift,
+                footnote,
+            });
+        }
+    }
+    styled_chars
+}
+
+/// The footnotes a line references, as the indices of their runs.
+fn footnote_references(line: &RunBrokenLine) -> Vec<usize> {
+    let mut references: Vec<usize> = Vec::new();
+    for run in line.chars.iter().filter_map(|sc| sc.footnote) {
+        if references.last() != Some(&run) {
+            references.push(run);
+        }
+    }
+    references
+}
+
+/// Extra spacing that stretches a line's glyphs to `text_width`, as
+/// `(word_spacing, char_spacing)`. Lines with CJK text are stretched between
+/// every pair of characters, as CJK typesetting does; other lines only at
+/// spaces (PDF `Tw`). Uses the sum of natural glyph advances (what PDF Tj
+/// actually renders) rather than KP-adjusted positions, which bake
+/// justification into char_positions and make slack ≈ 0.
+fn justify_spacing(glyphs: &[PositionedGlyph], text_width: f64) -> (f64, f64) {
+    let Some(idx) = glyphs.iter().rposition(|g| g.char_value != ' ') else {
+        return (0.0, 0.0);
+    };
+    let natural_width: f64 = glyphs[..=idx].iter().map(|g| g.x_advance).sum();
+    let slack = text_width - natural_width;
+    if slack.abs() <= 0.01 {
+        return (0.0, 0.0);
+    }
+    if glyphs[..=idx].iter().any(|g| is_cjk(g.char_value)) {
+        // Each glyph but the last gets the extra space after it
+        return if idx > 0 {
+            (0.0, slack / idx as f64)
+        } else {
+            (0.0, 0.0)
+        };
+    }
+    let space_count = glyphs[..=idx]
+        .iter()
+        .filter(|g| g.char_value == ' ')
+        .count();
+    if space_count > 0 {
+        (slack / space_count as f64, 0.0)
+    } else {
+        (0.0, 0.0)
+    }
+}
+
 /// After flex-grow expands an element's height, redistribute its children
 /// vertically according to its justify-content setting. Only meaningful for
 /// column containers whose height was just increased by flex-grow.
@@ -691,9 +1257,15 @@ fn apply_char_transform(ch: char, transform: TextTransform, is_word_start: bool)
 }
 
 /// The main layout engine.
-pub struct LayoutEngine {
+pub struct LayoutEngine<'r> {
     text_layout: TextLayout,
+    /// Reads the images the document refers to.
+    resolver: &'r dyn ResourceResolver,
     image_dim_cache: RefCell<HashMap<String, (u32, u32)>>,
+    /// Decoded images by source, so an image drawn on every page (a logo in
+    /// a fixed header) is read and decoded once.
+    image_cache: RefCell<HashMap<String, Result<Arc<crate::image_loader::LoadedImage>, String>>>,
+    diagnostics: RefCell<Vec<Diagnostic>>,
 }
 
 /// Tracks where we are on the current page during layout.
@@ -712,6 +1284,97 @@ struct PageCursor {
     content_y: f64,
     /// Extra Y offset applied on continuation pages (e.g. parent view's padding+border)
     continuation_top_offset: f64,
+    /// Space kept free at the bottom of every page (e.g. for repeating table footer rows)
+    reserved_bottom: f64,
+    /// Numbering section started on this page; not carried to continuation pages
+    numbering: Option<PageNumbering>,
+    /// Set while laying out the children of a multi-column container: each
+    /// "page" is then one column.
+    column_flow: Option<ColumnFlow>,
+    /// Floats placed among the children being laid out, which text wraps
+    /// around. They belong to the page they're on, so a new page has none.
+    floats: Vec<float::PlacedFloat>,
+    /// Footnotes placed at the bottom of this page.
+    footnotes: Vec<footnote::FootnoteBlock>,
+    /// Footnotes, or their remaining parts, continued on the next page.
+    footnote_overflow: Vec<footnote::FootnoteBlock>,
+    footnote_numbering: FootnoteNumbering,
+    /// Footnotes numbered so far, in the document or on this page.
+    footnote_count: usize,
+}
+
+/// Newspaper-style columns being filled. A page break inside the flow moves
+/// to the top of the next column, and after the last column to a new page.
+#[derive(Debug, Clone)]
+struct ColumnFlow {
+    count: usize,
+    /// The column being filled on the current page.
+    column: usize,
+    /// How many pages the flow has moved on.
+    page: usize,
+    /// Where the columns start on the current page.
+    top: f64,
+    /// Column height on one page of the flow, `(page, height)`, for
+    /// balancing that page's columns.
+    balance: Option<(usize, f64)>,
+}
+
+/// The run of children a multi-column container flows through its
+/// columns. Balancing the last page lays the run out again at several
+/// column heights; the first layout takes a snapshot as each child starts,
+/// so the later ones can pick up from the last child to start before the
+/// last page instead of from the top.
+#[derive(Default)]
+struct ColumnRun {
+    /// Whether to take snapshots.
+    record: bool,
+    /// The snapshot to pick up from.
+    resume: Option<RunSnapshot>,
+    /// The latest snapshot, and the latest one from an earlier page of the
+    /// flow than it.
+    latest: Option<RunSnapshot>,
+    earlier: Option<RunSnapshot>,
+}
+
+impl ColumnRun {
+    fn snapshot(&mut self, snapshot: RunSnapshot) {
+        if self
+            .latest
+            .as_ref()
+            .is_some_and(|latest| latest.page() < snapshot.page())
+        {
+            self.earlier = self.latest.take();
+        }
+        self.latest = Some(snapshot);
+    }
+
+    /// The last snapshot taken before flow page `page` started.
+    fn before_page(&mut self, page: usize) -> Option<RunSnapshot> {
+        [self.latest.take(), self.earlier.take()]
+            .into_iter()
+            .flatten()
+            .find(|snapshot| snapshot.page() < page)
+    }
+}
+
+/// The layout of a multi-column run as one of its children starts.
+#[derive(Clone)]
+struct RunSnapshot {
+    /// The child, and how many children in the flow came before it.
+    item: usize,
+    in_flow: usize,
+    /// Where the run's content box starts, for its absolute children.
+    box_y: f64,
+    cursor: PageCursor,
+    /// How many columns were finished.
+    columns: usize,
+}
+
+impl RunSnapshot {
+    /// The page of the flow the child starts on.
+    fn page(&self) -> usize {
+        self.cursor.column_flow.as_ref().map_or(0, |flow| flow.page)
+    }
 }
 
 impl PageCursor {
@@ -732,12 +1395,34 @@ impl PageCursor {
             content_x: config.margin.left,
             content_y: config.margin.top,
             continuation_top_offset: 0.0,
+            reserved_bottom: 0.0,
+            numbering: None,
+            column_flow: None,
+            floats: Vec::new(),
+            footnotes: Vec::new(),
+            footnote_overflow: Vec::new(),
+            footnote_numbering: FootnoteNumbering::default(),
+            footnote_count: 0,
         }
     }
 
     fn remaining_height(&self) -> f64 {
         let footer_height: f64 = self.fixed_footer.iter().map(|(_, h)| *h).sum();
-        (self.content_height - self.y - footer_height).max(0.0)
+        let remaining = self.content_height
+            - self.y
+            - footer_height
+            - self.reserved_bottom
+            - footnote::area_height(&self.footnotes);
+        let remaining = match &self.column_flow {
+            Some(ColumnFlow {
+                page,
+                top,
+                balance: Some((balanced_page, height)),
+                ..
+            }) if page == balanced_page => remaining.min(top + height - self.y),
+            _ => remaining,
+        };
+        remaining.max(0.0)
     }
 
     fn finalize(&self) -> LayoutPage {
@@ -750,6 +1435,8 @@ impl PageCursor {
             fixed_footer: self.fixed_footer.clone(),
             watermarks: self.watermarks.clone(),
             config: self.config.clone(),
+            numbering: self.numbering.clone(),
+            footnotes: self.footnotes.clone(),
         }
     }
 
@@ -759,25 +1446,93 @@ impl PageCursor {
         cursor.fixed_footer = self.fixed_footer.clone();
         cursor.watermarks = self.watermarks.clone();
         cursor.continuation_top_offset = self.continuation_top_offset;
+        cursor.reserved_bottom = self.reserved_bottom;
 
         let header_height: f64 = cursor.fixed_header.iter().map(|(_, h)| *h).sum();
         cursor.y = header_height + cursor.continuation_top_offset;
+        self.carry_footnote_count(&mut cursor);
+        cursor.footnotes = self.footnote_overflow.clone();
+
+        if let Some(flow) = &self.column_flow {
+            let mut flow = flow.clone();
+            if flow.column + 1 < flow.count {
+                // The next column is on the same page, with the same footnotes
+                flow.column += 1;
+                cursor.y = flow.top;
+                cursor.footnotes = self.footnotes.clone();
+                cursor.footnote_overflow = self.footnote_overflow.clone();
+                cursor.footnote_count = self.footnote_count;
+            } else {
+                flow.column = 0;
+                flow.page += 1;
+                flow.top = cursor.y;
+            }
+            cursor.column_flow = Some(flow);
+        }
 
         cursor
     }
+
+    /// Carry the footnote numbering on to `next`, which starts a new page.
+    fn carry_footnote_count(&self, next: &mut PageCursor) {
+        next.footnote_numbering = self.footnote_numbering;
+        next.footnote_count = match self.footnote_numbering {
+            FootnoteNumbering::Document => self.footnote_count,
+            FootnoteNumbering::Page => 0,
+        };
+    }
+
+    /// Finish the footnotes continued past the current page on pages of
+    /// their own.
+    fn flush_footnotes(&mut self, pages: &mut Vec<LayoutPage>) {
+        while !self.footnote_overflow.is_empty() {
+            pages.push(self.finalize());
+            *self = self.new_page();
+        }
+    }
+
+    /// Whether anything has been placed on the page.
+    fn has_content(&self) -> bool {
+        !self.elements.is_empty() || self.y > 0.0 || !self.footnotes.is_empty()
+    }
 }
 
-impl Default for LayoutEngine {
+impl Default for LayoutEngine<'static> {
     fn default() -> Self {
         Self::new()
     }
 }
 
-impl LayoutEngine {
+impl LayoutEngine<'static> {
+    /// An engine that reads referenced images from the current directory.
     pub fn new() -> Self {
+        LayoutEngine::with_resolver(&CURRENT_DIR_RESOLVER)
+    }
+}
+
+impl<'r> LayoutEngine<'r> {
+    /// An engine that reads referenced images through `resolver`.
+    pub fn with_resolver(resolver: &'r dyn ResourceResolver) -> Self {
         Self {
             text_layout: TextLayout::new(),
+            resolver,
             image_dim_cache: RefCell::new(HashMap::new()),
+            image_cache: RefCell::new(HashMap::new()),
+            diagnostics: RefCell::new(Vec::new()),
+        }
+    }
+
+    /// Diagnostics from the most recent `layout` call.
+    pub fn diagnostics(&self) -> Vec<Diagnostic> {
+        self.diagnostics.borrow().clone()
+    }
+
+    /// Record a diagnostic, once: tables and flex rows can lay the same
+    /// node out more than once.
+    fn report(&self, diagnostic: Diagnostic) {
+        let mut diagnostics = self.diagnostics.borrow_mut();
+        if !diagnostics.contains(&diagnostic) {
+            diagnostics.push(diagnostic);
         }
     }
 
@@ -786,7 +1541,7 @@ impl LayoutEngine {
         if let Some(dims) = self.image_dim_cache.borrow().get(src) {
             return Some(*dims);
         }
-        if let Ok(dims) = crate::image_loader::load_image_dimensions(src) {
+        if let Ok(dims) = crate::image_loader::load_image_dimensions(src, self.resolver) {
             self.image_dim_cache
                 .borrow_mut()
                 .insert(src.to_string(), dims);
@@ -796,10 +1551,128 @@ impl LayoutEngine {
         }
     }
 
+    /// Look up a cached decoded image, or load and cache it.
+    fn get_image(&self, src: &str) -> Result<Arc<crate::image_loader::LoadedImage>, String> {
+        if let Some(image) = self.image_cache.borrow().get(src) {
+            return image.clone();
+        }
+        let image = crate::image_loader::load_image(src, self.resolver).map(Arc::new);
+        self.image_cache
+            .borrow_mut()
+            .insert(src.to_string(), image.clone());
+        image
+    }
+
+    /// The background image or gradient `node` draws, if any. An image that
+    /// can't be loaded is left out.
+    fn get_background(&self, node: &Node, style: &ResolvedStyle) -> Option<background::Background> {
+        let stops = background::gradient_stops;
+        match style.background_image.as_ref()? {
+            BackgroundImage::Url(src) => match self.get_image(src) {
+                Ok(image_data) => Some(background::Background::Image {
+                    image_data,
+                    size: style.background_size,
+                    repeat: style.background_repeat,
+                    position: style.background_position,
+                }),
+                Err(e) => {
+                    self.report(Diagnostic::for_node(
+                        DiagnosticCode::ImageLoadFailed,
+                        format!("Background image could not be loaded, leaving it out: {e}"),
+                        node,
+                    ));
+                    None
+                }
+            },
+            BackgroundImage::LinearGradient(gradient) => {
+                Some(background::Background::LinearGradient {
+                    angle: gradient.angle,
+                    stops: stops(&gradient.stops),
+                })
+            }
+            BackgroundImage::RadialGradient(gradient) => {
+                Some(background::Background::RadialGradient {
+                    stops: stops(&gradient.stops),
+                })
+            }
+        }
+    }
+
     /// Main entry point: lay out a document into pages.
     pub fn layout(&self, document: &Document, font_context: &FontContext) -> Vec<LayoutPage> {
+        let mut pages = if CrossReferences::needed(document) {
+            self.paginate_cross_referenced(document, font_context, MAX_LAYOUT_PASSES)
+        } else {
+            self.paginate(document, font_context)
+        };
+
+        let numbers = number_layout_pages(&pages);
+        self.inject_fixed_elements(&mut pages, &numbers, font_context);
+        self.resolve_page_number_tokens(&mut pages, &numbers, font_context);
+
+        for diagnostic in missing_link_targets(&pages) {
+            self.report(diagnostic);
+        }
+        if CrossReferences::needed(document) {
+            let labels = page_ref_labels(&pages, &numbers);
+            for diagnostic in missing_page_refs(document, &labels) {
+                self.report(diagnostic);
+            }
+        }
+
+        pages
+    }
+
+    /// Lay out a document that prints cross-referenced page numbers.
+    /// Page references and tables of contents print page numbers that can
+    /// move the content they point to, so lay out again until every target
+    /// page is stable, at most `max_passes` times.
+    fn paginate_cross_referenced(
+        &self,
+        document: &Document,
+        font_context: &FontContext,
+        max_passes: usize,
+    ) -> Vec<LayoutPage> {
+        // Table of contents rows link to their bookmark's node id
+        let mut count = 0;
+        let document = &Document {
+            children: document
+                .children
+                .iter()
+                .map(|node| toc::anchor_bookmarks(node, &mut count))
+                .collect(),
+            ..document.clone()
+        };
+        let mut refs = CrossReferences::estimate(document);
+        let mut pages = self.paginate(&refs.apply(document), font_context);
+        for pass in 1.. {
+            let resolved = CrossReferences::collect(&pages);
+            if resolved == refs {
+                break;
+            }
+            if pass >= max_passes {
+                self.report(Diagnostic::new(
+                    DiagnosticCode::UnsettledPageRefs,
+                    format!(
+                        "cross-referenced page numbers were still moving after {} layout passes and may be out of date",
+                        max_passes
+                    ),
+                ));
+                break;
+            }
+            refs = resolved;
+            pages = self.paginate(&refs.apply(document), font_context);
+        }
+        pages
+    }
+
+    /// Lay out a document's content into pages. Fixed elements and
+    /// page-number tokens are filled in afterwards by `layout`.
+    fn paginate(&self, document: &Document, font_context: &FontContext) -> Vec<LayoutPage> {
+        self.diagnostics.borrow_mut().clear();
         let mut pages: Vec<LayoutPage> = Vec::new();
         let mut cursor = PageCursor::new(&document.default_page);
+        cursor.footnote_numbering = document.footnote_numbering;
 
         // Build a root resolved style from document default_style + lang
         let base = document.default_style.clone().unwrap_or_default();
@@ -812,10 +1685,15 @@ impl LayoutEngine {
         for node in &document.children {
             match &node.kind {
                 NodeKind::Page { config } => {
-                    if !cursor.elements.is_empty() || cursor.y > 0.0 {
+                    cursor.flush_footnotes(&mut pages);
+                    if cursor.has_content() {
                         pages.push(cursor.finalize());
                     }
+                    let previous = cursor;
                     cursor = PageCursor::new(config);
+                    previous.carry_footnote_count(&mut cursor);
+                    cursor.numbering = config.numbering.clone();
+                    let start_page = pages.len();
 
                     // Build a page-level root style that carries document lang
                     // AND has a fixed height matching the page content area.
@@ -836,6 +1714,9 @@ impl LayoutEngine {
                         Some(&page_root),
                         font_context,
                     );
+                    if let Some(id) = &node.id {
+                        tag_first_element(&mut pages, &mut cursor, start_page, 0, id);
+                    }
                 }
                 NodeKind::PageBreak => {
                     pages.push(cursor.finalize());
@@ -858,12 +1739,11 @@ impl LayoutEngine {
             }
         }
 
-        if !cursor.elements.is_empty() || cursor.y > 0.0 {
+        cursor.flush_footnotes(&mut pages);
+        if cursor.has_content() {
             pages.push(cursor.finalize());
         }
 
-        self.inject_fixed_elements(&mut pages, font_context);
-
         pages
     }
 
@@ -878,13 +1758,57 @@ impl LayoutEngine {
         parent_style: Option<&ResolvedStyle>,
         font_context: &FontContext,
         cross_axis_height: Option<f64>,
+    ) {
+        let start_page = pages.len();
+        let start_len = cursor.elements.len();
+        self.layout_node_content(
+            node,
+            cursor,
+            pages,
+            x,
+            available_width,
+            parent_style,
+            font_context,
+            cross_axis_height,
+        );
+        if let Some(id) = &node.id {
+            tag_first_element(pages, cursor, start_page, start_len, id);
+        }
+        if let Some(title) = &node.bookmark {
+            nest_bookmarks(pages, cursor, start_page, start_len, title);
+        }
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn layout_node_content(
+        &self,
+        node: &Node,
+        cursor: &mut PageCursor,
+        pages: &mut Vec<LayoutPage>,
+        x: f64,
+        available_width: f64,
+        parent_style: Option<&ResolvedStyle>,
+        font_context: &FontContext,
+        cross_axis_height: Option<f64>,
     ) {
         let mut style = node.style.resolve(parent_style, available_width);
 
+        let is_image = matches!(node.kind, NodeKind::Image { .. });
+
+        // With neither side fixed, the aspect ratio sets the height from the
+        // width the box fills. Images keep their own sizing.
+        if let (Some(ratio), SizeConstraint::Auto) = (style.aspect_ratio, style.height) {
+            if !is_image {
+                style.height =
+                    SizeConstraint::Fixed((available_width - style.margin.horizontal()) / ratio);
+            }
+        }
+
         // When a flex row stretches a child, inject the cross-axis height so
         // justify-content, flex-grow, and other height-dependent logic works.
+        // Images keep their size.
         if let Some(h) = cross_axis_height {
-            if matches!(style.height, SizeConstraint::Auto) {
+            if matches!(style.height, SizeConstraint::Auto) && !is_image {
                 style.height = SizeConstraint::Fixed(h);
             }
         }
@@ -939,6 +1863,11 @@ impl LayoutEngine {
             }
 
             NodeKind::Image { width, height, .. } => {
+                // A fixed size in the style takes precedence, as when measuring
+                let fixed = |size: SizeConstraint, prop: Option<f64>| match size {
+                    SizeConstraint::Fixed(v) => Some(v),
+                    SizeConstraint::Auto => prop,
+                };
                 self.layout_image(
                     node,
                     &style,
@@ -946,8 +1875,8 @@ impl LayoutEngine {
                     pages,
                     x,
                     available_width,
-                    *width,
-                    *height,
+                    fixed(style.width, *width),
+                    fixed(style.height, *height),
                 );
             }
 
@@ -964,6 +1893,27 @@ impl LayoutEngine {
                 );
             }
 
+            NodeKind::TableOfContents { leader } => {
+                let width = match style.width {
+                    SizeConstraint::Fixed(w) => w,
+                    SizeConstraint::Auto => available_width - style.margin.horizontal(),
+                } - style.padding.horizontal()
+                    - style.border_width.horizontal();
+                let fitted = toc::fit_leaders(node, leader, width, |text| {
+                    let text_style = text.style.resolve(Some(&style), width);
+                    self.measure_intrinsic_width(text, &text_style, font_context)
+                });
+                self.layout_view(
+                    &fitted,
+                    &style,
+                    cursor,
+                    pages,
+                    x,
+                    available_width,
+                    font_context,
+                );
+            }
+
             NodeKind::View | NodeKind::Page { .. } => {
                 self.layout_view(
                     node,
@@ -1076,11 +2026,24 @@ impl LayoutEngine {
 
         let fits = total_height <= cursor.remaining_height() - margin.vertical();
 
-        if fits || !style.breakable {
+        if !is_multi_column(style) && (fits || !style.breakable) {
             if !fits && !style.breakable {
                 pages.push(cursor.finalize());
                 *cursor = cursor.new_page();
-            }
+                let room = cursor.remaining_height() - margin.vertical();
+                if total_height > room {
+                    self.report(Diagnostic::for_node(
+                        DiagnosticCode::ContentOverflow,
+                        format!(
+                            "{} can't break across pages and is {:.0}pt tall, but a page only has {:.0}pt; the rest is clipped",
+                            node_kind_name(&node.kind),
+                            total_height,
+                            room
+                        ),
+                        node,
+                    ));
+                }
+            }
 
             // Snapshot-and-collect: lay out children first, then wrap in parent
             let rect_y = cursor.content_y + cursor.y + margin.top;
@@ -1125,6 +2088,7 @@ impl LayoutEngine {
                 height: total_height,
                 draw: DrawCommand::Rect {
                     background: style.background_color,
+                    background_image: self.get_background(node, style).map(Box::new),
                     border_width: style.border_width,
                     border_color: style.border_color,
                     border_radius: style.border_radius,
@@ -1136,6 +2100,8 @@ impl LayoutEngine {
                 source_location: node.source_location.clone(),
                 href: node.href.clone(),
                 bookmark: node.bookmark.clone(),
+                bookmark_level: 0,
+                node_id: None,
                 alt: None,
                 is_header_row: false,
                 overflow: style.overflow,
@@ -1196,6 +2162,8 @@ impl LayoutEngine {
                 source_location: None,
                 href: None,
                 bookmark: node.bookmark.clone(),
+                bookmark_level: 0,
+                node_id: None,
                 alt: None,
                 is_header_row: false,
                 overflow: Overflow::default(),
@@ -1215,6 +2183,16 @@ impl LayoutEngine {
                 inner_width,
                 font_context,
             );
+        } else if is_multi_column(style) {
+            self.layout_columns(
+                &node.children,
+                style,
+                cursor,
+                pages,
+                children_x,
+                inner_width,
+                font_context,
+            );
         } else {
             self.layout_children(
                 &node.children,
@@ -1232,6 +2210,7 @@ impl LayoutEngine {
 
         // Check if this view has any visual styling worth wrapping
         let has_visual = style.background_color.is_some()
+            || style.background_image.is_some()
             || style.border_width.top > 0.0
             || style.border_width.right > 0.0
             || style.border_width.bottom > 0.0
@@ -1247,6 +2226,7 @@ impl LayoutEngine {
 
         let draw_cmd = DrawCommand::Rect {
             background: style.background_color,
+            background_image: self.get_background(node, style).map(Box::new),
             border_width: style.border_width,
             border_color: style.border_color,
             border_radius: style.border_radius,
@@ -1270,6 +2250,8 @@ impl LayoutEngine {
                 source_location: node.source_location.clone(),
                 href: node.href.clone(),
                 bookmark: node.bookmark.clone(),
+                bookmark_level: 0,
+                node_id: None,
                 alt: None,
                 is_header_row: false,
                 overflow: style.overflow,
@@ -1297,6 +2279,8 @@ impl LayoutEngine {
                     source_location: node.source_location.clone(),
                     href: node.href.clone(),
                     bookmark: node.bookmark.clone(),
+                    bookmark_level: 0,
+                    node_id: None,
                     alt: None,
                     is_header_row: false,
                     overflow: Overflow::default(),
@@ -1325,6 +2309,8 @@ impl LayoutEngine {
                         source_location: node.source_location.clone(),
                         href: None,
                         bookmark: None,
+                        bookmark_level: 0,
+                        node_id: None,
                         alt: None,
                         is_header_row: false,
                         overflow: Overflow::default(),
@@ -1351,6 +2337,8 @@ impl LayoutEngine {
                     source_location: node.source_location.clone(),
                     href: None,
                     bookmark: None,
+                    bookmark_level: 0,
+                    node_id: None,
                     alt: None,
                     is_header_row: false,
                     overflow: Overflow::default(),
@@ -1361,6 +2349,229 @@ impl LayoutEngine {
         cursor.y += padding.bottom + border.bottom + margin.bottom;
     }
 
+    /// Lay out children in newspaper-style columns (`column_count`). Each
+    /// run of children between `column_span: All` children flows through
+    /// the columns, continuing onto new pages; a spanning child is laid out
+    /// across all the columns below the run before it, which is balanced.
+    #[allow(clippy::too_many_arguments)]
+    fn layout_columns(
+        &self,
+        children: &[Node],
+        style: &ResolvedStyle,
+        cursor: &mut PageCursor,
+        pages: &mut Vec<LayoutPage>,
+        x: f64,
+        width: f64,
+        font_context: &FontContext,
+    ) {
+        let mut run_start = 0;
+        for (i, child) in children.iter().enumerate() {
+            if matches!(child.style.column_span, Some(ColumnSpan::All)) {
+                let run = &children[run_start..i];
+                self.flow_columns(run, true, style, cursor, pages, x, width, font_context);
+                if run_start < i {
+                    cursor.y += style.row_gap;
+                }
+                self.layout_node(
+                    child,
+                    cursor,
+                    pages,
+                    x,
+                    width,
+                    Some(style),
+                    font_context,
+                    None,
+                );
+                if i + 1 < children.len() {
+                    cursor.y += style.row_gap;
+                }
+                run_start = i + 1;
+            }
+        }
+        self.flow_columns(
+            &children[run_start..],
+            matches!(style.column_fill, ColumnFill::Balance),
+            style,
+            cursor,
+            pages,
+            x,
+            width,
+            font_context,
+        );
+    }
+
+    /// Flow `children` through the columns of a multi-column container.
+    ///
+    /// The children are laid out one column wide on a scratch cursor whose
+    /// page breaks move to the next column (see [`ColumnFlow`]), so every
+    /// kind of content breaks between columns the way it breaks between
+    /// pages. Each scratch page is then one column, shifted into place.
+    /// Balancing searches for the shortest columns on the last page that
+    /// still hold its content, laying out again from the last child to
+    /// start before that page rather than from the top of the run.
+    #[allow(clippy::too_many_arguments)]
+    fn flow_columns(
+        &self,
+        children: &[Node],
+        balance: bool,
+        style: &ResolvedStyle,
+        cursor: &mut PageCursor,
+        pages: &mut Vec<LayoutPage>,
+        x: f64,
+        width: f64,
+        font_context: &FontContext,
+    ) {
+        if children.is_empty() {
+            return;
+        }
+        let count = style.column_count as usize;
+        let gap = style.column_gap;
+        let column_width = ((width - gap * (count - 1) as f64) / count as f64).max(0.0);
+
+        // The elements of each column, the footnotes on the page as of each
+        // column, and the cursor where the last one ends. Picking up from a
+        // snapshot gives the columns from the snapshot's on.
+        let flow = |run: &mut ColumnRun, balance: Option<(usize, f64)>| {
+            let mut scratch = match &run.resume {
+                Some(snapshot) => snapshot.cursor.clone(),
+                None => {
+                    let mut scratch = cursor.clone();
+                    scratch.elements.clear();
+                    scratch.column_flow = Some(ColumnFlow {
+                        count,
+                        column: 0,
+                        page: 0,
+                        top: cursor.y,
+                        balance: None,
+                    });
+                    scratch
+                }
+            };
+            if let Some(flow) = &mut scratch.column_flow {
+                flow.balance = balance;
+            }
+            let mut scratch_pages = Vec::new();
+            self.layout_run_children(
+                children,
+                &mut scratch,
+                &mut scratch_pages,
+                x,
+                column_width,
+                Some(style),
+                font_context,
+                Some(run),
+            );
+            let (mut columns, mut footnotes): (Vec<_>, Vec<_>) = scratch_pages
+                .into_iter()
+                .map(|page| (page.elements, page.footnotes))
+                .unzip();
+            columns.push(std::mem::take(&mut scratch.elements));
+            footnotes.push(scratch.footnotes.clone());
+            (columns, footnotes, scratch)
+        };
+
+        let mut run = ColumnRun {
+            record: balance,
+            ..ColumnRun::default()
+        };
+        let (mut columns, mut footnotes, mut end) = flow(&mut run, None);
+        let page_count = columns.len().div_ceil(count);
+        if balance {
+            // Only the last page changes: lay out again from the last child
+            // to start before it, keeping the columns before that child's
+            let last_page = page_count - 1;
+            let start = run.before_page(last_page);
+            let kept = start.as_ref().map_or(0, |snapshot| snapshot.columns);
+            let mut short = 0.0;
+            let mut tall = cursor.content_height;
+            while tall - short > 0.5 {
+                let height = (short + tall) / 2.0;
+                let (balanced, balanced_footnotes, balanced_end) = flow(
+                    &mut ColumnRun {
+                        resume: start.clone(),
+                        ..ColumnRun::default()
+                    },
+                    Some((last_page, height)),
+                );
+                if kept + balanced.len() <= page_count * count {
+                    tall = height;
+                    columns.truncate(kept);
+                    columns.extend(balanced);
+                    footnotes.truncate(kept);
+                    footnotes.extend(balanced_footnotes);
+                    end = balanced_end;
+                } else {
+                    short = height;
+                }
+            }
+        }
+
+        let column_count = columns.len();
+        let mut columns = columns.into_iter();
+        for page_idx in 0..page_count {
+            if page_idx > 0 {
+                pages.push(cursor.finalize());
+                *cursor = cursor.new_page();
+            }
+            // The page's last column has all the footnotes referenced on it
+            let last_column = ((page_idx + 1) * count).min(column_count) - 1;
+            cursor.footnotes = std::mem::take(&mut footnotes[last_column]);
+            let top = cursor.y;
+            let mut bottom = if page_idx == page_count - 1 {
+                end.y
+            } else {
+                top
+            };
+            let mut filled = 0;
+            for (column, elements) in columns.by_ref().take(count).enumerate() {
+                if !elements.is_empty() {
+                    filled = column + 1;
+                }
+                for mut el in elements {
+                    offset_element_x(&mut el, column as f64 * (column_width + gap));
+                    bottom = bottom.max(el.y + el.height - cursor.content_y);
+                    cursor.elements.push(el);
+                }
+            }
+
+            // Rules between the columns that have content
+            if style.column_rule_width > 0.0 {
+                for column in 1..filled {
+                    let rule_x = x + column as f64 * (column_width + gap)
+                        - (gap + style.column_rule_width) / 2.0;
+                    cursor.elements.push(LayoutElement {
+                        x: rule_x,
+                        y: cursor.content_y + top,
+                        width: style.column_rule_width,
+                        height: bottom - top,
+                        draw: DrawCommand::Rect {
+                            background: Some(style.column_rule_color),
+                            background_image: None,
+                            border_width: Edges::uniform(0.0),
+                            border_color: EdgeValues::uniform(style.column_rule_color),
+                            border_radius: CornerValues::uniform(0.0),
+                            opacity: style.opacity,
+                        },
+                        children: vec![],
+                        node_type: None,
+                        resolved_style: None,
+                        source_location: None,
+                        href: None,
+                        bookmark: None,
+                        bookmark_level: 0,
+                        node_id: None,
+                        alt: None,
+                        is_header_row: false,
+                        overflow: Overflow::default(),
+                    });
+                }
+            }
+            cursor.y = bottom;
+        }
+        cursor.footnote_overflow = end.footnote_overflow;
+        cursor.footnote_count = end.footnote_count;
+    }
+
     #[allow(clippy::too_many_arguments)]
     fn layout_children(
         &self,
@@ -1373,8 +2584,39 @@ impl LayoutEngine {
         parent_style: Option<&ResolvedStyle>,
         font_context: &FontContext,
     ) {
+        self.layout_run_children(
+            children,
+            cursor,
+            pages,
+            content_x,
+            available_width,
+            parent_style,
+            font_context,
+            None,
+        );
+    }
+
+    /// Lay out `children`, as `layout_children` does. For the run of a
+    /// multi-column container, `run` takes snapshots as the children start,
+    /// or picks up from one (see [`ColumnRun`]).
+    #[allow(clippy::too_many_arguments)]
+    fn layout_run_children(
+        &self,
+        children: &[Node],
+        cursor: &mut PageCursor,
+        pages: &mut Vec<LayoutPage>,
+        content_x: f64,
+        available_width: f64,
+        parent_style: Option<&ResolvedStyle>,
+        font_context: &FontContext,
+        mut run: Option<&mut ColumnRun>,
+    ) {
+        let resume = run.as_mut().and_then(|run| run.resume.take());
+
         // Save parent content box position for absolute children
-        let parent_box_y = cursor.content_y + cursor.y;
+        let parent_box_y = resume
+            .as_ref()
+            .map_or(cursor.content_y + cursor.y, |snapshot| snapshot.box_y);
         let parent_box_x = content_x;
 
         // Separate absolute vs flow children
@@ -1410,12 +2652,62 @@ impl LayoutEngine {
 
                 // Track each child's element range for align-items adjustment
                 let mut child_ranges: Vec<(usize, usize)> = Vec::new();
-
-                for (i, child) in items.iter().enumerate() {
-                    if i > 0 {
-                        cursor.y += row_gap;
+                let (first, mut in_flow) = resume
+                    .as_ref()
+                    .map_or((0, 0), |snapshot| (snapshot.item, snapshot.in_flow));
+
+                for (item, child) in items.iter().enumerate().skip(first) {
+                    if let Some(run) = run.as_mut().filter(|run| run.record) {
+                        run.snapshot(RunSnapshot {
+                            item,
+                            in_flow,
+                            box_y: parent_box_y,
+                            cursor: cursor.clone(),
+                            columns: pages.len(),
+                        });
                     }
                     let child_start = cursor.elements.len();
+                    if child.style.float.is_some_and(|f| f != Float::None) {
+                        self.layout_float(
+                            child,
+                            cursor,
+                            pages,
+                            content_x,
+                            available_width,
+                            parent_style,
+                            font_context,
+                        );
+                        child_ranges.push((child_start, cursor.elements.len()));
+                        continue;
+                    }
+                    if in_flow > 0 {
+                        cursor.y += row_gap;
+                    }
+                    in_flow += 1;
+
+                    // Make room beside the floats before it. Text wraps around
+                    // them itself; other content doesn't see them.
+                    let start_page = pages.len();
+                    let mut floats = std::mem::take(&mut cursor.floats);
+                    let (content_x, available_width) = if floats.is_empty() {
+                        (content_x, available_width)
+                    } else {
+                        let child_style = child.style.resolve(parent_style, available_width);
+                        let (y, x, width) = self.beside_floats(
+                            &floats,
+                            child,
+                            &child_style,
+                            content_x,
+                            available_width,
+                            cursor.y,
+                            font_context,
+                        );
+                        cursor.y = y;
+                        if wraps_around_floats(child, &child_style) {
+                            cursor.floats = std::mem::take(&mut floats);
+                        }
+                        (x, width)
+                    };
 
                     // For align-items Center/FlexEnd, measure child width and adjust x.
                     // Returns (child_x, layout_width): layout_width is what we pass
@@ -1459,10 +2751,19 @@ impl LayoutEngine {
                         font_context,
                         None,
                     );
+                    if pages.len() == start_page && !floats.is_empty() {
+                        cursor.floats = floats;
+                    }
 
                     child_ranges.push((child_start, cursor.elements.len()));
                 }
 
+                // The container encloses its floats
+                if let Some(bottom) = float::clearance(&cursor.floats) {
+                    cursor.y = cursor.y.max(bottom);
+                }
+                cursor.floats.clear();
+
                 // flex-grow: distribute extra vertical space proportionally
                 // Compute container inner height from parent style or page content area
                 let container_inner_h: Option<f64> = parent_style
@@ -1662,6 +2963,118 @@ impl LayoutEngine {
         }
     }
 
+    /// Lay out a floated child against one side of the content box, level
+    /// with the flow, without moving the flow down. A float that doesn't
+    /// fit on the page moves to the next one.
+    #[allow(clippy::too_many_arguments)]
+    fn layout_float(
+        &self,
+        node: &Node,
+        cursor: &mut PageCursor,
+        pages: &mut Vec<LayoutPage>,
+        x: f64,
+        available_width: f64,
+        parent_style: Option<&ResolvedStyle>,
+        font_context: &FontContext,
+    ) {
+        let style = node.style.resolve(parent_style, available_width);
+        let (width, height) = self.measure_float(node, &style, available_width, font_context);
+        let mut placed = float::place(
+            &cursor.floats,
+            style.float,
+            x,
+            available_width,
+            cursor.y,
+            width,
+            height,
+        );
+        if placed.bottom - cursor.y > cursor.remaining_height() && !cursor.elements.is_empty() {
+            pages.push(cursor.finalize());
+            *cursor = cursor.new_page();
+            placed = float::place(
+                &[],
+                style.float,
+                x,
+                available_width,
+                cursor.y,
+                width,
+                height,
+            );
+        }
+
+        let flow_y = cursor.y;
+        let start_page = pages.len();
+        let mut floats = std::mem::take(&mut cursor.floats);
+        cursor.y = placed.top;
+        self.layout_node(
+            node,
+            cursor,
+            pages,
+            placed.left,
+            width,
+            parent_style,
+            font_context,
+            None,
+        );
+        // A float too tall for a page breaks like any other content, and the
+        // flow continues below it
+        if pages.len() == start_page {
+            placed.bottom = cursor.y;
+            cursor.y = flow_y;
+            floats.push(placed);
+            cursor.floats = floats;
+        }
+    }
+
+    /// The margin box of a floated child, `(width, height)`: its own width,
+    /// or else its intrinsic width, at most the container's.
+    fn measure_float(
+        &self,
+        node: &Node,
+        style: &ResolvedStyle,
+        available_width: f64,
+        font_context: &FontContext,
+    ) -> (f64, f64) {
+        let width = match style.width {
+            SizeConstraint::Fixed(w) => w + style.margin.horizontal(),
+            SizeConstraint::Auto => self.measure_intrinsic_width(node, style, font_context),
+        }
+        .min(available_width);
+        let height =
+            self.measure_node_height(node, width, style, font_context) + style.margin.vertical();
+        (width, height)
+    }
+
+    /// Where an in-flow child goes among the floats placed before it, as
+    /// `(y, x, width)`. It moves down below the floats when it can't get
+    /// its min-content width beside them. Text keeps the full width to wrap
+    /// around them line by line; other content is narrowed to fit beside
+    /// them.
+    #[allow(clippy::too_many_arguments)]
+    fn beside_floats(
+        &self,
+        floats: &[float::PlacedFloat],
+        node: &Node,
+        style: &ResolvedStyle,
+        x: f64,
+        width: f64,
+        y: f64,
+        font_context: &FontContext,
+    ) -> (f64, f64, f64) {
+        if floats.is_empty() {
+            return (y, x, width);
+        }
+        let min_width = self
+            .measure_min_content_width(node, style, font_context)
+            .min(width);
+        let y = float::fit_below(floats, x, width, y, min_width);
+        if wraps_around_floats(node, style) {
+            return (y, x, width);
+        }
+        let (left, right) = float::insets(floats, x, width, y, f64::INFINITY);
+        (y, x + left, width - left - right)
+    }
+
     #[allow(clippy::too_many_arguments)]
     fn layout_flex_row(
         &self,
@@ -1965,97 +3378,545 @@ impl LayoutEngine {
         };
         let inner_width = table_width - padding.horizontal() - border.horizontal();
 
-        let col_widths = self.resolve_column_widths(column_defs, inner_width, &node.children);
-
-        let mut header_rows: Vec<&Node> = Vec::new();
-        let mut body_rows: Vec<&Node> = Vec::new();
+        let table = self.prepare_table(node, column_defs, inner_width, style, font_context);
 
-        for child in &node.children {
-            match &child.kind {
-                NodeKind::TableRow { is_header: true } => header_rows.push(child),
-                _ => body_rows.push(child),
-            }
-        }
+        // Keep room at the bottom of every page for the footer rows drawn
+        // when the table breaks
+        let continued_footer_height =
+            self.measure_table_footer_height(&table, false, style, font_context);
+        let mut ctx = TableContext {
+            table,
+            style,
+            cell_x: table_x + padding.left + border.left,
+            outer_reserved_bottom: cursor.reserved_bottom,
+            sums: table::ColumnSums::new(style.lang.as_deref()),
+        };
+        cursor.reserved_bottom += continued_footer_height;
 
         cursor.y += margin.top + padding.top + border.top;
 
-        let cell_x_start = table_x + padding.left + border.left;
-        for header_row in &header_rows {
-            self.layout_table_row(
-                header_row,
-                &col_widths,
-                style,
-                cursor,
-                cell_x_start,
-                font_context,
-                pages,
-            );
-        }
+        let header = &ctx.table.header;
+        self.layout_table_rows(
+            header,
+            0..header.rows.len(),
+            &ctx.table.col_widths,
+            style,
+            cursor,
+            ctx.cell_x,
+            font_context,
+            pages,
+        );
 
-        for body_row in &body_rows {
-            let row_height =
-                self.measure_table_row_height(body_row, &col_widths, style, font_context);
+        // Room a body row gets on a continuation page, below the repeated headers
+        let continuation_room =
+            cursor.new_page().remaining_height() - padding.top - border.top - header.height();
 
-            if row_height > cursor.remaining_height() {
-                pages.push(cursor.finalize());
-                *cursor = cursor.new_page();
+        // True while nothing but repeated headers sits on the current page,
+        // so a row that can't fit anywhere is placed instead of breaking forever
+        let mut fresh_page = false;
 
-                cursor.y += padding.top + border.top;
-                for header_row in &header_rows {
-                    self.layout_table_row(
-                        header_row,
-                        &col_widths,
-                        style,
-                        cursor,
-                        cell_x_start,
-                        font_context,
-                        pages,
-                    );
+        for group in table::row_groups(&ctx.table.body.placements) {
+            let body = &ctx.table.body;
+            let group_height: f64 = body.heights[group.clone()].iter().sum();
+
+            // A single row taller than a whole page is split across pages
+            // instead of overflowing, unless it opts out with `wrap: false`
+            if group.len() == 1
+                && group_height > cursor.remaining_height()
+                && group_height > continuation_room
+                && body.rows[group.start].is_breakable()
+            {
+                self.layout_split_table_row(&mut ctx, group.start, cursor, pages, font_context);
+                fresh_page = false;
+                continue;
+            }
+
+            // Keep row-spanned rows together when they fit on a page
+            if group_height > cursor.remaining_height() && !fresh_page {
+                self.break_table_page(&mut ctx, cursor, pages, font_context);
+                fresh_page = true;
+            }
+
+            // A group taller than the page is split at row boundaries, and
+            // a row in it taller than a whole page is split like a lone one
+            let mut start = group.start;
+            while start < group.end {
+                let heights = &ctx.table.body.heights;
+                let mut end = start;
+                let mut used = 0.0;
+                while end < group.end && used + heights[end] <= cursor.remaining_height() {
+                    used += heights[end];
+                    end += 1;
+                }
+
+                if end == start {
+                    if heights[start] > continuation_room
+                        && ctx.table.body.rows[start].is_breakable()
+                    {
+                        self.layout_split_table_row(&mut ctx, start, cursor, pages, font_context);
+                        fresh_page = false;
+                        start += 1;
+                        continue;
+                    }
+                    if fresh_page {
+                        end = start + 1;
+                    } else {
+                        self.break_table_page(&mut ctx, cursor, pages, font_context);
+                        fresh_page = true;
+                        continue;
+                    }
+                }
+
+                self.layout_table_rows(
+                    &ctx.table.body,
+                    start..end,
+                    &ctx.table.col_widths,
+                    style,
+                    cursor,
+                    ctx.cell_x,
+                    font_context,
+                    pages,
+                );
+                for row_idx in start..end {
+                    for diagnostic in ctx.add_row_sums(row_idx) {
+                        self.report(diagnostic);
+                    }
                 }
+                fresh_page = false;
+                start = end;
             }
+        }
 
-            self.layout_table_row(
-                body_row,
+        cursor.reserved_bottom = ctx.outer_reserved_bottom;
+
+        // Footer rows for the last fragment move to a new page together
+        // (under repeated headers) if they don't fit below the last row
+        let final_footer_height =
+            self.measure_table_footer_height(&ctx.table, true, style, font_context);
+        if final_footer_height > cursor.remaining_height() && !fresh_page {
+            self.break_table_page(&mut ctx, cursor, pages, font_context);
+        }
+        self.layout_table_footers(&mut ctx, true, cursor, pages, font_context);
+
+        cursor.y += padding.bottom + border.bottom + margin.bottom;
+    }
+
+    /// Place a table's rows on the column grid, resolve column widths, and
+    /// measure the header and body rows.
+    fn prepare_table<'a>(
+        &self,
+        node: &'a Node,
+        column_defs: &[ColumnDef],
+        inner_width: f64,
+        style: &ResolvedStyle,
+        font_context: &FontContext,
+    ) -> PreparedTable<'a> {
+        let (header_rows, body_rows, footers) = partition_table_rows(node);
+
+        // Spans never cross section boundaries, so place each section separately
+        let mut header = TableSection::new(header_rows);
+        let mut body = TableSection::new(body_rows);
+        let footer_section = TableSection::new(footers.iter().map(|(row, _)| *row).collect());
+        let num_columns = table::column_count(&header.placements)
+            .max(table::column_count(&body.placements))
+            .max(table::column_count(&footer_section.placements));
+
+        // Footer rows hold unresolved sum tokens, so only header and body
+        // content sizes auto columns
+        let col_widths = self.resolve_column_widths(
+            column_defs,
+            inner_width,
+            num_columns,
+            &[&header, &body],
+            style,
+            font_context,
+        );
+
+        for section in [&mut header, &mut body] {
+            section.heights = self.measure_table_row_heights(
+                &section.rows,
+                &section.placements,
                 &col_widths,
                 style,
-                cursor,
-                cell_x_start,
                 font_context,
-                pages,
             );
         }
 
-        cursor.y += padding.bottom + border.bottom + margin.bottom;
+        PreparedTable {
+            header,
+            body,
+            footers,
+            col_widths,
+        }
     }
 
-    #[allow(clippy::too_many_arguments)]
-    fn layout_table_row(
+    /// Height of the footer rows drawn at the end of a fragment, measured
+    /// with their sum tokens unresolved.
+    fn measure_table_footer_height(
         &self,
-        row: &Node,
-        col_widths: &[f64],
-        parent_style: &ResolvedStyle,
-        cursor: &mut PageCursor,
-        start_x: f64,
+        table: &PreparedTable,
+        is_last_fragment: bool,
+        style: &ResolvedStyle,
         font_context: &FontContext,
+    ) -> f64 {
+        let section = TableSection::new(table.footer_rows(is_last_fragment));
+        self.measure_table_row_heights(
+            &section.rows,
+            &section.placements,
+            &table.col_widths,
+            style,
+            font_context,
+        )
+        .iter()
+        .sum()
+    }
+
+    /// Draw the footer rows that close a table fragment, with their sum
+    /// tokens resolved for the rows placed so far.
+    fn layout_table_footers(
+        &self,
+        ctx: &mut TableContext,
+        is_last_fragment: bool,
+        cursor: &mut PageCursor,
         pages: &mut Vec<LayoutPage>,
+        font_context: &FontContext,
     ) {
-        let row_style = row
-            .style
-            .resolve(Some(parent_style), col_widths.iter().sum());
-
-        let row_height = self.measure_table_row_height(row, col_widths, parent_style, font_context);
-        let row_y = cursor.content_y + cursor.y;
-        let total_width: f64 = col_widths.iter().sum();
+        let rows: Vec<Node> = ctx
+            .table
+            .footer_rows(is_last_fragment)
+            .into_iter()
+            .map(|row| map_node_text(row, &|text| ctx.sums.substitute(text)))
+            .collect();
+        ctx.sums.end_page();
+        if rows.is_empty() {
+            return;
+        }
 
-        let is_header = matches!(row.kind, NodeKind::TableRow { is_header: true });
+        let mut section = TableSection::new(rows.iter().collect());
+        section.heights = self.measure_table_row_heights(
+            &section.rows,
+            &section.placements,
+            &ctx.table.col_widths,
+            ctx.style,
+            font_context,
+        );
+
+        // Footers are drawn into the space reserved for them
+        let reserved = cursor.reserved_bottom;
+        cursor.reserved_bottom = ctx.outer_reserved_bottom;
+        self.layout_table_rows(
+            &section,
+            0..section.rows.len(),
+            &ctx.table.col_widths,
+            ctx.style,
+            cursor,
+            ctx.cell_x,
+            font_context,
+            pages,
+        );
+        cursor.reserved_bottom = reserved;
+    }
+
+    /// Close the current table fragment with its footer rows, start a new
+    /// page, and repeat the header rows.
+    fn break_table_page(
+        &self,
+        ctx: &mut TableContext,
+        cursor: &mut PageCursor,
+        pages: &mut Vec<LayoutPage>,
+        font_context: &FontContext,
+    ) {
+        self.layout_table_footers(ctx, false, cursor, pages, font_context);
+
+        pages.push(cursor.finalize());
+        *cursor = cursor.new_page();
+
+        cursor.y += ctx.style.padding.top + ctx.style.border_width.top;
+        let header = &ctx.table.header;
+        self.layout_table_rows(
+            header,
+            0..header.rows.len(),
+            &ctx.table.col_widths,
+            ctx.style,
+            cursor,
+            ctx.cell_x,
+            font_context,
+            pages,
+        );
+    }
+
+    /// Lay out the rows in `range` as one contiguous segment on the current
+    /// page. Row spans are cut off at the end of the segment.
+    #[allow(clippy::too_many_arguments)]
+    fn layout_table_rows(
+        &self,
+        section: &TableSection,
+        range: std::ops::Range<usize>,
+        col_widths: &[f64],
+        parent_style: &ResolvedStyle,
+        cursor: &mut PageCursor,
+        start_x: f64,
+        font_context: &FontContext,
+        pages: &mut Vec<LayoutPage>,
+    ) {
+        let segment = &section.placements[range.clone()];
+        for row_idx in range.clone() {
+            let covered = table::covered_columns(segment, row_idx - range.start, col_widths.len());
+            self.layout_table_row(
+                section.rows[row_idx],
+                &section.placements[row_idx],
+                &covered,
+                &section.heights[row_idx..range.end],
+                col_widths,
+                parent_style,
+                cursor,
+                start_x,
+                font_context,
+                pages,
+            );
+        }
+    }
+
+    /// Lay out a row that is too tall for a page by flowing each cell's
+    /// content independently, then emitting one row fragment per page.
+    /// Cell text splits between lines the same way it does in a breakable
+    /// view, and header rows repeat above every continuation fragment.
+    #[allow(clippy::too_many_arguments)]
+    fn layout_split_table_row(
+        &self,
+        ctx: &mut TableContext,
+        row_idx: usize,
+        cursor: &mut PageCursor,
+        pages: &mut Vec<LayoutPage>,
+        font_context: &FontContext,
+    ) {
+        let row = ctx.table.body.rows[row_idx];
+        let placements = ctx.table.body.placements[row_idx].clone();
+        let col_widths = ctx.table.col_widths.clone();
+        let style = ctx.style;
+        let start_x = ctx.cell_x;
+
+        let total_width: f64 = col_widths.iter().sum();
+        let row_style = row.style.resolve(Some(style), total_width);
+
+        // Content on a continuation page resumes below the table's top edge
+        // and the repeated header rows
+        let resume_offset = cursor.continuation_top_offset
+            + style.padding.top
+            + style.border_width.top
+            + ctx.table.header.height();
+
+        // Flow each cell on its own cursor. Fragment k holds the elements
+        // the cell placed on the k-th page of the row.
+        let page_elements = std::mem::take(&mut cursor.elements);
+        let mut cells = Vec::new();
+        for placement in &placements {
+            let Some(cell) = row.children.get(placement.cell_index) else {
+                continue;
+            };
+            let cell_x = start_x + col_widths.iter().take(placement.col_start).sum::<f64>();
+            let col_width = table::span_width(placement.col_start, placement.col_end, &col_widths);
+            let cell_style = cell.style.resolve(Some(&row_style), col_width);
+            let inner_width =
+                col_width - cell_style.padding.horizontal() - cell_style.border_width.horizontal();
+            let content_x = cell_x + cell_style.padding.left + cell_style.border_width.left;
+
+            let mut cell_cursor = cursor.clone();
+            cell_cursor.continuation_top_offset =
+                resume_offset + cell_style.padding.top + cell_style.border_width.top;
+            cell_cursor.y += cell_style.padding.top + cell_style.border_width.top;
+
+            let mut cell_pages: Vec<LayoutPage> = Vec::new();
+            for child in &cell.children {
+                self.layout_node(
+                    child,
+                    &mut cell_cursor,
+                    &mut cell_pages,
+                    content_x,
+                    inner_width,
+                    Some(&cell_style),
+                    font_context,
+                    None,
+                );
+            }
+
+            let mut fragments: Vec<Vec<LayoutElement>> =
+                cell_pages.into_iter().map(|page| page.elements).collect();
+            fragments.push(cell_cursor.elements);
+            let end_y = cell_cursor.y + cell_style.padding.bottom + cell_style.border_width.bottom;
+
+            cells.push((cell, cell_x, col_width, cell_style, fragments, end_y));
+        }
+        cursor.elements = page_elements;
+
+        let fragment_count = cells
+            .iter()
+            .map(|(.., fragments, _)| fragments.len())
+            .max()
+            .unwrap_or(1);
+
+        // If no cell managed to place anything before the first break, skip
+        // the empty strip at the bottom of the current page
+        let first_fragment = if fragment_count > 1
+            && cells
+                .iter()
+                .all(|(.., fragments, _)| fragments[0].is_empty())
+        {
+            self.break_table_page(ctx, cursor, pages, font_context);
+            1
+        } else {
+            0
+        };
+        for diagnostic in ctx.add_row_sums(row_idx) {
+            self.report(diagnostic);
+        }
+
+        for k in first_fragment..fragment_count {
+            let is_last = k + 1 == fragment_count;
+            let row_y = cursor.content_y + cursor.y;
+            let row_height = if is_last {
+                cells
+                    .iter()
+                    .filter(|(.., fragments, _)| fragments.len() == fragment_count)
+                    .map(|(.., end_y)| *end_y - cursor.y)
+                    .fold(row_style.min_height, f64::max)
+            } else {
+                cursor.remaining_height()
+            };
+
+            let row_snapshot = cursor.elements.len();
+            for (cell, cell_x, col_width, cell_style, fragments, _) in &mut cells {
+                let children = fragments.get_mut(k).map(std::mem::take).unwrap_or_default();
+                cursor.elements.push(LayoutElement {
+                    x: *cell_x,
+                    y: row_y,
+                    width: *col_width,
+                    height: row_height,
+                    draw: if cell_style.background_color.is_some()
+                        || cell_style.border_width.horizontal() > 0.0
+                        || cell_style.border_width.vertical() > 0.0
+                    {
+                        DrawCommand::Rect {
+                            background: cell_style.background_color,
+                            background_image: None,
+                            border_width: cell_style.border_width,
+                            border_color: cell_style.border_color,
+                            border_radius: cell_style.border_radius,
+                            opacity: cell_style.opacity,
+                        }
+                    } else {
+                        DrawCommand::None
+                    },
+                    children,
+                    node_type: Some("TableCell".to_string()),
+                    resolved_style: Some(cell_style.clone()),
+                    source_location: cell.source_location.clone(),
+                    href: None,
+                    bookmark: if k == first_fragment {
+                        cell.bookmark.clone()
+                    } else {
+                        None
+                    },
+                    bookmark_level: 0,
+                    node_id: None,
+                    alt: None,
+                    is_header_row: false,
+                    overflow: Overflow::default(),
+                });
+            }
+
+            let row_children: Vec<LayoutElement> = cursor.elements.drain(row_snapshot..).collect();
+            cursor.elements.push(LayoutElement {
+                x: start_x,
+                y: row_y,
+                width: total_width,
+                height: row_height,
+                draw: if let Some(bg) = row_style.background_color {
+                    DrawCommand::Rect {
+                        background: Some(bg),
+                        background_image: None,
+                        border_width: Edges::default(),
+                        border_color: EdgeValues::uniform(Color::BLACK),
+                        border_radius: CornerValues::uniform(0.0),
+                        opacity: row_style.opacity,
+                    }
+                } else {
+                    DrawCommand::None
+                },
+                children: row_children,
+                node_type: Some("TableRow".to_string()),
+                resolved_style: Some(row_style.clone()),
+                source_location: row.source_location.clone(),
+                href: None,
+                bookmark: if k == first_fragment {
+                    row.bookmark.clone()
+                } else {
+                    None
+                },
+                bookmark_level: 0,
+                node_id: None,
+                alt: None,
+                is_header_row: false,
+                overflow: row_style.overflow,
+            });
+
+            cursor.y += row_height;
+
+            if !is_last {
+                self.break_table_page(ctx, cursor, pages, font_context);
+            }
+        }
+    }
+
+    /// Lay out a single table row. `row_heights` starts at this row and runs
+    /// to the end of the current segment; a cell spanning several rows gets
+    /// the sum of their heights.
+    #[allow(clippy::too_many_arguments)]
+    fn layout_table_row(
+        &self,
+        row: &Node,
+        placements: &[table::CellPlacement],
+        covered: &[bool],
+        row_heights: &[f64],
+        col_widths: &[f64],
+        parent_style: &ResolvedStyle,
+        cursor: &mut PageCursor,
+        start_x: f64,
+        font_context: &FontContext,
+        pages: &mut Vec<LayoutPage>,
+    ) {
+        let row_style = row
+            .style
+            .resolve(Some(parent_style), col_widths.iter().sum());
+
+        let row_height = row_heights.first().copied().unwrap_or(0.0);
+        let row_y = cursor.content_y + cursor.y;
+        let total_width: f64 = col_widths.iter().sum();
+
+        let is_header = matches!(
+            row.kind,
+            NodeKind::TableRow {
+                is_header: true,
+                ..
+            }
+        );
+
+        // A row background drawn as one rect would paint over cells spanning
+        // down from earlier rows, so paint it per cell instead
+        let row_background_on_cells =
+            row_style.background_color.is_some() && covered.iter().any(|c| *c);
 
         // Snapshot before laying out cells — we'll collect them as row children
         let row_snapshot = cursor.elements.len();
 
         let mut all_overflow_pages: Vec<LayoutPage> = Vec::new();
-        let mut cell_x = start_x;
-        for (i, cell) in row.children.iter().enumerate() {
-            let col_width = col_widths.get(i).copied().unwrap_or(0.0);
+        for placement in placements {
+            let Some(cell) = row.children.get(placement.cell_index) else {
+                continue;
+            };
+            let cell_x = start_x + col_widths.iter().take(placement.col_start).sum::<f64>();
+            let col_width = table::span_width(placement.col_start, placement.col_end, col_widths);
+            let cell_height: f64 = row_heights.iter().take(placement.row_span).sum();
 
             let cell_style = cell.style.resolve(Some(&row_style), col_width);
 
@@ -2101,18 +3962,25 @@ impl LayoutEngine {
             let cell_children: Vec<LayoutElement> =
                 cursor.elements.drain(cell_snapshot..).collect();
 
+            let background = if row_background_on_cells {
+                cell_style.background_color.or(row_style.background_color)
+            } else {
+                cell_style.background_color
+            };
+
             // Always push a cell element (with or without visual styling) to preserve hierarchy
             cursor.elements.push(LayoutElement {
                 x: cell_x,
                 y: row_y,
                 width: col_width,
-                height: row_height,
-                draw: if cell_style.background_color.is_some()
+                height: cell_height,
+                draw: if background.is_some()
                     || cell_style.border_width.horizontal() > 0.0
                     || cell_style.border_width.vertical() > 0.0
                 {
                     DrawCommand::Rect {
-                        background: cell_style.background_color,
+                        background,
+                        background_image: None,
                         border_width: cell_style.border_width,
                         border_color: cell_style.border_color,
                         border_radius: cell_style.border_radius,
@@ -2127,12 +3995,12 @@ impl LayoutEngine {
                 source_location: cell.source_location.clone(),
                 href: None,
                 bookmark: cell.bookmark.clone(),
+                bookmark_level: 0,
+                node_id: None,
                 alt: None,
                 is_header_row: is_header,
                 overflow: Overflow::default(),
             });
-
-            cell_x += col_width;
         }
 
         // Collect all cell elements as row children
@@ -2142,16 +4010,16 @@ impl LayoutEngine {
             y: row_y,
             width: total_width,
             height: row_height,
-            draw: if let Some(bg) = row_style.background_color {
-                DrawCommand::Rect {
+            draw: match row_style.background_color {
+                Some(bg) if !row_background_on_cells => DrawCommand::Rect {
                     background: Some(bg),
+                    background_image: None,
                     border_width: Edges::default(),
                     border_color: EdgeValues::uniform(Color::BLACK),
                     border_radius: CornerValues::uniform(0.0),
                     opacity: row_style.opacity,
-                }
-            } else {
-                DrawCommand::None
+                },
+                _ => DrawCommand::None,
             },
             children: row_children,
             node_type: Some("TableRow".to_string()),
@@ -2159,6 +4027,8 @@ impl LayoutEngine {
             source_location: row.source_location.clone(),
             href: None,
             bookmark: row.bookmark.clone(),
+            bookmark_level: 0,
+            node_id: None,
             alt: None,
             is_header_row: is_header,
             overflow: row_style.overflow,
@@ -2191,6 +4061,24 @@ impl LayoutEngine {
 
         cursor.y += margin.top;
 
+        if matches!(style.writing_mode, WritingMode::VerticalRl) {
+            self.layout_vertical_text(
+                content,
+                href,
+                runs,
+                style,
+                cursor,
+                pages,
+                text_x,
+                text_width,
+                font_context,
+                source_location,
+                bookmark,
+            );
+            cursor.y += margin.bottom;
+            return;
+        }
+
         // Runs path: if runs are provided, use multi-style line breaking
         if !runs.is_empty() {
             self.layout_text_runs(
@@ -2211,61 +4099,77 @@ impl LayoutEngine {
 
         let transformed = apply_text_transform(content, style.text_transform);
         let justify = matches!(style.text_align, TextAlign::Justify);
-        let lines = match style.line_breaking {
-            LineBreaking::Optimal => self.text_layout.break_into_lines_optimal(
-                font_context,
-                &transformed,
-                text_width,
-                style.font_size,
-                &style.font_family,
-                style.font_weight,
-                style.font_style,
-                style.letter_spacing,
-                style.hyphens,
-                style.lang.as_deref(),
-                justify,
-            ),
-            LineBreaking::Greedy => self.text_layout.break_into_lines(
-                font_context,
-                &transformed,
-                text_width,
-                style.font_size,
-                &style.font_family,
-                style.font_weight,
-                style.font_style,
-                style.letter_spacing,
-                style.hyphens,
-                style.lang.as_deref(),
-            ),
-        };
+        let break_lines = |widths: LineWidths| {
+            let first_width = widths.get(0);
+            let lines = match style.line_breaking {
+                LineBreaking::Optimal => self.text_layout.break_into_lines_optimal(
+                    font_context,
+                    &transformed,
+                    widths,
+                    style.font_size,
+                    &style.font_family,
+                    style.font_weight,
+                    style.font_stretch,
+                    style.font_style,
+                    &style.font_features,
+                    style.letter_spacing,
+                    style.hyphens,
+                    style.lang.as_deref(),
+                    justify,
+                ),
+                LineBreaking::Greedy => self.text_layout.break_into_lines(
+                    font_context,
+                    &transformed,
+                    widths,
+                    style.font_size,
+                    &style.font_family,
+                    style.font_weight,
+                    style.font_stretch,
+                    style.font_style,
+                    &style.font_features,
+                    style.letter_spacing,
+                    style.hyphens,
+                    style.lang.as_deref(),
+                ),
+            };
 
-        // Apply text overflow truncation (single-line modes)
-        let lines = match style.text_overflow {
-            TextOverflow::Ellipsis => self.text_layout.truncate_with_ellipsis(
-                font_context,
-                lines,
-                text_width,
-                style.font_size,
-                &style.font_family,
-                style.font_weight,
-                style.font_style,
-                style.letter_spacing,
-            ),
-            TextOverflow::Clip => self.text_layout.truncate_clip(
-                font_context,
-                lines,
-                text_width,
-                style.font_size,
-                &style.font_family,
-                style.font_weight,
-                style.font_style,
-                style.letter_spacing,
-            ),
-            TextOverflow::Wrap => lines,
+            // Apply text overflow truncation (single-line modes)
+            match style.text_overflow {
+                TextOverflow::Ellipsis => self.text_layout.truncate_with_ellipsis(
+                    font_context,
+                    lines,
+                    first_width,
+                    style.font_size,
+                    &style.font_family,
+                    style.font_weight,
+                    style.font_stretch,
+                    style.font_style,
+                    &style.font_features,
+                    style.letter_spacing,
+                ),
+                TextOverflow::Clip => self.text_layout.truncate_clip(
+                    font_context,
+                    lines,
+                    first_width,
+                    style.font_size,
+                    &style.font_family,
+                    style.font_weight,
+                    style.font_stretch,
+                    style.font_style,
+                    &style.font_features,
+                    style.letter_spacing,
+                ),
+                TextOverflow::Wrap => lines,
+            }
         };
 
         let line_height = style.font_size * style.line_height;
 
+        // Lines beside floats are narrower
+        let widths = float::line_widths(&cursor.floats, text_x, text_width, cursor.y, line_height);
+        let beside_floats = !widths.first.is_empty();
+        let mut lines = break_lines(widths);
+
         // Widow/orphan control: decide how to break before placing lines
         let line_heights: Vec<f64> = vec![line_height; lines.len()];
         let decision = page_break::decide_break(
@@ -2287,6 +4191,10 @@ impl LayoutEngine {
             *cursor = cursor.new_page();
             snapshot = cursor.elements.len();
             container_start_y = cursor.content_y + cursor.y;
+            // The floats stay behind, so the lines get the full width
+            if beside_floats {
+                lines = break_lines(LineWidths::uniform(text_width));
+            }
         }
 
         // For split decisions, track the widow/orphan-adjusted first break point
@@ -2332,6 +4240,8 @@ impl LayoutEngine {
                         } else {
                             None
                         },
+                        bookmark_level: 0,
+                        node_id: None,
                         alt: None,
                         is_header_row: false,
                         overflow: Overflow::default(),
@@ -2359,85 +4269,279 @@ impl LayoutEngine {
                 (last.x_offset + last.x_advance).max(line.width * 0.5)
             };
 
-            let line_x = match style.text_align {
-                TextAlign::Left => text_x,
-                TextAlign::Right => text_x + text_width - rendered_width,
-                TextAlign::Center => text_x + (text_width - rendered_width) / 2.0,
-                TextAlign::Justify => text_x,
-            };
+            let (line_left, line_room) =
+                line_beside_floats(cursor, text_x, text_width, line_height);
+            let line_x = match style.text_align {
+                TextAlign::Left => line_left,
+                TextAlign::Right => line_left + line_room - rendered_width,
+                TextAlign::Center => line_left + (line_room - rendered_width) / 2.0,
+                TextAlign::Justify => line_left,
+            };
+
+            // Justify: compute extra word spacing so the line fills the column width.
+            let is_last_line = line_idx == lines.len() - 1;
+            let (justified_width, (word_spacing, char_spacing)) =
+                if matches!(style.text_align, TextAlign::Justify) && !is_last_line {
+                    (line_room, justify_spacing(&glyphs, line_room))
+                } else {
+                    (rendered_width, (0.0, 0.0))
+                };
+
+            let text_line = TextLine {
+                x: line_x,
+                y: cursor.content_y + cursor.y + style.font_size,
+                glyphs,
+                width: justified_width,
+                height: line_height,
+                word_spacing,
+                char_spacing,
+                vertical: false,
+            };
+
+            cursor.elements.push(LayoutElement {
+                x: line_x,
+                y: cursor.content_y + cursor.y,
+                width: justified_width,
+                height: line_height,
+                draw: DrawCommand::Text {
+                    lines: vec![text_line],
+                    color: style.color,
+                    text_decoration: style.text_decoration,
+                    opacity: style.opacity,
+                },
+                children: vec![],
+                node_type: Some("TextLine".to_string()),
+                resolved_style: Some(style.clone()),
+                source_location: None,
+                href: href.map(|s| s.to_string()),
+                bookmark: None,
+                bookmark_level: 0,
+                node_id: None,
+                alt: None,
+                is_header_row: false,
+                overflow: Overflow::default(),
+            });
+
+            cursor.y += line_height;
+        }
+
+        // Wrap remaining lines into a Text container
+        let line_elements: Vec<LayoutElement> = cursor.elements.drain(snapshot..).collect();
+        if !line_elements.is_empty() {
+            let container_height = cursor.content_y + cursor.y - container_start_y;
+            cursor.elements.push(LayoutElement {
+                x: text_x,
+                y: container_start_y,
+                width: text_width,
+                height: container_height,
+                draw: DrawCommand::None,
+                children: line_elements,
+                node_type: Some("Text".to_string()),
+                resolved_style: Some(style.clone()),
+                source_location: source_location.cloned(),
+                href: href.map(|s| s.to_string()),
+                bookmark: if is_first_element {
+                    bookmark.map(|s| s.to_string())
+                } else {
+                    None
+                },
+                bookmark_level: 0,
+                node_id: None,
+                alt: None,
+                is_header_row: false,
+                overflow: Overflow::default(),
+            });
+        }
+
+        cursor.y += margin.bottom;
+    }
+
+    /// Layout text in the `vertical-rl` writing mode. Lines become columns
+    /// that run top to bottom, placed from the right edge of the text box
+    /// leftward. Columns are as long as the text's fixed `height`, or else
+    /// the space left on the page; once they fill the box's width, the rest
+    /// continues on the next page.
+    #[allow(clippy::too_many_arguments)]
+    fn layout_vertical_text(
+        &self,
+        content: &str,
+        href: Option<&str>,
+        runs: &[TextRun],
+        style: &ResolvedStyle,
+        cursor: &mut PageCursor,
+        pages: &mut Vec<LayoutPage>,
+        text_x: f64,
+        text_width: f64,
+        font_context: &FontContext,
+        source_location: Option<&SourceLocation>,
+        bookmark: Option<&str>,
+    ) {
+        let line_height = style.font_size * style.line_height;
+        let columns_per_page = ((text_width / line_height).floor() as usize).max(1);
+        let fixed_height = match style.height {
+            SizeConstraint::Fixed(h) => Some(h),
+            SizeConstraint::Auto => None,
+        };
+
+        let styled_chars = styled_chars_from_runs(runs, href, style, text_width);
+        let transformed = apply_text_transform(content, style.text_transform);
+        let justify = matches!(style.text_align, TextAlign::Justify);
+        let break_columns = |length: f64| -> Vec<Vec<PositionedGlyph>> {
+            if !runs.is_empty() {
+                let lines = match style.line_breaking {
+                    LineBreaking::Optimal => self.text_layout.break_runs_into_lines_optimal(
+                        font_context,
+                        &styled_chars,
+                        length,
+                        style.hyphens,
+                        style.lang.as_deref(),
+                        justify,
+                    ),
+                    LineBreaking::Greedy => self.text_layout.break_runs_into_lines(
+                        font_context,
+                        &styled_chars,
+                        length,
+                        style.hyphens,
+                        style.lang.as_deref(),
+                    ),
+                };
+                return lines
+                    .iter()
+                    .map(|line| {
+                        self.build_positioned_glyphs_runs(line, font_context, style.direction)
+                    })
+                    .collect();
+            }
+            let lines = match style.line_breaking {
+                LineBreaking::Optimal => self.text_layout.break_into_lines_optimal(
+                    font_context,
+                    &transformed,
+                    length,
+                    style.font_size,
+                    &style.font_family,
+                    style.font_weight,
+                    style.font_stretch,
+                    style.font_style,
+                    &style.font_features,
+                    style.letter_spacing,
+                    style.hyphens,
+                    style.lang.as_deref(),
+                    justify,
+                ),
+                LineBreaking::Greedy => self.text_layout.break_into_lines(
+                    font_context,
+                    &transformed,
+                    length,
+                    style.font_size,
+                    &style.font_family,
+                    style.font_weight,
+                    style.font_stretch,
+                    style.font_style,
+                    &style.font_features,
+                    style.letter_spacing,
+                    style.hyphens,
+                    style.lang.as_deref(),
+                ),
+            };
+            lines
+                .iter()
+                .map(|line| {
+                    self.build_positioned_glyphs_single_style(line, style, href, font_context)
+                })
+                .collect()
+        };
+
+        // Too little room for a fixed height, or for a few characters
+        let min_length = fixed_height.unwrap_or(style.font_size * 4.0);
+        if min_length > cursor.remaining_height() {
+            pages.push(cursor.finalize());
+            *cursor = cursor.new_page();
+        }
+        let mut column_length = fixed_height.unwrap_or_else(|| cursor.remaining_height());
+        let mut columns = break_columns(column_length);
+        // Columns that spill onto later pages shouldn't all be as short as
+        // the space left on this one
+        if fixed_height.is_none()
+            && columns.len() > columns_per_page
+            && cursor.remaining_height() < cursor.new_page().remaining_height()
+        {
+            pages.push(cursor.finalize());
+            *cursor = cursor.new_page();
+            column_length = cursor.remaining_height();
+            columns = break_columns(column_length);
+        }
 
-            // Justify: compute extra word spacing so the line fills the column width.
-            // Use the sum of natural glyph advances (what PDF Tj actually renders)
-            // rather than KP-adjusted positions, which bake justification into
-            // char_positions and make slack ≈ 0.
-            let is_last_line = line_idx == lines.len() - 1;
-            let (justified_width, word_spacing) =
-                if matches!(style.text_align, TextAlign::Justify) && !is_last_line {
-                    let last_non_space = glyphs.iter().rposition(|g| g.char_value != ' ');
-                    let (natural_width, space_count) = if let Some(idx) = last_non_space {
-                        let w: f64 = glyphs[..=idx].iter().map(|g| g.x_advance).sum();
-                        let s = glyphs[..=idx]
-                            .iter()
-                            .filter(|g| g.char_value == ' ')
-                            .count();
-                        (w, s)
-                    } else {
-                        (0.0, 0)
-                    };
-                    let slack = text_width - natural_width;
-                    let ws = if space_count > 0 && slack.abs() > 0.01 {
-                        slack / space_count as f64
-                    } else {
-                        0.0
-                    };
-                    (text_width, ws)
+        let column_count = columns.len();
+        let mut columns = columns.into_iter().enumerate().peekable();
+        let mut is_first_element = true;
+        while columns.peek().is_some() {
+            if !is_first_element {
+                pages.push(cursor.finalize());
+                *cursor = cursor.new_page();
+            }
+            let top = cursor.content_y + cursor.y;
+            let mut block_height = fixed_height.unwrap_or(0.0);
+            let mut line_elements = Vec::new();
+            for (slot, (column_idx, glyphs)) in columns.by_ref().take(columns_per_page).enumerate()
+            {
+                let rendered_length = glyphs
+                    .last()
+                    .map(|g| g.x_offset + g.x_advance)
+                    .unwrap_or(0.0);
+                let is_last_column = column_idx == column_count - 1;
+                let (length, (word_spacing, char_spacing)) = if justify && !is_last_column {
+                    (column_length, justify_spacing(&glyphs, column_length))
                 } else {
-                    (rendered_width, 0.0)
+                    (rendered_length, (0.0, 0.0))
                 };
+                let offset = match style.text_align {
+                    TextAlign::Left | TextAlign::Justify => 0.0,
+                    TextAlign::Right => column_length - length,
+                    TextAlign::Center => (column_length - length) / 2.0,
+                };
+                let column_x = text_x + text_width - (slot + 1) as f64 * line_height;
+                block_height = block_height.max(offset + length);
+
+                let text_line = TextLine {
+                    x: column_x + line_height / 2.0,
+                    y: top + offset,
+                    glyphs,
+                    width: length,
+                    height: line_height,
+                    word_spacing,
+                    char_spacing,
+                    vertical: true,
+                };
+                line_elements.push(LayoutElement {
+                    x: column_x,
+                    y: top + offset,
+                    width: line_height,
+                    height: length,
+                    draw: DrawCommand::Text {
+                        lines: vec![text_line],
+                        color: style.color,
+                        text_decoration: style.text_decoration,
+                        opacity: style.opacity,
+                    },
+                    children: vec![],
+                    node_type: Some("TextLine".to_string()),
+                    resolved_style: Some(style.clone()),
+                    source_location: None,
+                    href: href.map(|s| s.to_string()),
+                    bookmark: None,
+                    bookmark_level: 0,
+                    node_id: None,
+                    alt: None,
+                    is_header_row: false,
+                    overflow: Overflow::default(),
+                });
+            }
 
-            let text_line = TextLine {
-                x: line_x,
-                y: cursor.content_y + cursor.y + style.font_size,
-                glyphs,
-                width: justified_width,
-                height: line_height,
-                word_spacing,
-            };
-
-            cursor.elements.push(LayoutElement {
-                x: line_x,
-                y: cursor.content_y + cursor.y,
-                width: justified_width,
-                height: line_height,
-                draw: DrawCommand::Text {
-                    lines: vec![text_line],
-                    color: style.color,
-                    text_decoration: style.text_decoration,
-                    opacity: style.opacity,
-                },
-                children: vec![],
-                node_type: Some("TextLine".to_string()),
-                resolved_style: Some(style.clone()),
-                source_location: None,
-                href: href.map(|s| s.to_string()),
-                bookmark: None,
-                alt: None,
-                is_header_row: false,
-                overflow: Overflow::default(),
-            });
-
-            cursor.y += line_height;
-        }
-
-        // Wrap remaining lines into a Text container
-        let line_elements: Vec<LayoutElement> = cursor.elements.drain(snapshot..).collect();
-        if !line_elements.is_empty() {
-            let container_height = cursor.content_y + cursor.y - container_start_y;
             cursor.elements.push(LayoutElement {
                 x: text_x,
-                y: container_start_y,
+                y: top,
                 width: text_width,
-                height: container_height,
+                height: block_height,
                 draw: DrawCommand::None,
                 children: line_elements,
                 node_type: Some("Text".to_string()),
@@ -2449,13 +4553,15 @@ impl LayoutEngine {
                 } else {
                     None
                 },
+                bookmark_level: 0,
+                node_id: None,
                 alt: None,
                 is_header_row: false,
                 overflow: Overflow::default(),
             });
+            is_first_element = false;
+            cursor.y += block_height;
         }
-
-        cursor.y += margin.bottom;
     }
 
     /// Layout text runs with per-run styling.
@@ -2473,65 +4579,53 @@ impl LayoutEngine {
         source_location: Option<&SourceLocation>,
         bookmark: Option<&str>,
     ) {
-        // Build StyledChar list from runs
-        let mut styled_chars: Vec<StyledChar> = Vec::new();
-        for run in runs {
-            let run_style = run.style.resolve(Some(style), text_width);
-            let run_href = run.href.as_deref().or(parent_href);
-            let transform = run_style.text_transform;
-            let mut prev_is_whitespace = true;
-            for ch in run.content.chars() {
-                let transformed_ch = apply_char_transform(ch, transform, prev_is_whitespace);
-                prev_is_whitespace = ch.is_whitespace();
-                styled_chars.push(StyledChar {
-                    ch: transformed_ch,
-                    font_family: run_style.font_family.clone(),
-                    font_size: run_style.font_size,
-                    font_weight: run_style.font_weight,
-                    font_style: run_style.font_style,
-                    color: run_style.color,
-                    href: run_href.map(|s| s.to_string()),
-                    text_decoration: run_style.text_decoration,
-                    letter_spacing: run_style.letter_spacing,
-                });
-            }
-        }
+        let runs = &*footnote::number_runs(runs, cursor.footnote_count + 1, style);
+        let styled_chars = styled_chars_from_runs(runs, parent_href, style, text_width);
 
         // Break into lines
         let justify = matches!(style.text_align, TextAlign::Justify);
-        let broken_lines = match style.line_breaking {
-            LineBreaking::Optimal => self.text_layout.break_runs_into_lines_optimal(
-                font_context,
-                &styled_chars,
-                text_width,
-                style.hyphens,
-                style.lang.as_deref(),
-                justify,
-            ),
-            LineBreaking::Greedy => self.text_layout.break_runs_into_lines(
-                font_context,
-                &styled_chars,
-                text_width,
-                style.hyphens,
-                style.lang.as_deref(),
-            ),
-        };
+        let break_lines = |widths: LineWidths| {
+            let first_width = widths.get(0);
+            let broken_lines = match style.line_breaking {
+                LineBreaking::Optimal => self.text_layout.break_runs_into_lines_optimal(
+                    font_context,
+                    &styled_chars,
+                    widths,
+                    style.hyphens,
+                    style.lang.as_deref(),
+                    justify,
+                ),
+                LineBreaking::Greedy => self.text_layout.break_runs_into_lines(
+                    font_context,
+                    &styled_chars,
+                    widths,
+                    style.hyphens,
+                    style.lang.as_deref(),
+                ),
+            };
 
-        // Apply text overflow truncation (single-line modes)
-        let broken_lines = match style.text_overflow {
-            TextOverflow::Ellipsis => {
-                self.text_layout
-                    .truncate_runs_with_ellipsis(font_context, broken_lines, text_width)
-            }
-            TextOverflow::Clip => {
-                self.text_layout
-                    .truncate_runs_clip(font_context, broken_lines, text_width)
+            // Apply text overflow truncation (single-line modes)
+            match style.text_overflow {
+                TextOverflow::Ellipsis => self.text_layout.truncate_runs_with_ellipsis(
+                    font_context,
+                    broken_lines,
+                    first_width,
+                ),
+                TextOverflow::Clip => {
+                    self.text_layout
+                        .truncate_runs_clip(font_context, broken_lines, first_width)
+                }
+                TextOverflow::Wrap => broken_lines,
             }
-            TextOverflow::Wrap => broken_lines,
         };
 
         let line_height = style.font_size * style.line_height;
 
+        // Lines beside floats are narrower
+        let widths = float::line_widths(&cursor.floats, text_x, text_width, cursor.y, line_height);
+        let beside_floats = !widths.first.is_empty();
+        let mut broken_lines = break_lines(widths);
+
         // Widow/orphan control for text runs
         let line_heights: Vec<f64> = vec![line_height; broken_lines.len()];
         let decision = page_break::decide_break(
@@ -2551,6 +4645,10 @@ impl LayoutEngine {
             *cursor = cursor.new_page();
             snapshot = cursor.elements.len();
             container_start_y = cursor.content_y + cursor.y;
+            // The floats stay behind, so the lines get the full width
+            if beside_floats {
+                broken_lines = break_lines(LineWidths::uniform(text_width));
+            }
         }
 
         let forced_break_at = match decision {
@@ -2562,7 +4660,7 @@ impl LayoutEngine {
         let mut first_break_done = false;
 
         for (line_idx, run_line) in broken_lines.iter().enumerate() {
-            let needs_break = if let Some(break_at) = forced_break_at {
+            let mut needs_break = if let Some(break_at) = forced_break_at {
                 if !first_break_done && line_idx == break_at {
                     true
                 } else {
@@ -2572,6 +4670,29 @@ impl LayoutEngine {
                 line_height > cursor.remaining_height()
             };
 
+            // A line referencing footnotes moves to the next page with them
+            // when they can't start on this one
+            let references = footnote_references(run_line);
+            let mut footnotes = None;
+            if !needs_break && !references.is_empty() {
+                let laid_out = self.layout_footnotes(
+                    runs,
+                    &references,
+                    style,
+                    cursor,
+                    line_height,
+                    font_context,
+                );
+                if laid_out.0.len() < references.len()
+                    && cursor.footnote_overflow.is_empty()
+                    && !cursor.elements.is_empty()
+                {
+                    needs_break = true;
+                } else {
+                    footnotes = Some(laid_out);
+                }
+            }
+
             if needs_break {
                 first_break_done = true;
                 let line_elements: Vec<LayoutElement> = cursor.elements.drain(snapshot..).collect();
@@ -2593,6 +4714,8 @@ impl LayoutEngine {
                         } else {
                             None
                         },
+                        bookmark_level: 0,
+                        node_id: None,
                         alt: None,
                         is_header_row: false,
                         overflow: Overflow::default(),
@@ -2607,41 +4730,45 @@ impl LayoutEngine {
                 container_start_y = cursor.content_y + cursor.y;
             }
 
+            let mut run_line = Cow::Borrowed(run_line);
+            if !references.is_empty() {
+                let (placed, continued) = footnotes.unwrap_or_else(|| {
+                    self.layout_footnotes(
+                        runs,
+                        &references,
+                        style,
+                        cursor,
+                        line_height,
+                        font_context,
+                    )
+                });
+                // Marks numbered per page change when the text crosses a page
+                run_line =
+                    self.renumber_references(run_line, cursor.footnote_count + 1, font_context);
+                cursor.footnotes.extend(placed);
+                cursor.footnote_overflow.extend(continued);
+                cursor.footnote_count += references.len();
+            }
+            let run_line = &*run_line;
+
+            let (line_left, line_room) =
+                line_beside_floats(cursor, text_x, text_width, line_height);
             let line_x = match style.text_align {
-                TextAlign::Left => text_x,
-                TextAlign::Right => text_x + text_width - run_line.width,
-                TextAlign::Center => text_x + (text_width - run_line.width) / 2.0,
-                TextAlign::Justify => text_x,
+                TextAlign::Left => line_left,
+                TextAlign::Right => line_left + line_room - run_line.width,
+                TextAlign::Center => line_left + (line_room - run_line.width) / 2.0,
+                TextAlign::Justify => line_left,
             };
 
             let glyphs = self.build_positioned_glyphs_runs(run_line, font_context, style.direction);
 
             // Justify: compute extra word spacing so the line fills the column width.
-            // Use the sum of natural glyph advances (what PDF Tj actually renders)
-            // rather than KP-adjusted line width.
             let is_last_line = line_idx == broken_lines.len() - 1;
-            let (justified_width, word_spacing) =
+            let (justified_width, (word_spacing, char_spacing)) =
                 if matches!(style.text_align, TextAlign::Justify) && !is_last_line {
-                    let last_non_space = glyphs.iter().rposition(|g| g.char_value != ' ');
-                    let (natural_width, space_count) = if let Some(idx) = last_non_space {
-                        let w: f64 = glyphs[..=idx].iter().map(|g| g.x_advance).sum();
-                        let s = glyphs[..=idx]
-                            .iter()
-                            .filter(|g| g.char_value == ' ')
-                            .count();
-                        (w, s)
-                    } else {
-                        (0.0, 0)
-                    };
-                    let slack = text_width - natural_width;
-                    let ws = if space_count > 0 && slack.abs() > 0.01 {
-                        slack / space_count as f64
-                    } else {
-                        0.0
-                    };
-                    (text_width, ws)
+                    (line_room, justify_spacing(&glyphs, line_room))
                 } else {
-                    (run_line.width, 0.0)
+                    (run_line.width, (0.0, 0.0))
                 };
 
             let text_line = TextLine {
@@ -2651,6 +4778,8 @@ impl LayoutEngine {
                 width: justified_width,
                 height: line_height,
                 word_spacing,
+                char_spacing,
+                vertical: false,
             };
 
             // Determine text decoration: use the run's decoration if any glyph has one
@@ -2678,6 +4807,8 @@ impl LayoutEngine {
                 source_location: None,
                 href: parent_href.map(|s| s.to_string()),
                 bookmark: None,
+                bookmark_level: 0,
+                node_id: None,
                 alt: None,
                 is_header_row: false,
                 overflow: Overflow::default(),
@@ -2705,6 +4836,8 @@ impl LayoutEngine {
                 } else {
                     None
                 },
+                bookmark_level: 0,
+                node_id: None,
                 alt: None,
                 is_header_row: false,
                 overflow: Overflow::default(),
@@ -2712,6 +4845,139 @@ impl LayoutEngine {
         }
     }
 
+    /// Lay out the footnotes `references` (indices into `runs`) of a line
+    /// `line_height` tall about to be placed at the cursor, numbered on from
+    /// the cursor's count: the blocks that start below the line on this page,
+    /// and the blocks continued on the next. Once one footnote continues,
+    /// the ones after it wait for the next page too.
+    #[allow(clippy::too_many_arguments)]
+    fn layout_footnotes(
+        &self,
+        runs: &[TextRun],
+        references: &[usize],
+        style: &ResolvedStyle,
+        cursor: &PageCursor,
+        line_height: f64,
+        font_context: &FontContext,
+    ) -> (Vec<footnote::FootnoteBlock>, Vec<footnote::FootnoteBlock>) {
+        let mut room = cursor.remaining_height() - line_height;
+        if cursor.footnotes.is_empty() {
+            room -= footnote::SEPARATOR_SPACE;
+        }
+        let mut placed = Vec::new();
+        let mut continued = Vec::new();
+        let mut deferred = !cursor.footnote_overflow.is_empty();
+        for (i, &run_idx) in references.iter().enumerate() {
+            let Some(body) = runs.get(run_idx).and_then(|run| run.footnote.as_ref()) else {
+                continue;
+            };
+            let label = footnote::label(cursor.footnote_count + i + 1);
+            let nodes = footnote::numbered_body(body, &label, style, cursor.content_width);
+            let room_here = if deferred { 0.0 } else { room.max(0.0) };
+            let mut blocks = self
+                .layout_footnote_body(&nodes, style, &cursor.config, room_here, font_context)
+                .into_iter();
+            if let Some(first) = blocks.next() {
+                // Content that can't break, like an image, overflows an
+                // empty area rather than moving on, so check it fits
+                if !deferred && !first.elements.is_empty() && first.height <= room + 0.01 {
+                    room -= first.height;
+                    placed.push(first);
+                } else if !first.elements.is_empty() {
+                    continued.push(first);
+                }
+            }
+            continued.extend(blocks);
+            deferred = !continued.is_empty();
+        }
+        (placed, continued)
+    }
+
+    /// Lay out a footnote's body, first into `room` at the bottom of a page
+    /// and then over whole pages: one block per page it spans. The first
+    /// block is empty if nothing fits in `room`.
+    fn layout_footnote_body(
+        &self,
+        nodes: &[Node],
+        style: &ResolvedStyle,
+        config: &PageConfig,
+        room: f64,
+        font_context: &FontContext,
+    ) -> Vec<footnote::FootnoteBlock> {
+        let mut scratch = PageCursor::new(config);
+        scratch.content_height = room;
+        let mut scratch_pages = Vec::new();
+        let (x, width) = (scratch.content_x, scratch.content_width);
+        for node in nodes {
+            self.layout_node(
+                node,
+                &mut scratch,
+                &mut scratch_pages,
+                x,
+                width,
+                Some(style),
+                font_context,
+                None,
+            );
+        }
+        let content_y = scratch.content_y;
+        let mut blocks: Vec<footnote::FootnoteBlock> = scratch_pages
+            .into_iter()
+            .map(|page| {
+                let height = page
+                    .elements
+                    .iter()
+                    .map(|el| el.y + el.height - content_y)
+                    .fold(0.0, f64::max);
+                footnote::FootnoteBlock {
+                    elements: page.elements,
+                    height,
+                }
+            })
+            .collect();
+        blocks.push(footnote::FootnoteBlock {
+            elements: scratch.elements,
+            height: scratch.y,
+        });
+        blocks
+    }
+
+    /// The line with its footnote reference marks numbered from `first`, in
+    /// case they were numbered for another page.
+    fn renumber_references<'a>(
+        &self,
+        line: Cow<'a, RunBrokenLine>,
+        first: usize,
+        font_context: &FontContext,
+    ) -> Cow<'a, RunBrokenLine> {
+        let mut chars: Vec<StyledChar> = Vec::with_capacity(line.chars.len());
+        let mut number = first;
+        let mut changed = false;
+        let mut i = 0;
+        while i < line.chars.len() {
+            let sc = &line.chars[i];
+            let Some(run) = sc.footnote else {
+                chars.push(sc.clone());
+                i += 1;
+                continue;
+            };
+            let end = line.chars[i..]
+                .iter()
+                .position(|c| c.footnote != Some(run))
+                .map_or(line.chars.len(), |len| i + len);
+            let label = footnote::label(number);
+            let mark: String = line.chars[i..end].iter().map(|c| c.ch).collect();
+            changed |= mark != label;
+            chars.extend(label.chars().map(|ch| StyledChar { ch, ..sc.clone() }));
+            number += 1;
+            i = end;
+        }
+        if !changed {
+            return line;
+        }
+        Cow::Owned(self.text_layout.run_line(font_context, &chars))
+    }
+
     /// Build PositionedGlyphs for a single-style BrokenLine.
     /// For custom fonts, shapes the line text to get real glyph IDs.
     /// For standard fonts, uses char-as-u16 glyph IDs.
@@ -2734,6 +5000,7 @@ impl LayoutEngine {
             &line.chars,
             &style.font_family,
             style.font_weight,
+            style.font_stretch,
             italic,
             font_context.registry(),
         );
@@ -2772,20 +5039,39 @@ impl LayoutEngine {
                     let sub_text: String = sub_chars.iter().collect();
                     let resolved_family = &font_run.family;
 
-                    if let Some(font_data) =
-                        font_context.font_data(resolved_family, style.font_weight, italic)
-                    {
+                    if let Some(font_data) = font_context.font_data(
+                        resolved_family,
+                        style.font_weight,
+                        style.font_stretch,
+                        italic,
+                    ) {
+                        let variations = font_context.variations(
+                            resolved_family,
+                            style.font_weight,
+                            style.font_stretch,
+                            italic,
+                        );
                         if let Some(shaped) = shaping::shape_text_with_direction(
                             &sub_text,
                             font_data,
+                            &variations,
+                            &style.font_features,
                             bidi_run.is_rtl,
                         ) {
                             let units_per_em = font_context.units_per_em(
                                 resolved_family,
                                 style.font_weight,
+                                style.font_stretch,
                                 italic,
                             );
                             let scale = style.font_size / units_per_em as f64;
+                            let synthetic_bold = font_context.synthetic_bold_width(
+                                resolved_family,
+                                style.font_weight,
+                                style.font_stretch,
+                                italic,
+                                style.font_size,
+                            );
 
                             for sg in &shaped {
                                 let cluster = sg.cluster as usize;
@@ -2809,7 +5095,9 @@ impl LayoutEngine {
 
                                 let glyph_x = x + sg.x_offset as f64 * scale;
                                 let glyph_y = sg.y_offset as f64 * scale;
-                                let advance = sg.x_advance as f64 * scale + style.letter_spacing;
+                                let advance = sg.x_advance as f64 * scale
+                                    + style.letter_spacing
+                                    + synthetic_bold;
 
                                 all_glyphs.push(PositionedGlyph {
                                     glyph_id: sg.glyph_id,
@@ -2819,13 +5107,16 @@ impl LayoutEngine {
                                     font_size: style.font_size,
                                     font_family: resolved_family.clone(),
                                     font_weight: style.font_weight,
+                                    font_stretch: style.font_stretch,
                                     font_style: style.font_style,
+                                    font_features: style.font_features.clone(),
                                     char_value,
                                     color: Some(style.color),
                                     href: href.map(|s| s.to_string()),
                                     text_decoration: style.text_decoration,
                                     letter_spacing: style.letter_spacing,
                                     cluster_text,
+                                    baseline_shift: 0.0,
                                 });
                                 bidi_levels.push(bidi_run.level);
                                 x += advance;
@@ -2842,6 +5133,7 @@ impl LayoutEngine {
                             ch,
                             resolved_family,
                             style.font_weight,
+                            style.font_stretch,
                             italic,
                             style.font_size,
                         );
@@ -2854,13 +5146,16 @@ impl LayoutEngine {
                             font_size: style.font_size,
                             font_family: resolved_family.clone(),
                             font_weight: style.font_weight,
+                            font_stretch: style.font_stretch,
                             font_style: style.font_style,
+                            font_features: style.font_features.clone(),
                             char_value: ch,
                             color: Some(style.color),
                             href: href.map(|s| s.to_string()),
                             text_decoration: style.text_decoration,
                             letter_spacing: style.letter_spacing,
                             cluster_text: None,
+                            baseline_shift: 0.0,
                         });
                         bidi_levels.push(bidi_run.level);
                         x += advance;
@@ -2878,15 +5173,35 @@ impl LayoutEngine {
 
         // Original single-font path (no comma in font_family)
         // Try shaping for custom fonts
-        if let Some(font_data) =
-            font_context.font_data(&style.font_family, style.font_weight, italic)
-        {
+        if let Some(font_data) = font_context.font_data(
+            &style.font_family,
+            style.font_weight,
+            style.font_stretch,
+            italic,
+        ) {
+            let variations = font_context.variations(
+                &style.font_family,
+                style.font_weight,
+                style.font_stretch,
+                italic,
+            );
             if has_bidi {
                 // BiDi path: analyze runs, shape each with correct direction
                 let bidi_runs = bidi::analyze_bidi(&line_text, direction);
-                let units_per_em =
-                    font_context.units_per_em(&style.font_family, style.font_weight, italic);
+                let units_per_em = font_context.units_per_em(
+                    &style.font_family,
+                    style.font_weight,
+                    style.font_stretch,
+                    italic,
+                );
                 let scale = style.font_size / units_per_em as f64;
+                let synthetic_bold = font_context.synthetic_bold_width(
+                    &style.font_family,
+                    style.font_weight,
+                    style.font_stretch,
+                    italic,
+                    style.font_size,
+                );
 
                 let mut all_glyphs = Vec::new();
                 let mut bidi_levels = Vec::new();
@@ -2896,9 +5211,13 @@ impl LayoutEngine {
                     let run_chars: Vec<char> = line.chars[run.char_start..run.char_end].to_vec();
                     let run_text: String = run_chars.iter().collect();
 
-                    if let Some(shaped) =
-                        shaping::shape_text_with_direction(&run_text, font_data, run.is_rtl)
-                    {
+                    if let Some(shaped) = shaping::shape_text_with_direction(
+                        &run_text,
+                        font_data,
+                        &variations,
+                        &style.font_features,
+                        run.is_rtl,
+                    ) {
                         for sg in &shaped {
                             let cluster = sg.cluster as usize;
                             let char_value = run_chars.get(cluster).copied().unwrap_or(' ');
@@ -2917,7 +5236,8 @@ impl LayoutEngine {
 
                             let glyph_x = x + sg.x_offset as f64 * scale;
                             let glyph_y = sg.y_offset as f64 * scale;
-                            let advance = sg.x_advance as f64 * scale + style.letter_spacing;
+                            let advance =
+                                sg.x_advance as f64 * scale + style.letter_spacing + synthetic_bold;
 
                             all_glyphs.push(PositionedGlyph {
                                 glyph_id: sg.glyph_id,
@@ -2927,13 +5247,16 @@ impl LayoutEngine {
                                 font_size: style.font_size,
                                 font_family: style.font_family.clone(),
                                 font_weight: style.font_weight,
+                                font_stretch: style.font_stretch,
                                 font_style: style.font_style,
+                                font_features: style.font_features.clone(),
                                 char_value,
                                 color: Some(style.color),
                                 href: href.map(|s| s.to_string()),
                                 text_decoration: style.text_decoration,
                                 letter_spacing: style.letter_spacing,
                                 cluster_text,
+                                baseline_shift: 0.0,
                             });
                             bidi_levels.push(run.level);
 
@@ -2949,20 +5272,36 @@ impl LayoutEngine {
             }
 
             // Pure LTR path: shape normally
-            if let Some(shaped) = shaping::shape_text(&line_text, font_data) {
-                let units_per_em =
-                    font_context.units_per_em(&style.font_family, style.font_weight, italic);
+            if let Some(shaped) =
+                shaping::shape_text(&line_text, font_data, &variations, &style.font_features)
+            {
+                let units_per_em = font_context.units_per_em(
+                    &style.font_family,
+                    style.font_weight,
+                    style.font_stretch,
+                    italic,
+                );
                 let scale = style.font_size / units_per_em as f64;
+                let synthetic_bold = font_context.synthetic_bold_width(
+                    &style.font_family,
+                    style.font_weight,
+                    style.font_stretch,
+                    italic,
+                    style.font_size,
+                );
 
                 return self.shaped_glyphs_to_positioned(
                     &shaped,
                     &line.chars,
                     &line.char_positions,
                     scale,
+                    synthetic_bold,
                     style.font_size,
                     &style.font_family,
                     style.font_weight,
+                    style.font_stretch,
                     style.font_style,
+                    &style.font_features,
                     Some(style.color),
                     href,
                     style.text_decoration,
@@ -2982,6 +5321,7 @@ impl LayoutEngine {
                     *ch,
                     &style.font_family,
                     style.font_weight,
+                    style.font_stretch,
                     italic,
                     style.font_size,
                 );
@@ -2993,13 +5333,16 @@ impl LayoutEngine {
                     font_size: style.font_size,
                     font_family: style.font_family.clone(),
                     font_weight: style.font_weight,
+                    font_stretch: style.font_stretch,
                     font_style: style.font_style,
+                    font_features: style.font_features.clone(),
                     char_value: *ch,
                     color: Some(style.color),
                     href: href.map(|s| s.to_string()),
                     text_decoration: style.text_decoration,
                     letter_spacing: style.letter_spacing,
                     cluster_text: None,
+                    baseline_shift: 0.0,
                 }
             })
             .collect();
@@ -3056,6 +5399,7 @@ impl LayoutEngine {
                         &sc.font_family,
                         sc.ch,
                         sc.font_weight,
+                        sc.font_stretch,
                         italic,
                     );
                     family
@@ -3087,7 +5431,8 @@ impl LayoutEngine {
             });
 
             // Check for custom font with shaping (using resolved single family)
-            if let Some(font_data) = font_context.font_data(resolved_family, sc.font_weight, italic)
+            if let Some(font_data) =
+                font_context.font_data(resolved_family, sc.font_weight, sc.font_stretch, italic)
             {
                 // Find contiguous run with same resolved font AND same BiDi direction
                 let run_start = i;
@@ -3103,6 +5448,8 @@ impl LayoutEngine {
                     // Group by resolved family, not original comma chain
                     if resolved_families[run_end] == *resolved_family
                         && next.font_weight == sc.font_weight
+                        && next.font_stretch == sc.font_stretch
+                        && next.font_features == sc.font_features
                         && next_italic == italic
                         && (next.font_size - sc.font_size).abs() < 0.001
                         && next_is_rtl == is_rtl
@@ -3114,12 +5461,33 @@ impl LayoutEngine {
                 }
 
                 let run_text: String = chars[run_start..run_end].iter().map(|c| c.ch).collect();
-                if let Some(shaped) =
-                    shaping::shape_text_with_direction(&run_text, font_data, is_rtl)
-                {
-                    let units_per_em =
-                        font_context.units_per_em(resolved_family, sc.font_weight, italic);
+                let variations = font_context.variations(
+                    resolved_family,
+                    sc.font_weight,
+                    sc.font_stretch,
+                    italic,
+                );
+                if let Some(shaped) = shaping::shape_text_with_direction(
+                    &run_text,
+                    font_data,
+                    &variations,
+                    &sc.font_features,
+                    is_rtl,
+                ) {
+                    let units_per_em = font_context.units_per_em(
+                        resolved_family,
+                        sc.font_weight,
+                        sc.font_stretch,
+                        italic,
+                    );
                     let scale = sc.font_size / units_per_em as f64;
+                    let synthetic_bold = font_context.synthetic_bold_width(
+                        resolved_family,
+                        sc.font_weight,
+                        sc.font_stretch,
+                        italic,
+                        sc.font_size,
+                    );
 
                     // Build char positions for this run segment
                     let run_chars: Vec<char> =
@@ -3135,6 +5503,7 @@ impl LayoutEngine {
                         &run_chars,
                         &run_positions,
                         scale,
+                        synthetic_bold,
                     );
                     // Override font_family to the resolved single family
                     for g in &mut run_glyphs {
@@ -3161,6 +5530,7 @@ impl LayoutEngine {
                 sc.ch,
                 resolved_family,
                 sc.font_weight,
+                sc.font_stretch,
                 italic,
                 sc.font_size,
             );
@@ -3172,13 +5542,16 @@ impl LayoutEngine {
                 font_size: sc.font_size,
                 font_family: resolved_family.clone(),
                 font_weight: sc.font_weight,
+                font_stretch: sc.font_stretch,
                 font_style: sc.font_style,
+                font_features: sc.font_features.clone(),
                 char_value: sc.ch,
                 color: Some(sc.color),
                 href: sc.href.clone(),
                 text_decoration: sc.text_decoration,
                 letter_spacing: sc.letter_spacing,
                 cluster_text: None,
+                baseline_shift: sc.baseline_shift,
             });
             bidi_levels.push(if is_rtl {
                 unicode_bidi::Level::rtl()
@@ -3205,10 +5578,13 @@ impl LayoutEngine {
         chars: &[char],
         _char_positions: &[f64],
         scale: f64,
+        synthetic_bold: f64,
         font_size: f64,
         font_family: &str,
         font_weight: u32,
+        font_stretch: u32,
         font_style: FontStyle,
+        font_features: &Arc<[FontFeature]>,
         color: Option<Color>,
         href: Option<&str>,
         text_decoration: TextDecoration,
@@ -3238,7 +5614,7 @@ impl LayoutEngine {
             // Use shaped position
             let glyph_x = x + sg.x_offset as f64 * scale;
             let glyph_y = sg.y_offset as f64 * scale;
-            let advance = sg.x_advance as f64 * scale + letter_spacing;
+            let advance = sg.x_advance as f64 * scale + letter_spacing + synthetic_bold;
 
             result.push(PositionedGlyph {
                 glyph_id: sg.glyph_id,
@@ -3248,13 +5624,16 @@ impl LayoutEngine {
                 font_size,
                 font_family: font_family.to_string(),
                 font_weight,
+                font_stretch,
                 font_style,
+                font_features: font_features.clone(),
                 char_value,
                 color,
                 href: href.map(|s| s.to_string()),
                 text_decoration,
                 letter_spacing,
                 cluster_text,
+                baseline_shift: 0.0,
             });
 
             x += advance;
@@ -3271,6 +5650,7 @@ impl LayoutEngine {
         chars: &[char],
         char_positions: &[f64],
         scale: f64,
+        synthetic_bold: f64,
     ) -> Vec<PositionedGlyph> {
         let mut result = Vec::with_capacity(shaped.len());
         // Use the first char position as the base offset for this run
@@ -3295,7 +5675,7 @@ impl LayoutEngine {
 
             let glyph_x = base_x + x + sg.x_offset as f64 * scale;
             let glyph_y = sg.y_offset as f64 * scale;
-            let advance = sg.x_advance as f64 * scale + sc.letter_spacing;
+            let advance = sg.x_advance as f64 * scale + sc.letter_spacing + synthetic_bold;
 
             result.push(PositionedGlyph {
                 glyph_id: sg.glyph_id,
@@ -3305,13 +5685,16 @@ impl LayoutEngine {
                 font_size: sc.font_size,
                 font_family: sc.font_family.clone(),
                 font_weight: sc.font_weight,
+                font_stretch: sc.font_stretch,
                 font_style: sc.font_style,
+                font_features: sc.font_features.clone(),
                 char_value,
                 color: Some(sc.color),
                 href: sc.href.clone(),
                 text_decoration: sc.text_decoration,
                 letter_spacing: sc.letter_spacing,
                 cluster_text,
+                baseline_shift: sc.baseline_shift,
             });
 
             x += advance;
@@ -3358,19 +5741,35 @@ impl LayoutEngine {
         };
 
         let loaded = if !src.is_empty() {
-            crate::image_loader::load_image(src).ok()
+            match self.get_image(src) {
+                Ok(image) => Some(image),
+                Err(e) => {
+                    self.report(Diagnostic::for_node(
+                        DiagnosticCode::ImageLoadFailed,
+                        format!("Image could not be loaded, drawing a placeholder: {e}"),
+                        node,
+                    ));
+                    None
+                }
+            }
         } else {
+            self.report(Diagnostic::for_node(
+                DiagnosticCode::ImageLoadFailed,
+                "Image has no src, drawing a placeholder",
+                node,
+            ));
             None
         };
 
-        // Compute display dimensions with aspect ratio preservation
+        // Compute display dimensions with aspect ratio preservation; the
+        // style's aspect ratio replaces the image's own
         let (img_width, img_height) = if let Some(ref img) = loaded {
             let intrinsic_w = img.width_px as f64;
             let intrinsic_h = img.height_px as f64;
-            let aspect = if intrinsic_w > 0.0 {
-                intrinsic_h / intrinsic_w
-            } else {
-                0.75
+            let aspect = match style.aspect_ratio {
+                Some(ratio) => 1.0 / ratio,
+                None if intrinsic_w > 0.0 => intrinsic_h / intrinsic_w,
+                None => 0.75,
             };
 
             match (explicit_width, explicit_height) {
@@ -3386,7 +5785,7 @@ impl LayoutEngine {
         } else {
             // Fallback dimensions when image can't be loaded
             let w = explicit_width.unwrap_or(available_width - margin.horizontal());
-            let h = explicit_height.unwrap_or(w * 0.75);
+            let h = explicit_height.unwrap_or(w / style.aspect_ratio.unwrap_or(4.0 / 3.0));
             (w, h)
         };
 
@@ -3400,7 +5799,18 @@ impl LayoutEngine {
         cursor.y += margin.top;
 
         let draw = if let Some(image_data) = loaded {
-            DrawCommand::Image { image_data }
+            let placement = object_fit::place(
+                style.object_fit,
+                style.object_position,
+                image_data.width_px as f64,
+                image_data.height_px as f64,
+                img_width,
+                img_height,
+            );
+            DrawCommand::Image {
+                image_data,
+                placement,
+            }
         } else {
             DrawCommand::ImagePlaceholder
         };
@@ -3417,6 +5827,8 @@ impl LayoutEngine {
             source_location: node.source_location.clone(),
             href: node.href.clone(),
             bookmark: node.bookmark.clone(),
+            bookmark_level: 0,
+            node_id: None,
             alt: node.alt.clone(),
             is_header_row: false,
             overflow: style.overflow,
@@ -3460,6 +5872,15 @@ impl LayoutEngine {
             });
 
         let commands = crate::svg::parse_svg(content, vb, svg_width, svg_height);
+        let unsupported = crate::svg::unsupported_elements(content);
+        if !unsupported.is_empty() {
+            let names: Vec<String> = unsupported.iter().map(|n| format!("<{n}>")).collect();
+            self.report(Diagnostic::for_node(
+                DiagnosticCode::UnsupportedSvgElement,
+                format!("SVG elements not drawn: {}", names.join(", ")),
+                node,
+            ));
+        }
 
         cursor.elements.push(LayoutElement {
             x: x + margin.left,
@@ -3478,6 +5899,8 @@ impl LayoutEngine {
             source_location: node.source_location.clone(),
             href: node.href.clone(),
             bookmark: node.bookmark.clone(),
+            bookmark_level: 0,
+            node_id: None,
             alt: node.alt.clone(),
             is_header_row: false,
             overflow: style.overflow,
@@ -3652,6 +6075,8 @@ impl LayoutEngine {
             source_location: node.source_location.clone(),
             href: node.href.clone(),
             bookmark: node.bookmark.clone(),
+            bookmark_level: 0,
+            node_id: None,
             alt: node.alt.clone(),
             is_header_row: false,
             overflow: style.overflow,
@@ -3708,70 +6133,40 @@ impl LayoutEngine {
             source_location: node.source_location.clone(),
             href: node.href.clone(),
             bookmark: node.bookmark.clone(),
+            bookmark_level: 0,
+            node_id: None,
             alt: node.alt.clone(),
             is_header_row: false,
             overflow: style.overflow,
         });
 
-        cursor.y += display_size + margin.bottom;
-    }
-
-    // ── Measurement helpers ─────────────────────────────────────
-
-    fn measure_node_height(
-        &self,
-        node: &Node,
-        available_width: f64,
-        style: &ResolvedStyle,
-        font_context: &FontContext,
-    ) -> f64 {
-        match &node.kind {
-            NodeKind::Text { content, runs, .. } => {
-                let measure_width = available_width - style.margin.horizontal();
-                if !runs.is_empty() {
-                    // Measure runs
-                    let mut styled_chars: Vec<StyledChar> = Vec::new();
-                    for run in runs {
-                        let run_style = run.style.resolve(Some(style), measure_width);
-                        for ch in run.content.chars() {
-                            styled_chars.push(StyledChar {
-                                ch,
-                                font_family: run_style.font_family.clone(),
-                                font_size: run_style.font_size,
-                                font_weight: run_style.font_weight,
-                                font_style: run_style.font_style,
-                                color: run_style.color,
-                                href: None,
-                                text_decoration: run_style.text_decoration,
-                                letter_spacing: run_style.letter_spacing,
-                            });
-                        }
-                    }
-                    let broken_lines = self.text_layout.break_runs_into_lines(
-                        font_context,
-                        &styled_chars,
-                        measure_width,
-                        style.hyphens,
-                        style.lang.as_deref(),
-                    );
-                    let line_height = style.font_size * style.line_height;
-                    (broken_lines.len() as f64) * line_height + style.padding.vertical()
-                } else {
-                    let lines = self.text_layout.break_into_lines(
-                        font_context,
-                        content,
-                        measure_width,
-                        style.font_size,
-                        &style.font_family,
-                        style.font_weight,
-                        style.font_style,
-                        style.letter_spacing,
-                        style.hyphens,
-                        style.lang.as_deref(),
-                    );
-                    let line_height = style.font_size * style.line_height;
-                    (lines.len() as f64) * line_height + style.padding.vertical()
+        cursor.y += display_size + margin.bottom;
+    }
+
+    // ── Measurement helpers ─────────────────────────────────────
+
+    fn measure_node_height(
+        &self,
+        node: &Node,
+        available_width: f64,
+        style: &ResolvedStyle,
+        font_context: &FontContext,
+    ) -> f64 {
+        match &node.kind {
+            NodeKind::Text { content, runs, .. } => {
+                if matches!(style.writing_mode, WritingMode::VerticalRl) {
+                    let (_, height) =
+                        self.measure_vertical_text(content, runs, style, font_context);
+                    return height + style.padding.vertical();
                 }
+                let measure_width = available_width - style.margin.horizontal();
+                self.measure_text_height(
+                    content,
+                    runs,
+                    style,
+                    LineWidths::uniform(measure_width),
+                    font_context,
+                )
             }
             NodeKind::Image {
                 src,
@@ -3786,11 +6181,15 @@ impl LayoutEngine {
                 if let Some(h) = explicit_h {
                     return *h + style.padding.vertical();
                 }
-                // 3. Compute from real image aspect ratio (header-only read, no pixel decode)
-                let aspect = self
-                    .get_image_dimensions(src)
-                    .map(|(w, h)| if w > 0 { h as f64 / w as f64 } else { 0.75 })
-                    .unwrap_or(0.75);
+                // 3. Compute from the style's aspect ratio, or the real image's
+                // (header-only read, no pixel decode)
+                let aspect = match style.aspect_ratio {
+                    Some(ratio) => 1.0 / ratio,
+                    None => self
+                        .get_image_dimensions(src)
+                        .map(|(w, h)| if w > 0 { h as f64 / w as f64 } else { 0.75 })
+                        .unwrap_or(0.75),
+                };
                 let w = if let SizeConstraint::Fixed(w) = style.width {
                     w
                 } else {
@@ -3805,11 +6204,30 @@ impl LayoutEngine {
             }
             NodeKind::Canvas { height, .. } => *height + style.margin.vertical(),
             NodeKind::Watermark { .. } => 0.0, // Watermarks take zero layout height
+            NodeKind::Table { columns } => {
+                if let SizeConstraint::Fixed(h) = style.height {
+                    return h;
+                }
+                let outer_width = match style.width {
+                    SizeConstraint::Fixed(w) => w,
+                    SizeConstraint::Auto => available_width - style.margin.horizontal(),
+                };
+                let inner_width =
+                    outer_width - style.padding.horizontal() - style.border_width.horizontal();
+                self.measure_table_height(node, columns, inner_width, style, font_context)
+                    + style.padding.vertical()
+                    + style.border_width.vertical()
+            }
             _ => {
                 // If a fixed height is specified, use it directly
                 if let SizeConstraint::Fixed(h) = style.height {
                     return h;
                 }
+                // Match layout_node_content: the aspect ratio sets the height
+                // from the width the box fills
+                if let Some(ratio) = style.aspect_ratio {
+                    return (available_width - style.margin.horizontal()) / ratio;
+                }
                 // Match layout_view: when width is Auto, margin reduces the outer width
                 let outer_width = match style.width {
                     SizeConstraint::Fixed(w) => w,
@@ -3817,13 +6235,180 @@ impl LayoutEngine {
                 };
                 let inner_width =
                     outer_width - style.padding.horizontal() - style.border_width.horizontal();
-                let children_height =
-                    self.measure_children_height(&node.children, inner_width, style, font_context);
+                let children_height = if is_multi_column(style) {
+                    self.measure_columns_height(&node.children, inner_width, style, font_context)
+                } else {
+                    self.measure_children_height(&node.children, inner_width, style, font_context)
+                };
                 children_height + style.padding.vertical() + style.border_width.vertical()
             }
         }
     }
 
+    /// The height of horizontal text broken into lines `widths` wide.
+    fn measure_text_height(
+        &self,
+        content: &str,
+        runs: &[TextRun],
+        style: &ResolvedStyle,
+        widths: LineWidths,
+        font_context: &FontContext,
+    ) -> f64 {
+        let line_count = if !runs.is_empty() {
+            // Measure runs
+            let mut styled_chars: Vec<StyledChar> = Vec::new();
+            for run in footnote::number_runs(runs, 1, style).iter() {
+                let run_style = run.style.resolve(Some(style), widths.rest);
+                for ch in run.content.chars() {
+                    styled_chars.push(StyledChar {
+                        ch,
+                        font_family: run_style.font_family.clone(),
+                        font_size: run_style.font_size,
+                        font_weight: run_style.font_weight,
+                        font_stretch: run_style.font_stretch,
+                        font_style: run_style.font_style,
+                        font_features: run_style.font_features.clone(),
+                        color: run_style.color,
+                        href: None,
+                        text_decoration: run_style.text_decoration,
+                        letter_spacing: run_style.letter_spacing,
+                        baseline_shift: 0.0,
+                        footnote: None,
+                    });
+                }
+            }
+            self.text_layout
+                .break_runs_into_lines(
+                    font_context,
+                    &styled_chars,
+                    widths,
+                    style.hyphens,
+                    style.lang.as_deref(),
+                )
+                .len()
+        } else {
+            self.text_layout
+                .break_into_lines(
+                    font_context,
+                    content,
+                    widths,
+                    style.font_size,
+                    &style.font_family,
+                    style.font_weight,
+                    style.font_stretch,
+                    style.font_style,
+                    &style.font_features,
+                    style.letter_spacing,
+                    style.hyphens,
+                    style.lang.as_deref(),
+                )
+                .len()
+        };
+        let line_height = style.font_size * style.line_height;
+        (visible_line_count(line_count, style) as f64) * line_height + style.padding.vertical()
+    }
+
+    /// Estimate the height of a multi-column container's children: each run
+    /// of children between spanning ones shared evenly between the columns,
+    /// but never shorter than its tallest child.
+    fn measure_columns_height(
+        &self,
+        children: &[Node],
+        width: f64,
+        style: &ResolvedStyle,
+        font_context: &FontContext,
+    ) -> f64 {
+        let count = style.column_count as usize;
+        let column_width =
+            ((width - style.column_gap * (count - 1) as f64) / count as f64).max(0.0);
+        let mut height = 0.0;
+        let mut parts = 0;
+        let mut run: Vec<f64> = Vec::new();
+        let flush_run = |run: &mut Vec<f64>, height: &mut f64, parts: &mut usize| {
+            if run.is_empty() {
+                return;
+            }
+            let total: f64 = run.iter().sum::<f64>() + style.row_gap * (run.len() - 1) as f64;
+            let tallest = run.iter().copied().fold(0.0, f64::max);
+            *height += (total / count as f64).max(tallest);
+            *parts += 1;
+            run.clear();
+        };
+        for child in children {
+            let child_style = child.style.resolve(Some(style), width);
+            if matches!(child_style.column_span, ColumnSpan::All) {
+                flush_run(&mut run, &mut height, &mut parts);
+                height += self.measure_node_height(child, width, &child_style, font_context)
+                    + child_style.margin.vertical();
+                parts += 1;
+            } else {
+                let child_style = child.style.resolve(Some(style), column_width);
+                run.push(
+                    self.measure_node_height(child, column_width, &child_style, font_context)
+                        + child_style.margin.vertical(),
+                );
+            }
+        }
+        flush_run(&mut run, &mut height, &mut parts);
+        height + style.row_gap * parts.saturating_sub(1) as f64
+    }
+
+    /// The size of vertical (`vertical-rl`) text outside the page flow, as
+    /// `(width, height)`: columns as long as its fixed `height`, or without
+    /// one a single column as long as the text.
+    fn measure_vertical_text(
+        &self,
+        content: &str,
+        runs: &[TextRun],
+        style: &ResolvedStyle,
+        font_context: &FontContext,
+    ) -> (f64, f64) {
+        let fixed_height = match style.height {
+            SizeConstraint::Fixed(h) => Some(h),
+            SizeConstraint::Auto => None,
+        };
+        let length = fixed_height.unwrap_or(f64::MAX);
+        let column_lengths: Vec<f64> = if runs.is_empty() {
+            let transformed = apply_text_transform(content, style.text_transform);
+            self.text_layout
+                .break_into_lines(
+                    font_context,
+                    &transformed,
+                    length,
+                    style.font_size,
+                    &style.font_family,
+                    style.font_weight,
+                    style.font_stretch,
+                    style.font_style,
+                    &style.font_features,
+                    style.letter_spacing,
+                    style.hyphens,
+                    style.lang.as_deref(),
+                )
+                .iter()
+                .map(|line| line.width)
+                .collect()
+        } else {
+            let runs = footnote::number_runs(runs, 1, style);
+            let styled_chars = styled_chars_from_runs(&runs, None, style, 0.0);
+            self.text_layout
+                .break_runs_into_lines(
+                    font_context,
+                    &styled_chars,
+                    length,
+                    style.hyphens,
+                    style.lang.as_deref(),
+                )
+                .iter()
+                .map(|line| line.width)
+                .collect()
+        };
+        let line_height = style.font_size * style.line_height;
+        let height =
+            fixed_height.unwrap_or_else(|| column_lengths.iter().copied().fold(0.0, f64::max));
+        (column_lengths.len() as f64 * line_height, height)
+    }
+
     fn measure_children_height(
         &self,
         children: &[Node],
@@ -3996,21 +6581,78 @@ impl LayoutEngine {
                 total
             }
             FlexDirection::Column | FlexDirection::ColumnReverse => {
+                // Floats are placed as in layout_children, relative to the
+                // top-left of the content box
                 let mut total = 0.0;
-                for (i, child) in children.iter().enumerate() {
+                let mut floats: Vec<float::PlacedFloat> = Vec::new();
+                let mut in_flow = 0;
+                for child in children {
                     let child_style = child.style.resolve(Some(parent_style), available_width);
-                    let child_height = self.measure_node_height(
+                    if child_style.float != Float::None {
+                        let (width, height) =
+                            self.measure_float(child, &child_style, available_width, font_context);
+                        let placed = float::place(
+                            &floats,
+                            child_style.float,
+                            0.0,
+                            available_width,
+                            total,
+                            width,
+                            height,
+                        );
+                        floats.push(placed);
+                        continue;
+                    }
+                    if in_flow > 0 {
+                        total += row_gap;
+                    }
+                    in_flow += 1;
+
+                    let (y, x, width) = self.beside_floats(
+                        &floats,
                         child,
-                        available_width,
                         &child_style,
+                        0.0,
+                        available_width,
+                        total,
                         font_context,
                     );
-                    total += child_height + child_style.margin.vertical();
-                    if i > 0 {
-                        total += row_gap;
-                    }
+                    total = y;
+                    let margin = &child_style.margin;
+                    let child_height = match &child.kind {
+                        NodeKind::Text { content, runs, .. }
+                            if !floats.is_empty() && wraps_around_floats(child, &child_style) =>
+                        {
+                            let line_height = child_style.font_size * child_style.line_height;
+                            let widths = float::line_widths(
+                                &floats,
+                                x + margin.left,
+                                width - margin.horizontal(),
+                                total + margin.top,
+                                line_height,
+                            );
+                            self.measure_text_height(
+                                content,
+                                runs,
+                                &child_style,
+                                widths,
+                                font_context,
+                            )
+                        }
+                        _ if width < available_width => {
+                            let narrowed = child.style.resolve(Some(parent_style), width);
+                            self.measure_node_height(child, width, &narrowed, font_context)
+                        }
+                        _ => self.measure_node_height(
+                            child,
+                            available_width,
+                            &child_style,
+                            font_context,
+                        ),
+                    };
+                    total += child_height + margin.vertical();
                 }
-                total
+                float::clearance(&floats).map_or(total, |bottom| total.max(bottom))
             }
         }
     }
@@ -4026,17 +6668,33 @@ impl LayoutEngine {
             NodeKind::Svg { width, .. } => {
                 *width + style.padding.horizontal() + style.margin.horizontal()
             }
-            NodeKind::Text { content, .. } => {
-                let transformed = apply_text_transform(content, style.text_transform);
-                let italic = matches!(style.font_style, FontStyle::Italic | FontStyle::Oblique);
-                let text_width = font_context.measure_string(
-                    &transformed,
-                    &style.font_family,
-                    style.font_weight,
-                    italic,
-                    style.font_size,
-                    style.letter_spacing,
-                );
+            NodeKind::Text { content, runs, .. }
+                if matches!(style.writing_mode, WritingMode::VerticalRl) =>
+            {
+                let (width, _) = self.measure_vertical_text(content, runs, style, font_context);
+                width + style.padding.horizontal() + style.margin.horizontal()
+            }
+            NodeKind::Text { content, runs, .. } => {
+                let measure = |text: &str, s: &ResolvedStyle| {
+                    let transformed = apply_text_transform(text, s.text_transform);
+                    let italic = matches!(s.font_style, FontStyle::Italic | FontStyle::Oblique);
+                    font_context.measure_string(
+                        &transformed,
+                        &s.font_family,
+                        s.font_weight,
+                        s.font_stretch,
+                        italic,
+                        s.font_size,
+                        s.letter_spacing,
+                    )
+                };
+                let text_width = if runs.is_empty() {
+                    measure(content, style)
+                } else {
+                    runs.iter()
+                        .map(|run| measure(&run.content, &run.style.resolve(Some(style), 0.0)))
+                        .sum()
+                };
                 // Add tiny epsilon to prevent exact-boundary line wrapping when
                 // this width is later used as max_width for line breaking
                 text_width + 0.01 + style.padding.horizontal() + style.margin.horizontal()
@@ -4051,10 +6709,10 @@ impl LayoutEngine {
                 } else if let Some((iw, ih)) = self.get_image_dimensions(src) {
                     let pixel_w = iw as f64;
                     let pixel_h = ih as f64;
-                    let aspect = if pixel_w > 0.0 {
-                        pixel_h / pixel_w
-                    } else {
-                        0.75
+                    let aspect = match style.aspect_ratio {
+                        Some(ratio) => 1.0 / ratio,
+                        None if pixel_w > 0.0 => pixel_h / pixel_w,
+                        None => 0.75,
                     };
                     // Check for height constraint (style or node prop)
                     let constrained_h = match style.height {
@@ -4122,6 +6780,10 @@ impl LayoutEngine {
         font_context: &FontContext,
     ) -> f64 {
         match &node.kind {
+            // Vertical columns don't get narrower when there's less width
+            NodeKind::Text { .. } if matches!(style.writing_mode, WritingMode::VerticalRl) => {
+                self.measure_intrinsic_width(node, style, font_context)
+            }
             NodeKind::Text { content, runs, .. } => {
                 let word_width = if !runs.is_empty() {
                     // For styled runs, measure each run's widest word
@@ -4136,7 +6798,9 @@ impl LayoutEngine {
                                 run_style.font_size,
                                 &run_style.font_family,
                                 run_style.font_weight,
+                                run_style.font_stretch,
                                 run_style.font_style,
+                                &run_style.font_features,
                                 run_style.letter_spacing,
                                 style.hyphens,
                                 style.lang.as_deref(),
@@ -4151,7 +6815,9 @@ impl LayoutEngine {
                         style.font_size,
                         &style.font_family,
                         style.font_weight,
+                        style.font_stretch,
                         style.font_style,
+                        &style.font_features,
                         style.letter_spacing,
                         style.hyphens,
                         style.lang.as_deref(),
@@ -4187,87 +6853,170 @@ impl LayoutEngine {
         }
     }
 
-    fn measure_table_row_height(
+    /// Measure the height of each row in a table section. Cells spanning
+    /// several rows stretch the last row they cover when they don't fit.
+    fn measure_table_row_heights(
         &self,
-        row: &Node,
+        rows: &[&Node],
+        placements: &[Vec<table::CellPlacement>],
         col_widths: &[f64],
         parent_style: &ResolvedStyle,
         font_context: &FontContext,
-    ) -> f64 {
-        let row_style = row
-            .style
-            .resolve(Some(parent_style), col_widths.iter().sum());
-        let mut max_height: f64 = 0.0;
+    ) -> Vec<f64> {
+        let mut heights = vec![0.0; rows.len()];
+        let mut spans: Vec<(usize, usize, f64)> = Vec::new();
 
-        for (i, cell) in row.children.iter().enumerate() {
-            let col_width = col_widths.get(i).copied().unwrap_or(0.0);
-            let cell_style = cell.style.resolve(Some(&row_style), col_width);
-            let inner_width =
-                col_width - cell_style.padding.horizontal() - cell_style.border_width.horizontal();
+        for (row_idx, (row, row_placements)) in rows.iter().zip(placements).enumerate() {
+            let row_style = row
+                .style
+                .resolve(Some(parent_style), col_widths.iter().sum());
 
-            let mut cell_content_height = 0.0;
-            for child in &cell.children {
-                let child_style = child.style.resolve(Some(&cell_style), inner_width);
-                cell_content_height +=
-                    self.measure_node_height(child, inner_width, &child_style, font_context);
+            for placement in row_placements {
+                let Some(cell) = row.children.get(placement.cell_index) else {
+                    continue;
+                };
+                let col_width =
+                    table::span_width(placement.col_start, placement.col_end, col_widths);
+                let cell_style = cell.style.resolve(Some(&row_style), col_width);
+                let inner_width = col_width
+                    - cell_style.padding.horizontal()
+                    - cell_style.border_width.horizontal();
+
+                let mut cell_content_height = 0.0;
+                for child in &cell.children {
+                    let child_style = child.style.resolve(Some(&cell_style), inner_width);
+                    cell_content_height +=
+                        self.measure_node_height(child, inner_width, &child_style, font_context);
+                }
+
+                let total = cell_content_height
+                    + cell_style.padding.vertical()
+                    + cell_style.border_width.vertical();
+                if placement.row_span > 1 {
+                    spans.push((row_idx, placement.row_span, total));
+                } else {
+                    heights[row_idx] = f64::max(heights[row_idx], total);
+                }
             }
 
-            let total = cell_content_height
-                + cell_style.padding.vertical()
-                + cell_style.border_width.vertical();
-            max_height = max_height.max(total);
+            heights[row_idx] = f64::max(heights[row_idx], row_style.min_height);
         }
 
-        max_height.max(row_style.min_height)
+        table::distribute_row_spans(&mut heights, &spans);
+        heights
+    }
+
+    /// Total height of a table's rows at the given inner width.
+    fn measure_table_height(
+        &self,
+        node: &Node,
+        column_defs: &[ColumnDef],
+        inner_width: f64,
+        style: &ResolvedStyle,
+        font_context: &FontContext,
+    ) -> f64 {
+        let table = self.prepare_table(node, column_defs, inner_width, style, font_context);
+        table.header.height()
+            + table.body.height()
+            + self.measure_table_footer_height(&table, true, style, font_context)
     }
 
+    /// Resolve column widths from their definitions. `Auto` columns, and
+    /// columns without a definition, share what `Fixed` and `Fraction`
+    /// columns leave over, sized from the content of their cells in
+    /// `sections`.
+    #[allow(clippy::too_many_arguments)]
     fn resolve_column_widths(
         &self,
         defs: &[ColumnDef],
         available_width: f64,
-        children: &[Node],
+        num_columns: usize,
+        sections: &[&TableSection],
+        table_style: &ResolvedStyle,
+        font_context: &FontContext,
     ) -> Vec<f64> {
-        if defs.is_empty() {
-            let num_cols = children.first().map(|row| row.children.len()).unwrap_or(1);
-            return vec![available_width / num_cols as f64; num_cols];
-        }
-
+        // Columns without a def, including every column of a table with
+        // none, are sized from their content
+        let num_cols = num_columns.max(defs.len()).max(1);
         let mut widths = Vec::new();
         let mut remaining = available_width;
-        let mut auto_count = 0;
+        let mut auto_columns = Vec::new();
 
-        for def in defs {
-            match def.width {
-                ColumnWidth::Fixed(w) => {
-                    widths.push(w);
+        for i in 0..num_cols {
+            match defs.get(i).map(|def| &def.width) {
+                Some(ColumnWidth::Fixed(w)) => {
+                    widths.push(*w);
                     remaining -= w;
                 }
-                ColumnWidth::Fraction(f) => {
+                Some(ColumnWidth::Fraction(f)) => {
                     let w = available_width * f;
                     widths.push(w);
                     remaining -= w;
                 }
-                ColumnWidth::Auto => {
+                Some(ColumnWidth::Auto) | None => {
                     widths.push(0.0);
-                    auto_count += 1;
+                    auto_columns.push(i);
                 }
             }
         }
 
-        if auto_count > 0 {
-            let auto_width = remaining / auto_count as f64;
-            for (i, def) in defs.iter().enumerate() {
-                if matches!(def.width, ColumnWidth::Auto) {
-                    widths[i] = auto_width;
-                }
+        if !auto_columns.is_empty() {
+            let cells = self.measure_table_cell_widths(sections, table_style, font_context);
+            let (min, max) = table::column_content_widths(num_cols, &cells);
+            let auto_min: Vec<f64> = auto_columns.iter().map(|&i| min[i]).collect();
+            let auto_max: Vec<f64> = auto_columns.iter().map(|&i| max[i]).collect();
+            let auto_widths = table::distribute_auto_widths(&auto_min, &auto_max, remaining);
+            for (&i, w) in auto_columns.iter().zip(auto_widths) {
+                widths[i] = w;
             }
         }
 
         widths
     }
 
-    fn inject_fixed_elements(&self, pages: &mut [LayoutPage], font_context: &FontContext) {
-        for page in pages.iter_mut() {
+    /// Min- and max-content widths of every cell in the given table sections,
+    /// for sizing `Auto` columns.
+    fn measure_table_cell_widths(
+        &self,
+        sections: &[&TableSection],
+        table_style: &ResolvedStyle,
+        font_context: &FontContext,
+    ) -> Vec<table::CellContentWidth> {
+        let mut cells = Vec::new();
+        for section in sections {
+            for (row, placements) in section.rows.iter().zip(&section.placements) {
+                let row_style = row.style.resolve(Some(table_style), 0.0);
+                for placement in placements {
+                    let Some(cell) = row.children.get(placement.cell_index) else {
+                        continue;
+                    };
+                    let cell_style = cell.style.resolve(Some(&row_style), 0.0);
+                    let (min, max) = match cell_style.width {
+                        SizeConstraint::Fixed(w) => (w, w),
+                        SizeConstraint::Auto => (
+                            self.measure_min_content_width(cell, &cell_style, font_context),
+                            self.measure_intrinsic_width(cell, &cell_style, font_context),
+                        ),
+                    };
+                    cells.push(table::CellContentWidth {
+                        col_start: placement.col_start,
+                        col_end: placement.col_end,
+                        min,
+                        max,
+                    });
+                }
+            }
+        }
+        cells
+    }
+
+    fn inject_fixed_elements(
+        &self,
+        pages: &mut [LayoutPage],
+        numbers: &[page_number::PageNumbers],
+        font_context: &FontContext,
+    ) {
+        for (page, numbers) in pages.iter_mut().zip(numbers) {
             // Inject watermarks behind all content
             if !page.watermarks.is_empty() {
                 let (page_w, page_h) = page.config.size.dimensions();
@@ -4297,7 +7046,9 @@ impl LayoutEngine {
                             text,
                             &style.font_family,
                             style.font_weight,
+                            style.font_stretch,
                             style.font_style,
+                            &style.font_features,
                         );
 
                         let mut glyphs = Vec::new();
@@ -4309,11 +7060,20 @@ impl LayoutEngine {
                             let units_per_em = font_context.units_per_em(
                                 &style.font_family,
                                 style.font_weight,
+                                style.font_stretch,
                                 italic,
                             ) as f64;
+                            let synthetic_bold = font_context.synthetic_bold_width(
+                                &style.font_family,
+                                style.font_weight,
+                                style.font_stretch,
+                                italic,
+                                *font_size,
+                            );
 
                             for sg in &shaped_glyphs {
-                                let advance = sg.x_advance as f64 / units_per_em * *font_size;
+                                let advance = sg.x_advance as f64 / units_per_em * *font_size
+                                    + synthetic_bold;
                                 let cluster_idx = sg.cluster as usize;
                                 let ch = text_chars.get(cluster_idx).copied().unwrap_or(' ');
                                 glyphs.push(PositionedGlyph {
@@ -4325,12 +7085,15 @@ impl LayoutEngine {
                                     font_size: *font_size,
                                     font_family: style.font_family.clone(),
                                     font_weight: style.font_weight,
+                                    font_stretch: style.font_stretch,
                                     font_style: style.font_style,
+                                    font_features: style.font_features.clone(),
                                     color: Some(color),
                                     href: None,
                                     text_decoration: TextDecoration::None,
                                     letter_spacing: style.letter_spacing,
                                     cluster_text: None,
+                                    baseline_shift: 0.0,
                                 });
                                 x_pos += advance + style.letter_spacing;
                             }
@@ -4341,6 +7104,7 @@ impl LayoutEngine {
                                     ch,
                                     &style.font_family,
                                     style.font_weight,
+                                    style.font_stretch,
                                     italic,
                                     *font_size,
                                 );
@@ -4353,12 +7117,15 @@ impl LayoutEngine {
                                     font_size: *font_size,
                                     font_family: style.font_family.clone(),
                                     font_weight: style.font_weight,
+                                    font_stretch: style.font_stretch,
                                     font_style: style.font_style,
+                                    font_features: style.font_features.clone(),
                                     color: Some(color),
                                     href: None,
                                     text_decoration: TextDecoration::None,
                                     letter_spacing: style.letter_spacing,
                                     cluster_text: None,
+                                    baseline_shift: 0.0,
                                 });
                                 x_pos += w + style.letter_spacing;
                             }
@@ -4373,6 +7140,8 @@ impl LayoutEngine {
                             width: text_width,
                             height: *font_size,
                             word_spacing: 0.0,
+                            char_spacing: 0.0,
+                            vertical: false,
                         };
 
                         watermark_elements.push(LayoutElement {
@@ -4393,6 +7162,8 @@ impl LayoutEngine {
                             source_location: None,
                             href: None,
                             bookmark: None,
+                            bookmark_level: 0,
+                            node_id: None,
                             alt: None,
                             is_header_row: false,
                             overflow: Overflow::default(),
@@ -4406,6 +7177,22 @@ impl LayoutEngine {
                 page.watermarks.clear();
             }
 
+            // Footnotes go at the bottom of the content area, above the footer
+            if !page.footnotes.is_empty() {
+                let cursor = PageCursor::new(&page.config);
+                let footer_height: f64 = page.fixed_footer.iter().map(|(_, h)| *h).sum();
+                let top =
+                    cursor.content_height - footer_height - footnote::area_height(&page.footnotes);
+                page.elements.extend(footnote::place(
+                    &page.footnotes,
+                    cursor.content_x,
+                    cursor.content_y,
+                    cursor.content_width,
+                    top,
+                ));
+                page.footnotes.clear();
+            }
+
             if page.fixed_header.is_empty() && page.fixed_footer.is_empty() {
                 continue;
             }
@@ -4414,6 +7201,7 @@ impl LayoutEngine {
             if !page.fixed_header.is_empty() {
                 let mut hdr_cursor = PageCursor::new(&page.config);
                 for (node, _h) in &page.fixed_header {
+                    let node = &map_node_text(node, &|text| numbers.substitute(text));
                     let cw = hdr_cursor.content_width;
                     let cx = hdr_cursor.content_x;
                     let style = node.style.resolve(None, cw);
@@ -4443,6 +7231,7 @@ impl LayoutEngine {
                 let target_y = ftr_cursor.content_height - total_ftr;
                 // Layout from y=0
                 for (node, _h) in &page.fixed_footer {
+                    let node = &map_node_text(node, &|text| numbers.substitute(text));
                     let cw = ftr_cursor.content_width;
                     let cx = ftr_cursor.content_x;
                     let style = node.style.resolve(None, cw);
@@ -4471,6 +7260,185 @@ impl LayoutEngine {
         }
     }
 
+    /// Second pass over the laid-out pages: reshape every text line that
+    /// still holds a page-number token (body text, table footer rows) with
+    /// the page's numbers, then realign it within its text block.
+    fn resolve_page_number_tokens(
+        &self,
+        pages: &mut [LayoutPage],
+        numbers: &[page_number::PageNumbers],
+        font_context: &FontContext,
+    ) {
+        for (page, numbers) in pages.iter_mut().zip(numbers) {
+            self.resolve_element_page_tokens(&mut page.elements, None, numbers, font_context);
+        }
+    }
+
+    /// `block` is the `(x, width)` of the text block the elements' lines
+    /// belong to.
+    fn resolve_element_page_tokens(
+        &self,
+        elements: &mut [LayoutElement],
+        block: Option<(f64, f64)>,
+        numbers: &page_number::PageNumbers,
+        font_context: &FontContext,
+    ) {
+        for el in elements {
+            if let DrawCommand::Text { lines, color, .. } = &mut el.draw {
+                let Some(style) = el.resolved_style.as_ref() else {
+                    continue;
+                };
+                let mut resized = false;
+                for line in lines.iter_mut() {
+                    let (block_x, block_width) = block.unwrap_or((line.x, line.width));
+                    resized |= self.resolve_line_page_tokens(
+                        line,
+                        *color,
+                        style,
+                        block_x,
+                        block_width,
+                        numbers,
+                        font_context,
+                    );
+                }
+                // A line element hugs its line, so it follows the new width
+                if resized {
+                    if let [line] = lines.as_slice() {
+                        el.x = line.x;
+                        el.width = line.width;
+                    }
+                }
+            }
+            let child_block = if el.node_type.as_deref() == Some("Text") {
+                Some((el.x, el.width))
+            } else {
+                None
+            };
+            self.resolve_element_page_tokens(&mut el.children, child_block, numbers, font_context);
+        }
+    }
+
+    /// Returns whether a token was replaced on a horizontal line, moving or
+    /// resizing it.
+    #[allow(clippy::too_many_arguments)]
+    fn resolve_line_page_tokens(
+        &self,
+        line: &mut TextLine,
+        color: Color,
+        style: &ResolvedStyle,
+        block_x: f64,
+        block_width: f64,
+        numbers: &page_number::PageNumbers,
+        font_context: &FontContext,
+    ) -> bool {
+        // Most lines hold no token, so look for one before copying the
+        // line's glyphs into styled chars
+        let mut previous = None;
+        let opens_token = line
+            .glyphs
+            .iter()
+            .flat_map(|g| {
+                let cluster = g.cluster_text.iter().flat_map(|cluster| cluster.chars());
+                cluster.chain(g.cluster_text.is_none().then_some(g.char_value))
+            })
+            .any(|ch| {
+                let opens = ch == '{' && previous == Some('{');
+                previous = Some(ch);
+                opens
+            });
+        if !opens_token {
+            return false;
+        }
+
+        let mut chars: Vec<StyledChar> = Vec::new();
+        for g in &line.glyphs {
+            let styled = |ch: char| StyledChar {
+                ch,
+                font_family: g.font_family.clone(),
+                font_size: g.font_size,
+                font_weight: g.font_weight,
+                font_stretch: g.font_stretch,
+                font_style: g.font_style,
+                font_features: g.font_features.clone(),
+                color: g.color.unwrap_or(color),
+                href: g.href.clone(),
+                text_decoration: g.text_decoration,
+                letter_spacing: g.letter_spacing,
+                baseline_shift: g.baseline_shift,
+                footnote: None,
+            };
+            match &g.cluster_text {
+                Some(cluster) => chars.extend(cluster.chars().map(styled)),
+                None => chars.push(styled(g.char_value)),
+            }
+        }
+        let text: String = chars.iter().map(|sc| sc.ch).collect();
+        if !page_number::has_page_tokens(&text) {
+            return false;
+        }
+
+        // Replace each token with its value, styled like the token's first char
+        let mut resolved: Vec<StyledChar> = Vec::with_capacity(chars.len());
+        let mut i = 0;
+        while i < chars.len() {
+            let token_end = (chars[i].ch == '{' && chars.get(i + 1).map(|sc| sc.ch) == Some('{'))
+                .then(|| {
+                    (i + 2..chars.len().saturating_sub(1))
+                        .find(|&j| chars[j].ch == '}' && chars[j + 1].ch == '}')
+                })
+                .flatten();
+            if let Some(end) = token_end {
+                let token: String = chars[i..end + 2].iter().map(|sc| sc.ch).collect();
+                let value = numbers.substitute(&token);
+                if value != token {
+                    resolved.extend(value.chars().map(|ch| StyledChar {
+                        ch,
+                        ..chars[i].clone()
+                    }));
+                    i = end + 2;
+                    continue;
+                }
+            }
+            resolved.push(chars[i].clone());
+            i += 1;
+        }
+
+        let run_line = self.text_layout.run_line(font_context, &resolved);
+        let glyphs = self.build_positioned_glyphs_runs(&run_line, font_context, style.direction);
+        let rendered_width = glyphs
+            .last()
+            .map(|g| g.x_offset + g.x_advance)
+            .unwrap_or(0.0);
+
+        // A vertical column keeps its place; only its length changes
+        if line.vertical {
+            line.glyphs = glyphs;
+            line.width = rendered_width;
+            line.word_spacing = 0.0;
+            line.char_spacing = 0.0;
+            return false;
+        }
+
+        // A justified line was stretched to the block width; keep it that way
+        let justified = matches!(style.text_align, TextAlign::Justify)
+            && (line.width - block_width).abs() < 0.01;
+        let (width, (word_spacing, char_spacing)) = if justified {
+            (block_width, justify_spacing(&glyphs, block_width))
+        } else {
+            (rendered_width, (0.0, 0.0))
+        };
+        line.x = match style.text_align {
+            TextAlign::Left | TextAlign::Justify => block_x,
+            TextAlign::Right => block_x + block_width - width,
+            TextAlign::Center => block_x + (block_width - width) / 2.0,
+        };
+        line.glyphs = glyphs;
+        line.width = width;
+        line.word_spacing = word_spacing;
+        line.char_spacing = char_spacing;
+        true
+    }
+
     /// Layout children as a CSS Grid.
     ///
     /// Uses the grid track definitions from the parent style to create a 2D grid,
@@ -4882,6 +7850,9 @@ mod tests {
             fonts: vec![],
             tagged: false,
             pdfa: None,
+            font_synthesis: Default::default(),
+            footnote_numbering: Default::default(),
+            image_policy: Default::default(),
             default_style: None,
             embedded_data: None,
         };
@@ -4937,6 +7908,9 @@ mod tests {
             fonts: vec![],
             tagged: false,
             pdfa: None,
+            font_synthesis: Default::default(),
+            footnote_numbering: Default::default(),
+            image_policy: Default::default(),
             default_style: None,
             embedded_data: None,
         };
@@ -4993,6 +7967,9 @@ mod tests {
             fonts: vec![],
             tagged: false,
             pdfa: None,
+            font_synthesis: Default::default(),
+            footnote_numbering: Default::default(),
+            image_policy: Default::default(),
             default_style: None,
             embedded_data: None,
         };
@@ -5060,6 +8037,9 @@ mod tests {
             fonts: vec![],
             tagged: false,
             pdfa: None,
+            font_synthesis: Default::default(),
+            footnote_numbering: Default::default(),
+            image_policy: Default::default(),
             default_style: None,
             embedded_data: None,
         };
@@ -5206,6 +8186,9 @@ mod tests {
             fonts: vec![],
             tagged: false,
             pdfa: None,
+            font_synthesis: Default::default(),
+            footnote_numbering: Default::default(),
+            image_policy: Default::default(),
             default_style: None,
             embedded_data: None,
         };
@@ -5272,6 +8255,9 @@ mod tests {
             fonts: vec![],
             tagged: false,
             pdfa: None,
+            font_synthesis: Default::default(),
+            footnote_numbering: Default::default(),
+            image_policy: Default::default(),
             default_style: None,
             embedded_data: None,
         };
@@ -5350,6 +8336,9 @@ mod tests {
             fonts: vec![],
             tagged: false,
             pdfa: None,
+            font_synthesis: Default::default(),
+            footnote_numbering: Default::default(),
+            image_policy: Default::default(),
             default_style: None,
             embedded_data: None,
         };
@@ -5406,6 +8395,9 @@ mod tests {
             fonts: vec![],
             tagged: false,
             pdfa: None,
+            font_synthesis: Default::default(),
+            footnote_numbering: Default::default(),
+            image_policy: Default::default(),
             default_style: None,
             embedded_data: None,
         };
@@ -5473,6 +8465,9 @@ mod tests {
             fonts: vec![],
             tagged: false,
             pdfa: None,
+            font_synthesis: Default::default(),
+            footnote_numbering: Default::default(),
+            image_policy: Default::default(),
             default_style: None,
             embedded_data: None,
         };
@@ -5551,6 +8546,9 @@ mod tests {
             fonts: vec![],
             tagged: false,
             pdfa: None,
+            font_synthesis: Default::default(),
+            footnote_numbering: Default::default(),
+            image_policy: Default::default(),
             default_style: None,
             embedded_data: None,
         };
@@ -5628,6 +8626,9 @@ mod tests {
             fonts: vec![],
             tagged: false,
             pdfa: None,
+            font_synthesis: Default::default(),
+            footnote_numbering: Default::default(),
+            image_policy: Default::default(),
             default_style: None,
             embedded_data: None,
         };
@@ -5699,6 +8700,9 @@ mod tests {
             fonts: vec![],
             tagged: false,
             pdfa: None,
+            font_synthesis: Default::default(),
+            footnote_numbering: Default::default(),
+            image_policy: Default::default(),
             default_style: None,
             embedded_data: None,
         };
@@ -5762,6 +8766,9 @@ mod tests {
             fonts: vec![],
             tagged: false,
             pdfa: None,
+            font_synthesis: Default::default(),
+            footnote_numbering: Default::default(),
+            image_policy: Default::default(),
             default_style: None,
             embedded_data: None,
         };
@@ -5828,4 +8835,42 @@ mod tests {
             intrinsic
         );
     }
+
+    #[test]
+    fn unsettled_page_refs_reported_after_last_pass() {
+        let engine = LayoutEngine::new();
+        let font_context = FontContext::new();
+        let mut target = make_text("Target", 12.0);
+        target.id = Some("end".to_string());
+        let doc = Document {
+            children: vec![
+                make_text("See page {{pageRef:end}}", 12.0),
+                Node {
+                    kind: NodeKind::PageBreak,
+                    ..make_styled_view(Style::default(), vec![])
+                },
+                target,
+            ],
+            metadata: Default::default(),
+            default_page: PageConfig::default(),
+            fonts: vec![],
+            tagged: false,
+            pdfa: None,
+            font_synthesis: Default::default(),
+            footnote_numbering: Default::default(),
+            image_policy: Default::default(),
+            default_style: None,
+            embedded_data: None,
+        };
+        let unsettled = |max_passes| {
+            engine.paginate_cross_referenced(&doc, &font_context, max_passes);
+            engine
+                .diagnostics()
+                .iter()
+                .any(|d| d.code == DiagnosticCode::UnsettledPageRefs)
+        };
+        // One pass only prints the estimate, which doesn't know the target
+        assert!(unsettled(1));
+        assert!(!unsettled(MAX_LAYOUT_PASSES));
+    }
 }
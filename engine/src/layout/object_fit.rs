@@ -0,0 +1,153 @@
+//! # Object Fit
+//!
+//! Where an image is drawn within its box when their proportions differ,
+//! following CSS `object-fit` and `object-position`. The image is scaled
+//! as the fit asks and positioned in the box; the PDF writer clips what
+//! falls outside the box.
+
+use crate::style::{Dimension, ObjectFit, ObjectPosition};
+
+/// Where an image is drawn, relative to the top-left corner of its box.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ImagePlacement {
+    pub x: f64,
+    pub y: f64,
+    pub width: f64,
+    pub height: f64,
+}
+
+impl ImagePlacement {
+    /// Whether any of the image falls outside a box `width` × `height`.
+    pub fn overflows(&self, width: f64, height: f64) -> bool {
+        const EPSILON: f64 = 0.001;
+        self.x < -EPSILON
+            || self.y < -EPSILON
+            || self.x + self.width > width + EPSILON
+            || self.y + self.height > height + EPSILON
+    }
+}
+
+/// Place an image `intrinsic_width` × `intrinsic_height` in a box `width` ×
+/// `height`.
+pub fn place(
+    fit: ObjectFit,
+    position: ObjectPosition,
+    intrinsic_width: f64,
+    intrinsic_height: f64,
+    width: f64,
+    height: f64,
+) -> ImagePlacement {
+    if intrinsic_width <= 0.0 || intrinsic_height <= 0.0 {
+        return stretched(width, height);
+    }
+    let contain = (width / intrinsic_width).min(height / intrinsic_height);
+    let scale = match fit {
+        ObjectFit::Fill => return stretched(width, height),
+        ObjectFit::Contain => contain,
+        ObjectFit::Cover => (width / intrinsic_width).max(height / intrinsic_height),
+        ObjectFit::None => 1.0,
+        ObjectFit::ScaleDown => contain.min(1.0),
+    };
+    position_in(
+        position,
+        intrinsic_width * scale,
+        intrinsic_height * scale,
+        width,
+        height,
+    )
+}
+
+/// Place an image drawn `image_width` × `image_height` at `position` in a
+/// box `width` × `height`.
+pub fn position_in(
+    position: ObjectPosition,
+    image_width: f64,
+    image_height: f64,
+    width: f64,
+    height: f64,
+) -> ImagePlacement {
+    ImagePlacement {
+        x: offset(position.x, width - image_width),
+        y: offset(position.y, height - image_height),
+        width: image_width,
+        height: image_height,
+    }
+}
+
+fn stretched(width: f64, height: f64) -> ImagePlacement {
+    ImagePlacement {
+        x: 0.0,
+        y: 0.0,
+        width,
+        height,
+    }
+}
+
+/// The offset of an image edge for a `position` along an axis where the box
+/// is `free` larger than the image (negative when the image overflows).
+fn offset(position: Dimension, free: f64) -> f64 {
+    match position {
+        Dimension::Pt(offset) => offset,
+        Dimension::Percent(percent) => free * percent / 100.0,
+        Dimension::Auto => free / 2.0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_contain_letterboxes_and_cover_crops() {
+        let center = ObjectPosition::default();
+        // A 2:1 image in a 100pt square
+        let contain = place(ObjectFit::Contain, center, 400.0, 200.0, 100.0, 100.0);
+        assert_eq!(
+            contain,
+            ImagePlacement {
+                x: 0.0,
+                y: 25.0,
+                width: 100.0,
+                height: 50.0
+            }
+        );
+        assert!(!contain.overflows(100.0, 100.0));
+
+        let cover = place(ObjectFit::Cover, center, 400.0, 200.0, 100.0, 100.0);
+        assert_eq!((cover.x, cover.width, cover.height), (-50.0, 200.0, 100.0));
+        assert!(cover.overflows(100.0, 100.0));
+
+        let fill = place(ObjectFit::Fill, center, 400.0, 200.0, 100.0, 100.0);
+        assert_eq!((fill.width, fill.height), (100.0, 100.0));
+    }
+
+    #[test]
+    fn test_position_and_scale_down() {
+        let top_left = ObjectPosition {
+            x: Dimension::Percent(0.0),
+            y: Dimension::Pt(10.0),
+        };
+        let cover = place(ObjectFit::Cover, top_left, 400.0, 200.0, 100.0, 100.0);
+        assert_eq!((cover.x, cover.y), (0.0, 10.0));
+
+        // Smaller than the box: drawn at its own size, centered
+        let small = place(
+            ObjectFit::ScaleDown,
+            ObjectPosition::default(),
+            40.0,
+            20.0,
+            100.0,
+            100.0,
+        );
+        assert_eq!((small.x, small.y, small.width), (30.0, 40.0, 40.0));
+        let large = place(
+            ObjectFit::ScaleDown,
+            ObjectPosition::default(),
+            400.0,
+            200.0,
+            100.0,
+            100.0,
+        );
+        assert_eq!(large.width, 100.0);
+    }
+}
@@ -0,0 +1,350 @@
+//! # Page Number Tokens
+//!
+//! Resolves `{{pageNumber}}`, `{{totalPages}}` and `{{sectionPages}}` once
+//! every page is known. The layout engine substitutes them into fixed
+//! headers and footers before laying those out, and reshapes any other text
+//! line that contains them, so the printed numbers are measured and aligned
+//! like the rest of the text.
+//!
+//! - A Page node with [`PageNumbering`] starts a new section: numbering
+//!   restarts at its `start` and uses its format until the next section
+//! - A format can also be given per token, e.g. `{{pageNumber:roman}}`
+//!   (`decimal`, `roman`, `ROMAN`, `alpha`, `ALPHA`)
+//!
+//! `{{pageRef:id}}` prints the page number of the node with that `id`. Since
+//! the printed number can itself move content, the layout engine resolves
+//! these by laying the document out again until every target page settles.
+
+use std::collections::HashMap;
+
+use crate::model::{PageNumberFormat, PageNumbering};
+
+/// Printed in place of a `{{pageRef:id}}` whose target isn't in the document.
+pub const MISSING_PAGE_REF: &str = "??";
+
+/// The page-number values for one laid-out page.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PageNumbers {
+    /// This page's number within its section.
+    pub number: u32,
+    /// Format used for `{{pageNumber}}` in this page's section.
+    pub format: PageNumberFormat,
+    /// Pages in this page's section.
+    pub section_pages: u32,
+    /// Pages in the whole document.
+    pub total_pages: u32,
+}
+
+impl PageNumbers {
+    /// This page's number, printed in its section's format.
+    pub fn label(&self) -> String {
+        format_page_number(self.number, self.format)
+    }
+
+    /// Replace every page-number token in `text`. Unknown tokens are kept.
+    pub fn substitute(&self, text: &str) -> String {
+        if !text.contains("{{") {
+            return text.to_string();
+        }
+        let mut out = String::with_capacity(text.len());
+        let mut rest = text;
+        while let Some(start) = rest.find("{{") {
+            out.push_str(&rest[..start]);
+            let token = &rest[start..];
+            match self.resolve_token(token) {
+                Some((value, len)) => {
+                    out.push_str(&value);
+                    rest = &token[len..];
+                }
+                None => {
+                    out.push_str("{{");
+                    rest = &token[2..];
+                }
+            }
+        }
+        out.push_str(rest);
+        out
+    }
+
+    /// Resolve a token at the start of `token`, returning the replacement
+    /// and the token's length.
+    fn resolve_token(&self, token: &str) -> Option<(String, usize)> {
+        let end = token.find("}}")?;
+        let inner = &token[2..end];
+        let (name, format) = match inner.split_once(':') {
+            Some((name, format)) => (name.trim(), Some(parse_format(format.trim())?)),
+            None => (inner.trim(), None),
+        };
+        let (value, default_format) = match name {
+            "pageNumber" => (self.number, self.format),
+            "sectionPages" => (self.section_pages, PageNumberFormat::Decimal),
+            "totalPages" => (self.total_pages, PageNumberFormat::Decimal),
+            _ => return None,
+        };
+        let formatted = format_page_number(value, format.unwrap_or(default_format));
+        Some((formatted, end + 2))
+    }
+}
+
+/// Whether `text` contains a page-number token.
+pub fn has_page_tokens(text: &str) -> bool {
+    ["{{pageNumber", "{{totalPages", "{{sectionPages"]
+        .iter()
+        .any(|t| text.contains(t))
+}
+
+/// Whether `text` contains a `{{pageRef:id}}` token.
+pub fn has_page_refs(text: &str) -> bool {
+    text.contains("{{pageRef:")
+}
+
+/// The ids of the `{{pageRef:id}}` tokens in `text`, in order.
+pub fn page_ref_ids(text: &str) -> Vec<&str> {
+    let mut ids = Vec::new();
+    let mut rest = text;
+    while let Some(start) = rest.find("{{pageRef:") {
+        let token = &rest[start + "{{pageRef:".len()..];
+        let Some(end) = token.find("}}") else {
+            break;
+        };
+        ids.push(token[..end].trim());
+        rest = &token[end + 2..];
+    }
+    ids
+}
+
+/// Replace every `{{pageRef:id}}` in `text` with the target's page number
+/// from `labels` (keyed by id), or [`MISSING_PAGE_REF`] when the id is
+/// unknown.
+pub fn substitute_page_refs(text: &str, labels: &HashMap<String, String>) -> String {
+    if !has_page_refs(text) {
+        return text.to_string();
+    }
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find("{{pageRef:") {
+        out.push_str(&rest[..start]);
+        let token = &rest[start..];
+        match token.find("}}") {
+            Some(end) => {
+                let id = token["{{pageRef:".len()..end].trim();
+                out.push_str(labels.get(id).map_or(MISSING_PAGE_REF, String::as_str));
+                rest = &token[end + 2..];
+            }
+            None => {
+                out.push_str(token);
+                rest = "";
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+/// Number the pages of a document. `section_starts[i]` is the numbering
+/// given by the Page node that begins on page `i`, if any.
+pub fn number_pages(section_starts: &[Option<PageNumbering>]) -> Vec<PageNumbers> {
+    let total_pages = section_starts.len() as u32;
+    let mut numbers: Vec<PageNumbers> = Vec::with_capacity(section_starts.len());
+    let mut section_begin = 0;
+    let mut next = 1;
+    let mut format = PageNumberFormat::Decimal;
+
+    for (i, start) in section_starts.iter().enumerate() {
+        if let Some(numbering) = start {
+            close_section(&mut numbers[section_begin..]);
+            section_begin = i;
+            next = numbering.start;
+            format = numbering.format;
+        }
+        numbers.push(PageNumbers {
+            number: next,
+            format,
+            section_pages: 0,
+            total_pages,
+        });
+        next += 1;
+    }
+    close_section(&mut numbers[section_begin..]);
+    numbers
+}
+
+fn close_section(section: &mut [PageNumbers]) {
+    let count = section.len() as u32;
+    for page in section {
+        page.section_pages = count;
+    }
+}
+
+fn parse_format(name: &str) -> Option<PageNumberFormat> {
+    match name {
+        "decimal" => Some(PageNumberFormat::Decimal),
+        "roman" => Some(PageNumberFormat::LowerRoman),
+        "ROMAN" => Some(PageNumberFormat::UpperRoman),
+        "alpha" => Some(PageNumberFormat::LowerAlpha),
+        "ALPHA" => Some(PageNumberFormat::UpperAlpha),
+        _ => None,
+    }
+}
+
+/// Print a page number. Roman numerals fall back to decimal for 0 and for
+/// numbers of 4000 and above; letters run a..z, aa..az, and so on.
+pub fn format_page_number(n: u32, format: PageNumberFormat) -> String {
+    match format {
+        PageNumberFormat::Decimal => n.to_string(),
+        PageNumberFormat::LowerRoman => to_roman(n).to_lowercase(),
+        PageNumberFormat::UpperRoman => to_roman(n),
+        PageNumberFormat::LowerAlpha => to_alpha(n).to_lowercase(),
+        PageNumberFormat::UpperAlpha => to_alpha(n),
+    }
+}
+
+fn to_roman(n: u32) -> String {
+    if n == 0 || n >= 4000 {
+        return n.to_string();
+    }
+    const NUMERALS: [(u32, &str); 13] = [
+        (1000, "M"),
+        (900, "CM"),
+        (500, "D"),
+        (400, "CD"),
+        (100, "C"),
+        (90, "XC"),
+        (50, "L"),
+        (40, "XL"),
+        (10, "X"),
+        (9, "IX"),
+        (5, "V"),
+        (4, "IV"),
+        (1, "I"),
+    ];
+    let mut rest = n;
+    let mut out = String::new();
+    for (value, numeral) in NUMERALS {
+        while rest >= value {
+            out.push_str(numeral);
+            rest -= value;
+        }
+    }
+    out
+}
+
+fn to_alpha(n: u32) -> String {
+    if n == 0 {
+        return n.to_string();
+    }
+    let mut rest = n;
+    let mut letters = Vec::new();
+    while rest > 0 {
+        rest -= 1;
+        letters.push((b'A' + (rest % 26) as u8) as char);
+        rest /= 26;
+    }
+    letters.iter().rev().collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn numbers(number: u32, format: PageNumberFormat) -> PageNumbers {
+        PageNumbers {
+            number,
+            format,
+            section_pages: 4,
+            total_pages: 9,
+        }
+    }
+
+    #[test]
+    fn test_format_roman() {
+        assert_eq!(format_page_number(4, PageNumberFormat::LowerRoman), "iv");
+        assert_eq!(
+            format_page_number(1994, PageNumberFormat::UpperRoman),
+            "MCMXCIV"
+        );
+        assert_eq!(format_page_number(0, PageNumberFormat::UpperRoman), "0");
+    }
+
+    #[test]
+    fn test_format_alpha() {
+        assert_eq!(format_page_number(1, PageNumberFormat::LowerAlpha), "a");
+        assert_eq!(format_page_number(26, PageNumberFormat::UpperAlpha), "Z");
+        assert_eq!(format_page_number(28, PageNumberFormat::UpperAlpha), "AB");
+    }
+
+    #[test]
+    fn test_substitute_tokens() {
+        let page = numbers(3, PageNumberFormat::Decimal);
+        assert_eq!(
+            page.substitute("Page {{pageNumber}} of {{totalPages}}"),
+            "Page 3 of 9"
+        );
+        assert_eq!(
+            page.substitute("{{pageNumber:roman}}/{{sectionPages:ROMAN}}"),
+            "iii/IV"
+        );
+    }
+
+    #[test]
+    fn test_substitute_uses_section_format() {
+        let page = numbers(2, PageNumberFormat::LowerRoman);
+        assert_eq!(page.substitute("- {{pageNumber}} -"), "- ii -");
+        assert_eq!(page.substitute("{{pageNumber:decimal}}"), "2");
+    }
+
+    #[test]
+    fn test_substitute_keeps_unknown_tokens() {
+        let page = numbers(1, PageNumberFormat::Decimal);
+        assert_eq!(
+            page.substitute("{{pageSum:2}} {{pageNumber:bogus}} {{pageNumber"),
+            "{{pageSum:2}} {{pageNumber:bogus}} {{pageNumber"
+        );
+    }
+
+    #[test]
+    fn test_substitute_page_refs() {
+        let labels = HashMap::from([("terms".to_string(), "12".to_string())]);
+        assert_eq!(
+            substitute_page_refs("See {{pageRef:terms}} and {{pageRef: terms }}.", &labels),
+            "See 12 and 12."
+        );
+        assert_eq!(
+            substitute_page_refs("{{pageRef:nowhere}} {{pageNumber}}", &labels),
+            "?? {{pageNumber}}"
+        );
+        assert_eq!(
+            substitute_page_refs("open {{pageRef:terms", &labels),
+            "open {{pageRef:terms"
+        );
+        assert_eq!(
+            page_ref_ids("{{pageRef:a}} and {{pageRef: b }} {{pageRef:c"),
+            ["a", "b"]
+        );
+    }
+
+    #[test]
+    fn test_number_pages_sections() {
+        let roman = PageNumbering {
+            start: 1,
+            format: PageNumberFormat::LowerRoman,
+        };
+        let body = PageNumbering {
+            start: 1,
+            format: PageNumberFormat::Decimal,
+        };
+        let pages = number_pages(&[Some(roman), None, Some(body), None, None]);
+        let labels: Vec<String> = pages.iter().map(|p| p.label()).collect();
+        assert_eq!(labels, vec!["i", "ii", "1", "2", "3"]);
+        assert_eq!(pages[0].section_pages, 2);
+        assert_eq!(pages[4].section_pages, 3);
+        assert!(pages.iter().all(|p| p.total_pages == 5));
+    }
+
+    #[test]
+    fn test_number_pages_without_sections() {
+        let pages = number_pages(&[None, None]);
+        assert_eq!(pages[1].number, 2);
+        assert_eq!(pages[1].section_pages, 2);
+    }
+}
@@ -0,0 +1,795 @@
+//! # Table Cell Placement
+//!
+//! Maps `TableCell` nodes onto the table's column grid, honoring `col_span`
+//! and `row_span` the way HTML tables do:
+//! - A cell occupies `col_span` consecutive columns
+//! - A cell with `row_span > 1` also occupies its columns in the following
+//!   rows, and later cells in those rows skip over the occupied slots
+//! - Rows linked together by row spans form a *row group*, which the layout
+//!   engine treats as one unit when deciding page breaks
+//!
+//! Auto-width columns are sized from their cells' min-content and
+//! max-content widths, following HTML's automatic table layout.
+//!
+//! It also keeps the per-column totals behind the `{{pageSum:N}}` and
+//! `{{runningSum:N}}` tokens that footer rows can show on each fragment.
+
+use std::ops::Range;
+
+/// Resolved position of a cell in the table grid.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CellPlacement {
+    /// Index of the cell within its row's children.
+    pub cell_index: usize,
+    /// First column (0-based).
+    pub col_start: usize,
+    /// One past the last column (exclusive).
+    pub col_end: usize,
+    /// Number of rows covered, including the row the cell starts in.
+    /// Clamped so it never extends past the last row.
+    pub row_span: usize,
+}
+
+/// Place the cells of consecutive rows onto the column grid.
+///
+/// `cell_spans[r]` lists the `(col_span, row_span)` of each cell in row `r`.
+/// Spans of zero are treated as one.
+pub fn place_cells(cell_spans: &[Vec<(u32, u32)>]) -> Vec<Vec<CellPlacement>> {
+    let num_rows = cell_spans.len();
+    // Per column: how many more rows (including the current one) are occupied
+    let mut occupied: Vec<usize> = Vec::new();
+    let mut result = Vec::with_capacity(num_rows);
+
+    for (row_idx, spans) in cell_spans.iter().enumerate() {
+        let rows_left = num_rows - row_idx;
+        let mut row_placements = Vec::with_capacity(spans.len());
+        let mut col = 0;
+
+        for (cell_index, &(col_span, row_span)) in spans.iter().enumerate() {
+            while occupied.get(col).copied().unwrap_or(0) > 0 {
+                col += 1;
+            }
+
+            let col_span = col_span.max(1) as usize;
+            let row_span = (row_span.max(1) as usize).min(rows_left);
+            let col_end = col + col_span;
+
+            if occupied.len() < col_end {
+                occupied.resize(col_end, 0);
+            }
+            for slot in &mut occupied[col..col_end] {
+                *slot = row_span;
+            }
+
+            row_placements.push(CellPlacement {
+                cell_index,
+                col_start: col,
+                col_end,
+                row_span,
+            });
+            col = col_end;
+        }
+
+        for slot in occupied.iter_mut() {
+            *slot = slot.saturating_sub(1);
+        }
+
+        result.push(row_placements);
+    }
+
+    result
+}
+
+/// Number of columns needed to hold every placed cell.
+pub fn column_count(placements: &[Vec<CellPlacement>]) -> usize {
+    placements
+        .iter()
+        .flat_map(|row| row.iter().map(|p| p.col_end))
+        .max()
+        .unwrap_or(0)
+}
+
+/// Split rows into groups that must be laid out together. A group ends at
+/// the first row boundary that no row-spanning cell crosses.
+pub fn row_groups(placements: &[Vec<CellPlacement>]) -> Vec<Range<usize>> {
+    let mut groups = Vec::new();
+    let mut start = 0;
+    let mut end = 0;
+
+    for (row_idx, row) in placements.iter().enumerate() {
+        let row_end = row
+            .iter()
+            .map(|p| row_idx + p.row_span)
+            .max()
+            .unwrap_or(row_idx + 1);
+        end = end.max(row_end).max(row_idx + 1);
+        if end == row_idx + 1 {
+            groups.push(start..end);
+            start = end;
+        }
+    }
+
+    if start < placements.len() {
+        groups.push(start..placements.len());
+    }
+
+    groups
+}
+
+/// For each column, whether row `row` is covered by a cell spanning down
+/// from an earlier row.
+pub fn covered_columns(
+    placements: &[Vec<CellPlacement>],
+    row: usize,
+    num_columns: usize,
+) -> Vec<bool> {
+    let mut covered = vec![false; num_columns];
+    for (row_idx, row_placements) in placements.iter().enumerate().take(row) {
+        for p in row_placements {
+            if row_idx + p.row_span > row {
+                for c in covered.iter_mut().take(p.col_end).skip(p.col_start) {
+                    *c = true;
+                }
+            }
+        }
+    }
+    covered
+}
+
+/// Grow row heights so every row-spanning cell fits.
+///
+/// `spans` lists `(first_row, row_span, needed_height)` for each cell that
+/// covers more than one row. When the spanned rows are too short, the
+/// deficit goes to the last spanned row, matching how browsers extend a
+/// row group downward.
+pub fn distribute_row_spans(row_heights: &mut [f64], spans: &[(usize, usize, f64)]) {
+    let mut ordered: Vec<&(usize, usize, f64)> = spans.iter().collect();
+    ordered.sort_by_key(|(_, span, _)| *span);
+
+    for &&(first, span, needed) in &ordered {
+        let end = (first + span).min(row_heights.len());
+        if first >= end {
+            continue;
+        }
+        let current: f64 = row_heights[first..end].iter().sum();
+        if needed > current {
+            row_heights[end - 1] += needed - current;
+        }
+    }
+}
+
+/// Width of the columns `col_start..col_end`.
+pub fn span_width(col_start: usize, col_end: usize, col_widths: &[f64]) -> f64 {
+    (col_start..col_end)
+        .map(|c| col_widths.get(c).copied().unwrap_or(0.0))
+        .sum()
+}
+
+/// Min-content and max-content width of one cell, as measured by the
+/// layout engine.
+#[derive(Debug, Clone)]
+pub struct CellContentWidth {
+    /// First column (0-based).
+    pub col_start: usize,
+    /// One past the last column (exclusive).
+    pub col_end: usize,
+    /// Narrowest width the cell can take without overflowing a word.
+    pub min: f64,
+    /// Width the cell takes with no line wrapping.
+    pub max: f64,
+}
+
+/// Per-column `(min, max)` content widths.
+///
+/// Single-column cells set the widths directly. A cell spanning several
+/// columns only widens them when it needs more than they already provide,
+/// and the extra is shared in proportion to each column's max width.
+pub fn column_content_widths(
+    num_columns: usize,
+    cells: &[CellContentWidth],
+) -> (Vec<f64>, Vec<f64>) {
+    let mut min = vec![0.0f64; num_columns];
+    let mut max = vec![0.0f64; num_columns];
+
+    let (single, mut spanning): (Vec<&CellContentWidth>, Vec<&CellContentWidth>) = cells
+        .iter()
+        .filter(|c| c.col_end <= num_columns && c.col_start < c.col_end)
+        .partition(|c| c.col_end - c.col_start == 1);
+
+    for cell in single {
+        min[cell.col_start] = min[cell.col_start].max(cell.min);
+        max[cell.col_start] = max[cell.col_start].max(cell.max.max(cell.min));
+    }
+
+    spanning.sort_by_key(|c| c.col_end - c.col_start);
+    for cell in spanning {
+        let range = cell.col_start..cell.col_end;
+        let weights: Vec<f64> = max[range.clone()].to_vec();
+        grow_span(&mut min[range.clone()], &weights, cell.min);
+        grow_span(&mut max[range], &weights, cell.max.max(cell.min));
+    }
+
+    for (lo, hi) in min.iter().zip(max.iter_mut()) {
+        *hi = hi.max(*lo);
+    }
+
+    (min, max)
+}
+
+/// Widen `widths` so they add up to at least `needed`, sharing the extra
+/// by `weights` (evenly when all weights are zero).
+fn grow_span(widths: &mut [f64], weights: &[f64], needed: f64) {
+    let current: f64 = widths.iter().sum();
+    if needed <= current || widths.is_empty() {
+        return;
+    }
+    let extra = needed - current;
+    let total_weight: f64 = weights.iter().sum();
+    for (w, weight) in widths.iter_mut().zip(weights) {
+        *w += if total_weight > 0.0 {
+            extra * weight / total_weight
+        } else {
+            extra / weights.len() as f64
+        };
+    }
+}
+
+/// Share `available` width between auto columns the way HTML's automatic
+/// table layout does:
+/// - Room for every column's max width: each gets its max, and the leftover
+///   goes to columns in proportion to their max width, so wide text columns
+///   absorb most of it
+/// - Room for the min widths but not the max: each column moves from its
+///   min toward its max by the same fraction
+/// - Not even room for the min widths: the min widths are scaled down
+pub fn distribute_auto_widths(min: &[f64], max: &[f64], available: f64) -> Vec<f64> {
+    let count = min.len();
+    if count == 0 {
+        return Vec::new();
+    }
+    let available = available.max(0.0);
+    let max: Vec<f64> = max.iter().zip(min).map(|(hi, lo)| hi.max(*lo)).collect();
+    let sum_min: f64 = min.iter().sum();
+    let sum_max: f64 = max.iter().sum();
+
+    if available >= sum_max {
+        let extra = available - sum_max;
+        if sum_max > 0.0 {
+            max.iter().map(|w| w + extra * w / sum_max).collect()
+        } else {
+            vec![available / count as f64; count]
+        }
+    } else if available >= sum_min {
+        let t = (available - sum_min) / (sum_max - sum_min);
+        min.iter()
+            .zip(&max)
+            .map(|(lo, hi)| lo + (hi - lo) * t)
+            .collect()
+    } else if sum_min > 0.0 {
+        min.iter().map(|w| w * available / sum_min).collect()
+    } else {
+        vec![available / count as f64; count]
+    }
+}
+
+/// Per-column totals of numeric body cells, for the `{{pageSum:N}}` and
+/// `{{runningSum:N}}` tokens in table footer rows. `N` is the zero-based
+/// column index; a spanning cell counts toward its first column.
+///
+/// Each column reads its numbers with one decimal mark: the first cell
+/// that makes it clear (`1.234,56`, `12,5`) sets it, and cells that could
+/// be read either way (`1,234`) follow the column, or the table's language
+/// before any cell has settled it.
+#[derive(Debug, Clone, Default)]
+pub struct ColumnSums {
+    /// Totals for the current table fragment.
+    page: Vec<f64>,
+    /// Totals for every fragment so far, including the current one.
+    running: Vec<f64>,
+    /// Most fraction digits seen per column, so sums print like the inputs.
+    decimals: Vec<usize>,
+    /// Whether any input in the column used thousands separators.
+    grouped: Vec<bool>,
+    /// The decimal mark each column's numbers are written with, once known.
+    marks: Vec<Option<char>>,
+    /// Whether the table's language writes decimals with a comma.
+    decimal_comma: bool,
+}
+
+impl ColumnSums {
+    /// Sums for a table in `lang`, which decides how a number such as
+    /// `1,234` is read when its column doesn't.
+    pub fn new(lang: Option<&str>) -> Self {
+        Self {
+            decimal_comma: lang.is_some_and(uses_decimal_comma),
+            ..Self::default()
+        }
+    }
+
+    /// Add a cell's value to its column. Text that isn't a number is
+    /// ignored. Returns false when the cell is a number left out of the
+    /// sums because its decimal mark contradicts the column's.
+    pub fn add(&mut self, column: usize, text: &str) -> bool {
+        let Some((negative, body)) = amount_body(text) else {
+            return true;
+        };
+        if self.page.len() <= column {
+            self.page.resize(column + 1, 0.0);
+            self.running.resize(column + 1, 0.0);
+            self.decimals.resize(column + 1, 0);
+            self.grouped.resize(column + 1, false);
+            self.marks.resize(column + 1, None);
+        }
+        let mark = match (decimal_mark(body), self.marks[column]) {
+            (Some(mark), Some(column_mark)) if mark != column_mark => return false,
+            (Some(mark), _) | (None, Some(mark)) => mark,
+            (None, None) if self.decimal_comma => ',',
+            (None, None) => '.',
+        };
+        let Some(amount) = parse_amount(body, mark) else {
+            return true;
+        };
+        if body.contains([',', '.']) {
+            self.marks[column] = Some(mark);
+        }
+        let value = if negative {
+            -amount.value
+        } else {
+            amount.value
+        };
+        self.page[column] += value;
+        self.running[column] += value;
+        self.decimals[column] = self.decimals[column].max(amount.decimals);
+        self.grouped[column] |= amount.grouped;
+        true
+    }
+
+    /// Close the current fragment: page sums start again from zero.
+    pub fn end_page(&mut self) {
+        for sum in &mut self.page {
+            *sum = 0.0;
+        }
+    }
+
+    /// Replace every sum token in `text` with the current total.
+    pub fn substitute(&self, text: &str) -> String {
+        if !text.contains("{{") {
+            return text.to_string();
+        }
+        let mut out = String::with_capacity(text.len());
+        let mut rest = text;
+        while let Some(start) = rest.find("{{") {
+            out.push_str(&rest[..start]);
+            let token = &rest[start..];
+            match self.resolve_token(token) {
+                Some((value, len)) => {
+                    out.push_str(&value);
+                    rest = &token[len..];
+                }
+                None => {
+                    out.push_str("{{");
+                    rest = &token[2..];
+                }
+            }
+        }
+        out.push_str(rest);
+        out
+    }
+
+    /// Resolve a token at the start of `token`, returning the replacement
+    /// and the token's length.
+    fn resolve_token(&self, token: &str) -> Option<(String, usize)> {
+        let end = token.find("}}")?;
+        let (name, column) = token[2..end].split_once(':')?;
+        let column: usize = column.trim().parse().ok()?;
+        let sums = match name.trim() {
+            "pageSum" => &self.page,
+            "runningSum" => &self.running,
+            _ => return None,
+        };
+        let value = sums.get(column).copied().unwrap_or(0.0);
+        let decimals = self.decimals.get(column).copied().unwrap_or(0);
+        let grouped = self.grouped.get(column).copied().unwrap_or(false);
+        let mark = self
+            .marks
+            .get(column)
+            .copied()
+            .flatten()
+            .unwrap_or(if self.decimal_comma { ',' } else { '.' });
+        Some((format_amount(value, decimals, grouped, mark), end + 2))
+    }
+}
+
+/// Languages that write `1.234,56` rather than `1,234.56`, by primary
+/// subtag.
+const DECIMAL_COMMA_LANGUAGES: &[&str] = &[
+    "be", "bg", "ca", "cs", "da", "de", "el", "es", "et", "eu", "fi", "fr", "gl", "hr", "hu", "id",
+    "is", "it", "lt", "lv", "nb", "nl", "nn", "no", "pl", "pt", "ro", "ru", "sk", "sl", "sr", "sv",
+    "tr", "uk", "vi",
+];
+
+fn uses_decimal_comma(lang: &str) -> bool {
+    let primary = lang.split(['-', '_']).next().unwrap_or("");
+    DECIMAL_COMMA_LANGUAGES
+        .iter()
+        .any(|code| code.eq_ignore_ascii_case(primary))
+}
+
+/// A number read from cell text, without its sign.
+#[derive(Debug, Clone, PartialEq)]
+struct Amount {
+    value: f64,
+    decimals: usize,
+    grouped: bool,
+}
+
+/// Split cell text such as `1,234.50`, `$99`, `-3` or `(12.00)` into its
+/// sign and digits. Currency symbols and surrounding whitespace are
+/// ignored; anything else that isn't part of a number makes the cell
+/// non-numeric, so percentages in an amount column aren't added into its
+/// sums.
+fn amount_body(text: &str) -> Option<(bool, &str)> {
+    let trimmed = text
+        .trim()
+        .trim_matches(|c: char| c.is_whitespace() || matches!(c, '$' | '€' | '£' | '¥'));
+    let (negative, body) =
+        if let Some(inner) = trimmed.strip_prefix('(').and_then(|t| t.strip_suffix(')')) {
+            (true, inner)
+        } else if let Some(inner) = trimmed.strip_prefix('-') {
+            (true, inner)
+        } else {
+            (false, trimmed)
+        };
+    let body = body.trim_start_matches(['$', '€', '£', '¥']);
+
+    let numeric = body.chars().any(|c| c.is_ascii_digit())
+        && body
+            .chars()
+            .all(|c| c.is_ascii_digit() || c == ',' || c == '.');
+    numeric.then_some((negative, body))
+}
+
+/// The decimal mark a number's separators make certain: the later of a
+/// comma and a period, or a lone separator not followed by exactly three
+/// digits. None when there's no separator or it could be either, as in
+/// `1,234`.
+fn decimal_mark(body: &str) -> Option<char> {
+    let last = body.rfind([',', '.'])?;
+    let mark = body[last..].chars().next()?;
+    let other = if mark == ',' { '.' } else { ',' };
+    if body.contains(other) {
+        return Some(mark);
+    }
+    let lone = body.matches(mark).count() == 1;
+    (lone && body.len() - last - 1 != 3).then_some(mark)
+}
+
+/// Parse a number's digits with `mark` as its decimal mark and the other
+/// separator grouping thousands. None when the separators don't fit.
+fn parse_amount(body: &str, mark: char) -> Option<Amount> {
+    let group = if mark == ',' { '.' } else { ',' };
+    let (int_part, frac_part) = match body.split_once(mark) {
+        Some((int_part, frac_part)) => (int_part, Some(frac_part)),
+        None => (body, None),
+    };
+    if frac_part.is_some_and(|f| f.is_empty() || !f.chars().all(|c| c.is_ascii_digit())) {
+        return None;
+    }
+    let groups: Vec<&str> = int_part.split(group).collect();
+    let grouped = groups.len() > 1;
+    if grouped
+        && (groups[0].is_empty() || groups[0].len() > 3 || groups[1..].iter().any(|g| g.len() != 3))
+    {
+        return None;
+    }
+
+    let mut digits = groups.concat();
+    if let Some(frac) = frac_part {
+        digits.push('.');
+        digits.push_str(frac);
+    }
+    Some(Amount {
+        value: digits.parse().ok()?,
+        decimals: frac_part.map_or(0, str::len),
+        grouped,
+    })
+}
+
+/// Format a sum with the given number of fraction digits and decimal
+/// mark, optionally grouping thousands with the other separator.
+fn format_amount(value: f64, decimals: usize, grouped: bool, mark: char) -> String {
+    let formatted = format!("{:.*}", decimals, value.abs());
+    let (int_part, frac_part) = match formatted.split_once('.') {
+        Some((i, f)) => (i.to_string(), Some(f.to_string())),
+        None => (formatted, None),
+    };
+
+    let int_part = if grouped {
+        let group = if mark == ',' { '.' } else { ',' };
+        let mut out = String::new();
+        for (i, ch) in int_part.chars().enumerate() {
+            if i > 0 && (int_part.len() - i) % 3 == 0 {
+                out.push(group);
+            }
+            out.push(ch);
+        }
+        out
+    } else {
+        int_part
+    };
+
+    let sign = if value < 0.0 && value.abs() >= 0.5 * 10f64.powi(-(decimals as i32)) {
+        "-"
+    } else {
+        ""
+    };
+    match frac_part {
+        Some(f) => format!("{}{}{}{}", sign, int_part, mark, f),
+        None => format!("{}{}", sign, int_part),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_place_cells_no_spans() {
+        let placements = place_cells(&[vec![(1, 1), (1, 1)], vec![(1, 1), (1, 1)]]);
+        assert_eq!(placements[1][1].col_start, 1);
+        assert_eq!(column_count(&placements), 2);
+    }
+
+    #[test]
+    fn test_place_cells_col_span() {
+        let placements = place_cells(&[vec![(1, 1), (1, 1), (1, 1)], vec![(2, 1), (1, 1)]]);
+        assert_eq!(placements[1][0].col_start, 0);
+        assert_eq!(placements[1][0].col_end, 2);
+        assert_eq!(placements[1][1].col_start, 2);
+        assert_eq!(column_count(&placements), 3);
+    }
+
+    #[test]
+    fn test_place_cells_row_span_skips_occupied_columns() {
+        // Row 0: [A rs=2][B][C]
+        // Row 1:         [D][E]
+        let placements = place_cells(&[vec![(1, 2), (1, 1), (1, 1)], vec![(1, 1), (1, 1)]]);
+        assert_eq!(placements[0][0].row_span, 2);
+        assert_eq!(placements[1][0].col_start, 1);
+        assert_eq!(placements[1][1].col_start, 2);
+    }
+
+    #[test]
+    fn test_place_cells_row_span_clamped_to_last_row() {
+        let placements = place_cells(&[vec![(1, 5)], vec![]]);
+        assert_eq!(placements[0][0].row_span, 2);
+    }
+
+    #[test]
+    fn test_place_cells_zero_span_treated_as_one() {
+        let placements = place_cells(&[vec![(0, 0), (1, 1)]]);
+        assert_eq!(placements[0][0].col_end, 1);
+        assert_eq!(placements[0][0].row_span, 1);
+        assert_eq!(placements[0][1].col_start, 1);
+    }
+
+    #[test]
+    fn test_row_groups() {
+        // Rows 1-2 are linked by a row span; rows 0 and 3 stand alone.
+        let placements = place_cells(&[
+            vec![(1, 1), (1, 1)],
+            vec![(1, 2), (1, 1)],
+            vec![(1, 1)],
+            vec![(1, 1), (1, 1)],
+        ]);
+        assert_eq!(row_groups(&placements), vec![0..1, 1..3, 3..4]);
+    }
+
+    #[test]
+    fn test_row_groups_chained_spans() {
+        // Overlapping spans chain rows 0-3 into a single group.
+        let placements = place_cells(&[
+            vec![(1, 2), (1, 1)],
+            vec![(1, 3)],
+            vec![(1, 1)],
+            vec![(1, 1), (1, 1)],
+        ]);
+        assert_eq!(row_groups(&placements), vec![0..4]);
+    }
+
+    #[test]
+    fn test_covered_columns() {
+        let placements = place_cells(&[vec![(1, 2), (1, 1)], vec![(1, 1)]]);
+        assert_eq!(covered_columns(&placements, 0, 2), vec![false, false]);
+        assert_eq!(covered_columns(&placements, 1, 2), vec![true, false]);
+    }
+
+    #[test]
+    fn test_distribute_row_spans_grows_last_row() {
+        let mut heights = vec![20.0, 20.0, 20.0];
+        distribute_row_spans(&mut heights, &[(0, 2, 60.0)]);
+        assert!((heights[0] - 20.0).abs() < 0.001);
+        assert!((heights[1] - 40.0).abs() < 0.001);
+        assert!((heights[2] - 20.0).abs() < 0.001);
+    }
+
+    #[test]
+    fn test_distribute_row_spans_no_change_when_fits() {
+        let mut heights = vec![30.0, 30.0];
+        distribute_row_spans(&mut heights, &[(0, 2, 50.0)]);
+        assert!((heights[0] - 30.0).abs() < 0.001);
+        assert!((heights[1] - 30.0).abs() < 0.001);
+    }
+
+    #[test]
+    fn test_span_width() {
+        let widths = vec![100.0, 200.0, 150.0];
+        assert!((span_width(0, 2, &widths) - 300.0).abs() < 0.001);
+        assert!((span_width(2, 4, &widths) - 150.0).abs() < 0.001);
+    }
+
+    #[test]
+    fn test_parse_amount() {
+        let parse = |text: &str| {
+            let (negative, body) = amount_body(text)?;
+            let amount = parse_amount(body, decimal_mark(body).unwrap_or('.'))?;
+            Some(if negative {
+                -amount.value
+            } else {
+                amount.value
+            })
+        };
+        assert_eq!(parse("1,234.50"), Some(1234.5));
+        assert_eq!(parse("$99"), Some(99.0));
+        assert_eq!(parse("(12.00)"), Some(-12.0));
+        assert_eq!(parse("-$3"), Some(-3.0));
+        assert_eq!(parse("1.234,56"), Some(1234.56));
+        assert_eq!(parse("12,5"), Some(12.5));
+        assert_eq!(parse("1,2,3"), None);
+        assert_eq!(amount_body(" 7 "), Some((false, "7")));
+        assert!(amount_body("Item 3").is_none());
+        assert!(amount_body("Total").is_none());
+        assert!(amount_body("").is_none());
+        assert!(amount_body("8%").is_none());
+        assert!(amount_body("Tax 8%").is_none());
+    }
+
+    #[test]
+    fn test_decimal_mark() {
+        assert_eq!(decimal_mark("1,234.50"), Some('.'));
+        assert_eq!(decimal_mark("1.234,56"), Some(','));
+        assert_eq!(decimal_mark("12,5"), Some(','));
+        assert_eq!(decimal_mark("1.234.567"), None);
+        assert_eq!(decimal_mark("1,234"), None);
+        assert_eq!(decimal_mark("42"), None);
+    }
+
+    #[test]
+    fn test_format_amount() {
+        assert_eq!(format_amount(1234567.5, 2, true, '.'), "1,234,567.50");
+        assert_eq!(format_amount(1234567.5, 2, true, ','), "1.234.567,50");
+        assert_eq!(format_amount(1234.0, 0, false, '.'), "1234");
+        assert_eq!(format_amount(-42.25, 2, false, '.'), "-42.25");
+        assert_eq!(format_amount(-0.001, 2, false, '.'), "0.00");
+    }
+
+    #[test]
+    fn test_column_sums_page_and_running() {
+        let mut sums = ColumnSums::default();
+        sums.add(1, "10.50");
+        sums.add(1, "1,000");
+        sums.add(0, "Widget");
+        assert_eq!(
+            sums.substitute("Page: {{pageSum:1}} / Total: {{runningSum:1}}"),
+            "Page: 1,010.50 / Total: 1,010.50"
+        );
+
+        sums.end_page();
+        sums.add(1, "5");
+        assert_eq!(
+            sums.substitute("{{pageSum:1}} / {{runningSum:1}}"),
+            "5.00 / 1,015.50"
+        );
+    }
+
+    #[test]
+    fn test_column_sums_follow_decimal_comma() {
+        let mut sums = ColumnSums::default();
+        assert!(sums.add(0, "1.234,56"));
+        assert!(sums.add(0, "12,5"));
+        // Ambiguous on its own: read with the column's comma
+        assert!(sums.add(0, "1.000"));
+        assert_eq!(sums.substitute("{{pageSum:0}}"), "2.247,06");
+        // Contradicts the column, so it's left out
+        assert!(!sums.add(0, "3.5"));
+        assert_eq!(sums.substitute("{{pageSum:0}}"), "2.247,06");
+
+        // Before any cell settles it, the table's language decides
+        let mut german = ColumnSums::new(Some("de-DE"));
+        german.add(0, "1,250");
+        german.add(0, "2");
+        assert_eq!(german.substitute("{{pageSum:0}}"), "3,250");
+        let mut english = ColumnSums::new(Some("en"));
+        english.add(0, "1,250");
+        english.add(0, "2");
+        assert_eq!(english.substitute("{{pageSum:0}}"), "1,252");
+        assert!(!english.add(0, "2,5"));
+    }
+
+    #[test]
+    fn test_column_sums_leaves_other_tokens() {
+        let sums = ColumnSums::default();
+        assert_eq!(
+            sums.substitute("Page {{pageNumber}}: {{pageSum:3}}"),
+            "Page {{pageNumber}}: 0"
+        );
+    }
+
+    fn cell(col_start: usize, col_end: usize, min: f64, max: f64) -> CellContentWidth {
+        CellContentWidth {
+            col_start,
+            col_end,
+            min,
+            max,
+        }
+    }
+
+    #[test]
+    fn test_column_content_widths_single_cells() {
+        let (min, max) = column_content_widths(
+            2,
+            &[
+                cell(0, 1, 20.0, 30.0),
+                cell(0, 1, 25.0, 28.0),
+                cell(1, 2, 50.0, 400.0),
+            ],
+        );
+        assert_eq!(min, vec![25.0, 50.0]);
+        assert_eq!(max, vec![30.0, 400.0]);
+    }
+
+    #[test]
+    fn test_column_content_widths_spanning_cell_grows_columns() {
+        let (min, max) = column_content_widths(
+            2,
+            &[
+                cell(0, 1, 10.0, 20.0),
+                cell(1, 2, 10.0, 60.0),
+                cell(0, 2, 20.0, 160.0),
+            ],
+        );
+        // min already covered (20 >= 20); max needs 80 more, shared 1:3
+        assert_eq!(min, vec![10.0, 10.0]);
+        assert!((max[0] - 40.0).abs() < 0.001);
+        assert!((max[1] - 120.0).abs() < 0.001);
+    }
+
+    #[test]
+    fn test_distribute_auto_widths_extra_space_by_max() {
+        let widths = distribute_auto_widths(&[20.0, 50.0], &[30.0, 270.0], 600.0);
+        assert!((widths[0] - 60.0).abs() < 0.001);
+        assert!((widths[1] - 540.0).abs() < 0.001);
+    }
+
+    #[test]
+    fn test_distribute_auto_widths_between_min_and_max() {
+        let widths = distribute_auto_widths(&[20.0, 50.0], &[30.0, 250.0], 160.0);
+        // Room for min (70) but not max (280): both move 90/210 of the way
+        assert!((widths[0] - (20.0 + 10.0 * 90.0 / 210.0)).abs() < 0.001);
+        assert!((widths.iter().sum::<f64>() - 160.0).abs() < 0.001);
+        assert!(widths[0] < 30.0);
+    }
+
+    #[test]
+    fn test_distribute_auto_widths_below_min() {
+        let widths = distribute_auto_widths(&[40.0, 60.0], &[100.0, 100.0], 50.0);
+        assert!((widths[0] - 20.0).abs() < 0.001);
+        assert!((widths[1] - 30.0).abs() < 0.001);
+    }
+
+    #[test]
+    fn test_distribute_auto_widths_empty_columns() {
+        let widths = distribute_auto_widths(&[0.0, 0.0], &[0.0, 0.0], 100.0);
+        assert_eq!(widths, vec![50.0, 50.0]);
+    }
+}
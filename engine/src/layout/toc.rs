@@ -0,0 +1,306 @@
+//! # Table of Contents
+//!
+//! Fills `TableOfContents` nodes with one row per bookmark. A row is a flex
+//! row of the bookmark's title, a leader filling the space in between, and
+//! the page number, linked to the bookmarked node through an internal `#id`
+//! href. Bookmarked nodes without an `id` are given one, so rows with the
+//! same title still link to their own bookmark. Rows for nested bookmarks
+//! are indented by their outline level.
+//!
+//! The page numbers are only known after layout, and the rows themselves
+//! push later content onto later pages, so the layout engine fills the
+//! tables in and lays the document out again until the numbers settle.
+
+use super::page_number::{PageNumbers, MISSING_PAGE_REF};
+use super::{LayoutElement, LayoutPage};
+use crate::model::{Edges, Node, NodeKind};
+use crate::style::{AlignItems, Dimension, FlexDirection, Style, TextAlign, TextOverflow};
+
+/// Prefix of the ids given to bookmarked nodes that don't have one.
+const ANCHOR_PREFIX: &str = "forme-toc-";
+
+/// Space between a row's title, leader and page number.
+const ROW_GAP: f64 = 4.0;
+
+/// Indent per outline level of a row's bookmark.
+const LEVEL_INDENT: f64 = 12.0;
+
+/// One row of a table of contents.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TocEntry {
+    /// The bookmark's title.
+    pub title: String,
+    /// The `id` of the bookmarked node, which the row links to.
+    pub target: String,
+    /// The printed number of the page the bookmark is on.
+    pub label: String,
+    /// The bookmark's outline level.
+    pub level: u32,
+}
+
+/// Whether `node` is or contains a table of contents.
+pub fn has_table_of_contents(node: &Node) -> bool {
+    matches!(node.kind, NodeKind::TableOfContents { .. })
+        || node.children.iter().any(has_table_of_contents)
+}
+
+/// Entries for every bookmark on the pages, in page order.
+pub fn collect_entries(pages: &[LayoutPage], numbers: &[PageNumbers]) -> Vec<TocEntry> {
+    fn collect(elements: &[LayoutElement], label: &str, entries: &mut Vec<TocEntry>) {
+        for el in elements {
+            if let Some(title) = &el.bookmark {
+                entries.push(TocEntry {
+                    title: title.clone(),
+                    target: el.node_id.clone().unwrap_or_else(|| title.clone()),
+                    label: label.to_string(),
+                    level: el.bookmark_level,
+                });
+            }
+            collect(&el.children, label, entries);
+        }
+    }
+    let mut entries = Vec::new();
+    for (page, numbers) in pages.iter().zip(numbers) {
+        collect(&page.elements, &numbers.label(), &mut entries);
+    }
+    entries
+}
+
+/// Entries for every bookmark in the node tree, before any layout. Their
+/// pages aren't known yet, so this only sizes the first layout pass.
+pub fn estimate_entries(nodes: &[Node]) -> Vec<TocEntry> {
+    fn collect(node: &Node, level: u32, entries: &mut Vec<TocEntry>) {
+        let mut child_level = level;
+        if let Some(title) = &node.bookmark {
+            entries.push(TocEntry {
+                title: title.clone(),
+                target: node.id.clone().unwrap_or_else(|| title.clone()),
+                label: MISSING_PAGE_REF.to_string(),
+                level,
+            });
+            child_level += 1;
+        }
+        for child in &node.children {
+            collect(child, child_level, entries);
+        }
+    }
+    let mut entries = Vec::new();
+    for node in nodes {
+        collect(node, 0, &mut entries);
+    }
+    entries
+}
+
+/// Copy `node`, giving every bookmarked node in it without an `id` a
+/// generated one. `count` numbers them across the document.
+pub fn anchor_bookmarks(node: &Node, count: &mut usize) -> Node {
+    let id = match (&node.id, &node.bookmark) {
+        (None, Some(_)) => {
+            *count += 1;
+            Some(format!("{}{}", ANCHOR_PREFIX, count))
+        }
+        _ => node.id.clone(),
+    };
+    Node {
+        kind: node.kind.clone(),
+        style: node.style.clone(),
+        children: node
+            .children
+            .iter()
+            .map(|child| anchor_bookmarks(child, count))
+            .collect(),
+        id,
+        source_location: node.source_location.clone(),
+        bookmark: node.bookmark.clone(),
+        href: node.href.clone(),
+        alt: node.alt.clone(),
+    }
+}
+
+/// Copy a table of contents `width` wide, giving each row's leader as many
+/// copies of `leader` as fit between its title and page number. `measure`
+/// is the width of a text node set on one line.
+pub fn fit_leaders(node: &Node, leader: &str, width: f64, measure: impl Fn(&Node) -> f64) -> Node {
+    let mut fitted = node.clone();
+    for row in &mut fitted.children {
+        let indent = row.style.padding.map_or(0.0, |padding| padding.left);
+        let [title, filler, label] = &mut row.children[..] else {
+            continue;
+        };
+        if !is_leader(filler) {
+            continue;
+        }
+        let room = width - indent - measure(title) - measure(label) - 2.0 * ROW_GAP;
+        let copy = measure(filler);
+        let copies = if copy > 0.0 && room > 0.0 {
+            (room / copy).floor() as usize
+        } else {
+            0
+        };
+        if let NodeKind::Text { content, .. } = &mut filler.kind {
+            *content = leader.repeat(copies);
+        }
+    }
+    fitted
+}
+
+/// Whether `node` is a row's leader, holding one copy of the leader until
+/// `fit_leaders` sizes it.
+fn is_leader(node: &Node) -> bool {
+    matches!(node.kind, NodeKind::Text { .. })
+        && node.style.flex_grow == Some(1.0)
+        && node.style.text_overflow == Some(TextOverflow::Clip)
+}
+
+/// Copy `node`, filling every table of contents in it with `entries`. The
+/// rows follow any children the table already has, such as a heading.
+pub fn fill(node: &Node, entries: &[TocEntry]) -> Node {
+    let mut children: Vec<Node> = node
+        .children
+        .iter()
+        .map(|child| fill(child, entries))
+        .collect();
+    if let NodeKind::TableOfContents { leader } = &node.kind {
+        children.extend(entries.iter().map(|entry| entry_row(entry, leader)));
+    }
+    Node {
+        kind: node.kind.clone(),
+        style: node.style.clone(),
+        children,
+        id: node.id.clone(),
+        source_location: node.source_location.clone(),
+        bookmark: node.bookmark.clone(),
+        href: node.href.clone(),
+        alt: node.alt.clone(),
+    }
+}
+
+fn entry_row(entry: &TocEntry, leader: &str) -> Node {
+    let title = Node::text(&entry.title, Style::default());
+    // The leader takes whatever width the title and number leave, clipped
+    // to one line and right-aligned so it ends flush with the number. The
+    // layout fits its copies to that width
+    let leader = Node::text(
+        leader,
+        Style {
+            flex_grow: Some(1.0),
+            flex_basis: Some(Dimension::Pt(0.0)),
+            text_align: Some(TextAlign::Right),
+            text_overflow: Some(TextOverflow::Clip),
+            ..Default::default()
+        },
+    );
+    let label = Node::text(
+        &entry.label,
+        Style {
+            flex_shrink: Some(0.0),
+            ..Default::default()
+        },
+    );
+    let mut row = Node::view(
+        Style {
+            flex_direction: Some(FlexDirection::Row),
+            align_items: Some(AlignItems::FlexEnd),
+            gap: Some(ROW_GAP),
+            padding: Some(Edges {
+                left: entry.level as f64 * LEVEL_INDENT,
+                ..Default::default()
+            }),
+            ..Default::default()
+        },
+        vec![title, leader, label],
+    );
+    row.href = Some(format!("#{}", entry.target));
+    row
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bookmarked(title: &str, children: Vec<Node>) -> Node {
+        let mut node = Node::view(Style::default(), children);
+        node.bookmark = Some(title.to_string());
+        node
+    }
+
+    #[test]
+    fn test_estimate_entries_in_document_order() {
+        let nodes = vec![
+            bookmarked("One", vec![bookmarked("One.A", vec![])]),
+            bookmarked("Two", vec![]),
+        ];
+        let entries: Vec<(String, u32)> = estimate_entries(&nodes)
+            .into_iter()
+            .map(|e| (e.title, e.level))
+            .collect();
+        assert_eq!(
+            entries,
+            vec![
+                ("One".to_string(), 0),
+                ("One.A".to_string(), 1),
+                ("Two".to_string(), 0)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_fill_builds_linked_rows() {
+        let heading = Node::text("Contents", Style::default());
+        let toc = Node {
+            kind: NodeKind::TableOfContents {
+                leader: "-".to_string(),
+            },
+            ..Node::view(Style::default(), vec![heading])
+        };
+        let doc = Node::view(Style::default(), vec![toc]);
+        assert!(has_table_of_contents(&doc));
+
+        let entries = vec![TocEntry {
+            title: "Intro".to_string(),
+            target: "intro".to_string(),
+            label: "3".to_string(),
+            level: 1,
+        }];
+        let filled = fill(&doc, &entries);
+        let rows = &filled.children[0].children[1..];
+        assert_eq!(rows.len(), 1);
+        assert_eq!(rows[0].href.as_deref(), Some("#intro"));
+        assert_eq!(rows[0].style.padding.map(|p| p.left), Some(LEVEL_INDENT));
+        let texts: Vec<&str> = rows[0]
+            .children
+            .iter()
+            .map(|child| match &child.kind {
+                NodeKind::Text { content, .. } => &content[..content.len().min(3)],
+                _ => "",
+            })
+            .collect();
+        assert_eq!(texts, vec!["Int", "-", "3"]);
+
+        // Ten points for the title and number, one per leader copy
+        let fitted = fit_leaders(&filled.children[0], "-", 60.0, |node| match &node.kind {
+            NodeKind::Text { content, .. } if content == "-" => 1.0,
+            _ => 10.0,
+        });
+        match &fitted.children[1].children[1].kind {
+            NodeKind::Text { content, .. } => {
+                assert_eq!(content.len(), 60 - LEVEL_INDENT as usize - 20 - 8)
+            }
+            _ => panic!("leader should be text"),
+        }
+    }
+
+    #[test]
+    fn test_anchor_bookmarks_keeps_ids() {
+        let mut named = bookmarked("Two", vec![]);
+        named.id = Some("two".to_string());
+        let doc = Node::view(
+            Style::default(),
+            vec![bookmarked("One", vec![bookmarked("One", vec![])]), named],
+        );
+        let anchored = anchor_bookmarks(&doc, &mut 0);
+        let entries = estimate_entries(&anchored.children);
+        let targets: Vec<&str> = entries.iter().map(|e| e.target.as_str()).collect();
+        assert_eq!(targets, vec!["forme-toc-1", "forme-toc-2", "two"]);
+    }
+}
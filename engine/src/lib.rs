@@ -26,6 +26,7 @@
 //!   [pdf]      — Serialize to PDF bytes
 //! ```
 
+pub mod diagnostics;
 pub mod error;
 pub mod font;
 pub mod image_loader;
@@ -33,6 +34,7 @@ pub mod layout;
 pub mod model;
 pub mod pdf;
 pub mod qrcode;
+pub mod resource;
 pub mod style;
 pub mod svg;
 pub mod template;
@@ -41,45 +43,91 @@ pub mod text;
 #[cfg(feature = "wasm")]
 pub mod wasm;
 
+pub use diagnostics::Diagnostic;
 pub use error::FormeError;
 
-use font::FontContext;
-use layout::{LayoutEngine, LayoutInfo};
+use font::{FontContext, FontRegistry};
+use layout::{LayoutEngine, LayoutInfo, LayoutPage};
 use model::{Document, FontEntry};
 use pdf::PdfWriter;
+use resource::{ResourceResolver, CURRENT_DIR_RESOLVER};
+
+/// Options for `render_with_diagnostics()` and `render_with_layout()`.
+pub struct RenderOptions<'r> {
+    /// Fail with `FormeError::Diagnostics` instead of working around problems.
+    pub strict: bool,
+    /// Reads the images and fonts the document refers to by path, URL or
+    /// name. Inline data URIs and base64 never reach it.
+    pub resolver: &'r dyn ResourceResolver,
+    /// Fonts available to every document, such as a font directory scanned
+    /// with `FontRegistry::scan_directory`. The document's own fonts are
+    /// registered on top of a copy of it.
+    pub fonts: Option<&'r FontRegistry>,
+}
+
+impl Default for RenderOptions<'static> {
+    fn default() -> Self {
+        Self {
+            strict: false,
+            resolver: &CURRENT_DIR_RESOLVER,
+            fonts: None,
+        }
+    }
+}
 
 /// Render a document to PDF bytes.
 ///
 /// This is the primary entry point. Takes a document tree and returns
 /// the raw bytes of a valid PDF file.
 pub fn render(document: &Document) -> Result<Vec<u8>, FormeError> {
-    let mut font_context = FontContext::new();
-    register_document_fonts(&mut font_context, &document.fonts);
-    let engine = LayoutEngine::new();
-    let pages = engine.layout(document, &font_context);
-    let writer = PdfWriter::new();
-    let tagged = document.tagged || matches!(document.pdfa, Some(model::PdfAConformance::A2a));
-    writer.write(
-        &pages,
-        &document.metadata,
-        &font_context,
-        tagged,
-        document.pdfa.as_ref(),
-        document.embedded_data.as_deref(),
-    )
+    render_with_diagnostics(document, &RenderOptions::default()).map(|(pdf, _)| pdf)
+}
+
+/// Render a document to PDF bytes along with diagnostics.
+///
+/// Same as `render()` but also returns every problem the engine worked
+/// around: undecodable fonts, images drawn as placeholders, skipped SVG
+/// elements, links without a target and clipped content. With
+/// `options.strict`, any diagnostic fails the render with
+/// `FormeError::Diagnostics` instead.
+pub fn render_with_diagnostics(
+    document: &Document,
+    options: &RenderOptions,
+) -> Result<(Vec<u8>, Vec<Diagnostic>), FormeError> {
+    render_pages(document, options).map(|(pdf, _, diagnostics)| (pdf, diagnostics))
 }
 
-/// Render a document to PDF bytes along with layout metadata.
+/// Render a document to PDF bytes along with layout metadata and
+/// diagnostics.
 ///
-/// Same as `render()` but also returns `LayoutInfo` describing the
-/// position and dimensions of every element on every page.
-pub fn render_with_layout(document: &Document) -> Result<(Vec<u8>, LayoutInfo), FormeError> {
-    let mut font_context = FontContext::new();
-    register_document_fonts(&mut font_context, &document.fonts);
-    let engine = LayoutEngine::new();
+/// Same as `render_with_diagnostics()` but also returns `LayoutInfo`
+/// describing the position and dimensions of every element on every page.
+pub fn render_with_layout(
+    document: &Document,
+    options: &RenderOptions,
+) -> Result<(Vec<u8>, LayoutInfo, Vec<Diagnostic>), FormeError> {
+    render_pages(document, options)
+        .map(|(pdf, pages, diagnostics)| (pdf, LayoutInfo::from_pages(&pages), diagnostics))
+}
+
+/// A rendered PDF with the pages it was written from and the diagnostics.
+type RenderedPages = (Vec<u8>, Vec<LayoutPage>, Vec<Diagnostic>);
+
+/// Lay out and write a document.
+fn render_pages(document: &Document, options: &RenderOptions) -> Result<RenderedPages, FormeError> {
+    let mut font_context = options.fonts.map_or_else(FontContext::new, |fonts| {
+        FontContext::with_registry(fonts.clone())
+    });
+    font_context.set_font_synthesis(document.font_synthesis);
+    let mut diagnostics =
+        register_document_fonts(&mut font_context, &document.fonts, options.resolver);
+    let engine = LayoutEngine::with_resolver(options.resolver);
     let pages = engine.layout(document, &font_context);
-    let layout_info = LayoutInfo::from_pages(&pages);
-    let writer = PdfWriter::new();
+    diagnostics.extend(engine.diagnostics());
+    if options.strict && !diagnostics.is_empty() {
+        return Err(FormeError::Diagnostics(diagnostics));
+    }
+    let writer = PdfWriter::with_image_policy(document.image_policy);
     let tagged = document.tagged || matches!(document.pdfa, Some(model::PdfAConformance::A2a));
     let pdf = writer.write(
         &pages,
@@ -89,29 +137,52 @@ pub fn render_with_layout(document: &Document) -> Result<(Vec<u8>, LayoutInfo),
         document.pdfa.as_ref(),
         document.embedded_data.as_deref(),
     )?;
-    Ok((pdf, layout_info))
+    Ok((pdf, pages, diagnostics))
 }
 
-/// Register custom fonts from the document's `fonts` array.
-fn register_document_fonts(font_context: &mut FontContext, fonts: &[FontEntry]) {
-    use base64::Engine as _;
-    let b64 = base64::engine::general_purpose::STANDARD;
+/// Register custom fonts from the document's `fonts` array, returning a
+/// diagnostic for every entry that can't be used.
+fn register_document_fonts(
+    font_context: &mut FontContext,
+    fonts: &[FontEntry],
+    resolver: &dyn ResourceResolver,
+) -> Vec<Diagnostic> {
+    use diagnostics::DiagnosticCode;
 
+    let mut diagnostics = Vec::new();
     for entry in fonts {
-        let bytes = if let Some(comma_pos) = entry.src.find(',') {
-            // data URI: "data:font/ttf;base64,AAAA..."
-            b64.decode(&entry.src[comma_pos + 1..]).ok()
-        } else {
-            // raw base64 string
-            b64.decode(&entry.src).ok()
-        };
-
-        if let Some(data) = bytes {
-            font_context
-                .registry_mut()
-                .register(&entry.family, entry.weight, entry.italic, data);
+        let variant = format!(
+            "Font \"{}\" (weight {}{})",
+            entry.family,
+            entry.weight,
+            if entry.italic { ", italic" } else { "" }
+        );
+        let data = resource::read_source(&entry.src, resolver)
+            .and_then(|data| font::collection::standalone_face(data, entry.face_index));
+        match data {
+            Ok(data) => {
+                if ttf_parser::Face::parse(&data, 0).is_err() {
+                    // Left unregistered, so text in the family falls back
+                    diagnostics.push(Diagnostic::new(
+                        DiagnosticCode::FontParseFailed,
+                        format!("{} is not a TrueType or OpenType font", variant),
+                    ));
+                    continue;
+                }
+                font_context.registry_mut().register(
+                    &entry.family,
+                    entry.weight,
+                    entry.italic,
+                    data,
+                );
+            }
+            Err(e) => diagnostics.push(Diagnostic::new(
+                DiagnosticCode::FontDecodeFailed,
+                format!("{} could not be loaded: {}", variant, e),
+            )),
         }
     }
+    diagnostics
 }
 
 /// Render a document described as JSON to PDF bytes.
@@ -120,10 +191,23 @@ pub fn render_json(json: &str) -> Result<Vec<u8>, FormeError> {
     render(&document)
 }
 
-/// Render a document described as JSON to PDF bytes along with layout metadata.
-pub fn render_json_with_layout(json: &str) -> Result<(Vec<u8>, LayoutInfo), FormeError> {
+/// Render a document described as JSON to PDF bytes along with diagnostics.
+pub fn render_json_with_diagnostics(
+    json: &str,
+    options: &RenderOptions,
+) -> Result<(Vec<u8>, Vec<Diagnostic>), FormeError> {
+    let document: Document = serde_json::from_str(json)?;
+    render_with_diagnostics(&document, options)
+}
+
+/// Render a document described as JSON to PDF bytes along with layout
+/// metadata and diagnostics.
+pub fn render_json_with_layout(
+    json: &str,
+    options: &RenderOptions,
+) -> Result<(Vec<u8>, LayoutInfo, Vec<Diagnostic>), FormeError> {
     let document: Document = serde_json::from_str(json)?;
-    render_with_layout(&document)
+    render_with_layout(&document, options)
 }
 
 /// Render a template with data to PDF bytes.
@@ -139,14 +223,16 @@ pub fn render_template(template_json: &str, data_json: &str) -> Result<Vec<u8>,
     render(&document)
 }
 
-/// Render a template with data to PDF bytes along with layout metadata.
+/// Render a template with data to PDF bytes along with layout metadata and
+/// diagnostics.
 pub fn render_template_with_layout(
     template_json: &str,
     data_json: &str,
-) -> Result<(Vec<u8>, LayoutInfo), FormeError> {
+    options: &RenderOptions,
+) -> Result<(Vec<u8>, LayoutInfo, Vec<Diagnostic>), FormeError> {
     let template: serde_json::Value = serde_json::from_str(template_json)?;
     let data: serde_json::Value = serde_json::from_str(data_json)?;
     let resolved = template::evaluate_template(&template, &data)?;
     let document: Document = serde_json::from_value(resolved)?;
-    render_with_layout(&document)
+    render_with_layout(&document, options)
 }
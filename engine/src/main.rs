@@ -3,6 +3,9 @@
 //! Usage:
 //!   forme input.json -o output.pdf
 //!   echo '{ ... }' | forme -o output.pdf
+//!   forme input.json --strict     (fail instead of working around problems)
+//!   forme input.json --assets ./assets   (read images and fonts from a directory)
+//!   forme input.json --fonts /usr/share/fonts   (make a font directory available)
 //!   forme --example > invoice.json
 
 use std::env;
@@ -41,9 +44,40 @@ fn main() {
         .map(|w| w[1].clone())
         .unwrap_or_else(|| "output.pdf".to_string());
 
+    // Images and fonts referenced by path are read from this directory only
+    let assets_dir = args
+        .windows(2)
+        .find(|w| w[0] == "--assets")
+        .map(|w| w[1].clone())
+        .unwrap_or_else(|| ".".to_string());
+    let resolver = forme::resource::FileSystemResolver::new(assets_dir);
+
+    // Fonts in this directory can be used by family name without a `fonts` entry
+    let fonts = args.windows(2).find(|w| w[0] == "--fonts").map(|w| {
+        let mut registry = forme::font::FontRegistry::new();
+        let scan = registry.scan_directory(&w[1]).unwrap_or_else(|e| {
+            eprintln!("{}", e);
+            process::exit(1);
+        });
+        for (path, reason) in &scan.skipped {
+            eprintln!("Skipped font '{}': {}", path.display(), reason);
+        }
+        eprintln!("Loaded {} font faces from {}", scan.registered.len(), w[1]);
+        registry
+    });
+
+    let options = forme::RenderOptions {
+        strict: args.iter().any(|a| a == "--strict"),
+        resolver: &resolver,
+        fonts: fonts.as_ref(),
+    };
+
     // Render
-    match forme::render_json(&input) {
-        Ok(pdf_bytes) => {
+    match forme::render_json_with_diagnostics(&input, &options) {
+        Ok((pdf_bytes, diagnostics)) => {
+            for d in &diagnostics {
+                eprintln!("{}", d);
+            }
             fs::write(&output_path, &pdf_bytes).unwrap_or_else(|e| {
                 eprintln!("Failed to write PDF to '{}': {}", output_path, e);
                 process::exit(1);
@@ -34,6 +34,22 @@ pub struct Document {
     #[serde(default)]
     pub fonts: Vec<FontEntry>,
 
+    /// Which styles may be faked for a custom family that has no face for
+    /// them: bold by stroking the glyphs, italic by slanting them. Both
+    /// are on by default.
+    #[serde(default)]
+    pub font_synthesis: crate::font::FontSynthesis,
+
+    /// Whether footnotes are numbered through the document or restart on
+    /// every page.
+    #[serde(default)]
+    pub footnote_numbering: FootnoteNumbering,
+
+    /// How images are resampled and compressed when embedded. By default
+    /// they're embedded at full resolution.
+    #[serde(default)]
+    pub image_policy: crate::image_loader::ImagePolicy,
+
     /// Default style applied to the root of the document tree.
     /// Useful for setting a global `font_family`, `font_size`, `color`, etc.
     #[serde(default, skip_serializing_if = "Option::is_none")]
@@ -53,6 +69,16 @@ pub struct Document {
     pub embedded_data: Option<String>,
 }
 
+/// How footnote reference marks are numbered.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub enum FootnoteNumbering {
+    /// 1, 2, 3, ... through the whole document.
+    #[default]
+    Document,
+    /// From 1 again on every page.
+    Page,
+}
+
 /// PDF/A conformance level.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum PdfAConformance {
@@ -66,10 +92,12 @@ pub enum PdfAConformance {
 
 /// A custom font to register with the engine.
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
 pub struct FontEntry {
     /// Font family name (e.g. "Inter", "Roboto").
     pub family: String,
-    /// Base64-encoded font data, or a data URI (e.g. "data:font/ttf;base64,...").
+    /// Base64-encoded font data, a data URI (e.g. "data:font/ttf;base64,..."),
+    /// or a path or name read through the render's resource resolver.
     pub src: String,
     /// Font weight (100-900). Defaults to 400.
     #[serde(default = "default_weight")]
@@ -77,6 +105,9 @@ pub struct FontEntry {
     /// Whether this is an italic variant.
     #[serde(default)]
     pub italic: bool,
+    /// Which face of a font collection (`.ttc`) to use. Defaults to 0.
+    #[serde(default)]
+    pub face_index: u32,
 }
 
 fn default_weight() -> u32 {
@@ -85,6 +116,7 @@ fn default_weight() -> u32 {
 
 /// Document metadata embedded in the PDF.
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
 pub struct Metadata {
     pub title: Option<String>,
     pub author: Option<String>,
@@ -93,10 +125,16 @@ pub struct Metadata {
     /// Document language (BCP 47 tag, e.g. "en-US"). Emitted as /Lang in the PDF Catalog.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub lang: Option<String>,
+    /// How many outline (bookmark) levels start expanded in the reader:
+    /// 0 shows only the top level, 1 also shows its children, and so on.
+    /// Every level starts expanded when unset.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub outline_open_depth: Option<u32>,
 }
 
 /// Configuration for a page: size, margins, orientation.
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
 pub struct PageConfig {
     /// Page size. Defaults to A4.
     #[serde(default = "PageSize::default")]
@@ -109,6 +147,11 @@ pub struct PageConfig {
     /// Whether this page auto-wraps content that overflows.
     #[serde(default = "default_true")]
     pub wrap: bool,
+
+    /// Starts a new page-numbering section on the first page of this Page
+    /// node. Pages without it continue the previous section's numbering.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub numbering: Option<PageNumbering>,
 }
 
 impl Default for PageConfig {
@@ -117,10 +160,48 @@ impl Default for PageConfig {
             size: PageSize::A4,
             margin: Edges::uniform(54.0), // ~0.75 inch
             wrap: true,
+            numbering: None,
+        }
+    }
+}
+
+/// Page numbering for a section of the document (e.g. roman numerals for
+/// front matter, then arabic numerals restarting at 1 for the body).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PageNumbering {
+    /// Number of the section's first page. Defaults to 1.
+    #[serde(default = "default_one")]
+    pub start: u32,
+    /// How `{{pageNumber}}` is printed in this section.
+    #[serde(default)]
+    pub format: PageNumberFormat,
+}
+
+impl Default for PageNumbering {
+    fn default() -> Self {
+        Self {
+            start: 1,
+            format: PageNumberFormat::default(),
         }
     }
 }
 
+/// How a page number is printed.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub enum PageNumberFormat {
+    /// 1, 2, 3, ...
+    #[default]
+    Decimal,
+    /// i, ii, iii, ...
+    LowerRoman,
+    /// I, II, III, ...
+    UpperRoman,
+    /// a, b, ..., z, aa, ab, ...
+    LowerAlpha,
+    /// A, B, ..., Z, AA, AB, ...
+    UpperAlpha,
+}
+
 fn default_true() -> bool {
     true
 }
@@ -276,6 +357,10 @@ pub enum NodeKind {
         /// breaks across pages. This is the killer feature.
         #[serde(default)]
         is_header: bool,
+        /// If set, this row is a table footer, drawn below the body rows of
+        /// each page fragment the table is split into (see [`TableFooter`]).
+        #[serde(default)]
+        footer: Option<TableFooter>,
     },
 
     /// A cell inside a TableRow.
@@ -341,6 +426,16 @@ pub enum NodeKind {
         #[serde(default = "default_watermark_angle")]
         angle: f64,
     },
+
+    /// A table of contents listing the document's bookmarks: one row per
+    /// bookmark with its title, a leader and its page number, linked to the
+    /// bookmarked page. The layout engine fills in the rows once the
+    /// bookmarks' pages are known.
+    TableOfContents {
+        /// Text repeated between an entry's title and its page number.
+        #[serde(default = "default_toc_leader")]
+        leader: String,
+    },
 }
 
 /// A canvas drawing operation.
@@ -431,6 +526,12 @@ pub struct TextRun {
     pub style: crate::style::Style,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub href: Option<String>,
+    /// Makes the run a footnote reference: its content is replaced by the
+    /// footnote's number, drawn as a superscript, and these nodes are the
+    /// footnote's body, placed at the bottom of the page the reference
+    /// lands on.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub footnote: Option<Vec<Node>>,
 }
 
 /// Positioning mode for a node.
@@ -453,6 +554,10 @@ fn default_watermark_angle() -> f64 {
     -45.0
 }
 
+fn default_toc_leader() -> String {
+    ".".to_string()
+}
+
 /// Column definition for tables.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ColumnDef {
@@ -466,10 +571,23 @@ pub enum ColumnWidth {
     Fraction(f64),
     /// Fixed width in points.
     Fixed(f64),
-    /// Distribute remaining space evenly among Auto columns.
+    /// Size to the column's cell content, sharing the space left by Fixed
+    /// and Fraction columns.
     Auto,
 }
 
+/// Which table fragments a footer row is drawn on.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum TableFooter {
+    /// At the bottom of every fragment, including the last.
+    Repeat,
+    /// At the bottom of every fragment except the last
+    /// (e.g. "Continued on next page", "Subtotal carried forward").
+    Continued,
+    /// Only once, after the last body row.
+    Final,
+}
+
 /// Where a fixed element is placed on the page.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum FixedPosition {
@@ -480,7 +598,7 @@ pub enum FixedPosition {
 }
 
 /// Source code location for click-to-source in the dev server inspector.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct SourceLocation {
     pub file: String,
@@ -538,10 +656,11 @@ impl Node {
     /// Is this node breakable across pages?
     pub fn is_breakable(&self) -> bool {
         match &self.kind {
-            NodeKind::View | NodeKind::Table { .. } | NodeKind::Text { .. } => {
-                self.style.wrap.unwrap_or(true)
-            }
-            NodeKind::TableRow { .. } => true,
+            NodeKind::View
+            | NodeKind::Table { .. }
+            | NodeKind::TableRow { .. }
+            | NodeKind::TableOfContents { .. }
+            | NodeKind::Text { .. } => self.style.wrap.unwrap_or(true),
             NodeKind::Image { .. } => false,
             NodeKind::Svg { .. } => false,
             NodeKind::Canvas { .. } => false,
@@ -24,24 +24,47 @@
 //! Standard PDF fonts (Helvetica, Times, Courier) use simple Type1 references.
 //! Custom TrueType fonts are embedded as CIDFontType2 with Identity-H encoding,
 //! producing 5 PDF objects per font: FontFile2, FontDescriptor, CIDFont,
-//! ToUnicode CMap, and the root Type0 dictionary.
+//! ToUnicode CMap, and the root Type0 dictionary. Fonts with PostScript
+//! (CFF) outlines produce the same objects, with a CIDFontType0 and an
+//! OpenType FontFile3 in place of the CIDFontType2 and FontFile2.
+//!
+//! Text asking for a bold or italic face its family doesn't have is drawn
+//! with the closest face, stroked as well as filled (text render mode 2)
+//! for bold and slanted through the text matrix for italic.
 
 pub(crate) mod tagged;
 pub(crate) mod xmp;
 
 use std::collections::{HashMap, HashSet};
 use std::fmt::Write as FmtWrite; // for write! on String
+use std::hash::{Hash, Hasher};
 use std::io::Write as IoWrite; // for write! on Vec<u8>
+use std::sync::Arc;
 
 use crate::error::FormeError;
-use crate::font::subset::subset_ttf;
-use crate::font::{FontContext, FontData, FontKey};
+use crate::font::subset::{subset_font, FontFormat};
+use crate::font::{
+    FontContext, FontData, FontKey, FontSynthesis, SyntheticStyle, Variation,
+    SYNTHETIC_BOLD_STROKE, SYNTHETIC_OBLIQUE_SKEW,
+};
+use crate::image_loader::{ImagePolicy, LoadedImage};
+use crate::layout::background::{self, Background};
 use crate::layout::*;
 use crate::model::*;
 use crate::style::{Color, FontStyle, Overflow, TextDecoration};
 use crate::svg::SvgCommand;
+use crate::text::is_cjk;
 use miniz_oxide::deflate::compress_to_vec_zlib;
 
+/// Distance from the top of an upright glyph's em box to its baseline in
+/// vertical text, as a fraction of the font size (the ideographic em box).
+const VERTICAL_EM_BOX_ASCENT: f64 = 0.88;
+
+/// Distance from the center of a vertical column to the baseline of a
+/// glyph turned to lie along it, as a fraction of the font size. Centers
+/// the cap height on the column.
+const VERTICAL_ROTATED_BASELINE: f64 = 0.35;
+
 /// A link annotation to be added to a page.
 struct LinkAnnotation {
     x: f64,
@@ -51,14 +74,27 @@ struct LinkAnnotation {
     href: String,
 }
 
+/// Where a node with an `id` starts, for `#id` links and named
+/// destinations.
+struct PdfDestination {
+    page_obj_id: usize,
+    y_pdf: f64,
+}
+
 /// A bookmark entry for the PDF outline tree.
 struct PdfBookmark {
     title: String,
     page_obj_id: usize,
     y_pdf: f64,
+    /// Nesting depth from layout; the entry becomes a child of the nearest
+    /// earlier entry with a smaller level.
+    level: u32,
 }
 
-pub struct PdfWriter;
+pub struct PdfWriter {
+    /// How images are resampled and compressed.
+    image_policy: ImagePolicy,
+}
 
 /// Embedding data for a custom TrueType font.
 #[allow(dead_code)]
@@ -68,7 +104,7 @@ struct CustomFontEmbedData {
     gid_remap: HashMap<u16, u16>,
     /// Maps original glyph IDs to their Unicode character(s) for ToUnicode CMap.
     glyph_to_char: HashMap<u16, char>,
-    /// Legacy fallback: maps chars to subset GIDs (for page number placeholders).
+    /// Fallback: maps chars to subset GIDs for glyphs missing from `gid_remap`.
     char_to_gid: HashMap<char, u16>,
     units_per_em: u16,
     ascender: i16,
@@ -92,15 +128,58 @@ struct PdfBuilder {
     font_objects: Vec<(FontKey, usize)>,
     /// Embedding data for custom fonts, keyed by FontKey.
     custom_font_data: HashMap<FontKey, CustomFontEmbedData>,
+    /// Maps the font a glyph asks for to the key of the font that draws it
+    /// (see `FontRegistry::resolve_key`).
+    font_keys: HashMap<FontKey, FontKey>,
+    /// Which styles may be faked when the drawing font lacks them.
+    font_synthesis: FontSynthesis,
     /// XObject obj IDs for images, indexed as /Im0, /Im1, ...
     /// Each entry is (main_xobject_id, optional_smask_xobject_id).
     image_objects: Vec<usize>,
+    /// Maps each shared image, by address, to its index in image_objects,
+    /// so an image drawn many times is written once.
+    image_indices: HashMap<*const LoadedImage, usize>,
+    /// Maps a digest of each distinct image's content to its index in
+    /// image_objects, so identical images from different sources share one
+    /// XObject. Each shared image is hashed once.
+    image_digests: HashMap<u64, usize>,
     /// Maps (page_index, element_position_in_page) to image index in image_objects.
     /// Used during content stream writing to find the right /ImN reference.
     image_index_map: HashMap<(usize, usize), usize>,
     /// ExtGState objects for opacity. Maps opacity value (as ordered bits) to
     /// (object_id, gs_name) e.g. (42, "GS0").
     ext_gstate_map: HashMap<u64, (usize, String)>,
+    /// Shading objects for gradient backgrounds. Maps the shading
+    /// dictionary to (object_id, shading_name) e.g. (43, "Sh0").
+    shading_map: HashMap<String, (usize, String)>,
+    /// Soft-mask ExtGState objects for gradients with translucent stops.
+    /// Maps the gradient's alpha shading dictionary to (object_id,
+    /// gs_name) e.g. (46, "SM0").
+    shading_masks: HashMap<String, (usize, String)>,
+}
+
+impl PdfBuilder {
+    /// The key of the font that draws `glyph`.
+    fn drawn_font_key(&self, glyph: &PositionedGlyph) -> FontKey {
+        let requested = requested_font_key(glyph);
+        self.font_keys.get(&requested).cloned().unwrap_or(requested)
+    }
+
+    /// The styles faked for `glyph` because the font drawing it lacks them.
+    fn synthetic_style(&self, glyph: &PositionedGlyph) -> SyntheticStyle {
+        self.font_synthesis
+            .synthesize(&requested_font_key(glyph), &self.drawn_font_key(glyph))
+    }
+}
+
+/// The font a glyph asks for, before resolution.
+fn requested_font_key(glyph: &PositionedGlyph) -> FontKey {
+    FontKey {
+        family: glyph.font_family.clone(),
+        weight: glyph.font_weight,
+        stretch: glyph.font_stretch,
+        italic: matches!(glyph.font_style, FontStyle::Italic | FontStyle::Oblique),
+    }
 }
 
 pub(crate) struct PdfObject {
@@ -117,7 +196,12 @@ impl Default for PdfWriter {
 
 impl PdfWriter {
     pub fn new() -> Self {
-        Self
+        Self::with_image_policy(ImagePolicy::default())
+    }
+
+    /// A writer that embeds images following `image_policy`.
+    pub fn with_image_policy(image_policy: ImagePolicy) -> Self {
+        Self { image_policy }
     }
 
     /// Write laid-out pages to a PDF byte vector.
@@ -134,9 +218,15 @@ impl PdfWriter {
             objects: Vec::new(),
             font_objects: Vec::new(),
             custom_font_data: HashMap::new(),
+            font_keys: HashMap::new(),
+            font_synthesis: font_context.font_synthesis(),
             image_objects: Vec::new(),
+            image_indices: HashMap::new(),
+            image_digests: HashMap::new(),
             image_index_map: HashMap::new(),
             ext_gstate_map: HashMap::new(),
+            shading_map: HashMap::new(),
+            shading_masks: HashMap::new(),
         };
 
         // Reserve object IDs:
@@ -179,6 +269,9 @@ impl PdfWriter {
         // Register ExtGState objects for opacity
         self.register_ext_gstates(&mut builder, pages);
 
+        // Register shading objects for gradient backgrounds
+        Self::register_shadings(&mut builder, pages);
+
         // Create tag builder for accessibility if requested
         let mut tag_builder = if tagged {
             Some(tagged::TagBuilder::new(pages.len()))
@@ -187,24 +280,20 @@ impl PdfWriter {
         };
 
         // Two-pass page processing:
-        // Pass 1: Build content streams, page objects, collect bookmarks + annotations
-        // Pass 2: Create annotation objects (needs full bookmark list for internal links)
+        // Pass 1: Build content streams, page objects, collect bookmarks,
+        //         destinations + annotations
+        // Pass 2: Create annotation objects (needs every link target)
         let mut page_obj_ids: Vec<usize> = Vec::new();
         let mut all_bookmarks: Vec<PdfBookmark> = Vec::new();
+        let mut destinations: HashMap<String, PdfDestination> = HashMap::new();
         let mut per_page_content_obj_ids: Vec<usize> = Vec::new();
         let mut per_page_annotations: Vec<Vec<LinkAnnotation>> = Vec::new();
         let mut per_page_resources: Vec<String> = Vec::new();
 
         // Pass 1: content streams, page objects (without /Annots), bookmarks
         for (page_idx, page) in pages.iter().enumerate() {
-            let content = self.build_content_stream_for_page(
-                page,
-                page_idx,
-                &builder,
-                page_idx + 1,
-                pages.len(),
-                tag_builder.as_mut(),
-            );
+            let content =
+                self.build_content_stream_for_page(page, page_idx, &builder, tag_builder.as_mut());
             let compressed = compress_to_vec_zlib(content.as_bytes(), 6);
 
             let content_obj_id = builder.objects.len();
@@ -238,6 +327,7 @@ impl PdfWriter {
             let font_resources = self.build_font_resource_dict(&builder.font_objects);
             let xobject_resources = self.build_xobject_resource_dict(page_idx, &builder);
             let ext_gstate_resources = self.build_ext_gstate_resource_dict(&builder);
+            let shading_resources = Self::build_shading_resource_dict(&builder);
             let mut resources = format!("/Font << {} >>", font_resources);
             if !xobject_resources.is_empty() {
                 let _ = write!(resources, " /XObject << {} >>", xobject_resources);
@@ -245,10 +335,14 @@ impl PdfWriter {
             if !ext_gstate_resources.is_empty() {
                 let _ = write!(resources, " /ExtGState << {} >>", ext_gstate_resources);
             }
+            if !shading_resources.is_empty() {
+                let _ = write!(resources, " /Shading << {} >>", shading_resources);
+            }
             per_page_resources.push(resources);
 
-            // Collect bookmarks (needs page_obj_id)
+            // Collect bookmarks and destinations (needs page_obj_id)
             Self::collect_bookmarks(&page.elements, page.height, page_obj_id, &mut all_bookmarks);
+            Self::collect_destinations(&page.elements, page.height, page_obj_id, &mut destinations);
 
             page_obj_ids.push(page_obj_id);
         }
@@ -266,13 +360,22 @@ impl PdfWriter {
                 );
 
                 if let Some(anchor) = annot.href.strip_prefix('#') {
-                    // Internal link: find matching bookmark by title
-                    if let Some(bm) = all_bookmarks.iter().find(|b| b.title == anchor) {
+                    // Internal link: a node id, else a bookmark title
+                    let target = destinations
+                        .get(anchor)
+                        .map(|d| (d.page_obj_id, d.y_pdf))
+                        .or_else(|| {
+                            all_bookmarks
+                                .iter()
+                                .find(|b| b.title == anchor)
+                                .map(|b| (b.page_obj_id, b.y_pdf))
+                        });
+                    if let Some((target_page_id, target_y)) = target {
                         let annot_obj_id = builder.objects.len();
                         let annot_dict = format!(
                             "<< /Type /Annot /Subtype /Link /Rect {} /Border [0 0 0] \
                              /A << /S /GoTo /D [{} 0 R /XYZ 0 {:.2} null] >> >>",
-                            rect, bm.page_obj_id, bm.y_pdf
+                            rect, target_page_id, target_y
                         );
                         builder.objects.push(PdfObject {
                             id: annot_obj_id,
@@ -280,7 +383,7 @@ impl PdfWriter {
                         });
                         annot_obj_ids.push(annot_obj_id);
                     }
-                    // No matching bookmark: skip silently
+                    // No matching target: layout already warned about it
                 } else {
                     // External link
                     let annot_obj_id = builder.objects.len();
@@ -331,7 +434,7 @@ impl PdfWriter {
 
         // Build outline tree if bookmarks exist
         let outlines_obj_id = if !all_bookmarks.is_empty() {
-            Some(self.write_outline_tree(&mut builder, &all_bookmarks))
+            Some(self.write_outline_tree(&mut builder, &all_bookmarks, metadata.outline_open_depth))
         } else {
             None
         };
@@ -445,6 +548,32 @@ impl PdfWriter {
             None
         };
 
+        // Named destinations for every node id, so `file.pdf#nameddest=id`
+        // opens at that node
+        let dests_names_id = if !destinations.is_empty() {
+            let mut names: Vec<(&String, &PdfDestination)> = destinations.iter().collect();
+            // Name tree keys must be sorted
+            names.sort_by(|a, b| a.0.as_bytes().cmp(b.0.as_bytes()));
+            let mut entries = String::new();
+            for (name, dest) in names {
+                let _ = write!(
+                    entries,
+                    " ({}) [{} 0 R /XYZ 0 {:.2} null]",
+                    Self::escape_pdf_string(name),
+                    dest.page_obj_id,
+                    dest.y_pdf
+                );
+            }
+            let dests_obj_id = builder.objects.len();
+            builder.objects.push(PdfObject {
+                id: dests_obj_id,
+                data: format!("<< /Names [{} ] >>", entries).into_bytes(),
+            });
+            Some(dests_obj_id)
+        } else {
+            None
+        };
+
         // Write Catalog (object 1)
         let mut catalog = String::from("<< /Type /Catalog /Pages 2 0 R");
         if let Some(outlines_id) = outlines_obj_id {
@@ -472,8 +601,15 @@ impl PdfWriter {
         if let Some(oi_id) = output_intent_id {
             write!(catalog, " /OutputIntents [{} 0 R]", oi_id).unwrap();
         }
-        if let Some(names_id) = embedded_names_id {
-            write!(catalog, " /Names << /EmbeddedFiles {} 0 R >>", names_id).unwrap();
+        if dests_names_id.is_some() || embedded_names_id.is_some() {
+            catalog.push_str(" /Names <<");
+            if let Some(dests_id) = dests_names_id {
+                write!(catalog, " /Dests {} 0 R", dests_id).unwrap();
+            }
+            if let Some(names_id) = embedded_names_id {
+                write!(catalog, " /EmbeddedFiles {} 0 R", names_id).unwrap();
+            }
+            catalog.push_str(" >>");
         }
         catalog.push_str(" >>");
         builder.objects[1].data = catalog.into_bytes();
@@ -523,8 +659,6 @@ impl PdfWriter {
         page: &LayoutPage,
         page_idx: usize,
         builder: &PdfBuilder,
-        page_number: usize,
-        total_pages: usize,
         mut tag_builder: Option<&mut tagged::TagBuilder>,
     ) -> String {
         let mut stream = String::new();
@@ -539,8 +673,6 @@ impl PdfWriter {
                 builder,
                 page_idx,
                 &mut element_counter,
-                page_number,
-                total_pages,
                 tag_builder.as_deref_mut(),
             );
         }
@@ -558,8 +690,6 @@ impl PdfWriter {
         builder: &PdfBuilder,
         page_idx: usize,
         element_counter: &mut usize,
-        page_number: usize,
-        total_pages: usize,
         mut tag_builder: Option<&mut tagged::TagBuilder>,
     ) {
         // Tagged PDF: emit BDC (begin marked content) for elements with a node_type
@@ -583,6 +713,7 @@ impl PdfWriter {
 
             DrawCommand::Rect {
                 background,
+                background_image,
                 border_width,
                 border_color,
                 border_radius,
@@ -615,6 +746,69 @@ impl PdfWriter {
                     }
                 }
 
+                if let Some(background_image) = background_image.as_deref() {
+                    let image_idx = if let Background::Image { .. } = background_image {
+                        let elem_idx = *element_counter;
+                        *element_counter += 1;
+                        builder.image_index_map.get(&(page_idx, elem_idx)).copied()
+                    } else {
+                        None
+                    };
+                    // Clipped to the box and its rounded corners
+                    let _ = writeln!(stream, "q");
+                    if border_radius.top_left > 0.0 {
+                        self.write_rounded_rect(stream, x, y, w, h, border_radius);
+                    } else {
+                        let _ = writeln!(stream, "{:.2} {:.2} {:.2} {:.2} re", x, y, w, h);
+                    }
+                    let _ = writeln!(stream, "W n");
+                    match background_image {
+                        Background::Image {
+                            image_data,
+                            size,
+                            repeat,
+                            position,
+                        } => {
+                            if let Some(img_idx) = image_idx {
+                                let tiles = background::tiles(
+                                    image_data.width_px as f64,
+                                    image_data.height_px as f64,
+                                    *size,
+                                    *repeat,
+                                    *position,
+                                    w,
+                                    h,
+                                );
+                                for tile in tiles {
+                                    let _ = write!(
+                                        stream,
+                                        "q\n{:.4} 0 0 {:.4} {:.2} {:.2} cm\n/Im{} Do\nQ\n",
+                                        tile.width,
+                                        tile.height,
+                                        x + tile.x,
+                                        y + h - tile.y - tile.height,
+                                        img_idx
+                                    );
+                                }
+                            }
+                        }
+                        gradient => {
+                            let shading = Self::gradient_shading(gradient, w, h)
+                                .and_then(|dict| builder.shading_map.get(&dict));
+                            if let Some((_, name)) = shading {
+                                let _ = writeln!(stream, "1 0 0 1 {:.2} {:.2} cm", x, y);
+                                let mask = Self::gradient_alpha_shading(gradient, w, h)
+                                    .and_then(|dict| builder.shading_masks.get(&dict));
+                                if let Some((_, gs_name)) = mask {
+                                    let _ = writeln!(stream, "/{} gs", gs_name);
+                                }
+                                let _ = writeln!(stream, "/{} sh", name);
+                            }
+                        }
+                    }
+                    let _ = writeln!(stream, "Q");
+                }
+
                 let bw = border_width;
                 if bw.top > 0.0 || bw.right > 0.0 || bw.bottom > 0.0 || bw.left > 0.0 {
                     if (bw.top - bw.right).abs() < 0.001
@@ -663,12 +857,22 @@ impl PdfWriter {
                     if line.glyphs.is_empty() {
                         continue;
                     }
+                    if line.vertical {
+                        self.write_vertical_line(stream, line, *color, page_height, builder);
+                        continue;
+                    }
 
                     // Group consecutive glyphs by (font_family, font_weight, font_style, font_size, color)
                     // to support multi-font text runs
                     let groups = Self::group_glyphs_by_style(&line.glyphs);
                     let pdf_y = page_height - line.y;
 
+                    // Synthetic bold changes the line width, so keep it
+                    // from leaking into later strokes
+                    let strokes_text = groups.iter().any(|g| builder.synthetic_style(g[0]).bold);
+                    if strokes_text {
+                        let _ = writeln!(stream, "q");
+                    }
                     let _ = writeln!(stream, "BT");
 
                     // Set word spacing for justification (PDF Tw operator)
@@ -680,6 +884,15 @@ impl PdfWriter {
                     let mut tm_x = 0.0_f64;
                     let mut tm_y = 0.0_f64;
                     let mut x_cursor = line.x;
+                    // Whether the text matrix is slanted, or glyphs stroked,
+                    // for synthetic styles
+                    let mut skewed = false;
+                    let mut stroking = false;
+                    // Text rise (Ts) for superscripts and subscripts
+                    let mut rise = 0.0_f64;
+                    // Glyphs drawn so far, each followed by the line's
+                    // inter-character justification
+                    let mut glyphs_drawn = 0;
 
                     // Track group spans for per-group text decoration
                     let mut group_spans: Vec<(f64, f64, TextDecoration, Color)> = Vec::new();
@@ -688,113 +901,73 @@ impl PdfWriter {
                         let first = &group[0];
                         let glyph_color = first.color.unwrap_or(*color);
 
-                        let idx = self.font_index(
-                            &first.font_family,
-                            first.font_weight,
-                            first.font_style,
-                            &builder.font_objects,
-                        );
-                        let italic =
-                            matches!(first.font_style, FontStyle::Italic | FontStyle::Oblique);
-                        let font_key = FontKey {
-                            family: first.font_family.clone(),
-                            weight: if first.font_weight >= 600 { 700 } else { 400 },
-                            italic,
-                        };
+                        let font_key = builder.drawn_font_key(first);
+                        let idx = self.font_index(&font_key, &builder.font_objects);
                         let font_name = format!("F{}", idx);
+                        let synthetic = builder.synthetic_style(first);
+                        let char_spacing = first.letter_spacing
+                            + line.char_spacing
+                            + Self::write_synthetic_bold(
+                                stream,
+                                synthetic.bold,
+                                &mut stroking,
+                                first.font_size,
+                                glyph_color,
+                            );
 
-                        // Td is relative to current text matrix position
-                        let dx = x_cursor - tm_x;
-                        let dy = pdf_y - tm_y;
                         let _ = writeln!(
                             stream,
-                            "{:.3} {:.3} {:.3} rg\n/{} {:.1} Tf\n{:.2} Tc\n{:.2} {:.2} Td",
+                            "{:.3} {:.3} {:.3} rg\n/{} {:.1} Tf\n{:.2} Tc",
                             glyph_color.r,
                             glyph_color.g,
                             glyph_color.b,
                             font_name,
                             first.font_size,
-                            first.letter_spacing,
-                            dx,
-                            dy
+                            char_spacing,
                         );
-                        tm_x = x_cursor;
-                        tm_y = pdf_y;
-
-                        // Check for page number placeholders
-                        let raw_text: String = group.iter().map(|g| g.char_value).collect();
-                        let has_placeholder = raw_text.contains("{{pageNumber}}")
-                            || raw_text.contains("{{totalPages}}");
-
-                        let is_custom = builder.custom_font_data.contains_key(&font_key);
-
-                        if is_custom {
-                            if let Some(embed_data) = builder.custom_font_data.get(&font_key) {
-                                let mut hex = String::new();
-                                if has_placeholder {
-                                    // Placeholder text: replace and use char→gid fallback
-                                    let text_after = raw_text
-                                        .replace("{{pageNumber}}", &page_number.to_string())
-                                        .replace("{{totalPages}}", &total_pages.to_string());
-                                    for ch in text_after.chars() {
-                                        let gid =
-                                            embed_data.char_to_gid.get(&ch).copied().unwrap_or(0);
-                                        let _ = write!(hex, "{:04X}", gid);
-                                    }
-                                } else {
-                                    // Shaped text: use glyph IDs directly (remapped through subset)
-                                    for g in group.iter() {
-                                        let new_gid = embed_data
-                                            .gid_remap
-                                            .get(&g.glyph_id)
-                                            .copied()
-                                            .unwrap_or_else(|| {
-                                                // Fallback: try char→gid
-                                                embed_data
-                                                    .char_to_gid
-                                                    .get(&g.char_value)
-                                                    .copied()
-                                                    .unwrap_or(0)
-                                            });
-                                        let _ = write!(hex, "{:04X}", new_gid);
-                                    }
-                                }
-                                let _ = writeln!(stream, "<{}> Tj", hex);
+                        if synthetic.oblique || skewed {
+                            // A slanted run sets the whole text matrix, as
+                            // does the first upright run after one
+                            let skew = if synthetic.oblique {
+                                SYNTHETIC_OBLIQUE_SKEW
                             } else {
-                                let _ = writeln!(stream, "<> Tj");
-                            }
+                                0.0
+                            };
+                            let _ = writeln!(
+                                stream,
+                                "1 0 {:.4} 1 {:.2} {:.2} Tm",
+                                skew, x_cursor, pdf_y
+                            );
+                            skewed = synthetic.oblique;
                         } else {
-                            let text_after = raw_text
-                                .replace("{{pageNumber}}", &page_number.to_string())
-                                .replace("{{totalPages}}", &total_pages.to_string());
-                            let mut text_str = String::new();
-                            for ch in text_after.chars() {
-                                let b = Self::unicode_to_winansi(ch).unwrap_or(b'?');
-                                match b {
-                                    b'\\' => text_str.push_str("\\\\"),
-                                    b'(' => text_str.push_str("\\("),
-                                    b')' => text_str.push_str("\\)"),
-                                    0x20..=0x7E => text_str.push(b as char),
-                                    _ => {
-                                        let _ = write!(text_str, "\\{:03o}", b);
-                                    }
-                                }
-                            }
-                            let _ = writeln!(stream, "({}) Tj", text_str);
+                            // Td is relative to current text matrix position
+                            let _ =
+                                writeln!(stream, "{:.2} {:.2} Td", x_cursor - tm_x, pdf_y - tm_y);
                         }
+                        tm_x = x_cursor;
+                        tm_y = pdf_y;
+                        if (first.baseline_shift - rise).abs() > 0.001 {
+                            rise = first.baseline_shift;
+                            let _ = writeln!(stream, "{:.2} Ts", rise);
+                        }
+
+                        let operand = Self::text_operand(builder, &font_key, group);
+                        let _ = writeln!(stream, "{} Tj", operand);
 
                         // Record span for per-group text decoration
                         let group_start_x = x_cursor;
 
                         // Advance x_cursor past this group using shaped advances
                         // Account for word_spacing on spaces (Tw adds to each space char)
+                        glyphs_drawn += group.len();
                         if let Some(last) = group.last() {
                             let space_count_in_group =
                                 group.iter().filter(|g| g.char_value == ' ').count();
                             x_cursor = line.x
                                 + last.x_offset
                                 + last.x_advance
-                                + space_count_in_group as f64 * line.word_spacing;
+                                + space_count_in_group as f64 * line.word_spacing
+                                + glyphs_drawn as f64 * line.char_spacing;
                         }
 
                         // Check if this group has text decoration
@@ -804,7 +977,14 @@ impl PdfWriter {
                         }
                     }
 
+                    // Text rise outlasts the text object, so reset it
+                    if rise != 0.0 {
+                        let _ = writeln!(stream, "0 Ts");
+                    }
                     let _ = writeln!(stream, "ET");
+                    if strokes_text {
+                        let _ = writeln!(stream, "Q");
+                    }
 
                     // Draw per-group text decorations
                     for (span_x, span_end_x, dec, dec_color) in &group_spans {
@@ -867,16 +1047,28 @@ impl PdfWriter {
                 }
             }
 
-            DrawCommand::Image { .. } => {
+            DrawCommand::Image { placement, .. } => {
                 let elem_idx = *element_counter;
                 *element_counter += 1;
                 if let Some(&img_idx) = builder.image_index_map.get(&(page_idx, elem_idx)) {
-                    let x = element.x;
-                    let y = page_height - element.y - element.height;
+                    let _ = writeln!(stream, "q");
+                    // Clip to the box when the fit crops the image
+                    if placement.overflows(element.width, element.height) {
+                        let _ = writeln!(
+                            stream,
+                            "{:.2} {:.2} {:.2} {:.2} re W n",
+                            element.x,
+                            page_height - element.y - element.height,
+                            element.width,
+                            element.height
+                        );
+                    }
+                    let x = element.x + placement.x;
+                    let y = page_height - element.y - placement.y - placement.height;
                     let _ = write!(
                         stream,
-                        "q\n{:.4} 0 0 {:.4} {:.2} {:.2} cm\n/Im{} Do\nQ\n",
-                        element.width, element.height, x, y, img_idx
+                        "{:.4} 0 0 {:.4} {:.2} {:.2} cm\n/Im{} Do\nQ\n",
+                        placement.width, placement.height, x, y, img_idx
                     );
                 } else {
                     // Fallback: grey placeholder if image index not found
@@ -1024,23 +1216,41 @@ impl PdfWriter {
                         -text_width / 2.0,
                         -cap_height / 2.0
                     );
+                    let mut skewed = false;
+                    let mut stroking = false;
                     for group in &groups {
                         let first = &group[0];
-                        let italic =
-                            matches!(first.font_style, FontStyle::Italic | FontStyle::Oblique);
-                        let fk = FontKey {
-                            family: first.font_family.clone(),
-                            weight: if first.font_weight >= 600 { 700 } else { 400 },
-                            italic,
-                        };
-                        let idx = self.font_index(
-                            &first.font_family,
-                            first.font_weight,
-                            first.font_style,
-                            &builder.font_objects,
-                        );
+                        let fk = builder.drawn_font_key(first);
+                        let idx = self.font_index(&fk, &builder.font_objects);
                         let font_name = format!("F{}", idx);
                         let _ = writeln!(stream, "/{} {:.1} Tf", font_name, first.font_size);
+                        let synthetic = builder.synthetic_style(first);
+                        let was_stroking = stroking;
+                        let char_spacing = Self::write_synthetic_bold(
+                            stream,
+                            synthetic.bold,
+                            &mut stroking,
+                            first.font_size,
+                            *color,
+                        );
+                        if synthetic.bold || was_stroking {
+                            let _ = writeln!(stream, "{:.2} Tc", char_spacing);
+                        }
+                        if synthetic.oblique || skewed {
+                            let skew = if synthetic.oblique {
+                                SYNTHETIC_OBLIQUE_SKEW
+                            } else {
+                                0.0
+                            };
+                            let _ = writeln!(
+                                stream,
+                                "1 0 {:.4} 1 {:.2} {:.2} Tm",
+                                skew,
+                                first.x_offset - text_width / 2.0,
+                                -cap_height / 2.0
+                            );
+                            skewed = synthetic.oblique;
+                        }
                         let is_custom = builder.custom_font_data.contains_key(&fk);
                         if is_custom {
                             if let Some(embed_data) = builder.custom_font_data.get(&fk) {
@@ -1095,8 +1305,6 @@ impl PdfWriter {
                 builder,
                 page_idx,
                 element_counter,
-                page_number,
-                total_pages,
                 tag_builder.as_deref_mut(),
             );
         }
@@ -1272,7 +1480,12 @@ impl PdfWriter {
         let mut font_usage_map: HashMap<FontKey, FontUsage> = HashMap::new();
 
         for page in pages {
-            Self::collect_font_usage(&page.elements, &mut font_usage_map);
+            Self::collect_font_usage(
+                &page.elements,
+                font_context,
+                &mut builder.font_keys,
+                &mut font_usage_map,
+            );
         }
 
         let mut keys: Vec<FontKey> = font_usage_map.keys().cloned().collect();
@@ -1282,6 +1495,7 @@ impl PdfWriter {
             a.family
                 .cmp(&b.family)
                 .then(a.weight.cmp(&b.weight))
+                .then(a.stretch.cmp(&b.stretch))
                 .then(a.italic.cmp(&b.italic))
         });
         keys.dedup();
@@ -1291,12 +1505,13 @@ impl PdfWriter {
             keys.push(FontKey {
                 family: "Helvetica".to_string(),
                 weight: 400,
+                stretch: 100,
                 italic: false,
             });
         }
 
         for key in &keys {
-            let font_data = font_context.resolve(&key.family, key.weight, key.italic);
+            let font_data = font_context.resolve(&key.family, key.weight, key.stretch, key.italic);
 
             match font_data {
                 FontData::Standard(std_font) => {
@@ -1332,12 +1547,26 @@ impl PdfWriter {
                         builder,
                         key,
                         data,
+                        &[],
                         used_glyph_ids.cloned().unwrap_or_default(),
                         used_chars.cloned().unwrap_or_default(),
                         glyph_to_char.cloned().unwrap_or_default(),
                     )?;
                     builder.font_objects.push((key.clone(), type0_obj_id));
                 }
+                FontData::Variable(font) => {
+                    let usage = font_usage_map.get(key);
+                    let type0_obj_id = Self::write_custom_font_objects(
+                        builder,
+                        key,
+                        &font.data,
+                        &font.variations(key.weight, key.stretch),
+                        usage.map(|u| u.glyph_ids.clone()).unwrap_or_default(),
+                        usage.map(|u| u.chars.clone()).unwrap_or_default(),
+                        usage.map(|u| u.glyph_to_char.clone()).unwrap_or_default(),
+                    )?;
+                    builder.font_objects.push((key.clone(), type0_obj_id));
+                }
             }
         }
 
@@ -1345,8 +1574,12 @@ impl PdfWriter {
     }
 
     /// Collect font usage data from layout elements: used chars, glyph IDs, and glyph→char mapping.
+    /// Usage is recorded under the key of the font that draws each glyph,
+    /// and `font_keys` maps each requested font to that key.
     fn collect_font_usage(
         elements: &[LayoutElement],
+        font_context: &FontContext,
+        font_keys: &mut HashMap<FontKey, FontKey>,
         font_usage: &mut HashMap<FontKey, FontUsage>,
     ) {
         for element in elements {
@@ -1358,13 +1591,17 @@ impl PdfWriter {
             if let Some(lines) = lines_opt {
                 for line in lines {
                     for glyph in &line.glyphs {
-                        let italic =
-                            matches!(glyph.font_style, FontStyle::Italic | FontStyle::Oblique);
-                        let key = FontKey {
-                            family: glyph.font_family.clone(),
-                            weight: if glyph.font_weight >= 600 { 700 } else { 400 },
-                            italic,
-                        };
+                        let key = font_keys
+                            .entry(requested_font_key(glyph))
+                            .or_insert_with_key(|requested| {
+                                font_context.registry().resolve_key(
+                                    &requested.family,
+                                    requested.weight,
+                                    requested.stretch,
+                                    requested.italic,
+                                )
+                            })
+                            .clone();
                         let usage = font_usage.entry(key).or_insert_with(|| FontUsage {
                             chars: HashSet::new(),
                             glyph_ids: HashSet::new(),
@@ -1390,50 +1627,142 @@ impl PdfWriter {
                     }
                 }
             }
-            Self::collect_font_usage(&element.children, font_usage);
+            Self::collect_font_usage(&element.children, font_context, font_keys, font_usage);
         }
     }
 
-    /// Walk all pages, create XObject PDF objects for each image,
+    /// Walk all pages, create an XObject PDF object for each distinct image,
     /// and populate the image_index_map for content stream reference.
+    /// Each image is prepared for the largest size it's drawn at.
     fn register_images(&self, builder: &mut PdfBuilder, pages: &[LayoutPage]) {
+        let mut images = Vec::new();
         for (page_idx, page) in pages.iter().enumerate() {
             let mut element_counter = 0usize;
-            Self::collect_images_recursive(&page.elements, page_idx, &mut element_counter, builder);
+            Self::collect_images_recursive(
+                &page.elements,
+                page_idx,
+                &mut element_counter,
+                builder,
+                &mut images,
+            );
+        }
+        for (image, width, height) in images {
+            let image = crate::image_loader::prepare_for_embedding(
+                image,
+                width,
+                height,
+                &self.image_policy,
+            );
+            let xobj_id = Self::write_image_xobject(builder, &image);
+            builder.image_objects.push(xobj_id);
         }
     }
 
-    fn collect_images_recursive(
-        elements: &[LayoutElement],
+    /// Number the images in `elements`, collecting each distinct image with
+    /// the largest width and height it's drawn at into `images`. A
+    /// background image is numbered before the element's children.
+    fn collect_images_recursive<'a>(
+        elements: &'a [LayoutElement],
         page_idx: usize,
         element_counter: &mut usize,
         builder: &mut PdfBuilder,
+        images: &mut Vec<(&'a LoadedImage, f64, f64)>,
     ) {
         for element in elements {
-            match &element.draw {
-                DrawCommand::Image { image_data } => {
-                    let elem_idx = *element_counter;
-                    *element_counter += 1;
-
-                    let img_idx = builder.image_objects.len();
-                    let xobj_id = Self::write_image_xobject(builder, image_data);
-                    builder.image_objects.push(xobj_id);
-                    builder
-                        .image_index_map
-                        .insert((page_idx, elem_idx), img_idx);
-                }
+            // The image drawn and the size it's drawn at
+            let drawn = match &element.draw {
+                DrawCommand::Image {
+                    image_data,
+                    placement,
+                } => Some((image_data, placement.width, placement.height)),
+                DrawCommand::Rect {
+                    background_image: Some(background_image),
+                    ..
+                } => match background_image.as_ref() {
+                    Background::Image {
+                        image_data,
+                        size,
+                        repeat,
+                        position,
+                    } => {
+                        let tiles = background::tiles(
+                            image_data.width_px as f64,
+                            image_data.height_px as f64,
+                            *size,
+                            *repeat,
+                            *position,
+                            element.width,
+                            element.height,
+                        );
+                        let (width, height) = tiles
+                            .first()
+                            .map_or((0.0, 0.0), |tile| (tile.width, tile.height));
+                        Some((image_data, width, height))
+                    }
+                    _ => None,
+                },
                 DrawCommand::ImagePlaceholder => {
                     *element_counter += 1;
+                    None
                 }
-                _ => {
-                    Self::collect_images_recursive(
-                        &element.children,
-                        page_idx,
-                        element_counter,
-                        builder,
-                    );
-                }
+                _ => None,
+            };
+
+            if let Some((image_data, width, height)) = drawn {
+                let elem_idx = *element_counter;
+                *element_counter += 1;
+
+                // Identical images share one XObject, whatever their source
+                let img_idx = match builder.image_indices.get(&Arc::as_ptr(image_data)) {
+                    Some(&img_idx) => {
+                        let (_, max_width, max_height) = &mut images[img_idx];
+                        *max_width = max_width.max(width);
+                        *max_height = max_height.max(height);
+                        img_idx
+                    }
+                    None => {
+                        let digest = {
+                            let mut hasher = std::collections::hash_map::DefaultHasher::new();
+                            image_data.hash(&mut hasher);
+                            hasher.finish()
+                        };
+                        let same = builder
+                            .image_digests
+                            .get(&digest)
+                            .copied()
+                            .filter(|&img_idx| images[img_idx].0 == image_data.as_ref());
+                        let img_idx = match same {
+                            Some(img_idx) => {
+                                let (_, max_width, max_height) = &mut images[img_idx];
+                                *max_width = max_width.max(width);
+                                *max_height = max_height.max(height);
+                                img_idx
+                            }
+                            None => {
+                                let img_idx = images.len();
+                                images.push((image_data, width, height));
+                                builder.image_digests.entry(digest).or_insert(img_idx);
+                                img_idx
+                            }
+                        };
+                        builder
+                            .image_indices
+                            .insert(Arc::as_ptr(image_data), img_idx);
+                        img_idx
+                    }
+                };
+                builder
+                    .image_index_map
+                    .insert((page_idx, elem_idx), img_idx);
             }
+
+            Self::collect_images_recursive(
+                &element.children,
+                page_idx,
+                element_counter,
+                builder,
+                images,
+            );
         }
     }
 
@@ -1478,14 +1807,218 @@ impl PdfWriter {
         }
     }
 
+    /// Create a shading object for each distinct gradient background, and a
+    /// soft mask for each distinct set of translucent stops.
+    fn register_shadings(builder: &mut PdfBuilder, pages: &[LayoutPage]) {
+        let mut shadings = Vec::new();
+        let mut masks = Vec::new();
+        for page in pages {
+            Self::collect_shadings_recursive(&page.elements, &mut shadings, &mut masks);
+        }
+        for dict in shadings {
+            if builder.shading_map.contains_key(&dict) {
+                continue;
+            }
+            let obj_id = builder.objects.len();
+            let name = format!("Sh{}", builder.shading_map.len());
+            builder.objects.push(PdfObject {
+                id: obj_id,
+                data: dict.clone().into_bytes(),
+            });
+            builder.shading_map.insert(dict, (obj_id, name));
+        }
+        // The alpha shading is painted in a transparency group and used as
+        // a luminosity soft mask while the color shading is drawn. The
+        // shading's coordinates pin down the box, so one mask per dict.
+        for (dict, width, height) in masks {
+            if builder.shading_masks.contains_key(&dict) {
+                continue;
+            }
+            let shading_id = builder.objects.len();
+            builder.objects.push(PdfObject {
+                id: shading_id,
+                data: dict.clone().into_bytes(),
+            });
+
+            let content = b"/Sh0 sh";
+            let form_id = builder.objects.len();
+            let mut form_data: Vec<u8> = Vec::new();
+            let _ = write!(
+                form_data,
+                "<< /Type /XObject /Subtype /Form /BBox [0 0 {:.4} {:.4}] \
+                 /Group << /S /Transparency /CS /DeviceGray >> \
+                 /Resources << /Shading << /Sh0 {} 0 R >> >> /Length {} >>\nstream\n",
+                width,
+                height,
+                shading_id,
+                content.len()
+            );
+            form_data.extend_from_slice(content);
+            form_data.extend_from_slice(b"\nendstream");
+            builder.objects.push(PdfObject {
+                id: form_id,
+                data: form_data,
+            });
+
+            let gs_id = builder.objects.len();
+            builder.objects.push(PdfObject {
+                id: gs_id,
+                data: format!(
+                    "<< /Type /ExtGState /SMask << /Type /Mask /S /Luminosity /G {} 0 R >> >>",
+                    form_id
+                )
+                .into_bytes(),
+            });
+            let name = format!("SM{}", builder.shading_masks.len());
+            builder.shading_masks.insert(dict, (gs_id, name));
+        }
+    }
+
+    fn collect_shadings_recursive(
+        elements: &[LayoutElement],
+        shadings: &mut Vec<String>,
+        masks: &mut Vec<(String, f64, f64)>,
+    ) {
+        for element in elements {
+            if let DrawCommand::Rect {
+                background_image: Some(gradient),
+                ..
+            } = &element.draw
+            {
+                let (width, height) = (element.width, element.height);
+                shadings.extend(Self::gradient_shading(gradient, width, height));
+                if let Some(dict) = Self::gradient_alpha_shading(gradient, width, height) {
+                    masks.push((dict, width, height));
+                }
+            }
+            Self::collect_shadings_recursive(&element.children, shadings, masks);
+        }
+    }
+
+    /// The shading dictionary for a gradient background in a box `width` ×
+    /// `height`, in coordinates from the box's bottom-left corner. None for
+    /// an image background, or a gradient with nothing to draw.
+    fn gradient_shading(gradient: &Background, width: f64, height: f64) -> Option<String> {
+        Self::shading_dict(gradient, width, height, "DeviceRGB", |color| {
+            format!("{:.4} {:.4} {:.4}", color.r, color.g, color.b)
+        })
+    }
+
+    /// The gray shading of a gradient's stop alphas, drawn as its soft
+    /// mask. None when every stop is opaque.
+    fn gradient_alpha_shading(gradient: &Background, width: f64, height: f64) -> Option<String> {
+        let stops = match gradient {
+            Background::LinearGradient { stops, .. } | Background::RadialGradient { stops } => {
+                stops
+            }
+            Background::Image { .. } => return None,
+        };
+        if stops.iter().all(|stop| stop.color.a >= 1.0) {
+            return None;
+        }
+        Self::shading_dict(gradient, width, height, "DeviceGray", |color| {
+            format!("{:.4}", color.a.clamp(0.0, 1.0))
+        })
+    }
+
+    /// A shading dictionary for a gradient in `color_space`, with each
+    /// stop's color written by `components`.
+    fn shading_dict(
+        gradient: &Background,
+        width: f64,
+        height: f64,
+        color_space: &str,
+        components: impl Fn(&Color) -> String,
+    ) -> Option<String> {
+        let (shading_type, coords, stops) = match gradient {
+            Background::LinearGradient { angle, stops } => {
+                let ((x0, y0), (x1, y1)) = background::linear_gradient_line(*angle, width, height);
+                let coords = format!("{:.4} {:.4} {:.4} {:.4}", x0, height - y0, x1, height - y1);
+                (2, coords, stops)
+            }
+            Background::RadialGradient { stops } => {
+                let (cx, cy) = (width / 2.0, height / 2.0);
+                let radius = background::radial_gradient_radius(width, height);
+                let coords = format!("{:.4} {:.4} 0 {:.4} {:.4} {:.4}", cx, cy, cx, cy, radius);
+                (3, coords, stops)
+            }
+            Background::Image { .. } => return None,
+        };
+        if stops.is_empty() || width <= 0.0 || height <= 0.0 {
+            return None;
+        }
+
+        // Hold the end colors out to the ends of the gradient
+        let mut points = stops.clone();
+        if points[0].offset > 0.0 {
+            points.insert(
+                0,
+                background::GradientStop {
+                    offset: 0.0,
+                    ..points[0]
+                },
+            );
+        }
+        if points[points.len() - 1].offset < 1.0 {
+            points.push(background::GradientStop {
+                offset: 1.0,
+                ..points[points.len() - 1]
+            });
+        }
+        // One exponential interpolation function per pair of stops,
+        // stitched together when there are more than two
+        let segment = |from: &background::GradientStop, to: &background::GradientStop| {
+            format!(
+                "<< /FunctionType 2 /Domain [0 1] /C0 [{}] /C1 [{}] /N 1 >>",
+                components(&from.color),
+                components(&to.color)
+            )
+        };
+        let function = if points.len() == 2 {
+            segment(&points[0], &points[1])
+        } else {
+            let functions: Vec<String> = points.windows(2).map(|w| segment(&w[0], &w[1])).collect();
+            let bounds: Vec<String> = points[1..points.len() - 1]
+                .iter()
+                .map(|stop| format!("{:.4}", stop.offset))
+                .collect();
+            format!(
+                "<< /FunctionType 3 /Domain [0 1] /Functions [{}] /Bounds [{}] /Encode [{}] >>",
+                functions.join(" "),
+                bounds.join(" "),
+                vec!["0 1"; functions.len()].join(" ")
+            )
+        };
+        Some(format!(
+            "<< /ShadingType {} /ColorSpace /{} /Coords [{}] /Function {} /Extend [true true] >>",
+            shading_type, color_space, coords, function
+        ))
+    }
+
+    /// Build the Shading resource dict entries for a page.
+    fn build_shading_resource_dict(builder: &PdfBuilder) -> String {
+        let mut entries: Vec<(&String, usize)> = builder
+            .shading_map
+            .values()
+            .map(|(obj_id, name)| (name, *obj_id))
+            .collect();
+        entries.sort_by_key(|(name, _)| (*name).clone());
+        entries
+            .iter()
+            .map(|(name, obj_id)| format!("/{} {} 0 R", name, obj_id))
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
     /// Build the ExtGState resource dict entries for a page.
     fn build_ext_gstate_resource_dict(&self, builder: &PdfBuilder) -> String {
-        if builder.ext_gstate_map.is_empty() {
+        if builder.ext_gstate_map.is_empty() && builder.shading_masks.is_empty() {
             return String::new();
         }
         let mut entries: Vec<(&String, usize)> = builder
             .ext_gstate_map
             .values()
+            .chain(builder.shading_masks.values())
             .map(|(obj_id, name)| (name, *obj_id))
             .collect();
         entries.sort_by_key(|(name, _)| (*name).clone());
@@ -1509,6 +2042,10 @@ impl PdfWriter {
                 let color_space_str = match color_space {
                     JpegColorSpace::DeviceRGB => "/DeviceRGB",
                     JpegColorSpace::DeviceGray => "/DeviceGray",
+                    JpegColorSpace::DeviceCMYK { inverted: false } => "/DeviceCMYK",
+                    JpegColorSpace::DeviceCMYK { inverted: true } => {
+                        "/DeviceCMYK /Decode [1 0 1 0 1 0 1 0]"
+                    }
                 };
 
                 let obj_id = builder.objects.len();
@@ -1537,30 +2074,9 @@ impl PdfWriter {
 
             ImagePixelData::Decoded { rgb, alpha } => {
                 // Write SMask first if alpha channel exists
-                let smask_id = alpha.as_ref().map(|alpha_data| {
-                    let compressed_alpha = compress_to_vec_zlib(alpha_data, 6);
-                    let smask_obj_id = builder.objects.len();
-                    let mut smask_data: Vec<u8> = Vec::new();
-                    let _ = write!(
-                        smask_data,
-                        "<< /Type /XObject /Subtype /Image \
-                         /Width {} /Height {} \
-                         /ColorSpace /DeviceGray \
-                         /BitsPerComponent 8 \
-                         /Filter /FlateDecode \
-                         /Length {} >>\nstream\n",
-                        image.width_px,
-                        image.height_px,
-                        compressed_alpha.len()
-                    );
-                    smask_data.extend_from_slice(&compressed_alpha);
-                    smask_data.extend_from_slice(b"\nendstream");
-                    builder.objects.push(PdfObject {
-                        id: smask_obj_id,
-                        data: smask_data,
-                    });
-                    smask_obj_id
-                });
+                let smask_id = alpha
+                    .as_ref()
+                    .map(|alpha_data| Self::write_smask(builder, image, alpha_data));
 
                 // Write main RGB image XObject
                 let compressed_rgb = compress_to_vec_zlib(rgb, 6);
@@ -1592,9 +2108,87 @@ impl PdfWriter {
                 });
                 obj_id
             }
+
+            ImagePixelData::Indexed {
+                palette,
+                bits_per_component,
+                indices,
+                alpha,
+            } => {
+                let smask_id = alpha
+                    .as_ref()
+                    .map(|alpha_data| Self::write_smask(builder, image, alpha_data));
+
+                // The palette as a hex string lookup table
+                let mut lookup = String::with_capacity(palette.len() * 2);
+                for byte in palette {
+                    let _ = write!(lookup, "{:02X}", byte);
+                }
+                let compressed_indices = compress_to_vec_zlib(indices, 6);
+                let obj_id = builder.objects.len();
+                let mut obj_data: Vec<u8> = Vec::new();
+
+                let smask_ref = smask_id
+                    .map(|id| format!(" /SMask {} 0 R", id))
+                    .unwrap_or_default();
+
+                let _ = write!(
+                    obj_data,
+                    "<< /Type /XObject /Subtype /Image \
+                     /Width {} /Height {} \
+                     /ColorSpace [/Indexed /DeviceRGB {} <{}>] \
+                     /BitsPerComponent {} \
+                     /Filter /FlateDecode \
+                     /Length {}{} >>\nstream\n",
+                    image.width_px,
+                    image.height_px,
+                    (palette.len() / 3).max(1) - 1,
+                    lookup,
+                    bits_per_component,
+                    compressed_indices.len(),
+                    smask_ref
+                );
+                obj_data.extend_from_slice(&compressed_indices);
+                obj_data.extend_from_slice(b"\nendstream");
+                builder.objects.push(PdfObject {
+                    id: obj_id,
+                    data: obj_data,
+                });
+                obj_id
+            }
         }
     }
 
+    /// Write an image's alpha channel as an SMask XObject. Returns its ID.
+    fn write_smask(
+        builder: &mut PdfBuilder,
+        image: &crate::image_loader::LoadedImage,
+        alpha: &[u8],
+    ) -> usize {
+        let compressed_alpha = compress_to_vec_zlib(alpha, 6);
+        let smask_obj_id = builder.objects.len();
+        let mut smask_data: Vec<u8> = Vec::new();
+        let _ = write!(
+            smask_data,
+            "<< /Type /XObject /Subtype /Image \
+             /Width {} /Height {} \
+             /ColorSpace /DeviceGray \
+             /BitsPerComponent 8 \
+             /Filter /FlateDecode \
+             /Length {} >>\nstream\n",
+            image.width_px,
+            image.height_px,
+            compressed_alpha.len()
+        );
+        smask_data.extend_from_slice(&compressed_alpha);
+        smask_data.extend_from_slice(b"\nendstream");
+        builder.objects.push(PdfObject {
+            id: smask_obj_id,
+            data: smask_data,
+        });
+        smask_obj_id
+    }
+
     /// Build the /XObject resource dict entries for a specific page.
     fn build_xobject_resource_dict(&self, page_idx: usize, builder: &PdfBuilder) -> String {
         let mut entries: Vec<(usize, usize)> = Vec::new();
@@ -1616,16 +2210,18 @@ impl PdfWriter {
             .join(" ")
     }
 
-    /// Write the 5 CIDFont PDF objects for a custom TrueType font.
+    /// Write the 5 CIDFont PDF objects for a custom TrueType or OpenType (CFF) font.
     /// Returns the object ID of the Type0 root font dictionary.
     ///
+    /// `variations`: axis coordinates of the instance to embed, empty for a static font.
     /// `used_glyph_ids`: original glyph IDs from shaping (from PositionedGlyph.glyph_id).
-    /// `used_chars`: characters used (for char→gid fallback, e.g., page number placeholders).
+    /// `used_chars`: characters used (for char→gid fallback).
     /// `glyph_to_char_map`: maps original glyph ID → first Unicode char (for ToUnicode CMap).
     fn write_custom_font_objects(
         builder: &mut PdfBuilder,
         key: &FontKey,
         ttf_data: &[u8],
+        variations: &[Variation],
         used_glyph_ids: HashSet<u16>,
         used_chars: HashSet<char>,
         glyph_to_char_map: HashMap<u16, char>,
@@ -1641,7 +2237,7 @@ impl PdfWriter {
         let ascender = face.ascender();
         let descender = face.descender();
 
-        // Build char → original glyph ID mapping (for fallback/placeholders)
+        // Build char → original glyph ID mapping (for fallback)
         let mut char_to_orig_gid: HashMap<char, u16> = HashMap::new();
         for &ch in &used_chars {
             if let Some(gid) = face.glyph_index(ch) {
@@ -1651,24 +2247,29 @@ impl PdfWriter {
 
         // Combine shaped glyph IDs + char-based glyph IDs for subsetting.
         // This ensures ligature glyphs (from shaping) AND individual char glyphs
-        // (for placeholder fallback) are all included.
+        // (for the char→gid fallback) are all included.
         let mut all_orig_gids: HashSet<u16> = used_glyph_ids.clone();
         for &gid in char_to_orig_gid.values() {
             all_orig_gids.insert(gid);
         }
 
         // Subset the font to only include used glyphs
-        let (embed_ttf, gid_remap) = match subset_ttf(ttf_data, &all_orig_gids) {
-            Ok(subset_result) => (subset_result.ttf_data, subset_result.gid_remap),
+        let (embed_ttf, gid_remap, format) = match subset_font(ttf_data, &all_orig_gids, variations)
+        {
+            Ok(subset_result) => (
+                subset_result.ttf_data,
+                subset_result.gid_remap,
+                subset_result.format,
+            ),
             Err(_) => {
                 // Subsetting failed — fall back to embedding the full font (identity remap)
                 let identity: HashMap<u16, u16> =
                     all_orig_gids.iter().map(|&gid| (gid, gid)).collect();
-                (ttf_data.to_vec(), identity)
+                (ttf_data.to_vec(), identity, FontFormat::of(&face))
             }
         };
 
-        // Build char→new_gid mapping (for placeholder fallback in content stream)
+        // Build char→new_gid mapping (for the fallback in content streams)
         let char_to_gid: HashMap<char, u16> = char_to_orig_gid
             .iter()
             .filter_map(|(&ch, &orig_gid)| gid_remap.get(&orig_gid).map(|&new_gid| (ch, new_gid)))
@@ -1690,23 +2291,32 @@ impl PdfWriter {
             new_gid_to_char.entry(new_gid).or_insert(ch);
         }
 
-        let pdf_font_name = Self::sanitize_font_name(&key.family, key.weight, key.italic);
+        let pdf_font_name =
+            Self::sanitize_font_name(&key.family, key.weight, key.stretch, key.italic);
 
-        // 1. FontFile2 stream — compressed subset TTF bytes
+        // 1. Font file stream — compressed subset TTF bytes (FontFile2), or
+        // an OpenType file with CFF outlines (FontFile3)
         let compressed_ttf = compress_to_vec_zlib(&embed_ttf, 6);
-        let fontfile2_id = builder.objects.len();
-        let mut fontfile2_data: Vec<u8> = Vec::new();
-        let _ = write!(
-            fontfile2_data,
-            "<< /Length {} /Length1 {} /Filter /FlateDecode >>\nstream\n",
-            compressed_ttf.len(),
-            embed_ttf.len()
-        );
-        fontfile2_data.extend_from_slice(&compressed_ttf);
-        fontfile2_data.extend_from_slice(b"\nendstream");
+        let font_file_id = builder.objects.len();
+        let mut font_file_data: Vec<u8> = Vec::new();
+        let _ = match format {
+            FontFormat::TrueType => write!(
+                font_file_data,
+                "<< /Length {} /Length1 {} /Filter /FlateDecode >>\nstream\n",
+                compressed_ttf.len(),
+                embed_ttf.len()
+            ),
+            FontFormat::OpenTypeCff => write!(
+                font_file_data,
+                "<< /Length {} /Subtype /OpenType /Filter /FlateDecode >>\nstream\n",
+                compressed_ttf.len(),
+            ),
+        };
+        font_file_data.extend_from_slice(&compressed_ttf);
+        font_file_data.extend_from_slice(b"\nendstream");
         builder.objects.push(PdfObject {
-            id: fontfile2_id,
-            data: fontfile2_data,
+            id: font_file_id,
+            data: font_file_data,
         });
 
         // Parse the subset font for metrics (width array uses subset GIDs)
@@ -1733,7 +2343,7 @@ impl PdfWriter {
             "<< /Type /FontDescriptor /FontName /{} /Flags {} \
              /FontBBox {} /ItalicAngle {} \
              /Ascent {} /Descent {} /CapHeight {} /StemV {} \
-             /FontFile2 {} 0 R >>",
+             /{} {} 0 R >>",
             pdf_font_name,
             flags,
             bbox_str,
@@ -1742,7 +2352,11 @@ impl PdfWriter {
             (descender as f64 * scale) as i32,
             cap_height as i32,
             stem_v,
-            fontfile2_id,
+            match format {
+                FontFormat::TrueType => "FontFile2",
+                FontFormat::OpenTypeCff => "FontFile3",
+            },
+            font_file_id,
         );
         builder.objects.push(PdfObject {
             id: font_descriptor_id,
@@ -1757,12 +2371,18 @@ impl PdfWriter {
             .glyph_hor_advance(ttf_parser::GlyphId(0))
             .map(|adv| (adv as f64 * 1000.0 / subset_upem as f64) as u32)
             .unwrap_or(1000);
+        // CIDs are the subset's glyph IDs. A CIDFontType0 has no
+        // CIDToGIDMap: a font program that isn't CID-keyed uses CIDs as glyph
+        // IDs, and a CID-keyed subset's charset maps each glyph to its own ID.
+        let (cid_subtype, cid_to_gid_map) = match format {
+            FontFormat::TrueType => ("CIDFontType2", " /CIDToGIDMap /Identity"),
+            FontFormat::OpenTypeCff => ("CIDFontType0", ""),
+        };
         let cidfont_dict = format!(
-            "<< /Type /Font /Subtype /CIDFontType2 /BaseFont /{} \
+            "<< /Type /Font /Subtype /{} /BaseFont /{} \
              /CIDSystemInfo << /Registry (Adobe) /Ordering (Identity) /Supplement 0 >> \
-             /FontDescriptor {} 0 R /DW {} /W {} \
-             /CIDToGIDMap /Identity >>",
-            pdf_font_name, font_descriptor_id, default_width, w_array,
+             /FontDescriptor {} 0 R /DW {} /W {}{} >>",
+            cid_subtype, pdf_font_name, font_descriptor_id, default_width, w_array, cid_to_gid_map,
         );
         builder.objects.push(PdfObject {
             id: cidfont_id,
@@ -1892,15 +2512,22 @@ impl PdfWriter {
     }
 
     /// Sanitize a font name for use as a PDF name object.
-    /// Strips spaces and special characters, appends weight/style suffixes.
-    fn sanitize_font_name(family: &str, weight: u32, italic: bool) -> String {
+    /// Strips spaces and special characters, appends weight/width/style suffixes.
+    fn sanitize_font_name(family: &str, weight: u32, stretch: u32, italic: bool) -> String {
         let mut name: String = family
             .chars()
             .filter(|c| c.is_alphanumeric() || *c == '-' || *c == '_')
             .collect();
 
-        if weight >= 700 {
-            name.push_str("-Bold");
+        match weight {
+            400 => {}
+            700 => name.push_str("-Bold"),
+            _ => {
+                let _ = write!(name, "-Wght{}", weight);
+            }
+        }
+        if stretch != 100 {
+            let _ = write!(name, "-Wdth{}", stretch);
         }
         if italic {
             name.push_str("-Italic");
@@ -1923,27 +2550,21 @@ impl PdfWriter {
             .join(" ")
     }
 
-    /// Look up the font index (/F0, /F1, etc.) for a given family+weight+style.
-    fn font_index(
-        &self,
-        family: &str,
-        weight: u32,
-        font_style: FontStyle,
-        font_objects: &[(FontKey, usize)],
-    ) -> usize {
-        let italic = matches!(font_style, FontStyle::Italic | FontStyle::Oblique);
-        let snapped_weight = if weight >= 600 { 700 } else { 400 };
+    /// Look up the font index (/F0, /F1, etc.) for the font that draws a glyph.
+    fn font_index(&self, font_key: &FontKey, font_objects: &[(FontKey, usize)]) -> usize {
+        let snapped_weight = if font_key.weight >= 600 { 700 } else { 400 };
 
         // Exact match
-        for (i, (key, _)) in font_objects.iter().enumerate() {
-            if key.family == family && key.weight == snapped_weight && key.italic == italic {
-                return i;
-            }
+        if let Some(i) = font_objects.iter().position(|(key, _)| key == font_key) {
+            return i;
         }
 
         // Fallback: try Helvetica with same weight/style
         for (i, (key, _)) in font_objects.iter().enumerate() {
-            if key.family == "Helvetica" && key.weight == snapped_weight && key.italic == italic {
+            if key.family == "Helvetica"
+                && key.weight == snapped_weight
+                && key.italic == font_key.italic
+            {
                 return i;
             }
         }
@@ -1952,6 +2573,139 @@ impl PdfWriter {
         0
     }
 
+    /// Switch between filling glyphs and filling and stroking them for
+    /// synthetic bold, returning the extra character spacing the stroke
+    /// needs. `stroking` tracks the text render mode already set.
+    fn write_synthetic_bold(
+        stream: &mut String,
+        bold: bool,
+        stroking: &mut bool,
+        font_size: f64,
+        color: Color,
+    ) -> f64 {
+        if !bold {
+            if *stroking {
+                let _ = writeln!(stream, "0 Tr");
+                *stroking = false;
+            }
+            return 0.0;
+        }
+        let stroke = font_size * SYNTHETIC_BOLD_STROKE;
+        let _ = writeln!(
+            stream,
+            "{:.3} {:.3} {:.3} RG\n{:.3} w",
+            color.r, color.g, color.b, stroke
+        );
+        if !*stroking {
+            let _ = writeln!(stream, "2 Tr");
+            *stroking = true;
+        }
+        stroke
+    }
+
+    /// The string operand that shows `glyphs` in the font `font_key`: glyph
+    /// IDs for embedded fonts, WinAnsi bytes for the standard fonts.
+    fn text_operand(
+        builder: &PdfBuilder,
+        font_key: &FontKey,
+        glyphs: &[&PositionedGlyph],
+    ) -> String {
+        if !builder.custom_font_data.contains_key(font_key) {
+            let mut text_str = String::new();
+            for ch in glyphs.iter().map(|g| g.char_value) {
+                let b = Self::unicode_to_winansi(ch).unwrap_or(b'?');
+                match b {
+                    b'\\' => text_str.push_str("\\\\"),
+                    b'(' => text_str.push_str("\\("),
+                    b')' => text_str.push_str("\\)"),
+                    0x20..=0x7E => text_str.push(b as char),
+                    _ => {
+                        let _ = write!(text_str, "\\{:03o}", b);
+                    }
+                }
+            }
+            return format!("({})", text_str);
+        }
+        let mut hex = String::new();
+        if let Some(embed_data) = builder.custom_font_data.get(font_key) {
+            // Shaped text: use glyph IDs directly (remapped through subset)
+            for g in glyphs {
+                let new_gid = embed_data
+                    .gid_remap
+                    .get(&g.glyph_id)
+                    .copied()
+                    .unwrap_or_else(|| {
+                        // Fallback: try char→gid
+                        embed_data
+                            .char_to_gid
+                            .get(&g.char_value)
+                            .copied()
+                            .unwrap_or(0)
+                    });
+                let _ = write!(hex, "{:04X}", new_gid);
+            }
+        }
+        format!("<{}>", hex)
+    }
+
+    /// Draw a column of vertical text, one glyph at a time down the column
+    /// centered on `line.x`. CJK glyphs stand upright, each in a one-em box;
+    /// other glyphs are turned 90° clockwise to lie along the column.
+    fn write_vertical_line(
+        &self,
+        stream: &mut String,
+        line: &TextLine,
+        color: Color,
+        page_height: f64,
+        builder: &PdfBuilder,
+    ) {
+        let _ = writeln!(stream, "q\nBT");
+        let mut stroking = false;
+        // Justification added to the glyphs above the current one
+        let mut extra_spacing = 0.0;
+        for glyph in &line.glyphs {
+            let glyph_color = glyph.color.unwrap_or(color);
+            let font_key = builder.drawn_font_key(glyph);
+            let idx = self.font_index(&font_key, &builder.font_objects);
+            let size = glyph.font_size;
+            Self::write_synthetic_bold(
+                stream,
+                builder.synthetic_style(glyph).bold,
+                &mut stroking,
+                size,
+                glyph_color,
+            );
+            let _ = writeln!(
+                stream,
+                "{:.3} {:.3} {:.3} rg\n/F{} {:.1} Tf",
+                glyph_color.r, glyph_color.g, glyph_color.b, idx, size
+            );
+            let top = page_height - line.y - glyph.x_offset - extra_spacing;
+            if is_cjk(glyph.char_value) {
+                let _ = writeln!(
+                    stream,
+                    "1 0 0 1 {:.2} {:.2} Tm",
+                    line.x - glyph.x_advance / 2.0,
+                    top - size * VERTICAL_EM_BOX_ASCENT
+                );
+            } else {
+                let _ = writeln!(
+                    stream,
+                    "0 -1 1 0 {:.2} {:.2} Tm",
+                    line.x - size * VERTICAL_ROTATED_BASELINE,
+                    top
+                );
+            }
+            let operand = Self::text_operand(builder, &font_key, &[glyph]);
+            let _ = writeln!(stream, "{} Tj", operand);
+            extra_spacing += line.char_spacing;
+            if glyph.char_value == ' ' {
+                extra_spacing += line.word_spacing;
+            }
+        }
+        let _ = writeln!(stream, "ET\nQ");
+    }
+
     /// Group consecutive glyphs by (font_family, font_weight, font_style, font_size, color)
     /// for multi-font text run rendering.
     fn group_glyphs_by_style(glyphs: &[PositionedGlyph]) -> Vec<Vec<&PositionedGlyph>> {
@@ -1966,9 +2720,11 @@ impl PdfWriter {
             let prev = current_group.last().unwrap();
             let same_style = glyph.font_family == prev.font_family
                 && glyph.font_weight == prev.font_weight
+                && glyph.font_stretch == prev.font_stretch
                 && std::mem::discriminant(&glyph.font_style)
                     == std::mem::discriminant(&prev.font_style)
                 && (glyph.font_size - prev.font_size).abs() < 0.01
+                && (glyph.baseline_shift - prev.baseline_shift).abs() < 0.01
                 && Self::colors_equal(&glyph.color, &prev.color);
 
             if same_style {
@@ -2036,15 +2792,44 @@ impl PdfWriter {
                     title: title.clone(),
                     page_obj_id,
                     y_pdf,
+                    level: element.bookmark_level,
                 });
             }
             Self::collect_bookmarks(&element.children, page_height, page_obj_id, bookmarks);
         }
     }
 
-    /// Build the PDF outline tree from bookmark entries.
+    /// Collect where each node `id` starts. The first element carrying an
+    /// id wins.
+    fn collect_destinations(
+        elements: &[LayoutElement],
+        page_height: f64,
+        page_obj_id: usize,
+        destinations: &mut HashMap<String, PdfDestination>,
+    ) {
+        for element in elements {
+            if let Some(ref id) = element.node_id {
+                destinations
+                    .entry(id.clone())
+                    .or_insert_with(|| PdfDestination {
+                        page_obj_id,
+                        y_pdf: page_height - element.y,
+                    });
+            }
+            Self::collect_destinations(&element.children, page_height, page_obj_id, destinations);
+        }
+    }
+
+    /// Build the PDF outline tree from bookmark entries, nesting each entry
+    /// under the nearest earlier one with a smaller level. Entries nested
+    /// `open_depth` or more levels deep start collapsed.
     /// Returns the object ID of the /Outlines dictionary.
-    fn write_outline_tree(&self, builder: &mut PdfBuilder, bookmarks: &[PdfBookmark]) -> usize {
+    fn write_outline_tree(
+        &self,
+        builder: &mut PdfBuilder,
+        bookmarks: &[PdfBookmark],
+        open_depth: Option<u32>,
+    ) -> usize {
         // Reserve the Outlines dictionary object
         let outlines_id = builder.objects.len();
         builder.objects.push(PdfObject {
@@ -2063,33 +2848,89 @@ impl PdfWriter {
             item_ids.push(item_id);
         }
 
-        // Fill in outline items with /Prev, /Next, /Parent, /Dest
-        for (i, (bm, &item_id)) in bookmarks.iter().zip(item_ids.iter()).enumerate() {
-            let mut dict = format!(
-                "<< /Title ({}) /Parent {} 0 R /Dest [{} 0 R /XYZ 0 {:.2} null]",
-                Self::escape_pdf_string(&bm.title),
-                outlines_id,
-                bm.page_obj_id,
-                bm.y_pdf,
-            );
-            if i > 0 {
-                let _ = write!(dict, " /Prev {} 0 R", item_ids[i - 1]);
+        // Find each entry's parent and depth in the tree
+        let mut parents: Vec<Option<usize>> = Vec::with_capacity(bookmarks.len());
+        let mut depths: Vec<u32> = Vec::with_capacity(bookmarks.len());
+        let mut ancestors: Vec<usize> = Vec::new();
+        for (i, bm) in bookmarks.iter().enumerate() {
+            while ancestors
+                .last()
+                .is_some_and(|&a| bookmarks[a].level >= bm.level)
+            {
+                ancestors.pop();
             }
-            if i + 1 < item_ids.len() {
-                let _ = write!(dict, " /Next {} 0 R", item_ids[i + 1]);
+            parents.push(ancestors.last().copied());
+            depths.push(ancestors.len() as u32);
+            ancestors.push(i);
+        }
+
+        let mut children: Vec<Vec<usize>> = vec![Vec::new(); bookmarks.len()];
+        let mut roots: Vec<usize> = Vec::new();
+        for (i, parent) in parents.iter().enumerate() {
+            match parent {
+                Some(p) => children[*p].push(i),
+                None => roots.push(i),
+            }
+        }
+
+        // Descendants shown when an entry is expanded. Children always come
+        // after their parent, so fill these in from the end.
+        let is_open = |i: usize| open_depth.is_none_or(|d| depths[i] < d);
+        let mut expanded_counts = vec![0i64; bookmarks.len()];
+        for i in (0..bookmarks.len()).rev() {
+            expanded_counts[i] = children[i]
+                .iter()
+                .map(|&c| 1 + if is_open(c) { expanded_counts[c] } else { 0 })
+                .sum();
+        }
+
+        // Fill in outline items with /Parent, /Prev, /Next, /First, /Last,
+        // /Count and /Dest
+        for siblings in std::iter::once(&roots).chain(children.iter()) {
+            for (k, &i) in siblings.iter().enumerate() {
+                let bm = &bookmarks[i];
+                let parent_id = parents[i].map_or(outlines_id, |p| item_ids[p]);
+                let mut dict = format!(
+                    "<< /Title ({}) /Parent {} 0 R /Dest [{} 0 R /XYZ 0 {:.2} null]",
+                    Self::escape_pdf_string(&bm.title),
+                    parent_id,
+                    bm.page_obj_id,
+                    bm.y_pdf,
+                );
+                if k > 0 {
+                    let _ = write!(dict, " /Prev {} 0 R", item_ids[siblings[k - 1]]);
+                }
+                if k + 1 < siblings.len() {
+                    let _ = write!(dict, " /Next {} 0 R", item_ids[siblings[k + 1]]);
+                }
+                if let (Some(&first), Some(&last)) = (children[i].first(), children[i].last()) {
+                    // Positive when expanded, negative when collapsed
+                    let count = if is_open(i) {
+                        expanded_counts[i]
+                    } else {
+                        -expanded_counts[i]
+                    };
+                    let _ = write!(
+                        dict,
+                        " /First {} 0 R /Last {} 0 R /Count {}",
+                        item_ids[first], item_ids[last], count
+                    );
+                }
+                dict.push_str(" >>");
+                builder.objects[item_ids[i]].data = dict.into_bytes();
             }
-            dict.push_str(" >>");
-            builder.objects[item_id].data = dict.into_bytes();
         }
 
         // Fill in Outlines dictionary
-        let first_id = item_ids.first().copied().unwrap_or(0);
-        let last_id = item_ids.last().copied().unwrap_or(0);
+        let first_id = roots.first().map_or(0, |&i| item_ids[i]);
+        let last_id = roots.last().map_or(0, |&i| item_ids[i]);
+        let visible: i64 = roots
+            .iter()
+            .map(|&r| 1 + if is_open(r) { expanded_counts[r] } else { 0 })
+            .sum();
         let outlines_dict = format!(
             "<< /Type /Outlines /First {} 0 R /Last {} 0 R /Count {} >>",
-            first_id,
-            last_id,
-            bookmarks.len()
+            first_id, last_id, visible
         );
         builder.objects[outlines_id].data = outlines_dict.into_bytes();
 
@@ -2211,6 +3052,29 @@ mod tests {
     use super::*;
     use crate::font::FontContext;
 
+    /// The decompressed contents of every Flate stream in `pdf`, joined.
+    fn decompress_content_streams(pdf: &[u8]) -> String {
+        let marker = b"/FlateDecode >>\nstream\n";
+        let mut content = String::new();
+        let mut pos = 0;
+        while let Some(start) = pdf[pos..]
+            .windows(marker.len())
+            .position(|w| w == marker)
+            .map(|i| pos + i + marker.len())
+        {
+            let len = pdf[start..]
+                .windows(10)
+                .position(|w| w == b"\nendstream")
+                .unwrap_or(pdf.len() - start);
+            if let Ok(data) = miniz_oxide::inflate::decompress_to_vec_zlib(&pdf[start..start + len])
+            {
+                content.push_str(&String::from_utf8_lossy(&data));
+            }
+            pos = start + len;
+        }
+        content
+    }
+
     #[test]
     fn test_escape_pdf_string() {
         assert_eq!(
@@ -2232,6 +3096,8 @@ mod tests {
             fixed_footer: vec![],
             watermarks: vec![],
             config: PageConfig::default(),
+            numbering: None,
+            footnotes: vec![],
         }];
         let metadata = Metadata::default();
         let bytes = writer
@@ -2256,6 +3122,8 @@ mod tests {
             fixed_footer: vec![],
             watermarks: vec![],
             config: PageConfig::default(),
+            numbering: None,
+            footnotes: vec![],
         }];
         let metadata = Metadata {
             title: Some("Test Document".to_string()),
@@ -2263,6 +3131,7 @@ mod tests {
             subject: None,
             creator: None,
             lang: None,
+            outline_open_depth: None,
         };
         let bytes = writer
             .write(&pages, &metadata, &font_context, false, None, None)
@@ -2302,15 +3171,20 @@ mod tests {
                                 font_size: 12.0,
                                 font_family: "Helvetica".to_string(),
                                 font_weight: 400,
+                                font_stretch: 100,
                                 font_style: FontStyle::Normal,
+                                font_features: Default::default(),
                                 char_value: 'A',
                                 color: None,
                                 href: None,
                                 text_decoration: TextDecoration::None,
                                 letter_spacing: 0.0,
                                 cluster_text: None,
+                                baseline_shift: 0.0,
                             }],
                             word_spacing: 0.0,
+                            char_spacing: 0.0,
+                            vertical: false,
                         }],
                         color: Color::BLACK,
                         text_decoration: TextDecoration::None,
@@ -2322,6 +3196,8 @@ mod tests {
                     source_location: None,
                     href: None,
                     bookmark: None,
+                    bookmark_level: 0,
+                    node_id: None,
                     alt: None,
                     is_header_row: false,
                     overflow: Overflow::default(),
@@ -2345,15 +3221,20 @@ mod tests {
                                 font_size: 12.0,
                                 font_family: "Helvetica".to_string(),
                                 font_weight: 700,
+                                font_stretch: 100,
                                 font_style: FontStyle::Normal,
+                                font_features: Default::default(),
                                 char_value: 'A',
                                 color: None,
                                 href: None,
                                 text_decoration: TextDecoration::None,
                                 letter_spacing: 0.0,
                                 cluster_text: None,
+                                baseline_shift: 0.0,
                             }],
                             word_spacing: 0.0,
+                            char_spacing: 0.0,
+                            vertical: false,
                         }],
                         color: Color::BLACK,
                         text_decoration: TextDecoration::None,
@@ -2365,6 +3246,8 @@ mod tests {
                     source_location: None,
                     href: None,
                     bookmark: None,
+                    bookmark_level: 0,
+                    node_id: None,
                     alt: None,
                     is_header_row: false,
                     overflow: Overflow::default(),
@@ -2374,6 +3257,8 @@ mod tests {
             fixed_footer: vec![],
             watermarks: vec![],
             config: PageConfig::default(),
+            numbering: None,
+            footnotes: vec![],
         }];
 
         let metadata = Metadata::default();
@@ -2393,29 +3278,447 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_variable_font_instances_embedded_separately() {
+        let mut font_context = FontContext::new();
+        font_context.registry_mut().register(
+            "Flex",
+            400,
+            false,
+            crate::font::variable::tests::make_variable_font(),
+        );
+        let text = |weight| {
+            Node::text(
+                "Hello",
+                crate::style::Style {
+                    font_family: Some("Flex".to_string()),
+                    font_weight: Some(weight),
+                    ..Default::default()
+                },
+            )
+        };
+        let doc = Document {
+            children: vec![text(400), text(650), text(1000), text(950)],
+            metadata: Metadata::default(),
+            default_page: PageConfig::default(),
+            fonts: vec![],
+            tagged: false,
+            pdfa: None,
+            font_synthesis: Default::default(),
+            footnote_numbering: Default::default(),
+            image_policy: Default::default(),
+            default_style: None,
+            embedded_data: None,
+        };
+        let pages = crate::layout::LayoutEngine::new().layout(&doc, &font_context);
+        let bytes = PdfWriter::new()
+            .write(&pages, &doc.metadata, &font_context, false, None, None)
+            .unwrap();
+        let text = String::from_utf8_lossy(&bytes);
+
+        // 1000 and 950 both clamp to the 900 instance
+        for name in ["Flex", "Flex-Wght650", "Flex-Wght900"] {
+            let type0 = format!("/Subtype /Type0 /BaseFont /{} ", name);
+            assert_eq!(text.matches(&type0).count(), 1, "{}", name);
+        }
+        assert_eq!(text.matches("/Subtype /Type0").count(), 3);
+    }
+
+    #[test]
+    fn test_missing_faces_drawn_with_synthetic_styles() {
+        let render = |synthesis: FontSynthesis| {
+            let mut font_context = FontContext::new();
+            font_context.set_font_synthesis(synthesis);
+            font_context.registry_mut().register(
+                "Body",
+                400,
+                false,
+                include_bytes!("../../fonts/NotoSans-Regular.ttf").to_vec(),
+            );
+            let doc = Document {
+                children: vec![Node::text(
+                    "Hello",
+                    crate::style::Style {
+                        font_family: Some("Body".to_string()),
+                        font_weight: Some(700),
+                        font_style: Some(FontStyle::Italic),
+                        ..Default::default()
+                    },
+                )],
+                metadata: Metadata::default(),
+                default_page: PageConfig::default(),
+                fonts: vec![],
+                tagged: false,
+                pdfa: None,
+                font_synthesis: synthesis,
+                footnote_numbering: Default::default(),
+                image_policy: Default::default(),
+                default_style: None,
+                embedded_data: None,
+            };
+            let pages = crate::layout::LayoutEngine::new().layout(&doc, &font_context);
+            PdfWriter::new()
+                .write(&pages, &doc.metadata, &font_context, false, None, None)
+                .unwrap()
+        };
+
+        // The regular face is embedded once, stroked and slanted
+        let bytes = render(FontSynthesis::default());
+        let text = String::from_utf8_lossy(&bytes);
+        assert_eq!(text.matches("/Subtype /Type0").count(), 1);
+        assert!(text.contains("/BaseFont /Body "));
+        let content = decompress_content_streams(&bytes);
+        assert!(content.contains("2 Tr"), "{}", content);
+        assert!(content.contains("0.360 w"), "{}", content);
+        assert!(content.contains("1 0 0.2126 1 "), "{}", content);
+
+        let content = decompress_content_streams(&render(FontSynthesis::NONE));
+        assert!(!content.contains("2 Tr"));
+        assert!(!content.contains(" Tm"));
+    }
+
+    #[test]
+    fn test_cff_font_embedded_as_cid_font_type0() {
+        let mut font_context = FontContext::new();
+        font_context.registry_mut().register(
+            "Otf",
+            400,
+            false,
+            crate::font::cff::tests::make_otf(false),
+        );
+        let doc = Document {
+            children: vec![Node::text(
+                "Hello",
+                crate::style::Style {
+                    font_family: Some("Otf".to_string()),
+                    ..Default::default()
+                },
+            )],
+            metadata: Metadata::default(),
+            default_page: PageConfig::default(),
+            fonts: vec![],
+            tagged: false,
+            pdfa: None,
+            font_synthesis: Default::default(),
+            footnote_numbering: Default::default(),
+            image_policy: Default::default(),
+            default_style: None,
+            embedded_data: None,
+        };
+        let pages = crate::layout::LayoutEngine::new().layout(&doc, &font_context);
+        let bytes = PdfWriter::new()
+            .write(&pages, &doc.metadata, &font_context, false, None, None)
+            .unwrap();
+        let text = String::from_utf8_lossy(&bytes);
+
+        assert!(text.contains("/Subtype /CIDFontType0 /BaseFont /Otf "));
+        assert!(text.contains("/FontFile3 "));
+        assert!(text.contains("/Subtype /OpenType"));
+        assert!(!text.contains("CIDFontType2"));
+        assert!(!text.contains("/CIDToGIDMap"));
+    }
+
+    #[test]
+    fn test_vertical_text_turns_latin_and_keeps_cjk_upright() {
+        let font_context = FontContext::new();
+        let doc = Document {
+            children: vec![Node::text(
+                "AB\u{65E5}\u{672C}",
+                crate::style::Style {
+                    writing_mode: Some(crate::style::WritingMode::VerticalRl),
+                    ..Default::default()
+                },
+            )],
+            metadata: Metadata::default(),
+            default_page: PageConfig::default(),
+            fonts: vec![],
+            tagged: false,
+            pdfa: None,
+            font_synthesis: Default::default(),
+            footnote_numbering: Default::default(),
+            image_policy: Default::default(),
+            default_style: None,
+            embedded_data: None,
+        };
+        let pages = crate::layout::LayoutEngine::new().layout(&doc, &font_context);
+        let bytes = PdfWriter::new()
+            .write(&pages, &doc.metadata, &font_context, false, None, None)
+            .unwrap();
+        let content = decompress_content_streams(&bytes);
+        assert_eq!(content.matches("0 -1 1 0 ").count(), 2, "{}", content);
+        assert_eq!(content.matches("1 0 0 1 ").count(), 2, "{}", content);
+    }
+
+    #[test]
+    fn test_superscript_run_raised_with_text_rise() {
+        let font_context = FontContext::new();
+        let run = |content: &str, vertical_align| TextRun {
+            content: content.to_string(),
+            style: crate::style::Style {
+                vertical_align,
+                ..Default::default()
+            },
+            href: None,
+            footnote: None,
+        };
+        let mut text = Node::text("", crate::style::Style::default());
+        text.kind = NodeKind::Text {
+            content: String::new(),
+            href: None,
+            runs: vec![
+                run("E = mc", None),
+                run("2", Some(crate::style::VerticalAlign::Super)),
+            ],
+        };
+        let doc = Document {
+            children: vec![text],
+            metadata: Metadata::default(),
+            default_page: PageConfig::default(),
+            fonts: vec![],
+            tagged: false,
+            pdfa: None,
+            font_synthesis: Default::default(),
+            footnote_numbering: Default::default(),
+            image_policy: Default::default(),
+            default_style: None,
+            embedded_data: None,
+        };
+        let pages = crate::layout::LayoutEngine::new().layout(&doc, &font_context);
+        let bytes = PdfWriter::new()
+            .write(&pages, &doc.metadata, &font_context, false, None, None)
+            .unwrap();
+        let content = decompress_content_streams(&bytes);
+        // Raised by a third of the 12pt text, then reset for later text
+        assert!(content.contains("3.96 Ts"), "{}", content);
+        assert!(content.contains("0 Ts\nET"), "{}", content);
+    }
+
+    #[test]
+    fn test_cropped_image_clipped_to_its_box() {
+        use base64::Engine;
+        let font_context = FontContext::new();
+        let mut png = Vec::new();
+        image::DynamicImage::new_rgb8(40, 20)
+            .write_to(&mut std::io::Cursor::new(&mut png), image::ImageFormat::Png)
+            .unwrap();
+        let image = |object_fit| Node {
+            kind: NodeKind::Image {
+                src: base64::engine::general_purpose::STANDARD.encode(&png),
+                width: Some(100.0),
+                height: Some(100.0),
+            },
+            style: crate::style::Style {
+                object_fit: Some(object_fit),
+                ..Default::default()
+            },
+            ..Node::view(crate::style::Style::default(), vec![])
+        };
+        let content = |object_fit| {
+            let doc = Document {
+                children: vec![image(object_fit)],
+                metadata: Metadata::default(),
+                default_page: PageConfig::default(),
+                fonts: vec![],
+                tagged: false,
+                pdfa: None,
+                font_synthesis: Default::default(),
+                footnote_numbering: Default::default(),
+                image_policy: Default::default(),
+                default_style: None,
+                embedded_data: None,
+            };
+            let pages = crate::layout::LayoutEngine::new().layout(&doc, &font_context);
+            let bytes = PdfWriter::new()
+                .write(&pages, &doc.metadata, &font_context, false, None, None)
+                .unwrap();
+            decompress_content_streams(&bytes)
+        };
+        // Twice the box's width, centered on it and clipped
+        let cover = content(crate::style::ObjectFit::Cover);
+        assert!(
+            cover.contains("re W n\n200.0000 0 0 100.0000 "),
+            "{}",
+            cover
+        );
+        let contain = content(crate::style::ObjectFit::Contain);
+        assert!(contain.contains("100.0000 0 0 50.0000 "), "{}", contain);
+        assert!(!contain.contains("re W n"), "{}", contain);
+    }
+
+    #[test]
+    fn test_background_gradient_and_tiled_image() {
+        use crate::style::{
+            BackgroundImage, BackgroundRepeat, BackgroundSize, Color, ColorStop, Dimension,
+            LinearGradient, Style,
+        };
+        use base64::Engine;
+        let font_context = FontContext::new();
+        let mut png = Vec::new();
+        image::DynamicImage::new_rgb8(10, 10)
+            .write_to(&mut std::io::Cursor::new(&mut png), image::ImageFormat::Png)
+            .unwrap();
+        let view = |background_image| {
+            Node::view(
+                Style {
+                    width: Some(Dimension::Pt(100.0)),
+                    height: Some(Dimension::Pt(50.0)),
+                    background_image: Some(background_image),
+                    background_size: Some(BackgroundSize::Size {
+                        width: Dimension::Pt(25.0),
+                        height: Dimension::Auto,
+                    }),
+                    background_repeat: Some(BackgroundRepeat::RepeatX),
+                    ..Default::default()
+                },
+                vec![],
+            )
+        };
+        let gradient = BackgroundImage::LinearGradient(LinearGradient {
+            angle: 90.0,
+            stops: vec![
+                ColorStop {
+                    color: Color::BLACK,
+                    offset: None,
+                },
+                ColorStop {
+                    color: Color::WHITE,
+                    offset: None,
+                },
+            ],
+        });
+        let src = base64::engine::general_purpose::STANDARD.encode(&png);
+        let doc = Document {
+            children: vec![view(gradient), view(BackgroundImage::Url(src))],
+            metadata: Metadata::default(),
+            default_page: PageConfig::default(),
+            fonts: vec![],
+            tagged: false,
+            pdfa: None,
+            font_synthesis: Default::default(),
+            footnote_numbering: Default::default(),
+            image_policy: Default::default(),
+            default_style: None,
+            embedded_data: None,
+        };
+        let pages = crate::layout::LayoutEngine::new().layout(&doc, &font_context);
+        let bytes = PdfWriter::new()
+            .write(&pages, &doc.metadata, &font_context, false, None, None)
+            .unwrap();
+        let text = String::from_utf8_lossy(&bytes);
+        assert!(text.contains("/ShadingType 2"), "{}", text);
+        let content = decompress_content_streams(&bytes);
+        assert!(content.contains("re\nW n\n1 0 0 1 "), "{}", content);
+        assert!(content.contains("/Sh0 sh"), "{}", content);
+        // 25pt tiles across the 100pt box, one XObject
+        assert_eq!(content.matches("/Im0 Do").count(), 4, "{}", content);
+        assert!(!text.contains("/Im1"), "{}", text);
+    }
+
+    #[test]
+    fn test_translucent_gradient_stops_drawn_through_soft_mask() {
+        use crate::style::{BackgroundImage, Color, ColorStop, Dimension, LinearGradient, Style};
+        let font_context = FontContext::new();
+        let view = |end: Color| {
+            let gradient = BackgroundImage::LinearGradient(LinearGradient {
+                angle: 90.0,
+                stops: vec![
+                    ColorStop {
+                        color: Color::BLACK,
+                        offset: None,
+                    },
+                    ColorStop {
+                        color: end,
+                        offset: None,
+                    },
+                ],
+            });
+            Node::view(
+                Style {
+                    width: Some(Dimension::Pt(100.0)),
+                    height: Some(Dimension::Pt(50.0)),
+                    background_image: Some(gradient),
+                    ..Default::default()
+                },
+                vec![],
+            )
+        };
+        let translucent = Color {
+            a: 0.25,
+            ..Color::WHITE
+        };
+        let doc = Document {
+            children: vec![view(Color::WHITE), view(translucent)],
+            metadata: Metadata::default(),
+            default_page: PageConfig::default(),
+            fonts: vec![],
+            tagged: false,
+            pdfa: None,
+            font_synthesis: Default::default(),
+            footnote_numbering: Default::default(),
+            image_policy: Default::default(),
+            default_style: None,
+            embedded_data: None,
+        };
+        let pages = crate::layout::LayoutEngine::new().layout(&doc, &font_context);
+        let bytes = PdfWriter::new()
+            .write(&pages, &doc.metadata, &font_context, false, None, None)
+            .unwrap();
+        let text = String::from_utf8_lossy(&bytes);
+        // Stop alphas as a gray shading, masking the color shading
+        assert!(
+            text.contains("/ColorSpace /DeviceGray") && text.contains("/C0 [1.0000] /C1 [0.2500]"),
+            "{}",
+            text
+        );
+        assert!(
+            text.contains("/SMask << /Type /Mask /S /Luminosity /G "),
+            "{}",
+            text
+        );
+        assert!(
+            text.contains("/Group << /S /Transparency /CS /DeviceGray >>"),
+            "{}",
+            text
+        );
+        assert!(text.contains("/ExtGState << /SM0 "), "{}", text);
+        // Same colors, one shading; only the translucent gradient is masked
+        let content = decompress_content_streams(&bytes);
+        assert_eq!(content.matches("/Sh0 sh").count(), 2, "{}", content);
+        assert_eq!(content.matches(" gs\n").count(), 1, "{}", content);
+        assert!(content.contains("/SM0 gs\n/Sh0 sh"), "{}", content);
+        assert!(!text.contains("/SM1"), "{}", text);
+    }
+
     #[test]
     fn test_sanitize_font_name() {
-        assert_eq!(PdfWriter::sanitize_font_name("Inter", 400, false), "Inter");
         assert_eq!(
-            PdfWriter::sanitize_font_name("Inter", 700, false),
+            PdfWriter::sanitize_font_name("Inter", 400, 100, false),
+            "Inter"
+        );
+        assert_eq!(
+            PdfWriter::sanitize_font_name("Inter", 700, 100, false),
             "Inter-Bold"
         );
         assert_eq!(
-            PdfWriter::sanitize_font_name("Inter", 400, true),
+            PdfWriter::sanitize_font_name("Inter", 400, 100, true),
             "Inter-Italic"
         );
         assert_eq!(
-            PdfWriter::sanitize_font_name("Inter", 700, true),
+            PdfWriter::sanitize_font_name("Inter", 700, 100, true),
             "Inter-Bold-Italic"
         );
         assert_eq!(
-            PdfWriter::sanitize_font_name("Noto Sans", 400, false),
+            PdfWriter::sanitize_font_name("Noto Sans", 400, 100, false),
             "NotoSans"
         );
         assert_eq!(
-            PdfWriter::sanitize_font_name("Font (Display)", 400, false),
+            PdfWriter::sanitize_font_name("Font (Display)", 400, 100, false),
             "FontDisplay"
         );
+        assert_eq!(
+            PdfWriter::sanitize_font_name("Inter", 650, 75, false),
+            "Inter-Wght650-Wdth75"
+        );
     }
 
     #[test]
@@ -2503,15 +3806,20 @@ mod tests {
                             font_size: 12.0,
                             font_family: "Helvetica".to_string(),
                             font_weight: 400,
+                            font_stretch: 100,
                             font_style: FontStyle::Normal,
+                            font_features: Default::default(),
                             char_value: 'H',
                             color: None,
                             href: None,
                             text_decoration: TextDecoration::None,
                             letter_spacing: 0.0,
                             cluster_text: None,
+                            baseline_shift: 0.0,
                         }],
                         word_spacing: 0.0,
+                        char_spacing: 0.0,
+                        vertical: false,
                     }],
                     color: Color::BLACK,
                     text_decoration: TextDecoration::None,
@@ -2523,6 +3831,8 @@ mod tests {
                 source_location: None,
                 href: None,
                 bookmark: None,
+                bookmark_level: 0,
+                node_id: None,
                 alt: None,
                 is_header_row: false,
                 overflow: Overflow::default(),
@@ -2531,6 +3841,8 @@ mod tests {
             fixed_footer: vec![],
             watermarks: vec![],
             config: PageConfig::default(),
+            numbering: None,
+            footnotes: vec![],
         }];
 
         let metadata = Metadata::default();
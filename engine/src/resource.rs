@@ -0,0 +1,267 @@
+//! # Resource Resolution
+//!
+//! Image `src` and font `src` strings are either inline data (a data URI
+//! or raw base64) or a reference to an asset stored somewhere else. Inline
+//! data is decoded here; references are handed to a [`ResourceResolver`],
+//! so the caller decides where assets may come from.
+//!
+//! Three resolvers ship with the engine:
+//! - [`FileSystemResolver`] reads files inside one base directory and
+//!   refuses any path that leads outside it
+//! - [`MemoryResolver`] serves assets from an in-memory map
+//! - [`DenyAllResolver`] refuses every reference, for documents that must
+//!   be fully self-contained
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+/// Fetches the bytes of an asset a document refers to by name.
+pub trait ResourceResolver {
+    /// Return the bytes `reference` names, such as `./logo.png` or
+    /// `s3://bucket/logo.png`, or a message saying why they can't be read.
+    fn resolve(&self, reference: &str) -> Result<Vec<u8>, String>;
+}
+
+/// Reads assets from files inside a base directory.
+///
+/// Relative references are resolved against the base directory. Absolute
+/// paths are allowed only when they lie inside it. Paths that lead out of
+/// the base directory, through `..` or a symlink, are refused.
+#[derive(Debug, Clone, Default)]
+pub struct FileSystemResolver {
+    /// `None` means the process's current directory at resolve time.
+    base_dir: Option<PathBuf>,
+}
+
+impl FileSystemResolver {
+    /// A resolver confined to `base_dir`.
+    pub fn new(base_dir: impl Into<PathBuf>) -> Self {
+        Self {
+            base_dir: Some(base_dir.into()),
+        }
+    }
+
+    /// A resolver confined to the current directory.
+    pub const fn current_dir() -> Self {
+        Self { base_dir: None }
+    }
+}
+
+impl ResourceResolver for FileSystemResolver {
+    #[cfg(not(target_arch = "wasm32"))]
+    fn resolve(&self, reference: &str) -> Result<Vec<u8>, String> {
+        let base = match &self.base_dir {
+            Some(dir) => dir.clone(),
+            None => std::env::current_dir()
+                .map_err(|e| format!("Failed to read current directory: {}", e))?,
+        };
+        let base = base
+            .canonicalize()
+            .map_err(|e| format!("Failed to open base directory '{}': {}", base.display(), e))?;
+        let path = base.join(reference);
+        let path = path
+            .canonicalize()
+            .map_err(|e| format!("Failed to read '{}': {}", reference, e))?;
+        if !path.starts_with(&base) {
+            return Err(format!(
+                "Refusing to read '{}': it is outside '{}'",
+                reference,
+                base.display()
+            ));
+        }
+        std::fs::read(&path).map_err(|e| format!("Failed to read '{}': {}", reference, e))
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    fn resolve(&self, reference: &str) -> Result<Vec<u8>, String> {
+        Err(format!(
+            "File paths are not supported in WASM: '{}'. Use data URIs, base64, or a custom resolver.",
+            reference
+        ))
+    }
+}
+
+/// The resolver used when none is given: files under the current directory.
+pub(crate) static CURRENT_DIR_RESOLVER: FileSystemResolver = FileSystemResolver::current_dir();
+
+/// Serves assets from an in-memory map, keyed by the exact reference.
+#[derive(Debug, Clone, Default)]
+pub struct MemoryResolver {
+    resources: HashMap<String, Vec<u8>>,
+}
+
+impl MemoryResolver {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Serve `data` for `reference`, replacing any earlier entry.
+    pub fn insert(&mut self, reference: impl Into<String>, data: Vec<u8>) {
+        self.resources.insert(reference.into(), data);
+    }
+}
+
+impl ResourceResolver for MemoryResolver {
+    fn resolve(&self, reference: &str) -> Result<Vec<u8>, String> {
+        self.resources
+            .get(reference)
+            .cloned()
+            .ok_or_else(|| format!("No resource named '{}'", reference))
+    }
+}
+
+/// Refuses every reference. Only inline data can be used.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DenyAllResolver;
+
+impl ResourceResolver for DenyAllResolver {
+    fn resolve(&self, reference: &str) -> Result<Vec<u8>, String> {
+        Err(format!(
+            "Refusing to read '{}': external resources are disabled",
+            reference
+        ))
+    }
+}
+
+/// Longest bare `src` that's offered to the resolver before it's decoded as
+/// raw base64. Inline images and fonts run far longer than asset names.
+const MAX_NAME_LEN: usize = 256;
+
+/// Read the bytes a `src` string stands for: data URIs are decoded, and
+/// anything else is a reference passed to `resolver`. A short bare name
+/// the resolver doesn't have is decoded as raw base64 if it can be, and a
+/// long one is decoded first, reaching the resolver only if it isn't
+/// base64.
+pub fn read_source(src: &str, resolver: &dyn ResourceResolver) -> Result<Vec<u8>, String> {
+    // Data URI: data:image/png;base64,iVBOR...
+    if src.starts_with("data:") {
+        let comma_pos = src
+            .find(',')
+            .ok_or_else(|| "Invalid data URI: missing comma".to_string())?;
+        return base64_decode(&src[comma_pos + 1..]);
+    }
+
+    // Explicit paths and URLs are always references, even when they happen
+    // to be valid base64 (which may contain '/')
+    if is_reference(src) {
+        return resolver.resolve(src);
+    }
+
+    // Short asset names like "logo" are valid base64 too, so ask the
+    // resolver first and fall back to raw base64. A '.' never appears in
+    // base64, so a long name with one is asked for directly
+    if src.len() <= MAX_NAME_LEN || src.contains('.') {
+        return resolver
+            .resolve(src)
+            .or_else(|err| base64_decode(src).map_err(|_| err));
+    }
+    base64_decode(src).or_else(|_| resolver.resolve(src))
+}
+
+fn is_reference(src: &str) -> bool {
+    src.starts_with('/') || src.starts_with("./") || src.starts_with("../") || src.contains("://")
+}
+
+fn base64_decode(input: &str) -> Result<Vec<u8>, String> {
+    use base64::Engine;
+    base64::engine::general_purpose::STANDARD
+        .decode(input)
+        .map_err(|e| format!("Base64 decode error: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sandbox() -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("forme-resource-{}", std::process::id()));
+        std::fs::create_dir_all(dir.join("assets")).unwrap();
+        std::fs::write(dir.join("assets/logo.bin"), b"logo").unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_file_system_resolver_reads_inside_base() {
+        let dir = sandbox();
+        let resolver = FileSystemResolver::new(&dir);
+        assert_eq!(resolver.resolve("./assets/logo.bin").unwrap(), b"logo");
+        assert_eq!(
+            resolver.resolve("assets/../assets/logo.bin").unwrap(),
+            b"logo"
+        );
+        let absolute = dir.join("assets/logo.bin");
+        assert_eq!(
+            resolver.resolve(absolute.to_str().unwrap()).unwrap(),
+            b"logo"
+        );
+    }
+
+    #[test]
+    fn test_file_system_resolver_refuses_traversal() {
+        let dir = sandbox();
+        let resolver = FileSystemResolver::new(dir.join("assets"));
+        let err = resolver
+            .resolve("../assets/../../etc/hostname")
+            .unwrap_err();
+        assert!(err.contains("outside") || err.contains("Failed to read"));
+        std::fs::write(dir.join("secret.bin"), b"secret").unwrap();
+        let err = resolver.resolve("../secret.bin").unwrap_err();
+        assert!(err.contains("outside"), "{}", err);
+        let secret = dir.join("secret.bin");
+        assert!(resolver.resolve(secret.to_str().unwrap()).is_err());
+    }
+
+    #[test]
+    fn test_read_source_inline_and_references() {
+        let mut memory = MemoryResolver::new();
+        memory.insert("logo.png", vec![1, 2, 3]);
+        memory.insert("s3://assets/logo.png", vec![4]);
+        assert_eq!(
+            read_source("data:image/png;base64,AQID", &memory).unwrap(),
+            [1, 2, 3]
+        );
+        assert_eq!(read_source("AQID", &memory).unwrap(), [1, 2, 3]);
+        assert_eq!(read_source("logo.png", &memory).unwrap(), [1, 2, 3]);
+        assert_eq!(read_source("s3://assets/logo.png", &memory).unwrap(), [4]);
+        assert!(read_source("missing.png", &memory).is_err());
+    }
+
+    #[test]
+    fn test_read_source_prefers_names_that_look_like_base64() {
+        let mut memory = MemoryResolver::new();
+        memory.insert("logo", vec![7, 7]);
+        assert_eq!(read_source("logo", &memory).unwrap(), [7, 7]);
+        // Names the resolver doesn't have still decode as base64
+        assert_eq!(read_source("font", &memory).unwrap(), [126, 137, 237]);
+    }
+
+    #[test]
+    fn test_read_source_decodes_long_base64_without_resolving() {
+        struct Refuse;
+        impl ResourceResolver for Refuse {
+            fn resolve(&self, reference: &str) -> Result<Vec<u8>, String> {
+                panic!(
+                    "resolver asked for {} bytes of inline data",
+                    reference.len()
+                )
+            }
+        }
+        use base64::Engine;
+        let payload = vec![42u8; 1024];
+        let src = base64::engine::general_purpose::STANDARD.encode(&payload);
+        assert_eq!(read_source(&src, &Refuse).unwrap(), payload);
+
+        // A long name that isn't base64 still reaches the resolver
+        let mut memory = MemoryResolver::new();
+        let name = format!("assets/{}", "deep/".repeat(60));
+        memory.insert(name.clone(), vec![9]);
+        assert_eq!(read_source(&name, &memory).unwrap(), [9]);
+    }
+
+    #[test]
+    fn test_deny_all_allows_only_inline_data() {
+        assert_eq!(read_source("AQID", &DenyAllResolver).unwrap(), [1, 2, 3]);
+        let err = read_source("./logo.png", &DenyAllResolver).unwrap_err();
+        assert!(err.contains("disabled"));
+    }
+}
@@ -8,7 +8,8 @@
 //! for PDF documents, and we implement them correctly.
 
 use crate::model::{Edges, Position};
-use serde::{Deserialize, Serialize};
+use serde::{de, Deserialize, Deserializer, Serialize};
+use std::sync::Arc;
 
 /// The complete set of style properties for a node.
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
@@ -27,6 +28,10 @@ pub struct Style {
     pub max_width: Option<Dimension>,
     /// Maximum height.
     pub max_height: Option<Dimension>,
+    /// Preferred width-to-height ratio (CSS `aspect-ratio`): sets the height
+    /// from the width, or the width from a fixed height. Overrides an
+    /// image's own proportions.
+    pub aspect_ratio: Option<f64>,
 
     /// Padding inside the border.
     #[serde(default)]
@@ -82,6 +87,20 @@ pub struct Style {
     /// Grid placement for this child item.
     pub grid_placement: Option<GridPlacement>,
 
+    // ── Multi-column Layout ──────────────────────────────────────
+    /// Number of columns the children flow into, newspaper-style (CSS
+    /// `column-count`). The gap between them is `column_gap`, 1em unless set.
+    pub column_count: Option<u32>,
+    /// How content is shared between the columns on the last page.
+    pub column_fill: Option<ColumnFill>,
+    /// Width of the line drawn between columns (CSS `column-rule-width`).
+    pub column_rule_width: Option<f64>,
+    /// Color of the line drawn between columns. Defaults to the text color.
+    pub column_rule_color: Option<Color>,
+    /// Whether this child spans all of its parent's columns (CSS
+    /// `column-span`).
+    pub column_span: Option<ColumnSpan>,
+
     // ── Typography ─────────────────────────────────────────────
     /// Font family name.
     pub font_family: Option<String>,
@@ -89,8 +108,21 @@ pub struct Style {
     pub font_size: Option<f64>,
     /// Font weight (100-900).
     pub font_weight: Option<u32>,
+    /// Font width as a percentage of normal (50-200), as in CSS
+    /// `font-stretch`. Selects a width of a variable font or a condensed or
+    /// expanded face.
+    pub font_stretch: Option<u32>,
     /// Font style.
     pub font_style: Option<FontStyle>,
+    /// OpenType features to turn on or off when shaping, as in CSS
+    /// `font-feature-settings`. Applied after the `font_variant_*`
+    /// properties, so it can override them.
+    pub font_feature_settings: Option<Vec<FontFeature>>,
+    /// Numeric glyph variants (CSS `font-variant-numeric`), such as
+    /// tabular figures.
+    pub font_variant_numeric: Option<Vec<FontVariantNumeric>>,
+    /// Capital glyph variants (CSS `font-variant-caps`), such as small caps.
+    pub font_variant_caps: Option<FontVariantCaps>,
     /// Line height as a multiplier of font size.
     pub line_height: Option<f64>,
     /// Text alignment within the text block.
@@ -101,12 +133,18 @@ pub struct Style {
     pub text_decoration: Option<TextDecoration>,
     /// Text transform.
     pub text_transform: Option<TextTransform>,
+    /// Raise or lower a text run from the baseline (CSS `vertical-align`),
+    /// for superscripts and subscripts.
+    pub vertical_align: Option<VerticalAlign>,
     /// Hyphenation mode (CSS `hyphens` property).
     pub hyphens: Option<Hyphens>,
     /// BCP 47 language tag for hyphenation and line breaking.
     pub lang: Option<String>,
     /// Text direction (ltr, rtl, or auto).
     pub direction: Option<Direction>,
+    /// Writing mode (CSS `writing-mode`): horizontal lines, or vertical
+    /// lines running top to bottom.
+    pub writing_mode: Option<WritingMode>,
     /// Text overflow behavior (wrap, ellipsis, clip).
     pub text_overflow: Option<TextOverflow>,
     /// Line breaking algorithm: optimal (Knuth-Plass, default) or greedy.
@@ -120,6 +158,16 @@ pub struct Style {
     pub color: Option<Color>,
     /// Background color.
     pub background_color: Option<Color>,
+    /// Background image or gradient, drawn over the background color and
+    /// clipped to the border radius.
+    pub background_image: Option<BackgroundImage>,
+    /// How a background image is sized. Default: its intrinsic size.
+    pub background_size: Option<BackgroundSize>,
+    /// Whether a background image is tiled. Default: `Repeat`.
+    pub background_repeat: Option<BackgroundRepeat>,
+    /// Where a background image (the first tile, when tiled) sits in the
+    /// box. Default: top left.
+    pub background_position: Option<ObjectPosition>,
     /// Opacity (0.0 - 1.0).
     pub opacity: Option<f64>,
 
@@ -142,10 +190,21 @@ pub struct Style {
     pub bottom: Option<f64>,
     /// Left offset (for absolute positioning).
     pub left: Option<f64>,
+    /// Take the node out of the flow and place it against one side of its
+    /// parent, with the text after it wrapping around it.
+    pub float: Option<Float>,
+
+    // ── Images ─────────────────────────────────────────────────
+    /// How an image fills its box when their proportions differ (CSS
+    /// `object-fit`).
+    pub object_fit: Option<ObjectFit>,
+    /// Where the image sits in its box when it doesn't fill it exactly
+    /// (CSS `object-position`). Centered by default.
+    pub object_position: Option<ObjectPosition>,
 
     // ── Page Behavior ──────────────────────────────────────────
     /// Whether this node can be broken across pages.
-    /// `true` = breakable (default for View, Text, Table).
+    /// `true` = breakable (default for View, Text, Table, TableRow).
     /// `false` = keep on one page; if it doesn't fit, move to next page.
     pub wrap: Option<bool>,
 
@@ -207,6 +266,145 @@ pub enum GridTrackSize {
     MinMax(Box<GridTrackSize>, Box<GridTrackSize>),
 }
 
+/// How a multi-column container fills its columns, as in CSS `column-fill`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ColumnFill {
+    /// Fill each column before moving to the next (default).
+    #[default]
+    Auto,
+    /// Share the content of the last page evenly between its columns.
+    /// Content before a spanning child is always balanced.
+    Balance,
+}
+
+/// Whether a child of a multi-column container spans its columns, as in
+/// CSS `column-span`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ColumnSpan {
+    /// Flow within one column (default).
+    #[default]
+    None,
+    /// Span all columns, like a heading across a newspaper page.
+    All,
+}
+
+/// Which side of its container a node floats to, as in CSS `float`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Float {
+    /// Stay in the flow (default).
+    #[default]
+    None,
+    Left,
+    Right,
+}
+
+/// How an image is sized within its box, as in CSS `object-fit`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ObjectFit {
+    /// Stretch to fill the box exactly (default).
+    #[default]
+    Fill,
+    /// Scale to fit inside the box, keeping its proportions (letterboxed).
+    Contain,
+    /// Scale to cover the whole box, keeping its proportions; the overflow
+    /// is clipped.
+    Cover,
+    /// Draw at its intrinsic size (a pixel to a point), clipped to the box.
+    None,
+    /// `None` or `Contain`, whichever is smaller.
+    ScaleDown,
+}
+
+/// Where an image sits in its box, as in CSS `object-position`: offsets of
+/// the image's left and top edges in points, or percentages that line up
+/// that point of the image with the same point of the box.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct ObjectPosition {
+    pub x: Dimension,
+    pub y: Dimension,
+}
+
+impl Default for ObjectPosition {
+    fn default() -> Self {
+        Self {
+            x: Dimension::Percent(50.0),
+            y: Dimension::Percent(50.0),
+        }
+    }
+}
+
+/// A background drawn over a node's background color, as in CSS
+/// `background-image`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum BackgroundImage {
+    /// An image source, read like an Image's `src`.
+    Url(String),
+    /// Colors blended along a line through the box's center.
+    LinearGradient(LinearGradient),
+    /// Colors blended outward from the box's center, in a circle reaching
+    /// its farthest corner.
+    RadialGradient(RadialGradient),
+}
+
+/// A linear gradient, as in CSS `linear-gradient()`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LinearGradient {
+    /// Direction the gradient runs, in degrees clockwise from "to top".
+    /// Default: 180 (top to bottom).
+    #[serde(default = "LinearGradient::default_angle")]
+    pub angle: f64,
+    pub stops: Vec<ColorStop>,
+}
+
+impl LinearGradient {
+    fn default_angle() -> f64 {
+        180.0
+    }
+}
+
+/// A radial gradient, as in CSS `radial-gradient(circle farthest-corner)`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RadialGradient {
+    pub stops: Vec<ColorStop>,
+}
+
+/// A gradient color stop. Translucent stops fade the gradient through a
+/// soft mask.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct ColorStop {
+    pub color: Color,
+    /// Where the stop is along the gradient, from 0 (start) to 1 (end).
+    /// Stops without one are spread evenly between their neighbors.
+    #[serde(default)]
+    pub offset: Option<f64>,
+}
+
+/// How a background image is sized, as in CSS `background-size`.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+pub enum BackgroundSize {
+    /// Its intrinsic size, a pixel to a point (default).
+    #[default]
+    Auto,
+    /// Scale to cover the whole box, keeping its proportions.
+    Cover,
+    /// Scale to fit inside the box, keeping its proportions.
+    Contain,
+    /// An explicit width and height; `Auto` on one side keeps the image's
+    /// proportions.
+    Size { width: Dimension, height: Dimension },
+}
+
+/// Whether a background image is tiled, as in CSS `background-repeat`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum BackgroundRepeat {
+    /// Tile in both directions (default).
+    #[default]
+    Repeat,
+    RepeatX,
+    RepeatY,
+    NoRepeat,
+}
+
 /// Grid item placement.
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -300,6 +498,139 @@ pub enum TextDecoration {
     LineThrough,
 }
 
+/// Where a text run sits relative to the baseline of its text.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum VerticalAlign {
+    #[default]
+    Baseline,
+    /// Raised, as a superscript.
+    Super,
+    /// Lowered, as a subscript.
+    Sub,
+}
+
+impl VerticalAlign {
+    /// How far a run is raised above the baseline (negative: lowered) in
+    /// text of `font_size`.
+    pub fn baseline_shift(self, font_size: f64) -> f64 {
+        match self {
+            VerticalAlign::Baseline => 0.0,
+            VerticalAlign::Super => font_size * 0.33,
+            VerticalAlign::Sub => -font_size * 0.2,
+        }
+    }
+}
+
+/// An OpenType feature setting: a four-letter feature tag and its value.
+/// Most features are switched on with 1 and off with 0; alternates such as
+/// `salt` take the index of the alternate.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FontFeature {
+    /// Four printable ASCII characters. Any other tag fails deserialization.
+    #[serde(deserialize_with = "deserialize_feature_tag")]
+    pub tag: String,
+    pub value: u32,
+}
+
+impl FontFeature {
+    pub fn new(tag: &str, value: u32) -> Self {
+        Self {
+            tag: tag.to_string(),
+            value,
+        }
+    }
+
+    /// The tag's bytes, or `None` if it isn't four printable ASCII
+    /// characters.
+    pub fn tag_bytes(&self) -> Option<[u8; 4]> {
+        let bytes: [u8; 4] = self.tag.as_bytes().try_into().ok()?;
+        bytes
+            .iter()
+            .all(|b| (b' '..=b'~').contains(b))
+            .then_some(bytes)
+    }
+}
+
+fn deserialize_feature_tag<'de, D: Deserializer<'de>>(deserializer: D) -> Result<String, D::Error> {
+    let tag = String::deserialize(deserializer)?;
+    if FontFeature::new(&tag, 0).tag_bytes().is_none() {
+        return Err(de::Error::custom(format!(
+            "invalid OpenType feature tag \"{}\": expected four printable ASCII characters",
+            tag
+        )));
+    }
+    Ok(tag)
+}
+
+/// CSS `font-variant-numeric` values.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum FontVariantNumeric {
+    /// Figures aligned to capital height (`lnum`).
+    LiningNums,
+    /// Figures with ascenders and descenders (`onum`).
+    OldstyleNums,
+    /// Figures with individual widths (`pnum`).
+    ProportionalNums,
+    /// Figures of equal width, for columns of numbers (`tnum`).
+    TabularNums,
+    /// Fractions with a slash (`frac`).
+    DiagonalFractions,
+    /// Fractions stacked over a bar (`afrc`).
+    StackedFractions,
+    /// Ordinal suffixes such as 1st (`ordn`).
+    Ordinal,
+    /// A zero with a slash through it (`zero`).
+    SlashedZero,
+}
+
+impl FontVariantNumeric {
+    fn feature_tag(self) -> &'static str {
+        match self {
+            FontVariantNumeric::LiningNums => "lnum",
+            FontVariantNumeric::OldstyleNums => "onum",
+            FontVariantNumeric::ProportionalNums => "pnum",
+            FontVariantNumeric::TabularNums => "tnum",
+            FontVariantNumeric::DiagonalFractions => "frac",
+            FontVariantNumeric::StackedFractions => "afrc",
+            FontVariantNumeric::Ordinal => "ordn",
+            FontVariantNumeric::SlashedZero => "zero",
+        }
+    }
+}
+
+/// CSS `font-variant-caps` values.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum FontVariantCaps {
+    #[default]
+    Normal,
+    /// Lowercase letters as small capitals (`smcp`).
+    SmallCaps,
+    /// All letters as small capitals (`smcp`, `c2sc`).
+    AllSmallCaps,
+    /// Lowercase letters as petite capitals (`pcap`).
+    PetiteCaps,
+    /// All letters as petite capitals (`pcap`, `c2pc`).
+    AllPetiteCaps,
+    /// Capitals as small capitals, lowercase unchanged (`unic`).
+    Unicase,
+    /// Capitals designed for all-caps titles (`titl`).
+    TitlingCaps,
+}
+
+impl FontVariantCaps {
+    fn feature_tags(self) -> &'static [&'static str] {
+        match self {
+            FontVariantCaps::Normal => &[],
+            FontVariantCaps::SmallCaps => &["smcp"],
+            FontVariantCaps::AllSmallCaps => &["smcp", "c2sc"],
+            FontVariantCaps::PetiteCaps => &["pcap"],
+            FontVariantCaps::AllPetiteCaps => &["pcap", "c2pc"],
+            FontVariantCaps::Unicase => &["unic"],
+            FontVariantCaps::TitlingCaps => &["titl"],
+        }
+    }
+}
+
 #[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
 pub enum TextTransform {
     #[default]
@@ -344,6 +675,18 @@ pub enum Direction {
     Auto,
 }
 
+/// Writing mode, as in CSS `writing-mode`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum WritingMode {
+    /// Horizontal lines stacked top to bottom (default).
+    #[default]
+    HorizontalTb,
+    /// Vertical lines, read top to bottom, stacked right to left, as in
+    /// Japanese and Chinese typesetting.
+    VerticalRl,
+}
+
 /// Line breaking algorithm.
 #[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
@@ -484,6 +827,7 @@ pub struct ResolvedStyle {
     pub min_height: f64,
     pub max_width: f64,
     pub max_height: f64,
+    pub aspect_ratio: Option<f64>,
     pub padding: Edges,
     pub margin: Edges,
 
@@ -511,25 +855,46 @@ pub struct ResolvedStyle {
     pub grid_auto_columns: Option<GridTrackSize>,
     pub grid_placement: Option<GridPlacement>,
 
+    // Multi-column
+    pub column_count: u32,
+    pub column_fill: ColumnFill,
+    pub column_rule_width: f64,
+    pub column_rule_color: Color,
+    pub column_span: ColumnSpan,
+
     // Text
     pub font_family: String,
     pub font_size: f64,
     pub font_weight: u32,
+    pub font_stretch: u32,
     pub font_style: FontStyle,
+    pub font_feature_settings: Vec<FontFeature>,
+    pub font_variant_numeric: Vec<FontVariantNumeric>,
+    pub font_variant_caps: FontVariantCaps,
+    /// The OpenType features text is shaped with: `vert` for vertical text,
+    /// then the `font_variant_*` properties, then `font_feature_settings`.
+    /// Shared with every character and glyph of the node's text.
+    pub font_features: Arc<[FontFeature]>,
     pub line_height: f64,
     pub text_align: TextAlign,
     pub letter_spacing: f64,
     pub text_decoration: TextDecoration,
     pub text_transform: TextTransform,
+    pub vertical_align: VerticalAlign,
     pub hyphens: Hyphens,
     pub lang: Option<String>,
     pub direction: Direction,
+    pub writing_mode: WritingMode,
     pub text_overflow: TextOverflow,
     pub line_breaking: LineBreaking,
 
     // Visual
     pub color: Color,
     pub background_color: Option<Color>,
+    pub background_image: Option<BackgroundImage>,
+    pub background_size: BackgroundSize,
+    pub background_repeat: BackgroundRepeat,
+    pub background_position: ObjectPosition,
     pub opacity: f64,
     pub overflow: Overflow,
     pub border_width: Edges,
@@ -542,6 +907,11 @@ pub struct ResolvedStyle {
     pub right: Option<f64>,
     pub bottom: Option<f64>,
     pub left: Option<f64>,
+    pub float: Float,
+
+    // Images
+    pub object_fit: ObjectFit,
+    pub object_position: ObjectPosition,
 
     // Page behavior
     pub breakable: bool,
@@ -566,25 +936,79 @@ impl Style {
             .unwrap_or_else(|| "Helvetica".to_string());
 
         let font_size = self.font_size.unwrap_or(parent_font_size);
+        let column_count = self.column_count.unwrap_or(1).max(1);
+
+        // The three feature properties inherit separately, then combine
+        let font_feature_settings = self
+            .font_feature_settings
+            .clone()
+            .or_else(|| parent.map(|p| p.font_feature_settings.clone()))
+            .unwrap_or_default();
+        let font_variant_numeric = self
+            .font_variant_numeric
+            .clone()
+            .or_else(|| parent.map(|p| p.font_variant_numeric.clone()))
+            .unwrap_or_default();
+        let font_variant_caps = self
+            .font_variant_caps
+            .unwrap_or(parent.map(|p| p.font_variant_caps).unwrap_or_default());
+        let writing_mode = self
+            .writing_mode
+            .unwrap_or(parent.map(|p| p.writing_mode).unwrap_or_default());
+        // Vertical text uses the font's vertical alternates, for punctuation
+        // and brackets, unless the settings turn them off
+        let vertical_alternates = match writing_mode {
+            WritingMode::VerticalRl => Some(FontFeature::new("vert", 1)),
+            WritingMode::HorizontalTb => None,
+        };
+        let font_features = vertical_alternates
+            .into_iter()
+            .chain(
+                font_variant_numeric
+                    .iter()
+                    .map(|v| FontFeature::new(v.feature_tag(), 1)),
+            )
+            .chain(
+                font_variant_caps
+                    .feature_tags()
+                    .iter()
+                    .map(|tag| FontFeature::new(tag, 1)),
+            )
+            .chain(font_feature_settings.iter().cloned())
+            .collect();
+
+        let width = self
+            .width
+            .map(|d| match d {
+                Dimension::Pt(v) => SizeConstraint::Fixed(v),
+                Dimension::Percent(p) => SizeConstraint::Fixed(available_width * p / 100.0),
+                Dimension::Auto => SizeConstraint::Auto,
+            })
+            .unwrap_or(SizeConstraint::Auto);
+        let height = self
+            .height
+            .map(|d| match d {
+                Dimension::Pt(v) => SizeConstraint::Fixed(v),
+                Dimension::Percent(p) => SizeConstraint::Fixed(p), // height % is complex, simplified
+                Dimension::Auto => SizeConstraint::Auto,
+            })
+            .unwrap_or(SizeConstraint::Auto);
+        // One fixed side and the aspect ratio fix the other. With both auto,
+        // layout sets the height from the width the box fills.
+        let aspect_ratio = self.aspect_ratio.filter(|r| r.is_finite() && *r > 0.0);
+        let (width, height) = match (aspect_ratio, width, height) {
+            (Some(ratio), SizeConstraint::Fixed(w), SizeConstraint::Auto) => {
+                (width, SizeConstraint::Fixed(w / ratio))
+            }
+            (Some(ratio), SizeConstraint::Auto, SizeConstraint::Fixed(h)) => {
+                (SizeConstraint::Fixed(h * ratio), height)
+            }
+            _ => (width, height),
+        };
 
         ResolvedStyle {
-            width: self
-                .width
-                .map(|d| match d {
-                    Dimension::Pt(v) => SizeConstraint::Fixed(v),
-                    Dimension::Percent(p) => SizeConstraint::Fixed(available_width * p / 100.0),
-                    Dimension::Auto => SizeConstraint::Auto,
-                })
-                .unwrap_or(SizeConstraint::Auto),
-
-            height: self
-                .height
-                .map(|d| match d {
-                    Dimension::Pt(v) => SizeConstraint::Fixed(v),
-                    Dimension::Percent(p) => SizeConstraint::Fixed(p), // height % is complex, simplified
-                    Dimension::Auto => SizeConstraint::Auto,
-                })
-                .unwrap_or(SizeConstraint::Auto),
+            width,
+            height,
 
             min_width: self
                 .min_width
@@ -599,6 +1023,7 @@ impl Style {
                 .max_height
                 .and_then(|d| d.resolve(0.0))
                 .unwrap_or(f64::INFINITY),
+            aspect_ratio,
 
             padding: self.padding.unwrap_or_default(),
             margin: self.margin.unwrap_or_default(),
@@ -623,7 +1048,12 @@ impl Style {
                 .unwrap_or(SizeConstraint::Auto),
             gap: self.gap.unwrap_or(0.0),
             row_gap: self.row_gap.or(self.gap).unwrap_or(0.0),
-            column_gap: self.column_gap.or(self.gap).unwrap_or(0.0),
+            // The normal gap between newspaper columns is 1em
+            column_gap: self.column_gap.or(self.gap).unwrap_or(if column_count > 1 {
+                font_size
+            } else {
+                0.0
+            }),
 
             grid_template_columns: self.grid_template_columns.clone(),
             grid_template_rows: self.grid_template_rows.clone(),
@@ -631,14 +1061,29 @@ impl Style {
             grid_auto_columns: self.grid_auto_columns.clone(),
             grid_placement: self.grid_placement.clone(),
 
+            column_count,
+            column_fill: self.column_fill.unwrap_or_default(),
+            column_rule_width: self.column_rule_width.unwrap_or(0.0),
+            column_rule_color: self
+                .column_rule_color
+                .unwrap_or(self.color.unwrap_or(parent_color)),
+            column_span: self.column_span.unwrap_or_default(),
+
             font_family: self.font_family.clone().unwrap_or(parent_font_family),
             font_size,
             font_weight: self
                 .font_weight
                 .unwrap_or(parent.map(|p| p.font_weight).unwrap_or(400)),
+            font_stretch: self
+                .font_stretch
+                .unwrap_or(parent.map(|p| p.font_stretch).unwrap_or(100)),
             font_style: self
                 .font_style
                 .unwrap_or(parent.map(|p| p.font_style).unwrap_or_default()),
+            font_feature_settings,
+            font_variant_numeric,
+            font_variant_caps,
+            font_features,
             line_height: self
                 .line_height
                 .unwrap_or(parent.map(|p| p.line_height).unwrap_or(1.4)),
@@ -661,6 +1106,7 @@ impl Style {
             text_transform: self
                 .text_transform
                 .unwrap_or(parent.map(|p| p.text_transform).unwrap_or_default()),
+            vertical_align: self.vertical_align.unwrap_or_default(),
             hyphens: self
                 .hyphens
                 .unwrap_or(parent.map(|p| p.hyphens).unwrap_or_default()),
@@ -671,6 +1117,7 @@ impl Style {
             direction: self
                 .direction
                 .unwrap_or(parent.map(|p| p.direction).unwrap_or_default()),
+            writing_mode,
             text_overflow: self.text_overflow.unwrap_or_default(),
             line_breaking: self
                 .line_breaking
@@ -678,6 +1125,13 @@ impl Style {
 
             color: self.color.unwrap_or(parent_color),
             background_color: self.background_color,
+            background_image: self.background_image.clone(),
+            background_size: self.background_size.unwrap_or_default(),
+            background_repeat: self.background_repeat.unwrap_or_default(),
+            background_position: self.background_position.unwrap_or(ObjectPosition {
+                x: Dimension::Percent(0.0),
+                y: Dimension::Percent(0.0),
+            }),
             opacity: self.opacity.unwrap_or(1.0),
             overflow: self.overflow.unwrap_or_default(),
 
@@ -701,6 +1155,10 @@ impl Style {
             right: self.right,
             bottom: self.bottom,
             left: self.left,
+            float: self.float.unwrap_or_default(),
+
+            object_fit: self.object_fit.unwrap_or_default(),
+            object_position: self.object_position.unwrap_or_default(),
 
             breakable: self.wrap.unwrap_or(true),
             break_before: self.break_before.unwrap_or(false),
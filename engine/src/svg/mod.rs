@@ -55,6 +55,35 @@ pub fn parse_view_box(s: &str) -> Option<ViewBox> {
     }
 }
 
+/// Elements `parse_svg` draws, plus ones it can skip without losing
+/// anything visible.
+const SUPPORTED_ELEMENTS: &[&str] = &[
+    "svg", "g", "rect", "circle", "ellipse", "line", "polyline", "polygon", "path", "title",
+    "desc", "metadata",
+];
+
+/// Names of the elements in `content` that `parse_svg` skips, each listed
+/// once in document order.
+pub fn unsupported_elements(content: &str) -> Vec<String> {
+    let mut names: Vec<String> = Vec::new();
+    let mut reader = Reader::from_str(content);
+    let mut buf = Vec::new();
+    loop {
+        match reader.read_event_into(&mut buf) {
+            Ok(Event::Start(e)) | Ok(Event::Empty(e)) => {
+                let name = String::from_utf8_lossy(e.name().as_ref()).to_string();
+                if !SUPPORTED_ELEMENTS.contains(&name.as_str()) && !names.contains(&name) {
+                    names.push(name);
+                }
+            }
+            Ok(Event::Eof) | Err(_) => break,
+            _ => {}
+        }
+        buf.clear();
+    }
+    names
+}
+
 /// Parse SVG XML content into drawing commands.
 pub fn parse_svg(
     content: &str,
@@ -445,41 +474,33 @@ fn parse_path_d(d: &str) -> Vec<SvgCommand> {
 
     while i < tokens.len() {
         match tokens[i].as_str() {
-            "M" => {
-                if i + 2 < tokens.len() {
-                    cur_x = tokens[i + 1].parse().unwrap_or(0.0);
-                    cur_y = tokens[i + 2].parse().unwrap_or(0.0);
-                    start_x = cur_x;
-                    start_y = cur_y;
-                    commands.push(SvgCommand::MoveTo(cur_x, cur_y));
-                    i += 3;
-                    // Implicit LineTo for subsequent coordinate pairs
-                    while i + 1 < tokens.len() && is_number(&tokens[i]) {
-                        cur_x = tokens[i].parse().unwrap_or(0.0);
-                        cur_y = tokens[i + 1].parse().unwrap_or(0.0);
-                        commands.push(SvgCommand::LineTo(cur_x, cur_y));
-                        i += 2;
-                    }
-                } else {
-                    i += 1;
+            "M" if i + 2 < tokens.len() => {
+                cur_x = tokens[i + 1].parse().unwrap_or(0.0);
+                cur_y = tokens[i + 2].parse().unwrap_or(0.0);
+                start_x = cur_x;
+                start_y = cur_y;
+                commands.push(SvgCommand::MoveTo(cur_x, cur_y));
+                i += 3;
+                // Implicit LineTo for subsequent coordinate pairs
+                while i + 1 < tokens.len() && is_number(&tokens[i]) {
+                    cur_x = tokens[i].parse().unwrap_or(0.0);
+                    cur_y = tokens[i + 1].parse().unwrap_or(0.0);
+                    commands.push(SvgCommand::LineTo(cur_x, cur_y));
+                    i += 2;
                 }
             }
-            "m" => {
-                if i + 2 < tokens.len() {
-                    cur_x += tokens[i + 1].parse::<f64>().unwrap_or(0.0);
-                    cur_y += tokens[i + 2].parse::<f64>().unwrap_or(0.0);
-                    start_x = cur_x;
-                    start_y = cur_y;
-                    commands.push(SvgCommand::MoveTo(cur_x, cur_y));
-                    i += 3;
-                    while i + 1 < tokens.len() && is_number(&tokens[i]) {
-                        cur_x += tokens[i].parse::<f64>().unwrap_or(0.0);
-                        cur_y += tokens[i + 1].parse::<f64>().unwrap_or(0.0);
-                        commands.push(SvgCommand::LineTo(cur_x, cur_y));
-                        i += 2;
-                    }
-                } else {
-                    i += 1;
+            "m" if i + 2 < tokens.len() => {
+                cur_x += tokens[i + 1].parse::<f64>().unwrap_or(0.0);
+                cur_y += tokens[i + 2].parse::<f64>().unwrap_or(0.0);
+                start_x = cur_x;
+                start_y = cur_y;
+                commands.push(SvgCommand::MoveTo(cur_x, cur_y));
+                i += 3;
+                while i + 1 < tokens.len() && is_number(&tokens[i]) {
+                    cur_x += tokens[i].parse::<f64>().unwrap_or(0.0);
+                    cur_y += tokens[i + 1].parse::<f64>().unwrap_or(0.0);
+                    commands.push(SvgCommand::LineTo(cur_x, cur_y));
+                    i += 2;
                 }
             }
             "L" => {
@@ -791,6 +812,17 @@ mod tests {
         assert!(parse_view_box("bad").is_none());
     }
 
+    #[test]
+    fn test_unsupported_elements() {
+        let svg = r#"<svg><title>Logo</title><g><rect/><text>A</text><text>B</text></g>
+            <defs><linearGradient id="g"/></defs></svg>"#;
+        assert_eq!(
+            unsupported_elements(svg),
+            vec!["text", "defs", "linearGradient"]
+        );
+        assert!(unsupported_elements("<svg><path d=\"M0 0\"/></svg>").is_empty());
+    }
+
     #[test]
     fn test_parse_rect() {
         let cmds = parse_svg(
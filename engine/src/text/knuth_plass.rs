@@ -9,6 +9,8 @@
 
 use unicode_linebreak::BreakOpportunity;
 
+use super::LineWidths;
+
 /// An item in the Knuth-Plass item list.
 #[derive(Debug, Clone)]
 pub enum Item {
@@ -40,7 +42,8 @@ pub enum Item {
 /// Configuration for the Knuth-Plass algorithm.
 #[derive(Debug, Clone)]
 pub struct Config {
-    pub line_width: f64,
+    /// The width each line should fill.
+    pub line_widths: LineWidths,
     /// How much lines are allowed to stretch/shrink. Higher = more tolerance.
     pub tolerance: f64,
     /// Penalty for hyphenating a word.
@@ -54,7 +57,7 @@ pub struct Config {
 impl Default for Config {
     fn default() -> Self {
         Self {
-            line_width: 0.0,
+            line_widths: LineWidths::default(),
             tolerance: 2.0,
             hyphen_penalty: 50.0,
             double_hyphen_demerits: 3000.0,
@@ -438,7 +441,7 @@ pub fn find_breaks(items: &[Item], config: &Config) -> Option<Vec<LineSolution>>
                 };
 
                 let actual_width = line_width + penalty_width;
-                let target = config.line_width;
+                let target = config.line_widths.get(a.line);
 
                 let ratio = if actual_width < target {
                     // Need to stretch
@@ -630,7 +633,7 @@ pub fn find_breaks(items: &[Item], config: &Config) -> Option<Vec<LineSolution>>
             };
 
             let actual = line_w + penalty_w;
-            let target = config.line_width;
+            let target = config.line_widths.get(prev_bp.line);
             let ratio = if actual < target && line_stretch > 0.0 {
                 (target - actual) / line_stretch
             } else if actual > target && line_shrink > 0.0 {
@@ -661,7 +664,7 @@ pub fn reconstruct_lines(
     items: &[Item],
     chars: &[char],
     char_widths: &[f64],
-    line_width: f64,
+    line_widths: &LineWidths,
     justify: bool,
 ) -> Vec<super::BrokenLine> {
     let mut lines = Vec::new();
@@ -745,7 +748,7 @@ pub fn reconstruct_lines(
 
         // For justified text (non-last line), width should be close to target
         if apply_justify {
-            effective_width = effective_width.min(line_width);
+            effective_width = effective_width.min(line_widths.get(sol_idx));
         }
 
         lines.push(super::BrokenLine {
@@ -792,7 +795,7 @@ pub fn reconstruct_run_lines(
     items: &[Item],
     chars: &[super::StyledChar],
     char_widths: &[f64],
-    line_width: f64,
+    line_widths: &LineWidths,
     justify: bool,
 ) -> Vec<super::RunBrokenLine> {
     let mut lines = Vec::new();
@@ -885,7 +888,7 @@ pub fn reconstruct_run_lines(
         }
 
         if apply_justify {
-            effective_width = effective_width.min(line_width);
+            effective_width = effective_width.min(line_widths.get(sol_idx));
         }
 
         lines.push(super::RunBrokenLine {
@@ -946,7 +949,7 @@ mod tests {
             None,
         );
         let config = Config {
-            line_width: 200.0, // wide enough for everything
+            line_widths: LineWidths::uniform(200.0), // wide enough for everything
             ..Default::default()
         };
         let solutions = find_breaks(&items, &config).expect("Should find solution");
@@ -966,7 +969,7 @@ mod tests {
             None,
         );
         let config = Config {
-            line_width: 55.0, // "xx yy" = 50 wide, ratio 1.0 with 5 stretch
+            line_widths: LineWidths::uniform(55.0), // "xx yy" = 50 wide, ratio 1.0 with 5 stretch
             ..Default::default()
         };
         let solutions = find_breaks(&items, &config).expect("Should find solution");
@@ -990,7 +993,7 @@ mod tests {
             None,
         );
         let config = Config {
-            line_width: 200.0,
+            line_widths: LineWidths::uniform(200.0),
             ..Default::default()
         };
         let solutions = find_breaks(&items, &config).expect("Should find solution");
@@ -1017,11 +1020,18 @@ mod tests {
             None,
         );
         let config = Config {
-            line_width: 55.0,
+            line_widths: LineWidths::uniform(55.0),
             ..Default::default()
         };
         let solutions = find_breaks(&items, &config).expect("Should find solution");
-        let lines = reconstruct_lines(&solutions, &items, &chars, &widths, 55.0, true);
+        let lines = reconstruct_lines(
+            &solutions,
+            &items,
+            &chars,
+            &widths,
+            &config.line_widths,
+            true,
+        );
         assert!(lines.len() >= 2);
         // Non-final lines should have width close to line_width
         for (i, line) in lines.iter().enumerate() {
@@ -9,7 +9,8 @@ pub mod knuth_plass;
 pub mod shaping;
 
 use crate::font::FontContext;
-use crate::style::{Color, FontStyle, Hyphens, TextDecoration};
+use crate::style::{Color, FontFeature, FontStyle, Hyphens, TextDecoration};
+use std::sync::Arc;
 use unicode_linebreak::{linebreaks, BreakOpportunity};
 
 /// A line of text after line-breaking.
@@ -25,6 +26,44 @@ pub struct BrokenLine {
     pub width: f64,
 }
 
+/// The width available to each line of a paragraph. Text wrapping around a
+/// float is narrower beside it: `first` holds the widths of the opening
+/// lines, in order, and every later line is `rest` wide.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct LineWidths {
+    pub first: Vec<f64>,
+    pub rest: f64,
+}
+
+impl LineWidths {
+    /// Every line `width` wide.
+    pub fn uniform(width: f64) -> Self {
+        Self {
+            first: Vec::new(),
+            rest: width,
+        }
+    }
+
+    /// The width of line `line` (0-based).
+    pub fn get(&self, line: usize) -> f64 {
+        self.first.get(line).copied().unwrap_or(self.rest)
+    }
+
+    /// The widths of the lines from `line` on.
+    pub fn skip(&self, line: usize) -> Self {
+        Self {
+            first: self.first.iter().skip(line).copied().collect(),
+            rest: self.rest,
+        }
+    }
+}
+
+impl From<f64> for LineWidths {
+    fn from(width: f64) -> Self {
+        Self::uniform(width)
+    }
+}
+
 /// A styled character for multi-style line breaking.
 #[derive(Debug, Clone)]
 pub struct StyledChar {
@@ -32,11 +71,18 @@ pub struct StyledChar {
     pub font_family: String,
     pub font_size: f64,
     pub font_weight: u32,
+    pub font_stretch: u32,
     pub font_style: FontStyle,
+    pub font_features: Arc<[FontFeature]>,
     pub color: Color,
     pub href: Option<String>,
     pub text_decoration: TextDecoration,
     pub letter_spacing: f64,
+    /// How far the glyph is raised above the baseline, in points
+    /// (negative: lowered).
+    pub baseline_shift: f64,
+    /// For the characters of a footnote reference, the index of its run.
+    pub footnote: Option<usize>,
 }
 
 /// A line of text from multi-style (runs) line breaking.
@@ -78,9 +124,92 @@ fn compute_break_opportunities(text: &str) -> Vec<Option<BreakOpportunity>> {
         // byte_offset == text.len() means "break at end" — we ignore that
     }
 
+    apply_kinsoku(text, &mut result);
     result
 }
 
+/// Tighten UAX#14 with the strict CJK line-breaking rules (kinsoku shori):
+/// small kana, the prolonged sound mark and iteration marks may not start a
+/// line, on top of the closing punctuation UAX#14 already keeps back.
+fn apply_kinsoku(text: &str, opportunities: &mut [Option<BreakOpportunity>]) {
+    let mut prev = None;
+    for (i, ch) in text.chars().enumerate() {
+        let prohibited = is_kinsoku_line_start(ch) || prev.is_some_and(is_kinsoku_line_end);
+        if prohibited && matches!(opportunities[i], Some(BreakOpportunity::Allowed)) {
+            opportunities[i] = None;
+        }
+        prev = Some(ch);
+    }
+}
+
+/// Characters that may not begin a line in CJK text.
+fn is_kinsoku_line_start(ch: char) -> bool {
+    matches!(
+        ch,
+        // Closing brackets and quotes
+        ')' | ']' | '}' | '\u{3009}' | '\u{300B}' | '\u{300D}' | '\u{300F}' | '\u{3011}'
+            | '\u{3015}' | '\u{3017}' | '\u{3019}' | '\u{FF09}' | '\u{FF3D}' | '\u{FF5D}'
+            | '\u{2019}' | '\u{201D}'
+            // Punctuation: 、 。 ， ． ・ ： ； ？ ！ ‼ ⁇ ⁈ ⁉
+            | '\u{3001}' | '\u{3002}' | '\u{FF0C}' | '\u{FF0E}' | '\u{30FB}' | '\u{FF1A}'
+            | '\u{FF1B}' | '\u{FF1F}' | '\u{FF01}' | '\u{203C}' | '\u{2047}'..='\u{2049}'
+            // Small hiragana and katakana
+            | '\u{3041}' | '\u{3043}' | '\u{3045}' | '\u{3047}' | '\u{3049}' | '\u{3063}'
+            | '\u{3083}' | '\u{3085}' | '\u{3087}' | '\u{308E}' | '\u{3095}' | '\u{3096}'
+            | '\u{30A1}' | '\u{30A3}' | '\u{30A5}' | '\u{30A7}' | '\u{30A9}' | '\u{30C3}'
+            | '\u{30E3}' | '\u{30E5}' | '\u{30E7}' | '\u{30EE}' | '\u{30F5}' | '\u{30F6}'
+            | '\u{31F0}'..='\u{31FF}'
+            // Prolonged sound mark and iteration marks: ー 々 〻 ゝ ゞ ヽ ヾ
+            | '\u{30FC}' | '\u{3005}' | '\u{303B}' | '\u{309D}' | '\u{309E}' | '\u{30FD}'
+            | '\u{30FE}'
+    )
+}
+
+/// Characters that may not end a line in CJK text.
+fn is_kinsoku_line_end(ch: char) -> bool {
+    matches!(
+        ch,
+        // Opening brackets and quotes
+        '(' | '['
+            | '{'
+            | '\u{3008}'
+            | '\u{300A}'
+            | '\u{300C}'
+            | '\u{300E}'
+            | '\u{3010}'
+            | '\u{3014}'
+            | '\u{3016}'
+            | '\u{3018}'
+            | '\u{FF08}'
+            | '\u{FF3B}'
+            | '\u{FF5B}'
+            | '\u{2018}'
+            | '\u{201C}'
+    )
+}
+
+/// Whether `ch` is CJK text (ideographs, kana, hangul, CJK punctuation and
+/// full-width forms), which is justified between characters and drawn
+/// upright in vertical lines.
+pub fn is_cjk(ch: char) -> bool {
+    matches!(
+        ch,
+        '\u{1100}'..='\u{11FF}'
+            | '\u{2E80}'..='\u{2FDF}'
+            | '\u{3000}'..='\u{303F}'
+            | '\u{3040}'..='\u{30FF}'
+            | '\u{3100}'..='\u{31FF}'
+            | '\u{3200}'..='\u{4DBF}'
+            | '\u{4E00}'..='\u{9FFF}'
+            | '\u{A960}'..='\u{A97F}'
+            | '\u{AC00}'..='\u{D7FF}'
+            | '\u{F900}'..='\u{FAFF}'
+            | '\u{FE30}'..='\u{FE4F}'
+            | '\u{FF00}'..='\u{FFEF}'
+            | '\u{20000}'..='\u{3FFFF}'
+    )
+}
+
 /// Map a BCP 47 language tag to a `hypher::Lang` for hyphenation.
 ///
 /// Returns `Some(lang)` for supported languages, `None` for unsupported ones
@@ -145,7 +274,8 @@ impl TextLayout {
         Self
     }
 
-    /// Break a string into lines that fit within `max_width`.
+    /// Break a string into lines that fit within `widths`: a single width,
+    /// or a [`LineWidths`] giving some lines less room.
     ///
     /// Uses a greedy line-breaking algorithm with optional hyphenation.
     /// When `hyphens` is `Auto`, long words that don't fit are split at
@@ -156,15 +286,18 @@ impl TextLayout {
         &self,
         font_context: &FontContext,
         text: &str,
-        max_width: f64,
+        widths: impl Into<LineWidths>,
         font_size: f64,
         font_family: &str,
         font_weight: u32,
+        font_stretch: u32,
         font_style: FontStyle,
+        font_features: &[FontFeature],
         letter_spacing: f64,
         hyphens: Hyphens,
         lang: Option<&str>,
     ) -> Vec<BrokenLine> {
+        let widths = widths.into();
         if text.is_empty() {
             return vec![BrokenLine {
                 chars: vec![],
@@ -180,7 +313,9 @@ impl TextLayout {
             font_size,
             font_family,
             font_weight,
+            font_stretch,
             font_style,
+            font_features,
             letter_spacing,
         );
 
@@ -188,6 +323,7 @@ impl TextLayout {
             '-',
             font_family,
             font_weight,
+            font_stretch,
             matches!(font_style, FontStyle::Italic | FontStyle::Oblique),
             font_size,
         ) + letter_spacing;
@@ -257,6 +393,7 @@ impl TextLayout {
                 continue;
             }
 
+            let max_width = widths.get(lines.len());
             if line_width + char_width > max_width && line_start < i {
                 // Line overflow — break at the last break point if possible
                 if let Some(bp) = last_break_point {
@@ -483,7 +620,9 @@ impl TextLayout {
         font_size: f64,
         font_family: &str,
         font_weight: u32,
+        font_stretch: u32,
         font_style: FontStyle,
+        font_features: &[FontFeature],
         letter_spacing: f64,
     ) -> Vec<f64> {
         let italic = matches!(font_style, FontStyle::Italic | FontStyle::Oblique);
@@ -499,8 +638,22 @@ impl TextLayout {
 
         // Fast path: single font family (no comma)
         if !font_family.contains(',') {
-            if let Some(font_data) = font_context.font_data(font_family, font_weight, italic) {
-                let units_per_em = font_context.units_per_em(font_family, font_weight, italic);
+            if let Some(font_data) =
+                font_context.font_data(font_family, font_weight, font_stretch, italic)
+            {
+                let units_per_em =
+                    font_context.units_per_em(font_family, font_weight, font_stretch, italic);
+                let variations =
+                    font_context.variations(font_family, font_weight, font_stretch, italic);
+                // Synthetic bold widens every glyph, like extra letter spacing
+                let glyph_spacing = letter_spacing
+                    + font_context.synthetic_bold_width(
+                        font_family,
+                        font_weight,
+                        font_stretch,
+                        italic,
+                        font_size,
+                    );
 
                 if has_bidi {
                     // Shape each BiDi run with correct direction to match glyph builder
@@ -512,6 +665,8 @@ impl TextLayout {
                         if let Some(shaped) = shaping::shape_text_with_direction(
                             &run_text,
                             font_data,
+                            &variations,
+                            font_features,
                             bidi_run.is_rtl,
                         ) {
                             let num_chars = bidi_run.char_end - bidi_run.char_start;
@@ -520,7 +675,7 @@ impl TextLayout {
                                 num_chars,
                                 units_per_em,
                                 font_size,
-                                letter_spacing,
+                                glyph_spacing,
                             );
                             for (j, w) in cluster_w.into_iter().enumerate() {
                                 widths[bidi_run.char_start + j] = w;
@@ -531,6 +686,7 @@ impl TextLayout {
                                     chars[i],
                                     font_family,
                                     font_weight,
+                                    font_stretch,
                                     italic,
                                     font_size,
                                 ) + letter_spacing;
@@ -540,14 +696,16 @@ impl TextLayout {
                     return widths;
                 }
 
-                if let Some(shaped) = shaping::shape_text(text, font_data) {
+                if let Some(shaped) =
+                    shaping::shape_text(text, font_data, &variations, font_features)
+                {
                     let num_chars = chars.len();
                     return shaping::cluster_widths(
                         &shaped,
                         num_chars,
                         units_per_em,
                         font_size,
-                        letter_spacing,
+                        glyph_spacing,
                     );
                 }
             }
@@ -555,8 +713,14 @@ impl TextLayout {
             return text
                 .chars()
                 .map(|ch| {
-                    font_context.char_width(ch, font_family, font_weight, italic, font_size)
-                        + letter_spacing
+                    font_context.char_width(
+                        ch,
+                        font_family,
+                        font_weight,
+                        font_stretch,
+                        italic,
+                        font_size,
+                    ) + letter_spacing
                 })
                 .collect();
         }
@@ -570,8 +734,14 @@ impl TextLayout {
         chars
             .iter()
             .map(|&ch| {
-                font_context.char_width(ch, font_family, font_weight, italic, font_size)
-                    + letter_spacing
+                font_context.char_width(
+                    ch,
+                    font_family,
+                    font_weight,
+                    font_stretch,
+                    italic,
+                    font_size,
+                ) + letter_spacing
             })
             .collect()
     }
@@ -585,11 +755,14 @@ impl TextLayout {
         text: &str,
         font_family: &str,
         font_weight: u32,
+        font_stretch: u32,
         font_style: FontStyle,
+        font_features: &[FontFeature],
     ) -> Option<Vec<shaping::ShapedGlyph>> {
         let italic = matches!(font_style, FontStyle::Italic | FontStyle::Oblique);
-        let font_data = font_context.font_data(font_family, font_weight, italic)?;
-        shaping::shape_text(text, font_data)
+        let font_data = font_context.font_data(font_family, font_weight, font_stretch, italic)?;
+        let variations = font_context.variations(font_family, font_weight, font_stretch, italic);
+        shaping::shape_text(text, font_data, &variations, font_features)
     }
 
     /// Measure widths for styled chars, using shaping for contiguous runs
@@ -608,7 +781,8 @@ impl TextLayout {
             let italic = matches!(sc.font_style, FontStyle::Italic | FontStyle::Oblique);
 
             // Check if this char's font is a custom font with shaping data
-            if let Some(font_data) = font_context.font_data(&sc.font_family, sc.font_weight, italic)
+            if let Some(font_data) =
+                font_context.font_data(&sc.font_family, sc.font_weight, sc.font_stretch, italic)
             {
                 // Find the end of the contiguous run with the same font
                 let run_start = i;
@@ -619,6 +793,8 @@ impl TextLayout {
                         matches!(next.font_style, FontStyle::Italic | FontStyle::Oblique);
                     if next.font_family == sc.font_family
                         && next.font_weight == sc.font_weight
+                        && next.font_stretch == sc.font_stretch
+                        && next.font_features == sc.font_features
                         && next_italic == italic
                         && (next.font_size - sc.font_size).abs() < 0.001
                     {
@@ -630,16 +806,35 @@ impl TextLayout {
 
                 // Shape this run
                 let run_text: String = chars[run_start..run_end].iter().map(|c| c.ch).collect();
-                if let Some(shaped) = shaping::shape_text(&run_text, font_data) {
+                let variations = font_context.variations(
+                    &sc.font_family,
+                    sc.font_weight,
+                    sc.font_stretch,
+                    italic,
+                );
+                if let Some(shaped) =
+                    shaping::shape_text(&run_text, font_data, &variations, &sc.font_features)
+                {
                     let num_chars = run_end - run_start;
-                    let units_per_em =
-                        font_context.units_per_em(&sc.font_family, sc.font_weight, italic);
+                    let units_per_em = font_context.units_per_em(
+                        &sc.font_family,
+                        sc.font_weight,
+                        sc.font_stretch,
+                        italic,
+                    );
+                    let synthetic_bold = font_context.synthetic_bold_width(
+                        &sc.font_family,
+                        sc.font_weight,
+                        sc.font_stretch,
+                        italic,
+                        sc.font_size,
+                    );
                     let cluster_w = shaping::cluster_widths(
                         &shaped,
                         num_chars,
                         units_per_em,
                         sc.font_size,
-                        sc.letter_spacing,
+                        sc.letter_spacing + synthetic_bold,
                     );
                     for (j, w) in cluster_w.into_iter().enumerate() {
                         widths[run_start + j] = w;
@@ -654,6 +849,7 @@ impl TextLayout {
                 sc.ch,
                 &sc.font_family,
                 sc.font_weight,
+                sc.font_stretch,
                 italic,
                 sc.font_size,
             ) + sc.letter_spacing;
@@ -663,15 +859,16 @@ impl TextLayout {
         widths
     }
 
-    /// Break multi-style text (runs) into lines that fit within `max_width`.
+    /// Break multi-style text (runs) into lines that fit within `widths`.
     pub fn break_runs_into_lines(
         &self,
         font_context: &FontContext,
         chars: &[StyledChar],
-        max_width: f64,
+        widths: impl Into<LineWidths>,
         hyphens: Hyphens,
         lang: Option<&str>,
     ) -> Vec<RunBrokenLine> {
+        let widths = widths.into();
         if chars.is_empty() {
             return vec![RunBrokenLine {
                 chars: vec![],
@@ -741,6 +938,7 @@ impl TextLayout {
                 continue;
             }
 
+            let max_width = widths.get(lines.len());
             if line_width + char_width > max_width && line_start < i {
                 if let Some(bp) = last_break_point {
                     if bp >= line_start {
@@ -766,6 +964,7 @@ impl TextLayout {
                                 '-',
                                 &hyphen_style.font_family,
                                 hyphen_style.font_weight,
+                                hyphen_style.font_stretch,
                                 italic,
                                 hyphen_style.font_size,
                             ) + hyphen_style.letter_spacing;
@@ -814,6 +1013,7 @@ impl TextLayout {
                             '-',
                             &chars[line_start].font_family,
                             chars[line_start].font_weight,
+                            chars[line_start].font_stretch,
                             italic,
                             chars[line_start].font_size,
                         ) + chars[line_start].letter_spacing
@@ -905,6 +1105,12 @@ impl TextLayout {
             .collect()
     }
 
+    /// Measure styled characters as one line, without breaking.
+    pub fn run_line(&self, font_context: &FontContext, chars: &[StyledChar]) -> RunBrokenLine {
+        let widths = self.measure_styled_chars(font_context, chars);
+        self.make_run_line(chars, &widths)
+    }
+
     fn make_run_line(&self, chars: &[StyledChar], widths: &[f64]) -> RunBrokenLine {
         let mut positions = Vec::with_capacity(chars.len());
         let mut x = 0.0;
@@ -940,7 +1146,9 @@ impl TextLayout {
         font_size: f64,
         font_family: &str,
         font_weight: u32,
+        font_stretch: u32,
         font_style: FontStyle,
+        font_features: &[FontFeature],
         letter_spacing: f64,
         hyphens: Hyphens,
         lang: Option<&str>,
@@ -961,7 +1169,9 @@ impl TextLayout {
                                     font_size,
                                     font_family,
                                     font_weight,
+                                    font_stretch,
                                     font_style,
+                                    font_features,
                                     letter_spacing,
                                 )
                             })
@@ -979,7 +1189,9 @@ impl TextLayout {
                     font_size,
                     font_family,
                     font_weight,
+                    font_stretch,
                     font_style,
+                    font_features,
                     letter_spacing,
                 )
             })
@@ -995,7 +1207,9 @@ impl TextLayout {
         font_size: f64,
         font_family: &str,
         font_weight: u32,
+        font_stretch: u32,
         font_style: FontStyle,
+        font_features: &[FontFeature],
         letter_spacing: f64,
     ) -> f64 {
         self.measure_chars(
@@ -1004,7 +1218,9 @@ impl TextLayout {
             font_size,
             font_family,
             font_weight,
+            font_stretch,
             font_style,
+            font_features,
             letter_spacing,
         )
         .iter()
@@ -1019,16 +1235,19 @@ impl TextLayout {
         &self,
         font_context: &FontContext,
         text: &str,
-        max_width: f64,
+        widths: impl Into<LineWidths>,
         font_size: f64,
         font_family: &str,
         font_weight: u32,
+        font_stretch: u32,
         font_style: FontStyle,
+        font_features: &[FontFeature],
         letter_spacing: f64,
         hyphens: Hyphens,
         lang: Option<&str>,
         justify: bool,
     ) -> Vec<BrokenLine> {
+        let widths = widths.into();
         if text.is_empty() {
             return vec![BrokenLine {
                 chars: vec![],
@@ -1044,7 +1263,9 @@ impl TextLayout {
             font_size,
             font_family,
             font_weight,
+            font_stretch,
             font_style,
+            font_features,
             letter_spacing,
         );
 
@@ -1052,6 +1273,7 @@ impl TextLayout {
             '-',
             font_family,
             font_weight,
+            font_stretch,
             matches!(font_style, FontStyle::Italic | FontStyle::Oblique),
             font_size,
         ) + letter_spacing;
@@ -1107,11 +1329,13 @@ impl TextLayout {
                 let seg_lines = self.break_into_lines_optimal(
                     font_context,
                     &seg_text,
-                    max_width,
+                    widths.skip(all_lines.len()),
                     font_size,
                     font_family,
                     font_weight,
+                    font_stretch,
                     font_style,
+                    font_features,
                     letter_spacing,
                     hyphens,
                     lang,
@@ -1132,7 +1356,7 @@ impl TextLayout {
             lang,
         );
         let config = knuth_plass::Config {
-            line_width: max_width,
+            line_widths: widths,
             ..Default::default()
         };
 
@@ -1142,7 +1366,7 @@ impl TextLayout {
                 &items,
                 &chars,
                 &char_widths,
-                max_width,
+                &config.line_widths,
                 justify,
             )
         } else {
@@ -1150,11 +1374,13 @@ impl TextLayout {
             self.break_into_lines(
                 font_context,
                 text,
-                max_width,
+                config.line_widths,
                 font_size,
                 font_family,
                 font_weight,
+                font_stretch,
                 font_style,
+                font_features,
                 letter_spacing,
                 hyphens,
                 lang,
@@ -1169,11 +1395,12 @@ impl TextLayout {
         &self,
         font_context: &FontContext,
         chars: &[StyledChar],
-        max_width: f64,
+        widths: impl Into<LineWidths>,
         hyphens: Hyphens,
         lang: Option<&str>,
         justify: bool,
     ) -> Vec<RunBrokenLine> {
+        let widths = widths.into();
         if chars.is_empty() {
             return vec![RunBrokenLine {
                 chars: vec![],
@@ -1188,8 +1415,14 @@ impl TextLayout {
         let hyphen_width = if !chars.is_empty() {
             let sc = &chars[0];
             let italic = matches!(sc.font_style, FontStyle::Italic | FontStyle::Oblique);
-            font_context.char_width('-', &sc.font_family, sc.font_weight, italic, sc.font_size)
-                + sc.letter_spacing
+            font_context.char_width(
+                '-',
+                &sc.font_family,
+                sc.font_weight,
+                sc.font_stretch,
+                italic,
+                sc.font_size,
+            ) + sc.letter_spacing
         } else {
             0.0
         };
@@ -1232,7 +1465,7 @@ impl TextLayout {
                     let seg_lines = self.break_runs_into_lines_optimal(
                         font_context,
                         &seg_chars,
-                        max_width,
+                        widths.skip(all_lines.len()),
                         hyphens,
                         lang,
                         justify,
@@ -1253,7 +1486,7 @@ impl TextLayout {
                 let seg_lines = self.break_runs_into_lines_optimal(
                     font_context,
                     &seg_chars,
-                    max_width,
+                    widths.skip(all_lines.len()),
                     hyphens,
                     lang,
                     justify,
@@ -1272,7 +1505,7 @@ impl TextLayout {
             lang,
         );
         let config = knuth_plass::Config {
-            line_width: max_width,
+            line_widths: widths,
             ..Default::default()
         };
 
@@ -1282,12 +1515,12 @@ impl TextLayout {
                 &items,
                 chars,
                 &char_widths,
-                max_width,
+                &config.line_widths,
                 justify,
             )
         } else {
             // Fallback to greedy
-            self.break_runs_into_lines(font_context, chars, max_width, hyphens, lang)
+            self.break_runs_into_lines(font_context, chars, config.line_widths, hyphens, lang)
         }
     }
 
@@ -1301,7 +1534,9 @@ impl TextLayout {
         font_size: f64,
         font_family: &str,
         font_weight: u32,
+        font_stretch: u32,
         font_style: FontStyle,
+        font_features: &[FontFeature],
         letter_spacing: f64,
     ) -> Vec<BrokenLine> {
         if lines.is_empty() {
@@ -1318,9 +1553,14 @@ impl TextLayout {
 
         let ellipsis = '\u{2026}'; // …
         let italic = matches!(font_style, FontStyle::Italic | FontStyle::Oblique);
-        let ellipsis_width =
-            font_context.char_width(ellipsis, font_family, font_weight, italic, font_size)
-                + letter_spacing;
+        let ellipsis_width = font_context.char_width(
+            ellipsis,
+            font_family,
+            font_weight,
+            font_stretch,
+            italic,
+            font_size,
+        ) + letter_spacing;
 
         // Measure full first line — if it fits, return as-is
         let char_widths = self.measure_chars(
@@ -1329,7 +1569,9 @@ impl TextLayout {
             font_size,
             font_family,
             font_weight,
+            font_stretch,
             font_style,
+            font_features,
             letter_spacing,
         );
 
@@ -1405,7 +1647,9 @@ impl TextLayout {
         font_size: f64,
         font_family: &str,
         font_weight: u32,
+        font_stretch: u32,
         font_style: FontStyle,
+        font_features: &[FontFeature],
         letter_spacing: f64,
     ) -> Vec<BrokenLine> {
         if lines.is_empty() {
@@ -1424,7 +1668,9 @@ impl TextLayout {
             font_size,
             font_family,
             font_weight,
+            font_stretch,
             font_style,
+            font_features,
             letter_spacing,
         );
 
@@ -1527,6 +1773,7 @@ impl TextLayout {
             '\u{2026}',
             &last_style.font_family,
             last_style.font_weight,
+            last_style.font_stretch,
             italic,
             last_style.font_size,
         ) + last_style.letter_spacing;
@@ -1664,7 +1911,9 @@ mod tests {
             12.0,
             "Helvetica",
             400,
+            100,
             FontStyle::Normal,
+            &[],
             0.0,
             Hyphens::Manual,
             None,
@@ -1684,7 +1933,9 @@ mod tests {
             12.0,
             "Helvetica",
             400,
+            100,
             FontStyle::Normal,
+            &[],
             0.0,
             Hyphens::Manual,
             None,
@@ -1703,7 +1954,9 @@ mod tests {
             12.0,
             "Helvetica",
             400,
+            100,
             FontStyle::Normal,
+            &[],
             0.0,
             Hyphens::Manual,
             None,
@@ -1724,7 +1977,9 @@ mod tests {
             12.0,
             "Helvetica",
             400,
+            100,
             FontStyle::Normal,
+            &[],
             0.0,
             Hyphens::Manual,
             None,
@@ -1743,7 +1998,9 @@ mod tests {
             32.0,
             "Helvetica",
             400,
+            100,
             FontStyle::Normal,
+            &[],
             0.0,
         );
         let bold = tl.measure_width(
@@ -1752,7 +2009,9 @@ mod tests {
             32.0,
             "Helvetica",
             700,
+            100,
             FontStyle::Normal,
+            &[],
             0.0,
         );
         assert!(
@@ -1773,7 +2032,9 @@ mod tests {
             12.0,
             "Helvetica",
             400,
+            100,
             FontStyle::Normal,
+            &[],
             0.0,
             Hyphens::Auto,
             None,
@@ -1803,7 +2064,9 @@ mod tests {
             12.0,
             "Helvetica",
             400,
+            100,
             FontStyle::Normal,
+            &[],
             0.0,
             Hyphens::None,
             None,
@@ -1830,7 +2093,9 @@ mod tests {
             12.0,
             "Helvetica",
             400,
+            100,
             FontStyle::Normal,
+            &[],
             0.0,
             Hyphens::Manual,
             None,
@@ -1867,7 +2132,9 @@ mod tests {
             12.0,
             "Helvetica",
             400,
+            100,
             FontStyle::Normal,
+            &[],
             0.0,
             Hyphens::Auto,
             None,
@@ -1891,7 +2158,9 @@ mod tests {
             12.0,
             "Helvetica",
             400,
+            100,
             FontStyle::Normal,
+            &[],
             0.0,
             Hyphens::Auto,
             None,
@@ -1902,7 +2171,9 @@ mod tests {
             12.0,
             "Helvetica",
             400,
+            100,
             FontStyle::Normal,
+            &[],
             0.0,
             Hyphens::Manual,
             None,
@@ -1929,6 +2200,23 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_kinsoku_keeps_small_kana_and_brackets_attached() {
+        // ちょっと「日本」ー
+        let opps = compute_break_opportunities(
+            "\u{3061}\u{3087}\u{3063}\u{3068}\u{300C}\u{65E5}\u{672C}\u{300D}\u{30FC}",
+        );
+        let allowed = |i: usize| matches!(opps[i], Some(BreakOpportunity::Allowed));
+        // Not before small kana, after an opening bracket, or before a
+        // closing bracket or the prolonged sound mark
+        for i in [1, 2, 5, 7, 8] {
+            assert!(!allowed(i), "break allowed before char {}", i);
+        }
+        // Between ordinary kana, and before an opening bracket
+        assert!(allowed(3));
+        assert!(allowed(4));
+    }
+
     #[test]
     fn test_hyphenation_german() {
         let tl = TextLayout::new();
@@ -1941,7 +2229,9 @@ mod tests {
             12.0,
             "Helvetica",
             400,
+            100,
             FontStyle::Normal,
+            &[],
             0.0,
             Hyphens::Auto,
             Some("de"),
@@ -2014,7 +2304,9 @@ mod tests {
             12.0,
             "Helvetica",
             400,
+            100,
             FontStyle::Normal,
+            &[],
             0.0,
             Hyphens::Manual,
             None,
@@ -2036,7 +2328,9 @@ mod tests {
             12.0,
             "Helvetica",
             400,
+            100,
             FontStyle::Normal,
+            &[],
             0.0,
             Hyphens::Manual,
             None,
@@ -2047,7 +2341,9 @@ mod tests {
             12.0,
             "Helvetica",
             400,
+            100,
             FontStyle::Normal,
+            &[],
             0.0,
         );
         assert!(
@@ -2067,13 +2363,15 @@ mod tests {
             12.0,
             "Helvetica",
             400,
+            100,
             FontStyle::Normal,
+            &[],
             0.0,
             Hyphens::Manual,
             None,
         );
         // Multiple lines exist
-        assert!(lines.len() >= 1);
+        assert!(!lines.is_empty());
 
         let truncated = tl.truncate_with_ellipsis(
             &fc,
@@ -2082,7 +2380,9 @@ mod tests {
             12.0,
             "Helvetica",
             400,
+            100,
             FontStyle::Normal,
+            &[],
             0.0,
         );
         assert_eq!(truncated.len(), 1, "Should be single line");
@@ -2108,7 +2408,9 @@ mod tests {
             12.0,
             "Helvetica",
             400,
+            100,
             FontStyle::Normal,
+            &[],
             0.0,
             Hyphens::Manual,
             None,
@@ -2120,7 +2422,9 @@ mod tests {
             12.0,
             "Helvetica",
             400,
+            100,
             FontStyle::Normal,
+            &[],
             0.0,
         );
         assert_eq!(truncated.len(), 1);
@@ -2141,7 +2445,9 @@ mod tests {
             12.0,
             "Helvetica",
             400,
+            100,
             FontStyle::Normal,
+            &[],
             0.0,
             Hyphens::Manual,
             None,
@@ -2153,7 +2459,9 @@ mod tests {
             12.0,
             "Helvetica",
             400,
+            100,
             FontStyle::Normal,
+            &[],
             0.0,
         );
         assert_eq!(truncated.len(), 1, "Should be single line");
@@ -2189,7 +2497,9 @@ mod tests {
                 10.0,
                 "Helvetica",
                 400,
+                100,
                 FontStyle::Normal,
+                &[],
                 0.0,
                 Hyphens::Auto,
                 Some("en"),
@@ -2201,7 +2511,9 @@ mod tests {
                 10.0,
                 "Helvetica",
                 400,
+                100,
                 FontStyle::Normal,
+                &[],
                 0.0,
                 Hyphens::Auto,
                 Some("en"),
@@ -2249,7 +2561,9 @@ mod tests {
                 font_size,
                 "Helvetica",
                 400,
+                100,
                 FontStyle::Normal,
+                &[],
                 0.0,
                 Hyphens::Auto,
                 Some(lang),
@@ -2261,7 +2575,9 @@ mod tests {
                 font_size,
                 "Helvetica",
                 400,
+                100,
                 FontStyle::Normal,
+                &[],
                 0.0,
                 Hyphens::Auto,
                 Some(lang),
@@ -2288,7 +2604,7 @@ mod tests {
                         let last_pos = line.char_positions.get(last_idx).copied().unwrap_or(0.0);
                         let last_char = line.chars[last_idx];
                         let last_advance =
-                            fc.char_width(last_char, "Helvetica", 400, false, font_size);
+                            fc.char_width(last_char, "Helvetica", 400, 100, false, font_size);
                         let rendered_width = (last_pos + last_advance).max(line.width * 0.5);
                         eprintln!(
                             "{} {} line {}: width={:.4}, rendered={:.4}, max={:.4}, last_char={:?}, text={:?}",
@@ -2299,4 +2615,59 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn test_narrowed_lines_follow_line_widths() {
+        let tl = TextLayout::new();
+        let fc = ctx();
+        let text = "Floats push the opening lines of a paragraph aside, so those lines \
+                    break narrower than the lines that run below the float once it ends.";
+        let widths = LineWidths {
+            first: vec![80.0, 80.0, 80.0],
+            rest: 240.0,
+        };
+
+        let greedy = tl.break_into_lines(
+            &fc,
+            text,
+            widths.clone(),
+            10.0,
+            "Helvetica",
+            400,
+            100,
+            FontStyle::Normal,
+            &[],
+            0.0,
+            Hyphens::None,
+            None,
+        );
+        let optimal = tl.break_into_lines_optimal(
+            &fc,
+            text,
+            widths.clone(),
+            10.0,
+            "Helvetica",
+            400,
+            100,
+            FontStyle::Normal,
+            &[],
+            0.0,
+            Hyphens::None,
+            None,
+            false,
+        );
+        for lines in [&greedy, &optimal] {
+            assert!(lines.len() > 4);
+            for (i, line) in lines.iter().enumerate() {
+                assert!(
+                    line.width <= widths.get(i) + 0.01,
+                    "line {}: {}",
+                    i,
+                    line.text
+                );
+            }
+            // Below the float, lines use the full width again
+            assert!(lines[3].width > 80.0);
+        }
+    }
 }
@@ -6,6 +6,17 @@
 //!
 //! Standard PDF fonts (Helvetica, Times, Courier) bypass shaping entirely —
 //! they use WinAnsi encoding and don't have GSUB/GPOS tables.
+//!
+//! Variable fonts are shaped with the axis coordinates of the instance in
+//! use, so advances and GPOS adjustments match that instance. Static fonts
+//! pass no variations.
+//!
+//! OpenType features from the style (`font_features`, such as `tnum` or
+//! `smcp`) are applied on top of the shaper's defaults. A font without a
+//! feature simply ignores it.
+
+use crate::font::Variation;
+use crate::style::FontFeature;
 
 /// A single glyph produced by OpenType shaping.
 #[derive(Debug, Clone)]
@@ -30,8 +41,13 @@ pub struct ShapedGlyph {
 ///
 /// Returns `None` if the font data can't be parsed. For standard fonts
 /// (no font data), callers should skip shaping entirely.
-pub fn shape_text(text: &str, font_data: &[u8]) -> Option<Vec<ShapedGlyph>> {
-    shape_text_with_direction(text, font_data, false)
+pub fn shape_text(
+    text: &str,
+    font_data: &[u8],
+    variations: &[Variation],
+    features: &[FontFeature],
+) -> Option<Vec<ShapedGlyph>> {
+    shape_text_with_direction(text, font_data, variations, features, false)
 }
 
 /// Shape text with explicit direction control.
@@ -41,16 +57,38 @@ pub fn shape_text(text: &str, font_data: &[u8]) -> Option<Vec<ShapedGlyph>> {
 pub fn shape_text_with_direction(
     text: &str,
     font_data: &[u8],
+    variations: &[Variation],
+    features: &[FontFeature],
     is_rtl: bool,
 ) -> Option<Vec<ShapedGlyph>> {
-    let face = rustybuzz::Face::from_slice(font_data, 0)?;
+    let mut face = rustybuzz::Face::from_slice(font_data, 0)?;
+    if !variations.is_empty() {
+        let variations: Vec<rustybuzz::Variation> = variations
+            .iter()
+            .map(|v| rustybuzz::Variation {
+                tag: v.axis,
+                value: v.value,
+            })
+            .collect();
+        face.set_variations(&variations);
+    }
     let mut buffer = rustybuzz::UnicodeBuffer::new();
     buffer.push_str(text);
     if is_rtl {
         buffer.set_direction(rustybuzz::Direction::RightToLeft);
     }
 
-    let output = rustybuzz::shape(&face, &[], buffer);
+    // Tags are checked when a document is read; one built in code that
+    // isn't a valid tag is skipped rather than truncated into another
+    let features: Vec<rustybuzz::Feature> = features
+        .iter()
+        .filter_map(|f| {
+            let tag = rustybuzz::ttf_parser::Tag::from_bytes(&f.tag_bytes()?);
+            Some(rustybuzz::Feature::new(tag, f.value, ..))
+        })
+        .collect();
+
+    let output = rustybuzz::shape(&face, &features, buffer);
 
     let infos = output.glyph_infos();
     let positions = output.glyph_positions();
@@ -93,9 +131,11 @@ pub fn shape_text_with_direction(
 pub fn shape_text_with_offset(
     text: &str,
     font_data: &[u8],
+    variations: &[Variation],
+    features: &[FontFeature],
     char_offset: u32,
 ) -> Option<Vec<ShapedGlyph>> {
-    let mut glyphs = shape_text(text, font_data)?;
+    let mut glyphs = shape_text(text, font_data, variations, features)?;
     for g in &mut glyphs {
         g.cluster += char_offset;
     }
@@ -175,16 +215,29 @@ mod tests {
     // so we test the public functions with None returns for invalid data)
     #[test]
     fn test_shape_text_invalid_font() {
-        let result = shape_text("Hello", &[0, 1, 2, 3]);
+        let result = shape_text("Hello", &[0, 1, 2, 3], &[], &[]);
         assert!(result.is_none());
     }
 
     #[test]
     fn test_shape_text_empty() {
-        let result = shape_text("", &[0, 1, 2, 3]);
+        let result = shape_text("", &[0, 1, 2, 3], &[], &[]);
         assert!(result.is_none());
     }
 
+    #[test]
+    fn test_features_select_alternate_glyphs() {
+        let font = include_bytes!("../../fonts/NotoSans-Regular.ttf");
+        let glyph = |text: &str, features: &[FontFeature]| {
+            shape_text(text, font, &[], features).unwrap()[0].glyph_id
+        };
+        let small_caps = [FontFeature::new("smcp", 1)];
+        assert_ne!(glyph("a", &small_caps), glyph("a", &[]));
+        // A feature set to 0 is off
+        assert_eq!(glyph("a", &[FontFeature::new("smcp", 0)]), glyph("a", &[]));
+        assert_ne!(glyph("0", &[FontFeature::new("zero", 1)]), glyph("0", &[]));
+    }
+
     #[test]
     fn test_shaped_width_empty() {
         let width = shaped_width(&[], 1000, 12.0);
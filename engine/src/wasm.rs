@@ -1,5 +1,7 @@
 use wasm_bindgen::prelude::*;
 
+use crate::resource::{DenyAllResolver, ResourceResolver};
+
 #[wasm_bindgen]
 pub fn render_pdf(json: &str) -> Result<Vec<u8>, JsValue> {
     crate::render_json(json).map_err(|e| JsValue::from_str(&e.to_string()))
@@ -7,8 +9,9 @@ pub fn render_pdf(json: &str) -> Result<Vec<u8>, JsValue> {
 
 #[wasm_bindgen]
 pub fn render_pdf_with_layout(json: &str) -> Result<JsValue, JsValue> {
-    let (pdf_bytes, layout_info) =
-        crate::render_json_with_layout(json).map_err(|e| JsValue::from_str(&e.to_string()))?;
+    let (pdf_bytes, layout_info, _) =
+        crate::render_json_with_layout(json, &crate::RenderOptions::default())
+            .map_err(|e| JsValue::from_str(&e.to_string()))?;
 
     let result = js_sys::Object::new();
     let pdf_array = js_sys::Uint8Array::from(pdf_bytes.as_slice());
@@ -21,6 +24,51 @@ pub fn render_pdf_with_layout(json: &str) -> Result<JsValue, JsValue> {
     Ok(result.into())
 }
 
+/// Reads referenced assets by calling a JS function `(src) => Uint8Array | undefined`.
+struct JsResolver(js_sys::Function);
+
+impl ResourceResolver for JsResolver {
+    fn resolve(&self, reference: &str) -> Result<Vec<u8>, String> {
+        let value = self
+            .0
+            .call1(&JsValue::NULL, &JsValue::from_str(reference))
+            .map_err(|e| format!("Resolver failed for '{}': {:?}", reference, e))?;
+        if value.is_undefined() || value.is_null() {
+            return Err(format!("No resource named '{}'", reference));
+        }
+        Ok(js_sys::Uint8Array::new(&value).to_vec())
+    }
+}
+
+#[wasm_bindgen]
+pub fn render_pdf_with_diagnostics(
+    json: &str,
+    strict: bool,
+    resolve: Option<js_sys::Function>,
+) -> Result<JsValue, JsValue> {
+    let resolver: Box<dyn ResourceResolver> = match resolve {
+        Some(f) => Box::new(JsResolver(f)),
+        None => Box::new(DenyAllResolver),
+    };
+    let options = crate::RenderOptions {
+        strict,
+        resolver: resolver.as_ref(),
+        fonts: None,
+    };
+    let (pdf_bytes, diagnostics) = crate::render_json_with_diagnostics(json, &options)
+        .map_err(|e| JsValue::from_str(&e.to_string()))?;
+
+    let result = js_sys::Object::new();
+    let pdf_array = js_sys::Uint8Array::from(pdf_bytes.as_slice());
+    let diagnostics = serde_wasm_bindgen::to_value(&diagnostics)
+        .map_err(|e| JsValue::from_str(&format!("Diagnostics serialization error: {}", e)))?;
+
+    js_sys::Reflect::set(&result, &JsValue::from_str("pdf"), &pdf_array)?;
+    js_sys::Reflect::set(&result, &JsValue::from_str("diagnostics"), &diagnostics)?;
+
+    Ok(result.into())
+}
+
 #[wasm_bindgen]
 pub fn render_template_pdf(template_json: &str, data_json: &str) -> Result<Vec<u8>, JsValue> {
     crate::render_template(template_json, data_json).map_err(|e| JsValue::from_str(&e.to_string()))
@@ -31,8 +79,12 @@ pub fn render_template_pdf_with_layout(
     template_json: &str,
     data_json: &str,
 ) -> Result<JsValue, JsValue> {
-    let (pdf_bytes, layout_info) = crate::render_template_with_layout(template_json, data_json)
-        .map_err(|e| JsValue::from_str(&e.to_string()))?;
+    let (pdf_bytes, layout_info, _) = crate::render_template_with_layout(
+        template_json,
+        data_json,
+        &crate::RenderOptions::default(),
+    )
+    .map_err(|e| JsValue::from_str(&e.to_string()))?;
 
     let result = js_sys::Object::new();
     let pdf_array = js_sys::Uint8Array::from(pdf_bytes.as_slice());
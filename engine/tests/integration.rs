@@ -8,9 +8,11 @@
 //! - Page breaks happen at the right places
 //! - Table header repetition works
 
+use forme::diagnostics::{DiagnosticCode, Severity};
 use forme::font::FontContext;
 use forme::layout::LayoutEngine;
 use forme::model::*;
+use forme::resource::{DenyAllResolver, FileSystemResolver, MemoryResolver, ResourceResolver};
 use forme::style::*;
 
 // ─── Helpers ────────────────────────────────────────────────────
@@ -76,7 +78,10 @@ fn make_page_break() -> Node {
 
 fn make_table_row(is_header: bool, cells: Vec<Node>) -> Node {
     Node {
-        kind: NodeKind::TableRow { is_header },
+        kind: NodeKind::TableRow {
+            is_header,
+            footer: None,
+        },
         style: Style::default(),
         children: cells,
         id: None,
@@ -114,6 +119,9 @@ fn default_doc(children: Vec<Node>) -> Document {
         fonts: vec![],
         tagged: false,
         pdfa: None,
+        font_synthesis: Default::default(),
+        footnote_numbering: Default::default(),
+        image_policy: Default::default(),
         default_style: None,
         embedded_data: None,
     }
@@ -337,6 +345,314 @@ fn test_table_page_break_with_many_rows() {
     );
 }
 
+fn make_spanning_cell(col_span: u32, row_span: u32, children: Vec<Node>) -> Node {
+    let mut cell = make_table_cell(children);
+    cell.kind = NodeKind::TableCell { col_span, row_span };
+    cell
+}
+
+fn make_table(rows: Vec<Node>) -> Node {
+    Node {
+        kind: NodeKind::Table { columns: vec![] },
+        style: Style::default(),
+        children: rows,
+        id: None,
+        source_location: None,
+        bookmark: None,
+        href: None,
+        alt: None,
+    }
+}
+
+fn collect_elements_of_type<'a>(
+    elements: &'a [forme::layout::LayoutElement],
+    node_type: &str,
+) -> Vec<&'a forme::layout::LayoutElement> {
+    let mut found = Vec::new();
+    for el in elements {
+        if el.node_type.as_deref() == Some(node_type) {
+            found.push(el);
+        } else {
+            found.extend(collect_elements_of_type(&el.children, node_type));
+        }
+    }
+    found
+}
+
+fn collect_table_cells(
+    elements: &[forme::layout::LayoutElement],
+) -> Vec<&forme::layout::LayoutElement> {
+    collect_elements_of_type(elements, "TableCell")
+}
+
+#[test]
+fn test_table_col_span_covers_multiple_columns() {
+    let table = make_table(vec![
+        make_table_row(
+            false,
+            vec![
+                make_table_cell(vec![make_text("A", 10.0)]),
+                make_table_cell(vec![make_text("B", 10.0)]),
+                make_table_cell(vec![make_text("C", 10.0)]),
+            ],
+        ),
+        make_table_row(
+            false,
+            vec![
+                make_spanning_cell(2, 1, vec![make_text("Wide", 10.0)]),
+                make_table_cell(vec![make_text("D", 10.0)]),
+            ],
+        ),
+    ]);
+    let pages = layout_doc(&default_doc(vec![table]));
+    let cells = collect_table_cells(&pages[0].elements);
+    assert_eq!(cells.len(), 5);
+
+    let col_width = cells[0].width;
+    assert!(
+        (cells[3].width - 2.0 * col_width).abs() < 0.01,
+        "Spanning cell should be two columns wide: {} vs {}",
+        cells[3].width,
+        col_width
+    );
+    assert!(
+        (cells[4].x - cells[2].x).abs() < 0.01,
+        "Cell after the span should start in the third column"
+    );
+}
+
+#[test]
+fn test_table_row_span_covers_multiple_rows() {
+    let table = make_table(vec![
+        make_table_row(
+            false,
+            vec![
+                make_spanning_cell(1, 2, vec![make_text("Tall", 10.0)]),
+                make_table_cell(vec![make_text("B", 10.0)]),
+            ],
+        ),
+        make_table_row(false, vec![make_table_cell(vec![make_text("C", 10.0)])]),
+    ]);
+    let pages = layout_doc(&default_doc(vec![table]));
+    let cells = collect_table_cells(&pages[0].elements);
+    assert_eq!(cells.len(), 3);
+
+    let tall = cells[0];
+    let b = cells[1];
+    let c = cells[2];
+    assert!(
+        (tall.height - (b.height + c.height)).abs() < 0.01,
+        "Row-spanning cell should cover both rows"
+    );
+    assert!(
+        (c.x - b.x).abs() < 0.01,
+        "Cell in the second row should skip the occupied first column"
+    );
+}
+
+#[test]
+fn test_table_row_span_grows_spanned_rows() {
+    let tall_text = "Line of text that wraps. ".repeat(20);
+    let table = make_table(vec![
+        make_table_row(
+            false,
+            vec![
+                make_spanning_cell(1, 2, vec![make_text(&tall_text, 10.0)]),
+                make_table_cell(vec![make_text("B", 10.0)]),
+            ],
+        ),
+        make_table_row(false, vec![make_table_cell(vec![make_text("C", 10.0)])]),
+    ]);
+    let pages = layout_doc(&default_doc(vec![table]));
+    let cells = collect_table_cells(&pages[0].elements);
+    let tall = cells[0];
+    let b = cells[1];
+    let c = cells[2];
+    assert!(
+        c.height > b.height,
+        "Last spanned row should grow to fit the spanning cell"
+    );
+    assert!((tall.height - (b.height + c.height)).abs() < 0.01);
+}
+
+#[test]
+fn test_table_row_span_group_kept_on_one_page() {
+    let mut rows: Vec<Node> = (0..32)
+        .map(|i| {
+            make_table_row(
+                false,
+                vec![
+                    make_table_cell(vec![make_text(&format!("Row {}", i), 10.0)]),
+                    make_table_cell(vec![make_text("Data", 10.0)]),
+                ],
+            )
+        })
+        .collect();
+    // A group of four rows linked by a row span, near the bottom of the page
+    rows.push(make_table_row(
+        false,
+        vec![
+            make_spanning_cell(1, 4, vec![make_text("Group", 10.0)]),
+            make_table_cell(vec![make_text("G0", 10.0)]),
+        ],
+    ));
+    for i in 1..4 {
+        rows.push(make_table_row(
+            false,
+            vec![make_table_cell(vec![make_text(&format!("G{}", i), 10.0)])],
+        ));
+    }
+    let pages = layout_doc(&default_doc(vec![make_table(rows)]));
+    assert!(pages.len() >= 2, "Table should span two pages");
+
+    let page_of = |label: &str| {
+        pages.iter().position(|page| {
+            collect_table_cells(&page.elements).iter().any(|cell| {
+                let mut text = String::new();
+                extract_text_from_elements(&cell.children, &mut text);
+                text == label
+            })
+        })
+    };
+    let group_page = page_of("Group").expect("spanning cell should be laid out");
+    for label in ["G0", "G1", "G2", "G3"] {
+        assert_eq!(
+            page_of(label),
+            Some(group_page),
+            "{} should stay on the same page as the spanning cell",
+            label
+        );
+    }
+}
+
+fn make_auto_column_table(columns: Vec<ColumnWidth>, rows: Vec<Vec<&str>>) -> Node {
+    let mut table = make_table(
+        rows.into_iter()
+            .map(|cells| {
+                make_table_row(
+                    false,
+                    cells
+                        .into_iter()
+                        .map(|text| make_table_cell(vec![make_text(text, 10.0)]))
+                        .collect(),
+                )
+            })
+            .collect(),
+    );
+    table.kind = NodeKind::Table {
+        columns: columns
+            .into_iter()
+            .map(|width| ColumnDef { width })
+            .collect(),
+    };
+    table
+}
+
+#[test]
+fn test_table_auto_columns_size_to_content() {
+    let description = "A long description that needs plenty of room and will wrap onto several lines in any reasonable column";
+    let table = make_auto_column_table(
+        vec![ColumnWidth::Auto, ColumnWidth::Auto],
+        vec![vec!["12", description], vec!["7", description]],
+    );
+    let pages = layout_doc(&default_doc(vec![table]));
+    let cells = collect_table_cells(&pages[0].elements);
+
+    let qty_width = cells[0].width;
+    let desc_width = cells[1].width;
+    assert!(
+        qty_width < 60.0,
+        "Numeric column should shrink to its content, got {}",
+        qty_width
+    );
+    assert!(
+        qty_width > 8.0,
+        "Numeric column should fit padding and digits"
+    );
+    let content_width = 595.28 - PageConfig::default().margin.horizontal();
+    assert!(
+        (qty_width + desc_width - content_width).abs() < 0.01,
+        "Auto columns should fill the table width"
+    );
+}
+
+#[test]
+fn test_table_without_column_defs_sizes_to_content() {
+    let description = "A long description that needs plenty of room and will wrap onto several lines in any reasonable column";
+    let content_width = 595.28 - PageConfig::default().margin.horizontal();
+
+    let table = make_auto_column_table(vec![], vec![vec!["12", description]]);
+    let pages = layout_doc(&default_doc(vec![table]));
+    let cells = collect_table_cells(&pages[0].elements);
+    assert!(
+        cells[0].width < 60.0,
+        "Without defs, columns should size to content, got {}",
+        cells[0].width
+    );
+    assert!((cells[0].width + cells[1].width - content_width).abs() < 0.01);
+
+    // Columns past the last def are sized the same way
+    let table = make_auto_column_table(
+        vec![ColumnWidth::Fixed(100.0)],
+        vec![vec!["Fixed", "7", description]],
+    );
+    let pages = layout_doc(&default_doc(vec![table]));
+    let cells = collect_table_cells(&pages[0].elements);
+    assert!((cells[0].width - 100.0).abs() < 0.01);
+    assert!(cells[1].width < cells[2].width);
+    let total: f64 = cells.iter().map(|c| c.width).sum();
+    assert!((total - content_width).abs() < 0.01);
+}
+
+#[test]
+fn test_table_auto_columns_share_leftover_after_fixed() {
+    let table = make_auto_column_table(
+        vec![
+            ColumnWidth::Fixed(100.0),
+            ColumnWidth::Auto,
+            ColumnWidth::Auto,
+        ],
+        vec![vec![
+            "Fixed",
+            "Short",
+            "Somewhat longer text in this column",
+        ]],
+    );
+    let pages = layout_doc(&default_doc(vec![table]));
+    let cells = collect_table_cells(&pages[0].elements);
+    assert!((cells[0].width - 100.0).abs() < 0.01);
+    assert!(
+        cells[1].width < cells[2].width,
+        "Wider content should get the wider auto column"
+    );
+    let content_width = 595.28 - PageConfig::default().margin.horizontal();
+    let total: f64 = cells.iter().map(|c| c.width).sum();
+    assert!((total - content_width).abs() < 0.01);
+}
+
+#[test]
+fn test_table_auto_columns_keep_words_whole_when_squeezed() {
+    // Both columns have long text; neither should drop below its widest word
+    let long = "Supercalifragilistic words everywhere ".repeat(10);
+    let table = make_auto_column_table(
+        vec![ColumnWidth::Auto, ColumnWidth::Auto],
+        vec![vec!["Expialidocious", &long]],
+    );
+    let pages = layout_doc(&default_doc(vec![table]));
+    let cells = collect_table_cells(&pages[0].elements);
+    fn count_lines(elements: &[forme::layout::LayoutElement]) -> usize {
+        elements
+            .iter()
+            .map(|el| match &el.draw {
+                forme::layout::DrawCommand::Text { lines, .. } => lines.len(),
+                _ => count_lines(&el.children),
+            })
+            .sum()
+    }
+    let first_lines = count_lines(&cells[0].children);
+    assert_eq!(first_lines, 1, "Single-word cell should not wrap");
+}
+
 // ─── JSON Deserialization Tests ─────────────────────────────────
 
 #[test]
@@ -529,11 +845,15 @@ fn test_metadata_in_output() {
             subject: Some("Testing".to_string()),
             creator: None,
             lang: None,
+            outline_open_depth: None,
         },
         default_page: PageConfig::default(),
         fonts: vec![],
         tagged: false,
         pdfa: None,
+        font_synthesis: Default::default(),
+        footnote_numbering: Default::default(),
+        image_policy: Default::default(),
         default_style: None,
         embedded_data: None,
     };
@@ -671,6 +991,9 @@ fn render_with_custom_font(font_data: &[u8], text: &str) -> Vec<u8> {
         fonts: vec![],
         tagged: false,
         pdfa: None,
+        font_synthesis: Default::default(),
+        footnote_numbering: Default::default(),
+        image_policy: Default::default(),
         default_style: None,
         embedded_data: None,
     };
@@ -819,6 +1142,9 @@ fn test_mixed_standard_and_custom_fonts() {
         fonts: vec![],
         tagged: false,
         pdfa: None,
+        font_synthesis: Default::default(),
+        footnote_numbering: Default::default(),
+        image_policy: Default::default(),
         default_style: None,
         embedded_data: None,
     };
@@ -1031,6 +1357,109 @@ fn test_webp_image_produces_valid_pdf() {
     assert!(text.contains("/XObject"), "Page should reference XObject");
 }
 
+/// Helper: a minimal 8x8 baseline CMYK JPEG, every component mid-grey.
+/// `adobe` adds the APP14 segment Adobe's encoder writes.
+fn make_test_cmyk_jpeg(adobe: bool) -> Vec<u8> {
+    let mut data = vec![0xFF, 0xD8];
+    if adobe {
+        data.extend_from_slice(&[0xFF, 0xEE, 0x00, 0x0E]);
+        data.extend_from_slice(b"Adobe\x00\x64\x00\x00\x00\x00\x00");
+    }
+    // Quantization table of ones
+    data.extend_from_slice(&[0xFF, 0xDB, 0x00, 0x43, 0x00]);
+    data.extend_from_slice(&[1; 64]);
+    // Frame: 8x8, four components, no subsampling
+    data.extend_from_slice(&[0xFF, 0xC0, 0x00, 0x14, 8, 0, 8, 0, 8, 4]);
+    for id in 1..=4 {
+        data.extend_from_slice(&[id, 0x11, 0]);
+    }
+    // DC and AC tables with a single one-bit code, for a zero DC
+    // difference and end-of-block
+    for class in [0x00, 0x10] {
+        data.extend_from_slice(&[0xFF, 0xC4, 0x00, 0x14, class, 1]);
+        data.extend_from_slice(&[0; 16]);
+    }
+    // Scan: one block per component, two zero bits each
+    data.extend_from_slice(&[0xFF, 0xDA, 0x00, 0x0E, 4]);
+    for id in 1..=4 {
+        data.extend_from_slice(&[id, 0x00]);
+    }
+    data.extend_from_slice(&[0, 63, 0, 0x00, 0xFF, 0xD9]);
+    data
+}
+
+#[test]
+fn test_cmyk_jpeg_embedded_as_device_cmyk() {
+    let plain = to_data_uri(&make_test_cmyk_jpeg(false), "image/jpeg");
+    let adobe = to_data_uri(&make_test_cmyk_jpeg(true), "image/jpeg");
+    let mut doc = default_doc(vec![
+        make_image_node(&plain, Some(40.0), Some(40.0)),
+        make_image_node(&adobe, Some(40.0), Some(40.0)),
+    ]);
+    let bytes = render_to_pdf(&doc);
+    assert_valid_pdf(&bytes);
+    let text = String::from_utf8_lossy(&bytes);
+    assert!(text.contains("/ColorSpace /DeviceCMYK /BitsPerComponent 8 /Filter /DCTDecode"));
+    assert!(text.contains("/ColorSpace /DeviceCMYK /Decode [1 0 1 0 1 0 1 0]"));
+
+    // Resampling would convert the pixels to RGB, so they're kept as CMYK
+    doc.image_policy.max_dpi = Some(3.6);
+    let text = String::from_utf8_lossy(&render_to_pdf(&doc)).into_owned();
+    assert!(text.contains("/Width 8 /Height 8 /ColorSpace /DeviceCMYK"));
+    assert!(text.contains("/ColorSpace /DeviceCMYK /Decode [1 0 1 0 1 0 1 0]"));
+    assert!(!text.contains("/DeviceRGB"));
+}
+
+#[test]
+fn test_palette_png_embedded_as_indexed() {
+    let mut buf = Vec::new();
+    let mut encoder = png::Encoder::new(&mut buf, 16, 16);
+    encoder.set_color(png::ColorType::Indexed);
+    encoder.set_depth(png::BitDepth::Four);
+    encoder.set_palette(
+        (0..16u8)
+            .flat_map(|i| [i * 16, 0, 255 - i * 16])
+            .collect::<Vec<_>>(),
+    );
+    let mut writer = encoder.write_header().unwrap();
+    writer
+        .write_image_data(&(0..128u8).map(|i| (i % 16) * 17).collect::<Vec<_>>())
+        .unwrap();
+    drop(writer);
+    let src = to_data_uri(&buf, "image/png");
+
+    let bytes = render_to_pdf(&default_doc(vec![make_image_node(&src, Some(64.0), None)]));
+    assert_valid_pdf(&bytes);
+    let text = String::from_utf8_lossy(&bytes);
+    assert!(text.contains("/ColorSpace [/Indexed /DeviceRGB 15 <0000FF1000EF"));
+    assert!(text.contains("/BitsPerComponent 4"));
+    assert!(!text.contains("/SMask"));
+}
+
+#[test]
+fn test_gif_bmp_tiff_images_produce_valid_pdf() {
+    let img = image::DynamicImage::ImageRgb8(image::RgbImage::from_fn(6, 4, |x, _| {
+        image::Rgb([x as u8 * 40, 0, 0])
+    }));
+    for (format, mime) in [
+        (image::ImageFormat::Gif, "image/gif"),
+        (image::ImageFormat::Bmp, "image/bmp"),
+        (image::ImageFormat::Tiff, "image/tiff"),
+    ] {
+        let mut buf = Vec::new();
+        img.write_to(&mut std::io::Cursor::new(&mut buf), format)
+            .unwrap();
+        let src = to_data_uri(&buf, mime);
+        let doc = default_doc(vec![make_image_node(&src, Some(60.0), None)]);
+        let pages = layout_doc(&doc);
+        let images = collect_elements_of_type(&pages[0].elements, "Image");
+        assert_eq!(images[0].height, 40.0, "{:?}", format);
+        let bytes = render_to_pdf(&doc);
+        assert_valid_pdf(&bytes);
+        assert!(String::from_utf8_lossy(&bytes).contains("/Width 6 /Height 4"));
+    }
+}
+
 #[test]
 fn test_image_aspect_ratio() {
     // 8x4 image: aspect ratio 0.5
@@ -1140,84 +1569,402 @@ fn test_image_json_deserialization() {
     );
 }
 
-// ─── Fixed Header/Footer Tests ──────────────────────────────────
-
-fn make_fixed_header(text: &str) -> Node {
-    Node {
-        kind: NodeKind::Fixed {
-            position: FixedPosition::Header,
-        },
-        style: Style {
-            padding: Some(Edges::uniform(8.0)),
-            background_color: Some(Color::rgb(0.9, 0.9, 0.95)),
-            ..Default::default()
-        },
-        children: vec![make_text(text, 10.0)],
-        id: None,
-        source_location: None,
-        bookmark: None,
-        href: None,
-        alt: None,
+#[test]
+fn test_header_image_written_once_for_all_pages() {
+    let src = to_data_uri(&make_test_png(16, 16), "image/png");
+    let mut header = make_fixed_header("Statement");
+    header
+        .children
+        .push(make_image_node(&src, Some(40.0), Some(40.0)));
+    let mut children = vec![header];
+    for i in 0..120 {
+        children.push(make_text(&format!("Line {}", i), 12.0));
     }
-}
+    let doc = default_doc(children);
 
-fn make_fixed_footer(text: &str) -> Node {
-    Node {
-        kind: NodeKind::Fixed {
-            position: FixedPosition::Footer,
-        },
-        style: Style {
-            padding: Some(Edges::uniform(8.0)),
-            background_color: Some(Color::rgb(0.95, 0.95, 0.95)),
-            ..Default::default()
-        },
-        children: vec![make_text(text, 10.0)],
-        id: None,
-        source_location: None,
-        bookmark: None,
-        href: None,
-        alt: None,
-    }
+    // Every page draws the image decoded once in layout
+    let pages = layout_doc(&doc);
+    assert!(pages.len() >= 3);
+    let image_data =
+        |page: &forme::layout::LayoutPage| match &collect_elements_of_type(&page.elements, "Image")
+            [0]
+        .draw
+        {
+            forme::layout::DrawCommand::Image { image_data, .. } => image_data.clone(),
+            _ => unreachable!(),
+        };
+    let first = image_data(&pages[0]);
+    assert!(pages
+        .iter()
+        .all(|page| std::sync::Arc::ptr_eq(&image_data(page), &first)));
+
+    let bytes = render_to_pdf(&doc);
+    assert_valid_pdf(&bytes);
+    let text = String::from_utf8_lossy(&bytes);
+    assert_eq!(text.matches("/Subtype /Image").count(), 1);
+    assert_eq!(text.matches("/XObject << /Im0 ").count(), pages.len());
 }
 
 #[test]
-fn test_fixed_header_single_page() {
+fn test_identical_images_from_different_sources_share_xobject() {
+    use base64::Engine;
+    let png_data = make_test_png(8, 8);
+    let data_uri = to_data_uri(&png_data, "image/png");
+    let raw_b64 = base64::engine::general_purpose::STANDARD.encode(&png_data);
+    let other = to_data_uri(&make_test_png(8, 4), "image/png");
+
     let doc = default_doc(vec![
-        make_fixed_header("Header Text"),
-        make_text("Body content", 12.0),
+        make_image_node(&data_uri, Some(50.0), Some(50.0)),
+        make_image_node(&raw_b64, Some(50.0), Some(50.0)),
+        make_image_node(&other, Some(50.0), Some(25.0)),
     ]);
-    let pages = layout_doc(&doc);
-    assert_eq!(pages.len(), 1);
-    // Header + body elements should be present
-    assert!(
-        pages[0].elements.len() >= 2,
-        "Page should have header + body elements"
-    );
+    let bytes = render_to_pdf(&doc);
+    assert_valid_pdf(&bytes);
+    let text = String::from_utf8_lossy(&bytes);
+    assert_eq!(text.matches("/Subtype /Image").count(), 2);
+    assert!(text.contains("/XObject << /Im0 "));
+    assert!(!text.contains("/Im2"));
+}
+
+/// Helper: create an opaque PNG with photo-like noise, which compresses
+/// poorly without loss.
+fn make_noisy_png(width: u32, height: u32) -> Vec<u8> {
+    let img = image::RgbImage::from_fn(width, height, |x, y| {
+        let n = (x.wrapping_mul(7919) ^ y.wrapping_mul(104729)).wrapping_mul(2654435761);
+        image::Rgb([(n >> 8) as u8, (n >> 16) as u8, (x + y) as u8])
+    });
+    let mut buf = Vec::new();
+    let encoder = image::codecs::png::PngEncoder::new(&mut buf);
+    image::ImageEncoder::write_image(encoder, img.as_raw(), width, height, image::ColorType::Rgb8)
+        .unwrap();
+    buf
 }
 
 #[test]
-fn test_fixed_header_repeats_on_overflow() {
-    let mut children = vec![make_fixed_header("Page Header")];
-    // Add enough content to overflow to 3+ pages
-    for i in 0..120 {
-        children.push(make_text(&format!("Line {}", i), 12.0));
+fn test_image_policy_downsamples_to_largest_drawn_size() {
+    let src = to_data_uri(&make_noisy_png(600, 600), "image/png");
+    let mut doc = default_doc(vec![
+        make_image_node(&src, Some(100.0), Some(100.0)),
+        make_image_node(&src, Some(150.0), Some(150.0)),
+    ]);
+    let full = render_to_pdf(&doc);
+    assert!(String::from_utf8_lossy(&full).contains("/Width 600 /Height 600"));
+
+    doc.image_policy = forme::image_loader::ImagePolicy {
+        max_dpi: Some(72.0),
+        jpeg_quality: Some(80),
+    };
+    let bytes = render_to_pdf(&doc);
+    assert_valid_pdf(&bytes);
+    let text = String::from_utf8_lossy(&bytes);
+    assert!(text.contains("/Width 150 /Height 150"));
+    assert!(text.contains("/DCTDecode"));
+    assert!(bytes.len() < full.len());
+}
+
+#[test]
+fn test_image_policy_json() {
+    let json = r#"{
+        "children": [],
+        "imagePolicy": { "maxDpi": 150, "jpegQuality": 80 }
+    }"#;
+    let doc: Document = serde_json::from_str(json).unwrap();
+    assert_eq!(doc.image_policy.max_dpi, Some(150.0));
+    assert_eq!(doc.image_policy.jpeg_quality, Some(80));
+    let doc: Document = serde_json::from_str(r#"{"children": []}"#).unwrap();
+    assert_eq!(doc.image_policy, Default::default());
+}
+
+fn image_placement(
+    element: &forme::layout::LayoutElement,
+) -> forme::layout::object_fit::ImagePlacement {
+    match &element.draw {
+        forme::layout::DrawCommand::Image { placement, .. } => *placement,
+        _ => panic!("expected an image"),
     }
-    let doc = default_doc(children);
+}
+
+#[test]
+fn test_object_fit_cover_crops_and_contain_letterboxes() {
+    let src = to_data_uri(&make_test_png(40, 20), "image/png");
+    let mut cover = make_image_node(&src, Some(100.0), Some(100.0));
+    cover.style.object_fit = Some(ObjectFit::Cover);
+    let mut contain = make_image_node(&src, Some(100.0), Some(100.0));
+    contain.style.object_fit = Some(ObjectFit::Contain);
+    contain.style.object_position = Some(ObjectPosition {
+        x: Dimension::Percent(50.0),
+        y: Dimension::Percent(0.0),
+    });
+
+    let doc = default_doc(vec![cover]);
     let pages = layout_doc(&doc);
-    assert!(
-        pages.len() >= 3,
-        "Should have 3+ pages, got {}",
-        pages.len()
+    let images = collect_elements_of_type(&pages[0].elements, "Image");
+    assert_eq!((images[0].width, images[0].height), (100.0, 100.0));
+    let placement = image_placement(images[0]);
+    assert_eq!(
+        (placement.x, placement.width, placement.height),
+        (-50.0, 200.0, 100.0)
     );
+    assert_valid_pdf(&render_to_pdf(&doc));
 
-    // Every page should have elements (header renders on each)
-    for (i, page) in pages.iter().enumerate() {
-        assert!(
-            !page.elements.is_empty(),
-            "Page {} should have elements (header should render)",
-            i
-        );
-    }
+    let doc = default_doc(vec![contain]);
+    let pages = layout_doc(&doc);
+    let images = collect_elements_of_type(&pages[0].elements, "Image");
+    let placement = image_placement(images[0]);
+    assert_eq!(
+        (placement.x, placement.y, placement.height),
+        (0.0, 0.0, 50.0)
+    );
+}
+
+#[test]
+fn test_aspect_ratio_sets_height_from_width() {
+    let ratio = |aspect_ratio: f64, width: Option<Dimension>| Style {
+        aspect_ratio: Some(aspect_ratio),
+        width,
+        ..Default::default()
+    };
+    let full = make_styled_view(ratio(2.0, None), vec![]);
+    let fixed = make_styled_view(ratio(4.0, Some(Dimension::Pt(200.0))), vec![]);
+    let row = make_styled_view(
+        Style {
+            flex_direction: Some(FlexDirection::Row),
+            ..Default::default()
+        },
+        vec![make_styled_view(
+            ratio(1.0, Some(Dimension::Pt(120.0))),
+            vec![],
+        )],
+    );
+    let src = to_data_uri(&make_test_png(40, 20), "image/png");
+    let mut image = make_image_node(&src, Some(90.0), None);
+    image.style.aspect_ratio = Some(3.0);
+    let pages = layout_doc(&default_doc(vec![full, fixed, row, image]));
+
+    let elements = &pages[0].elements;
+    assert_eq!(elements[0].height, elements[0].width / 2.0);
+    assert_eq!((elements[1].width, elements[1].height), (200.0, 50.0));
+    assert_eq!(elements[2].height, 120.0);
+    assert_eq!(elements[2].children[0].height, 120.0);
+    let images = collect_elements_of_type(elements, "Image");
+    assert_eq!((images[0].width, images[0].height), (90.0, 30.0));
+}
+
+#[test]
+fn test_object_fit_json_deserialization() {
+    let json = r#"{
+        "children": [{
+            "kind": { "type": "Image", "src": "data:image/png;base64,AA==", "width": 100, "height": 100 },
+            "style": {
+                "objectFit": "ScaleDown",
+                "objectPosition": { "x": { "Pt": 10 }, "y": { "Percent": 100 } },
+                "aspectRatio": 1.5
+            }
+        }]
+    }"#;
+    let doc: Document = serde_json::from_str(json).unwrap();
+    let style = &doc.children[0].style;
+    assert_eq!(style.object_fit, Some(ObjectFit::ScaleDown));
+    assert!(matches!(style.object_position.unwrap().x, Dimension::Pt(x) if x == 10.0));
+    assert_eq!(style.aspect_ratio, Some(1.5));
+}
+
+#[test]
+fn test_background_image_json_deserialization() {
+    let json = r#"{
+        "children": [
+            {
+                "kind": { "type": "View" },
+                "style": {
+                    "backgroundImage": { "LinearGradient": {
+                        "angle": 90,
+                        "stops": [{ "color": { "r": 1, "g": 0, "b": 0, "a": 1 } }, { "color": { "r": 0, "g": 0, "b": 1, "a": 1 }, "offset": 0.4 }]
+                    } }
+                }
+            },
+            {
+                "kind": { "type": "View" },
+                "style": {
+                    "backgroundImage": { "Url": "logo.png" },
+                    "backgroundSize": { "Size": { "width": { "Percent": 50 }, "height": "Auto" } },
+                    "backgroundRepeat": "NoRepeat",
+                    "backgroundPosition": { "x": { "Percent": 100 }, "y": { "Pt": 4 } }
+                }
+            }
+        ]
+    }"#;
+    let doc: Document = serde_json::from_str(json).unwrap();
+    match &doc.children[0].style.background_image {
+        Some(BackgroundImage::LinearGradient(gradient)) => {
+            assert_eq!(gradient.angle, 90.0);
+            assert_eq!(gradient.stops[0].offset, None);
+            assert_eq!(gradient.stops[1].offset, Some(0.4));
+        }
+        other => panic!("expected a linear gradient, got {:?}", other),
+    }
+    let style = &doc.children[1].style;
+    assert!(
+        matches!(&style.background_image, Some(BackgroundImage::Url(src)) if src == "logo.png")
+    );
+    assert!(matches!(
+        style.background_size,
+        Some(BackgroundSize::Size {
+            width: Dimension::Percent(_),
+            height: Dimension::Auto
+        })
+    ));
+    assert_eq!(style.background_repeat, Some(BackgroundRepeat::NoRepeat));
+    assert!(matches!(style.background_position.unwrap().y, Dimension::Pt(y) if y == 4.0));
+}
+
+#[test]
+fn test_background_gradient_and_image_render() {
+    let gradient = make_styled_view(
+        Style {
+            height: Some(Dimension::Pt(60.0)),
+            border_radius: Some(CornerValues::uniform(8.0)),
+            background_image: Some(BackgroundImage::RadialGradient(RadialGradient {
+                stops: vec![
+                    ColorStop {
+                        color: Color::WHITE,
+                        offset: None,
+                    },
+                    ColorStop {
+                        color: Color::rgb(0.2, 0.4, 0.8),
+                        offset: Some(0.7),
+                    },
+                    ColorStop {
+                        color: Color::BLACK,
+                        offset: None,
+                    },
+                ],
+            })),
+            ..Default::default()
+        },
+        vec![],
+    );
+    let src = to_data_uri(&make_test_png(8, 8), "image/png");
+    let image = make_styled_view(
+        Style {
+            height: Some(Dimension::Pt(60.0)),
+            background_image: Some(BackgroundImage::Url(src)),
+            background_size: Some(BackgroundSize::Cover),
+            ..Default::default()
+        },
+        vec![make_text("Over the background", 12.0)],
+    );
+    let doc = default_doc(vec![gradient, image]);
+    let pages = layout_doc(&doc);
+    // Views with nothing but a background image still draw it
+    assert!(pages[0].elements.iter().all(|el| matches!(
+        &el.draw,
+        forme::layout::DrawCommand::Rect {
+            background_image: Some(_),
+            ..
+        }
+    )));
+    let pdf = render_to_pdf(&doc);
+    assert_valid_pdf(&pdf);
+    let text = String::from_utf8_lossy(&pdf);
+    assert!(text.contains("/ShadingType 3"));
+    assert!(text.contains("/FunctionType 3"));
+    assert!(text.contains("/Subtype /Image"));
+}
+
+#[test]
+fn test_broken_background_image_reported() {
+    let view = make_styled_view(
+        Style {
+            height: Some(Dimension::Pt(40.0)),
+            background_image: Some(BackgroundImage::Url("missing.png".to_string())),
+            ..Default::default()
+        },
+        vec![],
+    );
+    let diagnostics = diagnostics_of(&default_doc(vec![view]));
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].code, DiagnosticCode::ImageLoadFailed);
+    assert!(diagnostics[0].message.contains("Background image"));
+}
+
+// ─── Fixed Header/Footer Tests ──────────────────────────────────
+
+fn make_fixed_header(text: &str) -> Node {
+    Node {
+        kind: NodeKind::Fixed {
+            position: FixedPosition::Header,
+        },
+        style: Style {
+            padding: Some(Edges::uniform(8.0)),
+            background_color: Some(Color::rgb(0.9, 0.9, 0.95)),
+            ..Default::default()
+        },
+        children: vec![make_text(text, 10.0)],
+        id: None,
+        source_location: None,
+        bookmark: None,
+        href: None,
+        alt: None,
+    }
+}
+
+fn make_fixed_footer(text: &str) -> Node {
+    Node {
+        kind: NodeKind::Fixed {
+            position: FixedPosition::Footer,
+        },
+        style: Style {
+            padding: Some(Edges::uniform(8.0)),
+            background_color: Some(Color::rgb(0.95, 0.95, 0.95)),
+            ..Default::default()
+        },
+        children: vec![make_text(text, 10.0)],
+        id: None,
+        source_location: None,
+        bookmark: None,
+        href: None,
+        alt: None,
+    }
+}
+
+#[test]
+fn test_fixed_header_single_page() {
+    let doc = default_doc(vec![
+        make_fixed_header("Header Text"),
+        make_text("Body content", 12.0),
+    ]);
+    let pages = layout_doc(&doc);
+    assert_eq!(pages.len(), 1);
+    // Header + body elements should be present
+    assert!(
+        pages[0].elements.len() >= 2,
+        "Page should have header + body elements"
+    );
+}
+
+#[test]
+fn test_fixed_header_repeats_on_overflow() {
+    let mut children = vec![make_fixed_header("Page Header")];
+    // Add enough content to overflow to 3+ pages
+    for i in 0..120 {
+        children.push(make_text(&format!("Line {}", i), 12.0));
+    }
+    let doc = default_doc(children);
+    let pages = layout_doc(&doc);
+    assert!(
+        pages.len() >= 3,
+        "Should have 3+ pages, got {}",
+        pages.len()
+    );
+
+    // Every page should have elements (header renders on each)
+    for (i, page) in pages.iter().enumerate() {
+        assert!(
+            !page.elements.is_empty(),
+            "Page {} should have elements (header should render)",
+            i
+        );
+    }
 }
 
 #[test]
@@ -1745,75 +2492,1067 @@ fn test_empty_json_object_returns_ok() {
     assert!(result.is_ok(), "Empty children should return Ok");
 }
 
-// ─── Page Number Placeholder Tests ──────────────────────────────
+// ─── Diagnostics Tests ──────────────────────────────────────────
+
+fn diagnostics_of(doc: &Document) -> Vec<forme::Diagnostic> {
+    let (pdf, diagnostics) =
+        forme::render_with_diagnostics(doc, &forme::RenderOptions::default()).unwrap();
+    assert_valid_pdf(&pdf);
+    diagnostics
+}
 
 #[test]
-fn test_page_number_placeholder_single_page() {
-    let doc = default_doc(vec![Node {
-        kind: NodeKind::Page {
-            config: PageConfig::default(),
-        },
-        style: Style::default(),
-        children: vec![
-            Node {
-                kind: NodeKind::Fixed {
-                    position: FixedPosition::Footer,
-                },
-                style: Style::default(),
-                children: vec![make_text("Page {{pageNumber}} of {{totalPages}}", 12.0)],
-                id: None,
-                source_location: None,
-                bookmark: None,
-                href: None,
-                alt: None,
-            },
-            make_text("Hello", 12.0),
-        ],
-        id: None,
-        source_location: None,
-        bookmark: None,
-        href: None,
-        alt: None,
-    }]);
-    let pdf_bytes = forme::render(&doc).unwrap();
-    let pdf_str = String::from_utf8_lossy(&pdf_bytes);
-    // Streams are compressed, but the raw PDF bytes should not contain
-    // the placeholder strings (they should have been replaced before encoding).
-    assert!(
-        !pdf_str.contains("{{pageNumber}}"),
-        "Placeholder {{{{pageNumber}}}} should have been replaced"
+fn test_clean_document_has_no_diagnostics() {
+    let doc = default_doc(vec![make_text("Hello", 12.0)]);
+    assert!(diagnostics_of(&doc).is_empty());
+}
+
+#[test]
+fn test_broken_image_reports_node() {
+    let mut image = with_id(
+        make_image_node("not-an-image.png", Some(50.0), None),
+        "logo",
     );
-    assert!(
-        !pdf_str.contains("{{totalPages}}"),
-        "Placeholder {{{{totalPages}}}} should have been replaced"
+    image.source_location = Some(SourceLocation {
+        file: "Invoice.tsx".to_string(),
+        line: 8,
+        column: 3,
+    });
+    let diagnostics = diagnostics_of(&default_doc(vec![image]));
+    assert_eq!(diagnostics.len(), 1);
+    let d = &diagnostics[0];
+    assert_eq!(d.code, DiagnosticCode::ImageLoadFailed);
+    assert_eq!(d.severity, Severity::Error);
+    assert_eq!(d.node_id.as_deref(), Some("logo"));
+    assert_eq!(d.source_location.as_ref().map(|l| l.line), Some(8));
+}
+
+#[test]
+fn test_undecodable_font_reported() {
+    let mut doc = default_doc(vec![make_text("Hello", 12.0)]);
+    doc.fonts = vec![
+        FontEntry {
+            family: "Broken".to_string(),
+            src: "!!not base64!!".to_string(),
+            weight: 400,
+            italic: false,
+            face_index: 0,
+        },
+        FontEntry {
+            family: "Garbage".to_string(),
+            src: to_data_uri(b"definitely not a font", "font/ttf"),
+            weight: 700,
+            italic: false,
+            face_index: 0,
+        },
+    ];
+    let codes: Vec<DiagnosticCode> = diagnostics_of(&doc).iter().map(|d| d.code).collect();
+    assert_eq!(
+        codes,
+        vec![
+            DiagnosticCode::FontDecodeFailed,
+            DiagnosticCode::FontParseFailed
+        ]
     );
 }
 
 #[test]
-fn test_page_number_placeholder_multi_page() {
-    let mut page_children: Vec<Node> = vec![Node {
-        kind: NodeKind::Fixed {
-            position: FixedPosition::Footer,
+fn test_unparseable_font_falls_back_to_default() {
+    let mut text = make_text("Hello", 12.0);
+    text.style.font_family = Some("Garbage".to_string());
+    let mut doc = default_doc(vec![text]);
+    doc.fonts = vec![FontEntry {
+        family: "Garbage".to_string(),
+        src: to_data_uri(b"definitely not a font", "font/ttf"),
+        weight: 400,
+        italic: false,
+        face_index: 0,
+    }];
+    let (pdf, diagnostics) =
+        forme::render_with_diagnostics(&doc, &forme::RenderOptions::default()).unwrap();
+    assert_eq!(diagnostics[0].code, DiagnosticCode::FontParseFailed);
+    assert_valid_pdf(&pdf);
+    let text = String::from_utf8_lossy(&pdf);
+    assert!(text.contains("/BaseFont /Helvetica"), "{}", text);
+    assert!(!text.contains("Garbage"));
+    assert!(!text.contains("/FontFile2"));
+}
+
+#[test]
+fn test_unsupported_svg_elements_reported() {
+    let svg = Node {
+        kind: NodeKind::Svg {
+            width: 100.0,
+            height: 100.0,
+            view_box: None,
+            content: r#"<rect width="10" height="10"/><text x="5" y="5">Hi</text>"#.to_string(),
         },
+        ..make_text("", 12.0)
+    };
+    let diagnostics = diagnostics_of(&default_doc(vec![svg]));
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].code, DiagnosticCode::UnsupportedSvgElement);
+    assert!(diagnostics[0].message.contains("<text>"));
+}
+
+#[test]
+fn test_unbreakable_view_taller_than_page_reported() {
+    let tall = Node {
+        kind: NodeKind::View,
         style: Style {
-            font_size: Some(10.0),
-            ..Style::default()
+            height: Some(Dimension::Pt(2000.0)),
+            wrap: Some(false),
+            ..Default::default()
         },
-        children: vec![make_text("{{pageNumber}}/{{totalPages}}", 10.0)],
-        id: None,
-        source_location: None,
-        bookmark: None,
-        href: None,
-        alt: None,
-    }];
-    for _ in 0..80 {
-        page_children.push(make_text("Line of text to fill the page.", 12.0));
+        ..with_id(make_text("", 12.0), "poster")
+    };
+    let diagnostics = diagnostics_of(&default_doc(vec![tall]));
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].code, DiagnosticCode::ContentOverflow);
+    assert_eq!(diagnostics[0].node_id.as_deref(), Some("poster"));
+}
+
+#[test]
+fn test_diagnostics_not_repeated_across_layout_passes() {
+    // A table of contents lays the document out more than once
+    let doc = default_doc(vec![
+        make_toc(),
+        make_image_node("missing.png", Some(40.0), Some(40.0)),
+        make_chapter("One"),
+    ]);
+    let diagnostics = diagnostics_of(&doc);
+    assert_eq!(diagnostics.len(), 1);
+}
+
+#[test]
+fn test_strict_mode_turns_diagnostics_into_error() {
+    let strict = forme::RenderOptions {
+        strict: true,
+        ..Default::default()
+    };
+    let doc = default_doc(vec![make_image_node("missing.png", Some(40.0), Some(40.0))]);
+    match forme::render_with_diagnostics(&doc, &strict) {
+        Err(forme::FormeError::Diagnostics(diagnostics)) => {
+            assert_eq!(diagnostics[0].code, DiagnosticCode::ImageLoadFailed);
+        }
+        other => panic!("expected a diagnostics error, got {:?}", other.map(|_| ())),
     }
+    let err = forme::render_with_diagnostics(&doc, &strict).unwrap_err();
+    assert!(err.to_string().contains("ImageLoadFailed"));
 
-    let doc = default_doc(vec![Node {
-        kind: NodeKind::Page {
-            config: PageConfig::default(),
-        },
+    let clean = default_doc(vec![make_text("Hello", 12.0)]);
+    assert!(forme::render_with_diagnostics(&clean, &strict).is_ok());
+}
+
+// ─── Resource Resolver Tests ────────────────────────────────────
+
+fn render_with_resolver(
+    doc: &Document,
+    resolver: &dyn ResourceResolver,
+) -> (Vec<u8>, Vec<forme::Diagnostic>) {
+    let options = forme::RenderOptions {
+        strict: false,
+        resolver,
+        fonts: None,
+    };
+    forme::render_with_diagnostics(doc, &options).unwrap()
+}
+
+#[test]
+fn test_memory_resolver_serves_images_and_fonts() {
+    let mut resolver = MemoryResolver::new();
+    resolver.insert("logo.png", make_test_png(4, 4));
+    resolver.insert(
+        "s3://fonts/NotoSans.ttf",
+        include_bytes!("../fonts/NotoSans-Regular.ttf").to_vec(),
+    );
+    let mut doc = default_doc(vec![make_image_node("logo.png", Some(40.0), None)]);
+    doc.fonts = vec![FontEntry {
+        family: "Brand".to_string(),
+        src: "s3://fonts/NotoSans.ttf".to_string(),
+        weight: 400,
+        italic: false,
+        face_index: 0,
+    }];
+    let (pdf, diagnostics) = render_with_resolver(&doc, &resolver);
+    assert!(diagnostics.is_empty(), "{:?}", diagnostics);
+    let text = String::from_utf8_lossy(&pdf);
+    assert!(text.contains("/Subtype /Image"));
+}
+
+#[test]
+fn test_inline_images_need_no_resolver() {
+    let src = to_data_uri(&make_test_png(4, 4), "image/png");
+    let doc = default_doc(vec![make_image_node(&src, Some(40.0), None)]);
+    let (_, diagnostics) = render_with_resolver(&doc, &DenyAllResolver);
+    assert!(diagnostics.is_empty());
+}
+
+#[test]
+fn test_deny_all_resolver_refuses_paths() {
+    let doc = default_doc(vec![make_image_node("/etc/passwd", Some(40.0), Some(40.0))]);
+    let (_, diagnostics) = render_with_resolver(&doc, &DenyAllResolver);
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].code, DiagnosticCode::ImageLoadFailed);
+    assert!(diagnostics[0].message.contains("disabled"));
+}
+
+#[test]
+fn test_render_with_layout_uses_options() {
+    let mut memory = MemoryResolver::new();
+    memory.insert("logo", make_test_png(4, 4));
+    let doc = default_doc(vec![make_image_node("logo", Some(40.0), None)]);
+    let options = forme::RenderOptions {
+        resolver: &memory,
+        ..Default::default()
+    };
+    let (pdf, _, diagnostics) = forme::render_with_layout(&doc, &options).unwrap();
+    assert!(diagnostics.is_empty(), "{:?}", diagnostics);
+    assert!(String::from_utf8_lossy(&pdf).contains("/Subtype /Image"));
+
+    let doc = default_doc(vec![make_image_node("/etc/passwd", Some(40.0), Some(40.0))]);
+    let deny = forme::RenderOptions {
+        resolver: &DenyAllResolver,
+        ..Default::default()
+    };
+    let (_, _, diagnostics) = forme::render_with_layout(&doc, &deny).unwrap();
+    assert_eq!(diagnostics[0].code, DiagnosticCode::ImageLoadFailed);
+    assert!(diagnostics[0].message.contains("disabled"));
+}
+
+#[test]
+fn test_file_system_resolver_refuses_traversal() {
+    let dir = std::env::temp_dir().join(format!("forme-assets-{}", std::process::id()));
+    std::fs::create_dir_all(dir.join("assets")).unwrap();
+    std::fs::write(dir.join("assets/logo.png"), make_test_png(4, 4)).unwrap();
+    std::fs::write(dir.join("private.png"), make_test_png(4, 4)).unwrap();
+    let resolver = FileSystemResolver::new(dir.join("assets"));
+
+    let doc = default_doc(vec![
+        make_image_node("./logo.png", Some(40.0), None),
+        make_image_node("../private.png", Some(40.0), None),
+    ]);
+    let (_, diagnostics) = render_with_resolver(&doc, &resolver);
+    assert_eq!(diagnostics.len(), 1);
+    assert!(diagnostics[0].message.contains("outside"));
+}
+
+// ─── Font Directory Tests ───────────────────────────────────────
+
+const NOTO_SANS_REGULAR: &[u8] = include_bytes!("../fonts/NotoSans-Regular.ttf");
+const NOTO_SANS_BOLD: &[u8] = include_bytes!("../fonts/NotoSans-Bold.ttf");
+
+#[test]
+fn test_scan_font_directory() {
+    let dir = std::env::temp_dir().join(format!("forme-fonts-{}", std::process::id()));
+    std::fs::create_dir_all(dir.join("noto")).unwrap();
+    std::fs::write(dir.join("noto/NotoSans-Regular.ttf"), NOTO_SANS_REGULAR).unwrap();
+    std::fs::write(dir.join("noto/NotoSans-Bold.TTF"), NOTO_SANS_BOLD).unwrap();
+    std::fs::write(dir.join("broken.ttf"), b"not a font").unwrap();
+    std::fs::write(dir.join("README.txt"), b"ignored").unwrap();
+
+    let mut registry = forme::font::FontRegistry::new();
+    let scan = registry.scan_directory(&dir).unwrap();
+    let mut keys: Vec<(String, u32)> = scan
+        .registered
+        .iter()
+        .map(|k| (k.family.clone(), k.weight))
+        .collect();
+    keys.sort();
+    assert_eq!(
+        keys,
+        vec![
+            ("Noto Sans".to_string(), 400),
+            ("Noto Sans".to_string(), 700)
+        ]
+    );
+    assert_eq!(scan.skipped.len(), 1);
+    assert!(scan.skipped[0].0.ends_with("broken.ttf"));
+
+    assert!(registry.scan_directory(dir.join("missing")).is_err());
+
+    let options = forme::RenderOptions {
+        fonts: Some(&registry),
+        ..Default::default()
+    };
+    let doc = default_doc(vec![Node {
+        style: Style {
+            font_family: Some("Noto Sans".to_string()),
+            font_weight: Some(700),
+            ..Default::default()
+        },
+        ..make_text("Scanned", 12.0)
+    }]);
+    let (pdf, diagnostics) = forme::render_with_diagnostics(&doc, &options).unwrap();
+    assert_valid_pdf(&pdf);
+    assert!(diagnostics.is_empty());
+}
+
+#[test]
+fn test_font_entry_face_index_out_of_range() {
+    let mut doc = default_doc(vec![make_text("Hello", 12.0)]);
+    doc.fonts = vec![FontEntry {
+        family: "Brand".to_string(),
+        src: to_data_uri(NOTO_SANS_REGULAR, "font/ttf"),
+        weight: 400,
+        italic: false,
+        face_index: 3,
+    }];
+    let diagnostics = diagnostics_of(&doc);
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].code, DiagnosticCode::FontDecodeFailed);
+    assert!(diagnostics[0].message.contains("out of range"));
+}
+
+#[test]
+fn test_font_entry_face_index_json() {
+    let json = r#"{"family": "Noto Sans CJK JP", "src": "./NotoSansCJK.ttc", "faceIndex": 2}"#;
+    let entry: FontEntry = serde_json::from_str(json).unwrap();
+    assert_eq!(entry.face_index, 2);
+    assert_eq!(entry.weight, 400);
+}
+
+#[test]
+fn test_font_synthesis_json() {
+    let doc: Document = serde_json::from_str(r#"{"children": []}"#).unwrap();
+    assert!(doc.font_synthesis.weight && doc.font_synthesis.style);
+    let json = r#"{"children": [], "fontSynthesis": {"style": false}}"#;
+    let doc: Document = serde_json::from_str(json).unwrap();
+    assert!(doc.font_synthesis.weight);
+    assert!(!doc.font_synthesis.style);
+}
+
+#[test]
+fn test_font_features_apply_to_text_runs() {
+    // The second run inherits small caps from the text node and adds a
+    // slashed zero on top
+    let json = r#"{
+        "children": [{
+            "kind": {"type": "Text", "content": "", "runs": [
+                {"content": "a0"},
+                {"content": "a0", "style": {
+                    "fontVariantCaps": "Normal",
+                    "fontFeatureSettings": [{"tag": "zero", "value": 1}]
+                }},
+                {"content": "a0", "style": {"fontVariantNumeric": ["SlashedZero"]}}
+            ]},
+            "style": {"fontFamily": "Noto Sans", "fontVariantCaps": "SmallCaps"},
+            "children": []
+        }]
+    }"#;
+    let doc: Document = serde_json::from_str(json).unwrap();
+    let pages = layout_doc(&doc);
+    let glyphs: Vec<u16> = collect_text_lines(&pages[0].elements)
+        .iter()
+        .flat_map(|line| line.glyphs.iter().map(|g| g.glyph_id))
+        .collect();
+    assert_eq!(glyphs.len(), 6);
+    let (small_caps, zero, both) = (&glyphs[0..2], &glyphs[2..4], &glyphs[4..6]);
+    assert_ne!(small_caps[0], zero[0], "smcp should change 'a'");
+    assert_ne!(small_caps[1], zero[1], "zero should change '0'");
+    assert_eq!(both[0], small_caps[0]);
+    assert_eq!(both[1], zero[1]);
+}
+
+#[test]
+fn test_invalid_font_feature_tag_is_reported() {
+    for tag in ["sm", "smcp1", "sm\u{e9}"] {
+        let json = format!(
+            r#"{{"children": [{{
+                "kind": {{"type": "Text", "content": "a"}},
+                "style": {{"fontFeatureSettings": [{{"tag": "{}", "value": 1}}]}}
+            }}]}}"#,
+            tag
+        );
+        let msg = forme::render_json(&json).unwrap_err().to_string();
+        assert!(
+            msg.contains("invalid OpenType feature tag"),
+            "{:?} should be rejected: {}",
+            tag,
+            msg
+        );
+    }
+    // Shorter tags padded with spaces are still four characters
+    let style: Style =
+        serde_json::from_str(r#"{"fontFeatureSettings": [{"tag": "cv1 ", "value": 1}]}"#).unwrap();
+    assert!(style.font_feature_settings.is_some());
+}
+
+// ─── Vertical Text and CJK Tests ────────────────────────────────
+
+fn collect_text_lines(elements: &[forme::layout::LayoutElement]) -> Vec<forme::layout::TextLine> {
+    let mut lines = Vec::new();
+    for el in elements {
+        if let forme::layout::DrawCommand::Text { lines: l, .. } = &el.draw {
+            lines.extend(l.iter().cloned());
+        }
+        lines.extend(collect_text_lines(&el.children));
+    }
+    lines
+}
+
+fn make_vertical_text(content: &str, height: f64) -> Node {
+    let mut text = make_text(content, 12.0);
+    text.style.writing_mode = Some(WritingMode::VerticalRl);
+    text.style.height = Some(Dimension::Pt(height));
+    text
+}
+
+#[test]
+fn test_vertical_text_columns_flow_right_to_left() {
+    let doc = default_doc(vec![make_vertical_text(
+        &"\u{7E26}\u{66F8}\u{304D}\u{306E}\u{6587}\u{7AE0}\u{3002}".repeat(8),
+        120.0,
+    )]);
+    let pages = layout_doc(&doc);
+    let lines = collect_text_lines(&pages[0].elements);
+    assert!(lines.len() > 1, "expected several columns");
+    assert!(lines.iter().all(|l| l.vertical && l.width <= 120.01));
+
+    // The first column sits against the right edge, the rest to its left
+    let right_edge = pages[0].width - PageConfig::default().margin.right;
+    assert!((lines[0].x + lines[0].height / 2.0 - right_edge).abs() < 0.01);
+    assert!(lines.windows(2).all(|w| w[1].x < w[0].x));
+    assert!(lines[0]
+        .glyphs
+        .windows(2)
+        .all(|g| g[1].x_offset > g[0].x_offset));
+
+    let text = &pages[0].elements[0];
+    assert!((text.height - 120.0).abs() < 0.01);
+}
+
+#[test]
+fn test_vertical_text_continues_on_next_page() {
+    // Two columns fit across the box; the rest go to later pages
+    let mut view = make_view(vec![make_vertical_text(
+        &"\u{3042}\u{3044}\u{3046}\u{3048}\u{304A}".repeat(12),
+        60.0,
+    )]);
+    view.style.width = Some(Dimension::Pt(40.0));
+    let pages = layout_doc(&default_doc(vec![view]));
+    assert!(pages.len() > 1);
+    for page in &pages {
+        let columns = collect_text_lines(&page.elements);
+        assert!(!columns.is_empty() && columns.len() <= 2);
+    }
+}
+
+#[test]
+fn test_vertical_text_measured_as_columns_in_a_row() {
+    let row = make_styled_view(
+        Style {
+            flex_direction: Some(FlexDirection::Row),
+            ..Default::default()
+        },
+        vec![
+            make_vertical_text("\u{65E5}\u{672C}\u{8A9E}", 100.0),
+            make_text("Caption", 12.0),
+        ],
+    );
+    let pages = layout_doc(&default_doc(vec![row]));
+    let row = &pages[0].elements[0];
+    let vertical = &row.children[0];
+    // One 12pt column at the default line height
+    assert!(
+        (vertical.width - 12.0 * 1.4).abs() < 0.5,
+        "{}",
+        vertical.width
+    );
+    assert!((row.height - 100.0).abs() < 0.01, "{}", row.height);
+}
+
+fn collect_text_infos(elements: &[forme::layout::ElementInfo]) -> Vec<&forme::layout::ElementInfo> {
+    let mut infos = Vec::new();
+    for el in elements {
+        if el.kind == "Text" {
+            infos.push(el);
+        }
+        infos.extend(collect_text_infos(&el.children));
+    }
+    infos
+}
+
+#[test]
+fn test_vertical_text_layout_info_keeps_column_geometry() {
+    let mut text = make_text(&"\u{7E26}\u{66F8}\u{304D}\u{306E}\u{6587}".repeat(60), 12.0);
+    text.style.writing_mode = Some(WritingMode::VerticalRl);
+    let mut doc = default_doc(vec![text]);
+    doc.default_page.size = PageSize::A5;
+    let pages = layout_doc(&doc);
+    let lines = collect_text_lines(&pages[0].elements);
+    let (_, layout, _) = forme::render_with_layout(&doc, &forme::RenderOptions::default()).unwrap();
+
+    let columns = collect_text_infos(&layout.pages[0].elements);
+    assert!(columns.len() > 1, "expected several columns");
+    // Each column is a line height wide
+    for column in &columns {
+        assert!(
+            (column.width - 12.0 * 1.4).abs() < 0.5,
+            "{} x {}",
+            column.width,
+            column.height
+        );
+        assert!(column.height <= layout.pages[0].content_height + 0.01);
+    }
+    // Full columns run down the page, centered on their line's x
+    let first = columns[0];
+    assert!(first.height > first.width * 10.0, "{}", first.height);
+    assert!((first.x + first.width / 2.0 - lines[0].x).abs() < 0.5);
+}
+
+#[test]
+fn test_cjk_justified_between_characters() {
+    let mut cjk = make_text(&"\u{65E5}\u{672C}\u{8A9E}\u{306E}\u{6587}".repeat(30), 12.0);
+    cjk.style.text_align = Some(TextAlign::Justify);
+    let mut latin = make_text(&"justified words ".repeat(30), 12.0);
+    latin.style.text_align = Some(TextAlign::Justify);
+    let pages = layout_doc(&default_doc(vec![cjk, latin]));
+    let lines = collect_text_lines(&pages[0].elements);
+
+    let first_cjk = &lines[0];
+    assert!(first_cjk.char_spacing > 0.0);
+    assert_eq!(first_cjk.word_spacing, 0.0);
+    let first_latin = lines
+        .iter()
+        .find(|l| l.glyphs.first().is_some_and(|g| g.char_value == 'j'))
+        .unwrap();
+    assert!(first_latin.word_spacing > 0.0);
+    assert_eq!(first_latin.char_spacing, 0.0);
+}
+
+#[test]
+fn test_writing_mode_json() {
+    let style: Style = serde_json::from_str(r#"{"writingMode": "vertical-rl"}"#).unwrap();
+    let resolved = style.resolve(None, 500.0);
+    assert_eq!(resolved.writing_mode, WritingMode::VerticalRl);
+    assert_eq!(*resolved.font_features, [FontFeature::new("vert", 1)]);
+    // Children inherit the writing mode
+    let child = Style::default().resolve(Some(&resolved), 500.0);
+    assert_eq!(child.writing_mode, WritingMode::VerticalRl);
+}
+
+// ─── Multi-column Tests ─────────────────────────────────────────
+
+fn make_columns(style: Style, paragraphs: usize) -> Node {
+    let children = (0..paragraphs)
+        .map(|i| {
+            make_text(
+                &format!("Paragraph {} of the newsletter body text.", i),
+                10.0,
+            )
+        })
+        .collect();
+    make_styled_view(
+        Style {
+            column_count: Some(2),
+            column_gap: Some(20.0),
+            ..style
+        },
+        children,
+    )
+}
+
+/// The distinct x positions of the text lines on a page, rounded.
+fn line_columns(page: &forme::layout::LayoutPage) -> Vec<i64> {
+    let mut xs: Vec<i64> = collect_text_lines(&page.elements)
+        .iter()
+        .map(|l| l.x.round() as i64)
+        .collect();
+    xs.sort();
+    xs.dedup();
+    xs
+}
+
+#[test]
+fn test_columns_fill_in_order_then_continue_on_next_page() {
+    let pages = layout_doc(&default_doc(vec![make_columns(Style::default(), 120)]));
+    assert!(pages.len() > 1);
+    let margin = PageConfig::default().margin.left;
+    let column_width = (pages[0].width - PageConfig::default().margin.horizontal() - 20.0) / 2.0;
+    let second = (margin + column_width + 20.0).round() as i64;
+    assert_eq!(line_columns(&pages[0]), vec![margin.round() as i64, second]);
+
+    // The first column holds the first paragraphs
+    let lines = collect_text_lines(&pages[0].elements);
+    let first_line: String = lines[0].glyphs.iter().map(|g| g.char_value).collect();
+    assert!(first_line.starts_with("Paragraph 0 "));
+    assert!(lines.iter().all(|l| l.width <= column_width + 0.01));
+}
+
+#[test]
+fn test_columns_auto_fill_leaves_second_column_empty() {
+    let pages = layout_doc(&default_doc(vec![make_columns(Style::default(), 3)]));
+    assert_eq!(line_columns(&pages[0]).len(), 1);
+}
+
+#[test]
+fn test_columns_balance_the_last_page() {
+    let style = Style {
+        column_fill: Some(ColumnFill::Balance),
+        ..Default::default()
+    };
+    let pages = layout_doc(&default_doc(vec![make_columns(style, 6)]));
+    assert_eq!(pages.len(), 1);
+    assert_eq!(line_columns(&pages[0]).len(), 2);
+    let lines = collect_text_lines(&pages[0].elements);
+    let left = lines.iter().filter(|l| l.x == lines[0].x).count();
+    assert!(
+        left.abs_diff(lines.len() - left) <= 1,
+        "{} of {}",
+        left,
+        lines.len()
+    );
+}
+
+#[test]
+fn test_columns_balance_only_the_last_of_several_pages() {
+    let lines_of = |page: &forme::layout::LayoutPage| -> Vec<(i64, i64, String)> {
+        collect_text_lines(&page.elements)
+            .iter()
+            .map(|l| {
+                let text = l.glyphs.iter().map(|g| g.char_value).collect();
+                (l.x.round() as i64, l.y.round() as i64, text)
+            })
+            .collect()
+    };
+    let balanced = Style {
+        column_fill: Some(ColumnFill::Balance),
+        ..Default::default()
+    };
+    let filled = layout_doc(&default_doc(vec![make_columns(Style::default(), 300)]));
+    let pages = layout_doc(&default_doc(vec![make_columns(balanced, 300)]));
+    assert!(pages.len() > 2);
+    assert_eq!(pages.len(), filled.len());
+
+    // The pages before the last are laid out as they are without balancing
+    let last = pages.len() - 1;
+    for (page, expected) in pages[..last].iter().zip(&filled) {
+        assert_eq!(lines_of(page), lines_of(expected));
+    }
+    // The last page's lines are split evenly between its columns and end
+    // with the last paragraph
+    let lines = lines_of(&pages[last]);
+    let left = lines.iter().filter(|l| l.0 == lines[0].0).count();
+    assert!(
+        left.abs_diff(lines.len() - left) <= 1,
+        "{} of {}",
+        left,
+        lines.len()
+    );
+    assert!(lines.iter().any(|l| l.2.starts_with("Paragraph 299 ")));
+}
+
+#[test]
+fn test_column_span_heading_crosses_columns() {
+    let mut heading = make_text("Section heading", 16.0);
+    heading.style.column_span = Some(ColumnSpan::All);
+    let mut view = make_columns(Style::default(), 6);
+    view.children.insert(4, heading);
+    let after = make_text("After the columns", 10.0);
+    let pages = layout_doc(&default_doc(vec![view, after]));
+    let lines = collect_text_lines(&pages[0].elements);
+    let text =
+        |l: &forme::layout::TextLine| -> String { l.glyphs.iter().map(|g| g.char_value).collect() };
+
+    // The paragraphs before the heading are balanced above it
+    let heading_line = lines.iter().find(|l| text(l) == "Section heading").unwrap();
+    let above: Vec<_> = lines.iter().filter(|l| l.y < heading_line.y).collect();
+    assert_eq!(above.len(), 4);
+    assert!(above.iter().any(|l| l.x > heading_line.x));
+    // Content after the heading starts below it, back in the first column
+    let below = lines
+        .iter()
+        .find(|l| text(l).starts_with("Paragraph 4"))
+        .unwrap();
+    assert!(below.y > heading_line.y);
+    assert_eq!(below.x, heading_line.x);
+    // And the next sibling is below all of it
+    let after = lines
+        .iter()
+        .find(|l| text(l) == "After the columns")
+        .unwrap();
+    assert!(lines.iter().all(|l| l.y <= after.y));
+}
+
+#[test]
+fn test_column_rule_drawn_between_filled_columns() {
+    let style = Style {
+        column_fill: Some(ColumnFill::Balance),
+        column_rule_width: Some(1.0),
+        ..Default::default()
+    };
+    let pages = layout_doc(&default_doc(vec![make_columns(style, 6)]));
+    fn rules(elements: &[forme::layout::LayoutElement], found: &mut Vec<(f64, f64)>) {
+        for el in elements {
+            if matches!(el.draw, forme::layout::DrawCommand::Rect { .. }) && el.width == 1.0 {
+                found.push((el.x, el.height));
+            }
+            rules(&el.children, found);
+        }
+    }
+    let mut found = Vec::new();
+    rules(&pages[0].elements, &mut found);
+    assert_eq!(found.len(), 1);
+    let center = PageConfig::default().margin.left
+        + (pages[0].width - PageConfig::default().margin.horizontal()) / 2.0;
+    assert!((found[0].0 + 0.5 - center).abs() < 0.01);
+    assert!(found[0].1 > 0.0);
+}
+
+#[test]
+fn test_multi_column_json() {
+    let json = r#"{"columnCount": 3, "columnFill": "Balance", "columnRuleWidth": 0.5, "columnRuleColor": {"r": 1.0, "g": 0.0, "b": 0.0, "a": 1.0}}"#;
+    let style: Style = serde_json::from_str(json).unwrap();
+    let resolved = style.resolve(None, 500.0);
+    assert_eq!(resolved.column_count, 3);
+    assert_eq!(resolved.column_fill, ColumnFill::Balance);
+    // Without a gap, columns are 1em apart
+    assert_eq!(resolved.column_gap, 12.0);
+    let span: Style = serde_json::from_str(r#"{"columnSpan": "All"}"#).unwrap();
+    assert_eq!(span.column_span, Some(ColumnSpan::All));
+}
+
+// ─── Float Tests ────────────────────────────────────────────────
+
+const FLOAT_BODY: &str = "A headshot sits at the side of this biography, and the \
+    paragraph wraps around it line by line before running the full width of the \
+    page once it has passed the bottom of the image. The same goes for pull-quotes \
+    on product sheets, which sit in a box at one side of the copy.";
+
+fn make_float_image(side: Float, width: f64, height: f64) -> Node {
+    let src = to_data_uri(&make_test_jpeg(4, 4), "image/jpeg");
+    let mut image = make_image_node(&src, Some(width), Some(height));
+    image.style.float = Some(side);
+    image.style.margin = Some(Edges {
+        top: 0.0,
+        right: 8.0,
+        bottom: 4.0,
+        left: 8.0,
+    });
+    image
+}
+
+#[test]
+fn test_text_wraps_around_left_float() {
+    let body = FLOAT_BODY.repeat(2);
+    let doc = default_doc(vec![make_styled_view(
+        Style::default(),
+        vec![
+            make_float_image(Float::Left, 100.0, 60.0),
+            make_text(&body, 12.0),
+        ],
+    )]);
+    let pages = layout_doc(&doc);
+    let margin = PageConfig::default().margin;
+    let image = collect_elements_of_type(&pages[0].elements, "Image")[0];
+    assert_eq!((image.x, image.y), (margin.left + 8.0, margin.top));
+
+    // The float's margin box is 116 wide and 64 tall
+    let text =
+        |l: &forme::layout::TextLine| -> String { l.glyphs.iter().map(|g| g.char_value).collect() };
+    let lines = collect_text_lines(&pages[0].elements);
+    let (beside, below): (Vec<_>, Vec<_>) =
+        lines.iter().partition(|l| l.y - 12.0 < margin.top + 64.0);
+    assert_eq!(beside.len(), 4);
+    assert!(beside.iter().all(|l| l.x == margin.left + 116.0));
+    assert!(!below.is_empty());
+    assert!(below.iter().all(|l| l.x == margin.left));
+
+    // Without the float, the first line holds more of the text
+    let unwrapped = layout_doc(&default_doc(vec![make_text(&body, 12.0)]));
+    let unwrapped = collect_text_lines(&unwrapped[0].elements);
+    assert!(text(beside[0]).len() < text(&unwrapped[0]).len());
+    assert!(text(&unwrapped[0]).starts_with(&text(beside[0])));
+}
+
+#[test]
+fn test_pull_quote_floats_right_and_encloses_in_parent() {
+    let mut quote = make_styled_view(
+        Style {
+            width: Some(Dimension::Pt(150.0)),
+            float: Some(Float::Right),
+            background_color: Some(Color::rgb(0.9, 0.9, 0.9)),
+            ..Default::default()
+        },
+        vec![make_text(
+            "\u{201C}Best purchase we made all year.\u{201D}",
+            18.0,
+        )],
+    );
+    quote.style.padding = Some(Edges::uniform(6.0));
+    let article = make_styled_view(
+        Style {
+            background_color: Some(Color::rgb(1.0, 1.0, 0.9)),
+            ..Default::default()
+        },
+        vec![quote, make_text("Short intro.", 10.0)],
+    );
+    let after = make_text("After the article", 10.0);
+    let pages = layout_doc(&default_doc(vec![article, after]));
+    let margin = PageConfig::default().margin;
+    let content_right = pages[0].width - margin.right;
+
+    let views = collect_elements_of_type(&pages[0].elements, "View");
+    let (article, quote) = (views[0], &views[0].children[0]);
+    assert_eq!(quote.x + quote.width, content_right);
+    // The article grows to enclose the quote even though its text is short
+    assert_eq!(article.height, quote.height);
+
+    let lines = collect_text_lines(&pages[0].elements);
+    let intro = lines
+        .iter()
+        .find(|l| l.glyphs[0].char_value == 'S')
+        .unwrap();
+    assert!(intro.x + intro.width <= quote.x);
+    let after = lines
+        .iter()
+        .find(|l| l.glyphs[0].char_value == 'A')
+        .unwrap();
+    assert!(after.y - 10.0 >= quote.y + quote.height - 0.01);
+}
+
+#[test]
+fn test_blocks_beside_float_are_narrowed() {
+    let block = make_styled_view(
+        Style {
+            height: Some(Dimension::Pt(20.0)),
+            background_color: Some(Color::rgb(0.0, 0.0, 1.0)),
+            ..Default::default()
+        },
+        vec![],
+    );
+    let doc = default_doc(vec![make_styled_view(
+        Style::default(),
+        vec![make_float_image(Float::Left, 100.0, 60.0), block],
+    )]);
+    let pages = layout_doc(&doc);
+    let margin = PageConfig::default().margin;
+    let views = collect_elements_of_type(&pages[0].elements, "View");
+    let block = &views[0].children[1];
+    assert_eq!(block.x, margin.left + 116.0);
+    assert_eq!(block.width, pages[0].width - margin.horizontal() - 116.0);
+}
+
+#[test]
+fn test_float_json() {
+    let style: Style = serde_json::from_str(r#"{"float": "Right"}"#).unwrap();
+    assert_eq!(style.resolve(None, 500.0).float, Float::Right);
+    assert_eq!(Style::default().resolve(None, 500.0).float, Float::None);
+}
+
+// ─── Footnote Tests ─────────────────────────────────────────────
+
+/// A 12pt paragraph `text` citing a 9pt footnote `note` at its end.
+fn make_cited_text(text: &str, note: &str) -> Node {
+    let mut node = make_text("", 12.0);
+    node.kind = NodeKind::Text {
+        content: String::new(),
+        href: None,
+        runs: vec![
+            TextRun {
+                content: text.to_string(),
+                style: Style::default(),
+                href: None,
+                footnote: None,
+            },
+            TextRun {
+                content: String::new(),
+                style: Style::default(),
+                href: None,
+                footnote: Some(vec![make_text(note, 9.0)]),
+            },
+        ],
+    };
+    node
+}
+
+fn note_lines(page: &forme::layout::LayoutPage) -> Vec<forme::layout::TextLine> {
+    collect_text_lines(&page.elements)
+        .into_iter()
+        .filter(|l| l.glyphs.iter().any(|g| g.font_size == 9.0))
+        .collect()
+}
+
+#[test]
+fn test_footnote_placed_at_bottom_of_page() {
+    let doc = default_doc(vec![
+        make_cited_text("Revenue grew by a third", "Audited figures, FY2025."),
+        make_text("The next paragraph.", 12.0),
+    ]);
+    let pages = layout_doc(&doc);
+    assert_eq!(pages.len(), 1);
+
+    let lines = collect_text_lines(&pages[0].elements);
+    let cited = lines
+        .iter()
+        .find(|l| line_text(l).starts_with("Revenue"))
+        .unwrap();
+    assert_eq!(line_text(cited), "Revenue grew by a third1");
+    let mark = cited.glyphs.last().unwrap();
+    assert!(mark.baseline_shift > 0.0);
+    assert!(mark.font_size < 12.0);
+
+    let notes = note_lines(&pages[0]);
+    assert_eq!(notes.len(), 1);
+    assert_eq!(line_text(&notes[0]), "1 Audited figures, FY2025.");
+    let content_bottom = pages[0].height - PageConfig::default().margin.bottom;
+    assert!(notes[0].y > content_bottom - 20.0);
+    assert!(notes[0].y <= content_bottom);
+    assert!(lines
+        .iter()
+        .all(|l| l.y < notes[0].y || l.glyphs[0].font_size != 12.0));
+
+    assert_valid_pdf(&render_to_pdf(&doc));
+}
+
+#[test]
+fn test_footnote_numbering_per_document_and_per_page() {
+    let children = vec![
+        make_cited_text("First claim", "First source."),
+        make_cited_text("Second claim", "Second source."),
+        make_page_break(),
+        make_cited_text("Third claim", "Third source."),
+    ];
+    let labels = |numbering| -> Vec<Vec<String>> {
+        let mut doc = default_doc(children.clone());
+        doc.footnote_numbering = numbering;
+        layout_doc(&doc)
+            .iter()
+            .map(|page| {
+                note_lines(page)
+                    .iter()
+                    .map(|l| line_text(l)[..1].to_string())
+                    .collect()
+            })
+            .collect()
+    };
+    assert_eq!(
+        labels(FootnoteNumbering::Document),
+        [vec!["1", "2"], vec!["3"]]
+    );
+    assert_eq!(labels(FootnoteNumbering::Page), [vec!["1", "2"], vec!["1"]]);
+}
+
+#[test]
+fn test_long_footnote_continues_on_next_page() {
+    // 38 lines leave room for the cited line and a few lines of its note
+    let mut children: Vec<Node> = (0..38)
+        .map(|_| make_text("Line of text to fill the page.", 12.0))
+        .collect();
+    let note = "This note runs on for much longer than the space left. ".repeat(30);
+    children.push(make_cited_text("Cited at the foot of the page", &note));
+    let pages = layout_doc(&default_doc(children));
+    assert_eq!(pages.len(), 2);
+
+    let first = note_lines(&pages[0]);
+    let rest = note_lines(&pages[1]);
+    assert!(!first.is_empty() && !rest.is_empty());
+    assert!(line_text(&first[0]).starts_with("1 This note"));
+    // The continuation page holds only the rest of the note
+    assert!(collect_text_lines(&pages[1].elements)
+        .iter()
+        .all(|l| l.glyphs[0].font_size == 9.0));
+    let content_bottom = pages[0].height - PageConfig::default().margin.bottom;
+    for lines in [&first, &rest] {
+        assert!(lines.iter().all(|l| l.y <= content_bottom));
+    }
+
+    let whole = layout_doc(&default_doc(vec![make_cited_text("Cited", &note)]));
+    assert_eq!(first.len() + rest.len(), note_lines(&whole[0]).len());
+}
+
+#[test]
+fn test_footnote_json() {
+    let json = r#"{
+        "children": [{
+            "kind": { "type": "Text", "content": "", "runs": [
+                { "content": "Claim" },
+                { "content": "", "footnote": [
+                    { "kind": { "type": "Text", "content": "Source." }, "style": { "fontSize": 9 } }
+                ] }
+            ] }
+        }],
+        "footnoteNumbering": "Page"
+    }"#;
+    let doc: Document = serde_json::from_str(json).unwrap();
+    assert_eq!(doc.footnote_numbering, FootnoteNumbering::Page);
+    let pages = layout_doc(&doc);
+    assert_eq!(line_text(&note_lines(&pages[0])[0]), "1 Source.");
+
+    let style: Style = serde_json::from_str(r#"{"verticalAlign": "Sub"}"#).unwrap();
+    assert_eq!(style.vertical_align, Some(VerticalAlign::Sub));
+}
+
+// ─── Page Number Placeholder Tests ──────────────────────────────
+
+#[test]
+fn test_page_number_placeholder_single_page() {
+    let doc = default_doc(vec![Node {
+        kind: NodeKind::Page {
+            config: PageConfig::default(),
+        },
+        style: Style::default(),
+        children: vec![
+            Node {
+                kind: NodeKind::Fixed {
+                    position: FixedPosition::Footer,
+                },
+                style: Style::default(),
+                children: vec![make_text("Page {{pageNumber}} of {{totalPages}}", 12.0)],
+                id: None,
+                source_location: None,
+                bookmark: None,
+                href: None,
+                alt: None,
+            },
+            make_text("Hello", 12.0),
+        ],
+        id: None,
+        source_location: None,
+        bookmark: None,
+        href: None,
+        alt: None,
+    }]);
+    let pdf_bytes = forme::render(&doc).unwrap();
+    let pdf_str = String::from_utf8_lossy(&pdf_bytes);
+    // Streams are compressed, but the raw PDF bytes should not contain
+    // the placeholder strings (they should have been replaced before encoding).
+    assert!(
+        !pdf_str.contains("{{pageNumber}}"),
+        "Placeholder {{{{pageNumber}}}} should have been replaced"
+    );
+    assert!(
+        !pdf_str.contains("{{totalPages}}"),
+        "Placeholder {{{{totalPages}}}} should have been replaced"
+    );
+}
+
+#[test]
+fn test_page_number_placeholder_multi_page() {
+    let mut page_children: Vec<Node> = vec![Node {
+        kind: NodeKind::Fixed {
+            position: FixedPosition::Footer,
+        },
+        style: Style {
+            font_size: Some(10.0),
+            ..Style::default()
+        },
+        children: vec![make_text("{{pageNumber}}/{{totalPages}}", 10.0)],
+        id: None,
+        source_location: None,
+        bookmark: None,
+        href: None,
+        alt: None,
+    }];
+    for _ in 0..80 {
+        page_children.push(make_text("Line of text to fill the page.", 12.0));
+    }
+
+    let doc = default_doc(vec![Node {
+        kind: NodeKind::Page {
+            config: PageConfig::default(),
+        },
         style: Style::default(),
         children: page_children,
         id: None,
@@ -1858,6 +3597,195 @@ fn test_no_placeholder_unchanged() {
     );
 }
 
+fn make_numbered_page(numbering: Option<PageNumbering>, children: Vec<Node>) -> Node {
+    let mut page_children = vec![Node {
+        kind: NodeKind::Fixed {
+            position: FixedPosition::Footer,
+        },
+        style: Style::default(),
+        children: vec![Node {
+            style: Style {
+                font_size: Some(10.0),
+                text_align: Some(TextAlign::Right),
+                ..Default::default()
+            },
+            ..make_text("{{pageNumber}}", 10.0)
+        }],
+        id: None,
+        source_location: None,
+        bookmark: None,
+        href: None,
+        alt: None,
+    }];
+    page_children.extend(children);
+    Node::page(
+        PageConfig {
+            numbering,
+            ..Default::default()
+        },
+        Style::default(),
+        page_children,
+    )
+}
+
+/// The text of the last text line on a page (the footer, for these tests).
+fn last_line(page: &forme::layout::LayoutPage) -> &forme::layout::TextLine {
+    fn find(elements: &[forme::layout::LayoutElement]) -> Option<&forme::layout::TextLine> {
+        elements.iter().rev().find_map(|el| match &el.draw {
+            forme::layout::DrawCommand::Text { lines, .. } => lines.last(),
+            _ => find(&el.children),
+        })
+    }
+    find(&page.elements).expect("page has text")
+}
+
+fn line_text(line: &forme::layout::TextLine) -> String {
+    line.glyphs.iter().map(|g| g.char_value).collect()
+}
+
+#[test]
+fn test_page_number_resolved_in_layout_and_realigned() {
+    let filler: Vec<Node> = (0..80)
+        .map(|_| make_text("Line of text to fill the page.", 12.0))
+        .collect();
+    let pages = layout_doc(&default_doc(vec![make_numbered_page(None, filler)]));
+    assert!(pages.len() >= 2);
+
+    let right_edge = 595.28 - PageConfig::default().margin.right;
+    for (i, page) in pages.iter().enumerate() {
+        let line = last_line(page);
+        assert_eq!(line_text(line), (i + 1).to_string());
+        assert!(
+            (line.x + line.width - right_edge).abs() < 0.01,
+            "Right-aligned page number should end at the right margin, got {}",
+            line.x + line.width
+        );
+    }
+}
+
+#[test]
+fn test_page_number_sections_restart_with_formats() {
+    let front = make_numbered_page(
+        Some(PageNumbering {
+            start: 1,
+            format: PageNumberFormat::LowerRoman,
+        }),
+        vec![
+            make_text("Preface", 12.0),
+            make_page_break(),
+            make_text("Contents", 12.0),
+        ],
+    );
+    let body = make_numbered_page(
+        Some(PageNumbering::default()),
+        vec![make_text("Chapter 1", 12.0)],
+    );
+    let appendix = make_numbered_page(None, vec![make_text("Appendix", 12.0)]);
+    let pages = layout_doc(&default_doc(vec![front, body, appendix]));
+
+    let labels: Vec<String> = pages.iter().map(|p| line_text(last_line(p))).collect();
+    assert_eq!(labels, vec!["i", "ii", "1", "2"]);
+}
+
+#[test]
+fn test_page_number_tokens_in_body_text_are_reshaped() {
+    let doc = default_doc(vec![
+        make_text("Page {{pageNumber:ROMAN}} of {{totalPages}}", 12.0),
+        make_page_break(),
+        make_text("Section has {{sectionPages}} pages", 12.0),
+    ]);
+    let pages = layout_doc(&doc);
+    assert_eq!(extract_text_from_pages(&pages[..1]), "Page I of 2");
+    assert_eq!(extract_text_from_pages(&pages[1..]), "Section has 2 pages");
+}
+
+#[test]
+fn test_page_numbering_json() {
+    let json = r#"{
+        "children": [{
+            "kind": {"type": "Page", "config": {
+                "size": "A4",
+                "margin": {"top": 54, "right": 54, "bottom": 54, "left": 54},
+                "numbering": {"start": 5, "format": "UpperAlpha"}
+            }},
+            "children": [{"kind": {"type": "Text", "content": "{{pageNumber}}"}}]
+        }]
+    }"#;
+    let doc: Document = serde_json::from_str(json).unwrap();
+    let pages = layout_doc(&doc);
+    assert_eq!(extract_text_from_pages(&pages), "E");
+}
+
+fn with_id(node: Node, id: &str) -> Node {
+    Node {
+        id: Some(id.to_string()),
+        ..node
+    }
+}
+
+#[test]
+fn test_page_ref_resolves_to_target_page() {
+    let doc = default_doc(vec![
+        make_text("See Terms on page {{pageRef:terms}}.", 12.0),
+        make_page_break(),
+        make_text("Filler", 12.0),
+        make_page_break(),
+        with_id(
+            make_view(vec![with_id(make_text("Terms", 12.0), "terms-title")]),
+            "terms",
+        ),
+        make_text("Back to the title on page {{pageRef:terms-title}}.", 12.0),
+    ]);
+    let pages = layout_doc(&doc);
+    assert_eq!(pages.len(), 3);
+    assert_eq!(extract_text_from_pages(&pages[..1]), "See Terms on page 3.");
+    assert!(extract_text_from_pages(&pages[2..]).ends_with("title on page 3."));
+}
+
+#[test]
+fn test_page_ref_follows_target_across_page_overflow() {
+    // The target lands wherever the filler pushes it, not where it starts
+    let mut children = vec![make_text("Summary on page {{pageRef:summary}}", 12.0)];
+    for _ in 0..120 {
+        children.push(make_text("Line of text to fill the page.", 12.0));
+    }
+    children.push(with_id(make_text("Summary", 12.0), "summary"));
+    let pages = layout_doc(&default_doc(children));
+    let expected = format!("Summary on page {}", pages.len());
+    assert!(extract_text_from_pages(&pages[..1]).starts_with(&expected));
+}
+
+#[test]
+fn test_page_ref_uses_target_section_format() {
+    let front = make_numbered_page(
+        Some(PageNumbering {
+            start: 1,
+            format: PageNumberFormat::LowerRoman,
+        }),
+        vec![
+            make_text("Intro", 12.0),
+            make_page_break(),
+            with_id(make_text("Preface", 12.0), "preface"),
+        ],
+    );
+    let body = make_numbered_page(
+        Some(PageNumbering::default()),
+        vec![make_text(
+            "As the preface on page {{pageRef:preface}} explains",
+            12.0,
+        )],
+    );
+    let pages = layout_doc(&default_doc(vec![front, body]));
+    assert!(extract_text_from_pages(&pages[2..]).starts_with("As the preface on page ii explains"));
+}
+
+#[test]
+fn test_page_ref_to_missing_id() {
+    let doc = default_doc(vec![make_text("See page {{pageRef:nowhere}}", 12.0)]);
+    let pages = layout_doc(&doc);
+    assert_eq!(extract_text_from_pages(&pages), "See page ??");
+}
+
 // ── Feature 1: Links Tests ──────────────────────────────────────
 
 #[test]
@@ -1980,6 +3908,7 @@ fn test_text_runs_render_valid_pdf() {
                     content: "Hello ".to_string(),
                     style: Style::default(),
                     href: None,
+                    footnote: None,
                 },
                 TextRun {
                     content: "bold".to_string(),
@@ -1988,11 +3917,13 @@ fn test_text_runs_render_valid_pdf() {
                         ..Default::default()
                     },
                     href: None,
+                    footnote: None,
                 },
                 TextRun {
                     content: " world".to_string(),
                     style: Style::default(),
                     href: None,
+                    footnote: None,
                 },
             ],
         },
@@ -2022,6 +3953,7 @@ fn test_text_runs_with_href_per_run() {
                     content: "Normal text ".to_string(),
                     style: Style::default(),
                     href: None,
+                    footnote: None,
                 },
                 TextRun {
                     content: "linked text".to_string(),
@@ -2030,6 +3962,7 @@ fn test_text_runs_with_href_per_run() {
                         ..Default::default()
                     },
                     href: Some("https://example.com".to_string()),
+                    footnote: None,
                 },
             ],
         },
@@ -2126,6 +4059,104 @@ fn test_no_bookmarks_no_outlines() {
     );
 }
 
+fn make_bookmarked(title: &str, children: Vec<Node>) -> Node {
+    Node {
+        bookmark: Some(title.to_string()),
+        ..make_view(children)
+    }
+}
+
+fn nested_bookmarks_doc() -> Document {
+    // Section 1.1 runs long enough to push Section 1.2 onto the next page
+    let long_section = (0..60).map(|_| make_text("One", 12.0)).collect();
+    default_doc(vec![
+        make_bookmarked(
+            "Chapter 1",
+            vec![
+                make_bookmarked("Section 1.1", long_section),
+                make_bookmarked("Section 1.2", vec![make_text("Two", 12.0)]),
+            ],
+        ),
+        make_bookmarked(
+            "Chapter 2",
+            vec![make_bookmarked(
+                "Section 2.1",
+                vec![Node {
+                    bookmark: Some("Section 2.1.1".to_string()),
+                    ..make_text("Three", 12.0)
+                }],
+            )],
+        ),
+    ])
+}
+
+/// The outline item dictionary for a bookmark title.
+fn outline_item<'a>(pdf: &'a str, title: &str) -> &'a str {
+    let start = pdf.find(&format!("/Title ({title})")).unwrap();
+    let end = start + pdf[start..].find(">>").unwrap();
+    &pdf[start..end]
+}
+
+#[test]
+fn test_nested_bookmarks_get_levels() {
+    fn collect(elements: &[forme::layout::LayoutElement], found: &mut Vec<(String, u32)>) {
+        for el in elements {
+            if let Some(title) = &el.bookmark {
+                found.push((title.clone(), el.bookmark_level));
+            }
+            collect(&el.children, found);
+        }
+    }
+    let pages = layout_doc(&nested_bookmarks_doc());
+    assert_eq!(pages.len(), 2);
+    let mut found = Vec::new();
+    for page in &pages {
+        collect(&page.elements, &mut found);
+    }
+    let expected = [
+        ("Chapter 1", 0),
+        ("Section 1.1", 1),
+        ("Section 1.2", 1),
+        ("Chapter 2", 0),
+        ("Section 2.1", 1),
+        ("Section 2.1.1", 2),
+    ];
+    let expected: Vec<(String, u32)> = expected.iter().map(|(t, l)| (t.to_string(), *l)).collect();
+    assert_eq!(found, expected);
+}
+
+#[test]
+fn test_nested_bookmarks_write_outline_tree() {
+    let bytes = render_to_pdf(&nested_bookmarks_doc());
+    assert_valid_pdf(&bytes);
+    let text = String::from_utf8_lossy(&bytes);
+
+    assert!(text.contains("/Type /Outlines /First"));
+    assert!(text.contains("/Count 6 >>"), "every entry starts expanded");
+    let chapter = outline_item(&text, "Chapter 1");
+    assert!(chapter.contains("/First") && chapter.contains("/Last"));
+    assert!(chapter.contains("/Count 2"));
+    assert!(chapter.contains("/Next"));
+    assert!(outline_item(&text, "Chapter 2").contains("/Count 2"));
+    assert!(outline_item(&text, "Section 2.1").contains("/Count 1"));
+    assert!(!outline_item(&text, "Section 1.2").contains("/First"));
+    // Siblings only link to siblings
+    assert!(!outline_item(&text, "Section 1.2").contains("/Next"));
+}
+
+#[test]
+fn test_outline_open_depth_collapses_deeper_entries() {
+    let mut doc = nested_bookmarks_doc();
+    doc.metadata.outline_open_depth = Some(1);
+    let bytes = render_to_pdf(&doc);
+    let text = String::from_utf8_lossy(&bytes);
+
+    // Chapters stay expanded; Section 2.1 starts collapsed
+    assert!(text.contains("/Count 5 >>"));
+    assert!(outline_item(&text, "Chapter 2").contains("/Count 1"));
+    assert!(outline_item(&text, "Section 2.1").contains("/Count -1"));
+}
+
 #[test]
 fn test_bookmarks_json_deserialization() {
     let json = r#"{
@@ -2818,6 +4849,343 @@ fn test_table_cell_overflow_near_page_bottom() {
     assert_valid_pdf(&bytes);
 }
 
+fn make_tall_row_table(row_style: Style) -> Node {
+    // ~115 lines of 10pt text in a half-width column — taller than a Letter page
+    let tall_cell_text = "Tall cell line. ".repeat(400);
+    let mut tall_row = make_table_row(
+        false,
+        vec![
+            make_table_cell(vec![make_text(&tall_cell_text, 10.0)]),
+            make_table_cell(vec![make_text("Short", 10.0)]),
+        ],
+    );
+    tall_row.style = row_style;
+    make_table(vec![
+        make_table_row(
+            true,
+            vec![
+                make_table_cell(vec![make_text("Col A", 10.0)]),
+                make_table_cell(vec![make_text("Col B", 10.0)]),
+            ],
+        ),
+        tall_row,
+        make_table_row(
+            false,
+            vec![
+                make_table_cell(vec![make_text("After", 10.0)]),
+                make_table_cell(vec![make_text("Row", 10.0)]),
+            ],
+        ),
+    ])
+}
+
+#[test]
+fn test_table_tall_row_splits_across_pages() {
+    let doc = default_doc(vec![make_tall_row_table(Style::default())]);
+    let pages = layout_doc(&doc);
+    assert!(
+        pages.len() >= 2,
+        "Row taller than a page should split, got {} pages",
+        pages.len()
+    );
+
+    let mut pages_with_fragment = 0;
+    for (i, page) in pages.iter().enumerate() {
+        let mut text = String::new();
+        extract_text_from_elements(&page.elements, &mut text);
+        assert!(
+            text.starts_with("Col ACol B"),
+            "Page {} should start with the repeated header row, got {:?}",
+            i + 1,
+            &text[..text.len().min(20)]
+        );
+        if text.contains("Tall cell line.") {
+            pages_with_fragment += 1;
+        }
+
+        let content_bottom = page.height - PageConfig::default().margin.bottom;
+        for cell in collect_table_cells(&page.elements) {
+            assert!(
+                cell.y + cell.height <= content_bottom + 0.01,
+                "Cell on page {} overflows the page bottom ({} > {})",
+                i + 1,
+                cell.y + cell.height,
+                content_bottom
+            );
+        }
+    }
+
+    assert!(
+        pages_with_fragment >= 2,
+        "Tall cell should be split across pages, found on {}",
+        pages_with_fragment
+    );
+
+    let text = extract_text_from_pages(&pages);
+    assert!(text.ends_with("AfterRow"));
+    assert_valid_pdf(&render_to_pdf(&doc));
+}
+
+#[test]
+fn test_table_tall_row_in_row_span_group_splits_across_pages() {
+    let tall_cell_text = "Tall cell line. ".repeat(400);
+    let table = make_table(vec![
+        make_table_row(
+            false,
+            vec![
+                make_spanning_cell(1, 2, vec![make_text("Span", 10.0)]),
+                make_table_cell(vec![make_text("First", 10.0)]),
+            ],
+        ),
+        // Taller than a page, below the spanning cell
+        make_table_row(
+            false,
+            vec![make_table_cell(vec![make_text(&tall_cell_text, 10.0)])],
+        ),
+        make_table_row(
+            false,
+            vec![
+                make_table_cell(vec![make_text("After", 10.0)]),
+                make_table_cell(vec![make_text("Row", 10.0)]),
+            ],
+        ),
+    ]);
+    let doc = default_doc(vec![table]);
+    let pages = layout_doc(&doc);
+
+    let content_bottom = pages[0].height - PageConfig::default().margin.bottom;
+    let mut pages_with_fragment = 0;
+    for (i, page) in pages.iter().enumerate() {
+        let mut text = String::new();
+        extract_text_from_elements(&page.elements, &mut text);
+        if text.contains("Tall cell line.") {
+            pages_with_fragment += 1;
+        }
+        for cell in collect_table_cells(&page.elements) {
+            assert!(
+                cell.y + cell.height <= content_bottom + 0.01,
+                "Cell on page {} overflows the page bottom ({} > {})",
+                i + 1,
+                cell.y + cell.height,
+                content_bottom
+            );
+        }
+    }
+    assert!(
+        pages_with_fragment >= 2,
+        "Tall row should be split across pages, found on {}",
+        pages_with_fragment
+    );
+
+    let text = extract_text_from_pages(&pages);
+    assert!(text.starts_with("SpanFirst"), "{:?}", &text[..20]);
+    assert!(text.ends_with("AfterRow"));
+    assert_valid_pdf(&render_to_pdf(&doc));
+}
+
+#[test]
+fn test_table_row_wrap_false_stays_atomic() {
+    let doc = default_doc(vec![make_tall_row_table(Style {
+        wrap: Some(false),
+        ..Default::default()
+    })]);
+    let pages = layout_doc(&doc);
+
+    // The tall row and the row after it — no continuation fragments
+    let body_rows: usize = pages
+        .iter()
+        .map(|page| {
+            collect_elements_of_type(&page.elements, "TableRow")
+                .iter()
+                .filter(|row| !row.is_header_row)
+                .count()
+        })
+        .sum();
+    assert_eq!(
+        body_rows, 2,
+        "A row with wrap: false should be laid out as a single fragment"
+    );
+}
+
+// ─── Table Footer Tests ─────────────────────────────────────────
+
+fn make_footer_row(mode: TableFooter, cells: Vec<Node>) -> Node {
+    let mut row = make_table_row(false, cells);
+    row.kind = NodeKind::TableRow {
+        is_header: false,
+        footer: Some(mode),
+    };
+    row
+}
+
+fn make_ledger_table(num_rows: usize) -> Node {
+    let mut rows = vec![make_table_row(
+        true,
+        vec![
+            make_table_cell(vec![make_text("Item", 10.0)]),
+            make_table_cell(vec![make_text("Amount", 10.0)]),
+        ],
+    )];
+    for i in 0..num_rows {
+        rows.push(make_table_row(
+            false,
+            vec![
+                make_table_cell(vec![make_text(&format!("Item {}", i), 10.0)]),
+                make_table_cell(vec![make_text(&format!("{}", i + 1), 10.0)]),
+            ],
+        ));
+    }
+    // Footer rows can appear anywhere among the children
+    rows.insert(
+        1,
+        make_footer_row(
+            TableFooter::Continued,
+            vec![
+                make_table_cell(vec![make_text("Carried forward", 10.0)]),
+                make_table_cell(vec![make_text("{{pageSum:1}}/{{runningSum:1}}", 10.0)]),
+            ],
+        ),
+    );
+    rows.push(make_footer_row(
+        TableFooter::Final,
+        vec![
+            make_table_cell(vec![make_text("Grand total", 10.0)]),
+            make_table_cell(vec![make_text("{{runningSum:1}}", 10.0)]),
+        ],
+    ));
+    make_table(rows)
+}
+
+#[test]
+fn test_table_footer_rows_per_fragment() {
+    let pages = layout_doc(&default_doc(vec![make_ledger_table(80)]));
+    assert!(pages.len() >= 3, "80-row ledger should span 3+ pages");
+
+    let mut running = 0;
+    for (i, page) in pages.iter().enumerate() {
+        let mut text = String::new();
+        extract_text_from_elements(&page.elements, &mut text);
+
+        // Sum the body amounts on this page
+        let page_sum: i64 = collect_elements_of_type(&page.elements, "TableRow")
+            .iter()
+            .filter_map(|row| {
+                let cells = collect_table_cells(&row.children);
+                let mut label = String::new();
+                extract_text_from_elements(&cells.first()?.children, &mut label);
+                let mut amount = String::new();
+                extract_text_from_elements(&cells.get(1)?.children, &mut amount);
+                if !label.starts_with("Item ") {
+                    return None;
+                }
+                amount.parse::<i64>().ok()
+            })
+            .sum();
+        running += page_sum;
+
+        let is_last = i + 1 == pages.len();
+        if is_last {
+            assert!(!text.contains("Carried forward"));
+            assert!(
+                text.ends_with("Grand total3240"),
+                "Last page should end with the final footer, got {:?}",
+                &text[text.len().saturating_sub(40)..]
+            );
+        } else {
+            let expected = format!("Carried forward{}/{}", page_sum, running);
+            assert!(
+                text.ends_with(&expected),
+                "Page {} should end with {:?}, got {:?}",
+                i + 1,
+                expected,
+                &text[text.len().saturating_sub(40)..]
+            );
+            assert!(!text.contains("Grand total"));
+        }
+
+        let content_bottom = page.height - PageConfig::default().margin.bottom;
+        for cell in collect_table_cells(&page.elements) {
+            assert!(
+                cell.y + cell.height <= content_bottom + 0.01,
+                "Footer space should be reserved on page {}",
+                i + 1
+            );
+        }
+    }
+}
+
+#[test]
+fn test_table_footer_single_page_shows_only_final() {
+    let pages = layout_doc(&default_doc(vec![make_ledger_table(3)]));
+    assert_eq!(pages.len(), 1);
+    let text = extract_text_from_pages(&pages);
+    assert!(!text.contains("Carried forward"));
+    assert!(text.ends_with("Grand total6"), "got {:?}", text);
+}
+
+#[test]
+fn test_table_footer_sums_decimal_comma_amounts() {
+    let amount_row = |amount: &str| {
+        make_table_row(
+            false,
+            vec![
+                make_table_cell(vec![make_text("Item", 10.0)]),
+                make_table_cell(vec![make_text(amount, 10.0)]),
+            ],
+        )
+    };
+    let table = make_table(vec![
+        amount_row("1.234,56"),
+        amount_row("12,5"),
+        // Written the other way: left out and reported, not misread
+        amount_row("3.5"),
+        make_footer_row(
+            TableFooter::Final,
+            vec![
+                make_table_cell(vec![make_text("Total", 10.0)]),
+                make_table_cell(vec![make_text("{{runningSum:1}}", 10.0)]),
+            ],
+        ),
+    ]);
+    let doc = default_doc(vec![table]);
+    let text = extract_text_from_pages(&layout_doc(&doc));
+    assert!(text.ends_with("Total1.247,06"), "got {:?}", text);
+
+    let diagnostics = diagnostics_of(&doc);
+    assert_eq!(diagnostics.len(), 1, "{:?}", diagnostics);
+    assert_eq!(
+        diagnostics[0].code,
+        DiagnosticCode::InconsistentNumberFormat
+    );
+    assert!(diagnostics[0].message.contains("3.5"));
+}
+
+#[test]
+fn test_table_footer_json_deserialization() {
+    let json = r#"{
+        "children": [{
+            "kind": { "type": "Table" },
+            "children": [
+                { "kind": { "type": "TableRow", "footer": "Repeat" }, "children": [] },
+                { "kind": { "type": "TableRow" }, "children": [] }
+            ]
+        }]
+    }"#;
+    let doc: Document = serde_json::from_str(json).unwrap();
+    let table = &doc.children[0];
+    assert!(matches!(
+        table.children[0].kind,
+        NodeKind::TableRow {
+            footer: Some(TableFooter::Repeat),
+            ..
+        }
+    ));
+    assert!(matches!(
+        table.children[1].kind,
+        NodeKind::TableRow { footer: None, .. }
+    ));
+}
+
 // ─── Internal Link (Anchor) Tests ───────────────────────────────
 
 #[test]
@@ -3038,6 +5406,94 @@ fn test_view_href_produces_link_annotation() {
     );
 }
 
+fn make_link(text: &str, href: &str) -> Node {
+    Node {
+        kind: NodeKind::Text {
+            content: text.to_string(),
+            href: Some(href.to_string()),
+            runs: vec![],
+        },
+        ..make_text(text, 12.0)
+    }
+}
+
+#[test]
+fn test_internal_link_to_node_id() {
+    let doc = default_doc(vec![
+        make_link("See the terms", "#terms"),
+        make_page_break(),
+        with_id(make_text("Terms and conditions", 12.0), "terms"),
+    ]);
+    let bytes = render_to_pdf(&doc);
+    assert_valid_pdf(&bytes);
+    let text = String::from_utf8_lossy(&bytes);
+    assert_eq!(text.matches("/S /GoTo").count(), 1);
+}
+
+#[test]
+fn test_node_ids_become_named_destinations() {
+    let doc = default_doc(vec![
+        with_id(make_text("Beta", 12.0), "beta"),
+        make_page_break(),
+        with_id(make_text("Alpha", 12.0), "alpha"),
+    ]);
+    let bytes = render_to_pdf(&doc);
+    let text = String::from_utf8_lossy(&bytes);
+    assert!(text.contains("/Names << /Dests"));
+    // Name tree keys are sorted
+    let alpha = text.find("(alpha) [").expect("alpha destination");
+    let beta = text.find("(beta) [").expect("beta destination");
+    assert!(alpha < beta);
+}
+
+#[test]
+fn test_named_destinations_alongside_embedded_data() {
+    let mut doc = default_doc(vec![with_id(make_text("Intro", 12.0), "intro")]);
+    doc.embedded_data = Some("{}".to_string());
+    let bytes = render_to_pdf(&doc);
+    let text = String::from_utf8_lossy(&bytes);
+    assert!(text.contains("/Names << /Dests"));
+    assert!(text.contains(" /EmbeddedFiles "));
+}
+
+#[test]
+fn test_missing_link_target_warns_once() {
+    let doc = default_doc(vec![
+        make_link("Broken", "#nowhere"),
+        make_link("Broken again", "#nowhere"),
+        make_link("Fine", "#here"),
+        with_id(make_text("Here", 12.0), "here"),
+    ]);
+    let engine = LayoutEngine::new();
+    engine.layout(&doc, &FontContext::new());
+    let diagnostics = engine.diagnostics();
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].code, DiagnosticCode::MissingLinkTarget);
+    assert!(diagnostics[0].message.contains("#nowhere"));
+}
+
+#[test]
+fn test_missing_page_ref_target_warns_once() {
+    let doc = default_doc(vec![
+        with_id(make_text("See page {{pageRef:nowhere}}", 12.0), "see"),
+        make_text("And {{pageRef:nowhere}} again, {{pageRef:terms}}", 12.0),
+        with_id(make_text("Terms", 12.0), "terms"),
+    ]);
+    let engine = LayoutEngine::new();
+    engine.layout(&doc, &FontContext::new());
+    let diagnostics = engine.diagnostics();
+    assert_eq!(diagnostics.len(), 1, "{:?}", diagnostics);
+    assert_eq!(diagnostics[0].code, DiagnosticCode::MissingPageRefTarget);
+    assert_eq!(diagnostics[0].node_id.as_deref(), Some("see"));
+    assert!(diagnostics[0].message.contains("nowhere"));
+
+    let strict = forme::RenderOptions {
+        strict: true,
+        ..Default::default()
+    };
+    assert!(forme::render_with_diagnostics(&doc, &strict).is_err());
+}
+
 #[test]
 fn test_internal_link_json_deserialization() {
     let json = r##"{
@@ -3065,6 +5521,152 @@ fn test_internal_link_json_deserialization() {
     );
 }
 
+// ─── Table of Contents Tests ────────────────────────────────────
+
+fn make_toc() -> Node {
+    Node {
+        kind: NodeKind::TableOfContents {
+            leader: ".".to_string(),
+        },
+        ..make_view(vec![])
+    }
+}
+
+fn make_chapter(title: &str) -> Node {
+    Node {
+        bookmark: Some(title.to_string()),
+        ..make_view(vec![make_text(title, 12.0)])
+    }
+}
+
+#[test]
+fn test_table_of_contents_lists_bookmarks_with_pages() {
+    let doc = default_doc(vec![
+        make_toc(),
+        make_page_break(),
+        make_chapter("Chapter A"),
+        make_page_break(),
+        make_chapter("Chapter B"),
+    ]);
+    let pages = layout_doc(&doc);
+    assert_eq!(pages.len(), 3);
+
+    let toc_text = extract_text_from_pages(&pages[..1]);
+    assert!(toc_text.starts_with("Chapter A..."), "got {toc_text}");
+    assert!(toc_text.contains("..2Chapter B..."), "got {toc_text}");
+    assert!(toc_text.ends_with("..3"), "got {toc_text}");
+
+    // The page number sits at the right edge, with the leader running up to it
+    let texts = collect_elements_of_type(&pages[0].elements, "Text");
+    let label = texts.last().unwrap();
+    let content_right = pages[0].width - PageConfig::default().margin.right;
+    assert!((label.x + label.width - content_right).abs() < 1.0);
+}
+
+#[test]
+fn test_table_of_contents_relayout_when_it_spans_pages() {
+    // Sixty rows don't fit on one page, so every chapter lands a page later
+    // than the first layout pass put it
+    let mut children = vec![make_toc()];
+    for i in 1..=60 {
+        children.push(make_page_break());
+        children.push(make_chapter(&format!("Section {i}")));
+    }
+    let pages = layout_doc(&default_doc(children));
+    assert_eq!(pages.len(), 62);
+
+    let toc_text = extract_text_from_pages(&pages[..2]);
+    assert!(toc_text.starts_with("Section 1..."), "got {toc_text}");
+    assert!(toc_text.contains("..3Section 2..."), "got {toc_text}");
+    assert!(toc_text.ends_with("..62"), "got {toc_text}");
+}
+
+#[test]
+fn test_table_of_contents_rows_link_to_bookmarks() {
+    let doc = default_doc(vec![
+        make_toc(),
+        make_page_break(),
+        make_chapter("Chapter A"),
+        make_chapter("Chapter B"),
+    ]);
+    let bytes = render_to_pdf(&doc);
+    assert_valid_pdf(&bytes);
+    let text = String::from_utf8_lossy(&bytes);
+    assert_eq!(text.matches("/S /GoTo").count(), 2);
+}
+
+#[test]
+fn test_table_of_contents_rows_link_to_their_own_bookmark() {
+    // Two chapters share a title, and another node's id is that title
+    let mut named = make_text("Elsewhere", 12.0);
+    named.id = Some("Overview".to_string());
+    let doc = default_doc(vec![
+        make_toc(),
+        make_page_break(),
+        make_chapter("Overview"),
+        make_page_break(),
+        make_chapter("Overview"),
+        make_page_break(),
+        named,
+    ]);
+    let pages = layout_doc(&doc);
+    let rows: Vec<&forme::layout::LayoutElement> = pages[0]
+        .elements
+        .iter()
+        .flat_map(|el| std::iter::once(el).chain(&el.children))
+        .filter(|el| el.href.is_some())
+        .collect();
+    assert_eq!(rows.len(), 2);
+
+    let page_of_target = |row: &forme::layout::LayoutElement| {
+        let id = &row.href.as_deref().unwrap()[1..];
+        pages.iter().position(|page| {
+            fn has_id(elements: &[forme::layout::LayoutElement], id: &str) -> bool {
+                elements
+                    .iter()
+                    .any(|el| el.node_id.as_deref() == Some(id) || has_id(&el.children, id))
+            }
+            has_id(&page.elements, id)
+        })
+    };
+    assert_eq!(page_of_target(rows[0]), Some(1));
+    assert_eq!(page_of_target(rows[1]), Some(2));
+
+    // The leader holds only the copies that fit between title and number
+    for row in rows {
+        let leader = &row.children[1];
+        let lines = collect_text_lines(std::slice::from_ref(leader));
+        let dots = lines[0].glyphs.len();
+        assert!(dots > 10 && dots < 200, "got {} leader glyphs", dots);
+        assert!(lines[0].width <= leader.width + 0.01);
+    }
+    assert_valid_pdf(&render_to_pdf(&doc));
+}
+
+#[test]
+fn test_table_of_contents_json() {
+    let json = r#"{
+        "children": [
+            {
+                "kind": { "type": "TableOfContents" },
+                "children": [
+                    { "kind": { "type": "Text", "content": "Contents" } }
+                ]
+            },
+            { "kind": { "type": "PageBreak" } },
+            {
+                "kind": { "type": "Text", "content": "Introduction" },
+                "bookmark": "Introduction"
+            }
+        ]
+    }"#;
+    let doc: Document = serde_json::from_str(json).unwrap();
+    let pages = layout_doc(&doc);
+    let toc_text = extract_text_from_pages(&pages[..1]);
+    assert!(toc_text.starts_with("ContentsIntroduction..."));
+    assert!(toc_text.ends_with(".2"));
+}
+
 // ─── Breakable View Background/Border Preservation ──────────────
 
 /// Helper: count top-level Rect elements on a page
@@ -3114,10 +5716,14 @@ fn test_breakable_view_with_background_splits_across_pages() {
             },
             margin: Edges::uniform(20.0),
             wrap: true,
+            numbering: None,
         },
         fonts: vec![],
         tagged: false,
         pdfa: None,
+        font_synthesis: Default::default(),
+        footnote_numbering: Default::default(),
+        image_policy: Default::default(),
         default_style: None,
         embedded_data: None,
     };
@@ -3190,10 +5796,14 @@ fn test_breakable_view_background_does_not_overlap_footer() {
             },
             margin: Edges::uniform(margin),
             wrap: true,
+            numbering: None,
         },
         fonts: vec![],
         tagged: false,
         pdfa: None,
+        font_synthesis: Default::default(),
+        footnote_numbering: Default::default(),
+        image_policy: Default::default(),
         default_style: None,
         embedded_data: None,
     };
@@ -3257,10 +5867,14 @@ fn test_breakable_view_without_visual_stays_unwrapped() {
             },
             margin: Edges::uniform(20.0),
             wrap: true,
+            numbering: None,
         },
         fonts: vec![],
         tagged: false,
         pdfa: None,
+        font_synthesis: Default::default(),
+        footnote_numbering: Default::default(),
+        image_policy: Default::default(),
         default_style: None,
         embedded_data: None,
     };
@@ -3552,6 +6166,7 @@ fn test_breakable_view_continuation_page_has_top_padding() {
         },
         margin: Edges::uniform(20.0),
         wrap: true,
+        numbering: None,
     };
     let padding = 15.0;
 
@@ -3588,6 +6203,9 @@ fn test_breakable_view_continuation_page_has_top_padding() {
         fonts: vec![],
         tagged: false,
         pdfa: None,
+        font_synthesis: Default::default(),
+        footnote_numbering: Default::default(),
+        image_policy: Default::default(),
         default_style: None,
         embedded_data: None,
     };
@@ -3601,17 +6219,13 @@ fn test_breakable_view_continuation_page_has_top_padding() {
 
     // On continuation pages (page 2+), the wrapper Rect element should exist
     // and the first child inside it should be offset by padding.top from the Rect's top edge.
-    for page_idx in 1..pages.len() {
-        let page = &pages[page_idx];
+    for (page_idx, page) in pages.iter().enumerate().skip(1) {
         // Find the wrapper Rect element (the breakable view's background)
         let wrapper = page
             .elements
             .iter()
             .find(|el| matches!(el.draw, forme::layout::DrawCommand::Rect { .. }))
-            .expect(&format!(
-                "Page {} should have a wrapper Rect element",
-                page_idx + 1
-            ));
+            .unwrap_or_else(|| panic!("Page {} should have a wrapper Rect element", page_idx + 1));
 
         assert!(
             !wrapper.children.is_empty(),
@@ -3936,11 +6550,15 @@ fn test_document_lang_in_pdf_catalog() {
             subject: None,
             creator: None,
             lang: Some("en-US".to_string()),
+            outline_open_depth: None,
         },
         default_page: PageConfig::default(),
         fonts: vec![],
         tagged: false,
         pdfa: None,
+        font_synthesis: Default::default(),
+        footnote_numbering: Default::default(),
+        image_policy: Default::default(),
         default_style: None,
         embedded_data: None,
     };
@@ -4142,6 +6760,7 @@ fn test_justified_text_produces_valid_pdf() {
                     size: PageSize::Letter,
                     margin: Edges { top: 36.0, right: 36.0, bottom: 36.0, left: 36.0 },
                     wrap: true,
+                    numbering: None,
                 },
             },
             style: Style::default(),
@@ -4174,10 +6793,14 @@ fn test_justified_text_produces_valid_pdf() {
             size: PageSize::Letter,
             margin: Edges { top: 72.0, right: 72.0, bottom: 72.0, left: 72.0 },
             wrap: true,
+            numbering: None,
         },
         fonts: vec![],
         tagged: false,
         pdfa: None,
+        font_synthesis: Default::default(),
+        footnote_numbering: Default::default(),
+        image_policy: Default::default(),
         default_style: None,
         embedded_data: None,
     };
@@ -4204,6 +6827,7 @@ fn test_lang_inherits_to_text_nodes() {
                         left: 36.0,
                     },
                     wrap: true,
+                    numbering: None,
                 },
             },
             style: Style::default(),
@@ -4227,10 +6851,14 @@ fn test_lang_inherits_to_text_nodes() {
                 left: 72.0,
             },
             wrap: true,
+            numbering: None,
         },
         fonts: vec![],
         tagged: false,
         pdfa: None,
+        font_synthesis: Default::default(),
+        footnote_numbering: Default::default(),
+        image_policy: Default::default(),
         default_style: None,
         embedded_data: None,
     };
@@ -4255,6 +6883,7 @@ fn test_per_node_lang_override() {
                         left: 36.0,
                     },
                     wrap: true,
+                    numbering: None,
                 },
             },
             style: Style::default(),
@@ -4295,10 +6924,14 @@ fn test_per_node_lang_override() {
                 left: 72.0,
             },
             wrap: true,
+            numbering: None,
         },
         fonts: vec![],
         tagged: false,
         pdfa: None,
+        font_synthesis: Default::default(),
+        footnote_numbering: Default::default(),
+        image_policy: Default::default(),
         default_style: None,
         embedded_data: None,
     };
@@ -4322,6 +6955,9 @@ fn test_tagged_pdf_has_struct_tree_root() {
         fonts: vec![],
         tagged: true,
         pdfa: None,
+        font_synthesis: Default::default(),
+        footnote_numbering: Default::default(),
+        image_policy: Default::default(),
         default_style: None,
         embedded_data: None,
     };
@@ -4375,6 +7011,9 @@ fn test_tagged_pdf_parent_tree_consistency() {
         fonts: vec![],
         tagged: true,
         pdfa: None,
+        font_synthesis: Default::default(),
+        footnote_numbering: Default::default(),
+        image_policy: Default::default(),
         default_style: None,
         embedded_data: None,
     };
@@ -4421,6 +7060,7 @@ fn test_tagged_pdf_nested_text_roles() {
                     ..Default::default()
                 },
                 href: None,
+                footnote: None,
             }],
         },
         style: Style {
@@ -4446,6 +7086,9 @@ fn test_tagged_pdf_nested_text_roles() {
         fonts: vec![],
         tagged: true,
         pdfa: None,
+        font_synthesis: Default::default(),
+        footnote_numbering: Default::default(),
+        image_policy: Default::default(),
         default_style: None,
         embedded_data: None,
     };
@@ -4468,7 +7111,10 @@ fn test_tagged_pdf_nested_text_roles() {
 fn test_tagged_pdf_table_th_td() {
     // Table with header row and body rows
     let header_row = Node {
-        kind: NodeKind::TableRow { is_header: true },
+        kind: NodeKind::TableRow {
+            is_header: true,
+            footer: None,
+        },
         style: Style::default(),
         children: vec![Node {
             kind: NodeKind::TableCell {
@@ -4491,7 +7137,10 @@ fn test_tagged_pdf_table_th_td() {
     };
 
     let body_row = Node {
-        kind: NodeKind::TableRow { is_header: false },
+        kind: NodeKind::TableRow {
+            is_header: false,
+            footer: None,
+        },
         style: Style::default(),
         children: vec![Node {
             kind: NodeKind::TableCell {
@@ -4539,6 +7188,9 @@ fn test_tagged_pdf_table_th_td() {
         fonts: vec![],
         tagged: true,
         pdfa: None,
+        font_synthesis: Default::default(),
+        footnote_numbering: Default::default(),
+        image_policy: Default::default(),
         default_style: None,
         embedded_data: None,
     };
@@ -4589,6 +7241,9 @@ fn test_tagged_pdf_figure_alt_text() {
         fonts: vec![],
         tagged: true,
         pdfa: None,
+        font_synthesis: Default::default(),
+        footnote_numbering: Default::default(),
+        image_policy: Default::default(),
         default_style: None,
         embedded_data: None,
     };
@@ -4854,6 +7509,9 @@ fn test_qrcode_renders_to_pdf() {
         fonts: vec![],
         tagged: false,
         pdfa: None,
+        font_synthesis: Default::default(),
+        footnote_numbering: Default::default(),
+        image_policy: Default::default(),
         default_style: None,
         embedded_data: None,
     };
@@ -4884,11 +7542,15 @@ fn test_qrcode_with_explicit_size() {
         fonts: vec![],
         tagged: false,
         pdfa: None,
+        font_synthesis: Default::default(),
+        footnote_numbering: Default::default(),
+        image_policy: Default::default(),
         default_style: None,
         embedded_data: None,
     };
 
-    let (pdf, layout) = forme::render_with_layout(&doc).expect("Should render");
+    let (pdf, layout, _) =
+        forme::render_with_layout(&doc, &forme::RenderOptions::default()).expect("Should render");
     assert!(pdf.starts_with(b"%PDF"));
     assert_eq!(layout.pages.len(), 1);
     // The QR code element should be 50x50
@@ -4926,11 +7588,15 @@ fn test_qrcode_page_break() {
         fonts: vec![],
         tagged: false,
         pdfa: None,
+        font_synthesis: Default::default(),
+        footnote_numbering: Default::default(),
+        image_policy: Default::default(),
         default_style: None,
         embedded_data: None,
     };
 
-    let (pdf, layout) = forme::render_with_layout(&doc).expect("Should render");
+    let (pdf, layout, _) =
+        forme::render_with_layout(&doc, &forme::RenderOptions::default()).expect("Should render");
     assert!(pdf.starts_with(b"%PDF"));
     assert!(layout.pages.len() >= 2, "QR code should cause a page break");
 }
@@ -4980,6 +7646,9 @@ fn test_font_fallback_chain_in_document() {
         fonts: vec![],
         tagged: false,
         pdfa: None,
+        font_synthesis: Default::default(),
+        footnote_numbering: Default::default(),
+        image_policy: Default::default(),
         default_style: None,
         embedded_data: None,
     };
@@ -5051,15 +7720,20 @@ fn test_text_overflow_ellipsis_single_line() {
             },
             margin: Edges::uniform(10.0),
             wrap: true,
+            numbering: None,
         },
         fonts: vec![],
         tagged: false,
         pdfa: None,
+        font_synthesis: Default::default(),
+        footnote_numbering: Default::default(),
+        image_policy: Default::default(),
         default_style: None,
         embedded_data: None,
     };
 
-    let (_pdf, layout) = forme::render_with_layout(&doc).expect("Should render");
+    let (_pdf, layout, _) =
+        forme::render_with_layout(&doc, &forme::RenderOptions::default()).expect("Should render");
     // With ellipsis, there should be exactly 1 page with 1 text container
     assert_eq!(layout.pages.len(), 1);
     // The Text container should only have 1 TextLine child (single line)
@@ -5107,7 +7781,8 @@ fn test_flex_row_stretch_enables_justify_content() {
 
     let doc = default_doc(vec![row]);
 
-    let (_pdf, layout) = forme::render_with_layout(&doc).expect("Should render");
+    let (_pdf, layout, _) =
+        forme::render_with_layout(&doc, &forme::RenderOptions::default()).expect("Should render");
     assert_eq!(layout.pages.len(), 1);
 
     // page.elements[0] is the row View; its children are the two flex items
@@ -5175,7 +7850,8 @@ fn test_flex_row_stretch_enables_flex_grow() {
 
     let doc = default_doc(vec![row]);
 
-    let (_pdf, layout) = forme::render_with_layout(&doc).expect("Should render");
+    let (_pdf, layout, _) =
+        forme::render_with_layout(&doc, &forme::RenderOptions::default()).expect("Should render");
     assert_eq!(layout.pages.len(), 1);
 
     let page = &layout.pages[0];
@@ -5421,6 +8097,9 @@ fn test_document_default_style() {
         fonts: vec![],
         tagged: false,
         pdfa: None,
+        font_synthesis: Default::default(),
+        footnote_numbering: Default::default(),
+        image_policy: Default::default(),
         default_style: Some(Style {
             font_family: Some("Courier".to_string()),
             font_size: Some(16.0),
@@ -5452,6 +8131,9 @@ fn test_embedded_data_round_trip() {
         fonts: vec![],
         tagged: false,
         pdfa: None,
+        font_synthesis: Default::default(),
+        footnote_numbering: Default::default(),
+        image_policy: Default::default(),
         default_style: None,
         embedded_data: Some(data.to_string()),
     };
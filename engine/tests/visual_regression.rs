@@ -235,6 +235,9 @@ fn visual_invoice() {
         fonts: vec![],
         tagged: false,
         pdfa: None,
+        font_synthesis: Default::default(),
+        footnote_numbering: Default::default(),
+        image_policy: Default::default(),
         default_style: None,
         embedded_data: None,
     };
@@ -269,6 +272,9 @@ fn visual_multi_page_text() {
         fonts: vec![],
         tagged: false,
         pdfa: None,
+        font_synthesis: Default::default(),
+        footnote_numbering: Default::default(),
+        image_policy: Default::default(),
         default_style: None,
         embedded_data: None,
     };
@@ -284,7 +290,10 @@ fn visual_table_header_repetition() {
 
     // Header row
     rows.push(Node {
-        kind: NodeKind::TableRow { is_header: true },
+        kind: NodeKind::TableRow {
+            is_header: true,
+            footer: None,
+        },
         style: Style {
             background_color: Some(Color::hex("#e0e0e0")),
             ..Default::default()
@@ -327,7 +336,10 @@ fn visual_table_header_repetition() {
     // Body rows
     for i in 0..80 {
         rows.push(Node {
-            kind: NodeKind::TableRow { is_header: false },
+            kind: NodeKind::TableRow {
+                is_header: false,
+                footer: None,
+            },
             style: Style::default(),
             children: vec![
                 Node {
@@ -399,6 +411,9 @@ fn visual_table_header_repetition() {
         fonts: vec![],
         tagged: false,
         pdfa: None,
+        font_synthesis: Default::default(),
+        footnote_numbering: Default::default(),
+        image_policy: Default::default(),
         default_style: None,
         embedded_data: None,
     };
@@ -450,6 +465,9 @@ fn visual_flex_layout() {
         fonts: vec![],
         tagged: false,
         pdfa: None,
+        font_synthesis: Default::default(),
+        footnote_numbering: Default::default(),
+        image_policy: Default::default(),
         default_style: None,
         embedded_data: None,
     };
@@ -501,6 +519,9 @@ fn visual_justified_text() {
         fonts: vec![],
         tagged: false,
         pdfa: None,
+        font_synthesis: Default::default(),
+        footnote_numbering: Default::default(),
+        image_policy: Default::default(),
         default_style: None,
         embedded_data: None,
     };
@@ -576,6 +597,9 @@ fn visual_line_breaking_greedy_vs_optimal() {
         fonts: vec![],
         tagged: false,
         pdfa: None,
+        font_synthesis: Default::default(),
+        footnote_numbering: Default::default(),
+        image_policy: Default::default(),
         default_style: None,
         embedded_data: None,
     };
@@ -653,6 +677,9 @@ fn visual_text_alignment() {
         fonts: vec![],
         tagged: false,
         pdfa: None,
+        font_synthesis: Default::default(),
+        footnote_numbering: Default::default(),
+        image_policy: Default::default(),
         default_style: None,
         embedded_data: None,
     };
@@ -680,6 +707,9 @@ fn visual_tagged_no_visual_change() {
         fonts: vec![],
         tagged: false,
         pdfa: None,
+        font_synthesis: Default::default(),
+        footnote_numbering: Default::default(),
+        image_policy: Default::default(),
         default_style: None,
         embedded_data: None,
     };
@@ -695,6 +725,9 @@ fn visual_tagged_no_visual_change() {
         fonts: vec![],
         tagged: true,
         pdfa: None,
+        font_synthesis: Default::default(),
+        footnote_numbering: Default::default(),
+        image_policy: Default::default(),
         default_style: None,
         embedded_data: None,
     };
@@ -12,6 +12,8 @@ import type {
   QrCodeProps,
   CanvasProps,
   WatermarkProps,
+  TableOfContentsProps,
+  FootnoteProps,
 } from './types.js';
 
 /**
@@ -103,7 +105,8 @@ export function Text(_props: TextProps): null {
 }
 
 /**
- * An image element. Supports JPEG, PNG, and WebP via data URIs or file paths.
+ * An image element. Supports JPEG, PNG, WebP, GIF, BMP, and TIFF via data URIs
+ * or file paths.
  *
  * If only `width` or `height` is specified, the other dimension is calculated
  * from the image's aspect ratio.
@@ -300,6 +303,53 @@ export function Watermark(_props: WatermarkProps): null {
   return null;
 }
 
+/**
+ * A table of contents generated from the document's bookmarks.
+ *
+ * Lists every `bookmark` in the document with a dot leader and its page
+ * number, each entry linking to its page. The page numbers are filled in
+ * after layout.
+ *
+ * @param props.leader - Text repeated between each title and page number. Default: ".".
+ * @param props.style - Style for the entries (font, color, gap between rows)
+ * @param props.children - Content shown above the entries, such as a heading
+ *
+ * @example
+ * ```tsx
+ * <TableOfContents style={{ gap: 4 }}>
+ *   <Text style={{ fontSize: 18 }}>Contents</Text>
+ * </TableOfContents>
+ * <PageBreak />
+ * <View bookmark="Introduction">...</View>
+ * ```
+ */
+export function TableOfContents(_props: TableOfContentsProps): null {
+  return null;
+}
+
+/**
+ * A footnote, placed inside a `<Text>` where it's referenced.
+ *
+ * The reference is drawn as the footnote's number in superscript, and the
+ * children go at the bottom of the page the reference lands on. Footnotes
+ * are numbered through the document, or from 1 on every page with
+ * `<Document footnoteNumbering="page">`.
+ *
+ * @param props.style - Style for the reference mark
+ * @param props.children - The footnote's body: text, or any elements
+ *
+ * @example
+ * ```tsx
+ * <Text>
+ *   Revenue grew by a third in 2025.
+ *   <Footnote>Audited figures, fiscal year ending March.</Footnote>
+ * </Text>
+ * ```
+ */
+export function Footnote(_props: FootnoteProps): null {
+  return null;
+}
+
 /**
  * An explicit page break. Content after this element starts on a new page.
  *
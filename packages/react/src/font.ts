@@ -4,6 +4,8 @@ export interface FontRegistration {
   src: string | Uint8Array;
   fontWeight?: number | 'normal' | 'bold';
   fontStyle?: 'normal' | 'italic' | 'oblique';
+  /** Which face of a font collection (.ttc) to use. Defaults to 0. */
+  faceIndex?: number;
 }
 
 const globalFonts: FontRegistration[] = [];
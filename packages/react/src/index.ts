@@ -1,5 +1,5 @@
 // Components
-export { Document, Page, View, Text, Image, Table, Row, Cell, Fixed, Svg, QrCode, Canvas, Watermark, PageBreak } from './components.js';
+export { Document, Page, View, Text, Image, Table, Row, Cell, Fixed, Svg, QrCode, Canvas, Watermark, TableOfContents, Footnote, PageBreak } from './components.js';
 export { BarChart, LineChart, PieChart } from './charts.js';
 
 // Serialization
@@ -28,7 +28,10 @@ export type {
   Corners,
   EdgeColors,
   DocumentProps,
+  FontSynthesis,
+  ImagePolicy,
   PageProps,
+  PageNumberFormat,
   ViewProps,
   TextProps,
   ImageProps,
@@ -43,6 +46,8 @@ export type {
   CanvasContext,
   CanvasOp,
   WatermarkProps,
+  TableOfContentsProps,
+  FootnoteProps,
   ChartDataPoint,
   PieDataPoint,
   BarChartProps,
@@ -52,10 +57,12 @@ export type {
   // Forme JSON output
   FormeDocument,
   FormeFont,
+  FormeFontFeature,
   FormeNode,
   FormeNodeKind,
   FormeStyle,
   FormePageConfig,
+  FormePageNumbering,
   FormePageSize,
   FormeEdges,
   FormeMetadata,
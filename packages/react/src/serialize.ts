@@ -1,5 +1,5 @@
 import { type ReactElement, isValidElement, Children, Fragment } from 'react';
-import { Document, Page, View, Text, Image, Table, Row, Cell, Fixed, Svg, QrCode, Canvas, Watermark, PageBreak } from './components.js';
+import { Document, Page, View, Text, Image, Table, Row, Cell, Fixed, Svg, QrCode, Canvas, Watermark, TableOfContents, Footnote, PageBreak } from './components.js';
 import { Font, type FontRegistration } from './font.js';
 import {
   isRefMarker, getRefPath,
@@ -13,21 +13,28 @@ import type {
   Corners,
   EdgeColors,
   ColumnDef,
+  RowProps,
   TextRun,
   GridTrackSize,
   DocumentProps,
   FormeDocument,
   FormeFont,
+  FormeFontFeature,
   FormeNode,
   FormeNodeKind,
   FormeStyle,
   FormePageConfig,
+  FormePageNumbering,
+  PageNumberFormat,
   FormePageSize,
   FormeEdges,
   FormeColumnDef,
   FormeColumnWidth,
   FormeDimension,
   FormeColor,
+  FormeColorStop,
+  FormeBackgroundImage,
+  FormeBackgroundSize,
   FormeEdgeValues,
   FormeCornerValues,
   FormeGridTrackSize,
@@ -37,6 +44,7 @@ import type {
   CanvasOp,
   CanvasContext,
   WatermarkProps,
+  TableOfContentsProps,
 } from './types.js';
 
 // ─── Nesting validation ──────────────────────────────────────────────
@@ -133,6 +141,7 @@ export function serialize(element: ReactElement): FormeDocument {
   if (props.subject !== undefined) metadata.subject = props.subject;
   if (props.creator !== undefined) metadata.creator = props.creator;
   if (props.lang !== undefined) metadata.lang = props.lang;
+  if (props.outlineOpenDepth !== undefined) metadata.outlineOpenDepth = props.outlineOpenDepth;
 
   // Merge global + document fonts (document fonts override on conflict)
   const mergedFonts = mergeFonts(Font.getRegistered(), props.fonts);
@@ -150,6 +159,9 @@ export function serialize(element: ReactElement): FormeDocument {
   if (props.style) result.defaultStyle = mapStyle(props.style);
   if (props.tagged !== undefined) result.tagged = props.tagged;
   if (props.pdfa !== undefined) result.pdfa = props.pdfa;
+  if (props.fontSynthesis !== undefined) result.fontSynthesis = props.fontSynthesis;
+  if (props.footnoteNumbering !== undefined) result.footnoteNumbering = FOOTNOTE_NUMBERING_MAP[props.footnoteNumbering];
+  if (props.imagePolicy !== undefined) result.imagePolicy = props.imagePolicy;
 
   if (mergedFonts.length > 0) {
     result.fonts = mergedFonts;
@@ -158,10 +170,29 @@ export function serialize(element: ReactElement): FormeDocument {
   return result;
 }
 
+const FOOTNOTE_NUMBERING_MAP: Record<NonNullable<DocumentProps['footnoteNumbering']>, NonNullable<FormeDocument['footnoteNumbering']>> = {
+  'document': 'Document',
+  'page': 'Page',
+};
+
 // ─── Page serialization ──────────────────────────────────────────────
 
+const PAGE_NUMBER_FORMAT_MAP: Record<PageNumberFormat, FormePageNumbering['format']> = {
+  'decimal': 'Decimal',
+  'lower-roman': 'LowerRoman',
+  'upper-roman': 'UpperRoman',
+  'lower-alpha': 'LowerAlpha',
+  'upper-alpha': 'UpperAlpha',
+};
+
 function serializePage(element: ReactElement): FormeNode {
-  const props = element.props as { size?: string | { width: number; height: number }; margin?: number | string | number[] | Edges; children?: unknown };
+  const props = element.props as {
+    size?: string | { width: number; height: number };
+    margin?: number | string | number[] | Edges;
+    pageNumberStart?: number;
+    pageNumberFormat?: PageNumberFormat;
+    children?: unknown;
+  };
 
   let size: FormePageSize = 'A4';
   if (props.size !== undefined) {
@@ -178,6 +209,12 @@ function serializePage(element: ReactElement): FormeNode {
   }
 
   const config: FormePageConfig = { size, margin, wrap: true };
+  if (props.pageNumberStart !== undefined || props.pageNumberFormat !== undefined) {
+    config.numbering = {
+      start: props.pageNumberStart ?? 1,
+      format: PAGE_NUMBER_FORMAT_MAP[props.pageNumberFormat ?? 'decimal'],
+    };
+  }
   const childElements = flattenChildren(props.children);
   const children = serializeChildren(childElements, 'Page');
 
@@ -269,6 +306,9 @@ function serializeChild(child: unknown, parent: ParentContext = null): FormeNode
   if (element.type === Watermark) {
     return serializeWatermark(element);
   }
+  if (element.type === TableOfContents) {
+    return serializeTableOfContents(element);
+  }
   if (element.type === PageBreak) {
     return {
       kind: { type: 'PageBreak' },
@@ -306,7 +346,7 @@ function serializeChild(child: unknown, parent: ParentContext = null): FormeNode
 }
 
 function serializeView(element: ReactElement, _parent: ParentContext = null): FormeNode {
-  const props = element.props as { style?: Style; wrap?: boolean; bookmark?: string; href?: string; children?: unknown };
+  const props = element.props as { style?: Style; wrap?: boolean; bookmark?: string; href?: string; id?: string; children?: unknown };
   const style = mapStyle(props.style);
   if (props.wrap !== undefined) {
     style.wrap = props.wrap;
@@ -321,18 +361,19 @@ function serializeView(element: ReactElement, _parent: ParentContext = null): Fo
     sourceLocation: extractSourceLocation(element),
   };
   if (props.bookmark) node.bookmark = props.bookmark;
+  if (props.id) node.id = props.id;
   if (props.href) node.href = props.href;
 
   return node;
 }
 
 function serializeText(element: ReactElement): FormeNode {
-  const props = element.props as { style?: Style; href?: string; bookmark?: string; children?: unknown };
+  const props = element.props as { style?: Style; href?: string; bookmark?: string; id?: string; children?: unknown };
   const childElements = flattenChildren(props.children);
 
-  // Check if any child is a <Text> element (inline runs)
+  // Check if any child is a <Text> or <Footnote> element (inline runs)
   const hasTextChild = childElements.some(
-    c => isValidElement(c) && c.type === Text
+    c => isValidElement(c) && (c.type === Text || c.type === Footnote)
   );
 
   const kind: FormeNodeKind & { type: 'Text' } = { type: 'Text', content: '' };
@@ -351,6 +392,8 @@ function serializeText(element: ReactElement): FormeNode {
         if (childProps.style) run.style = mapStyle(childProps.style);
         if (childProps.href) run.href = childProps.href;
         runs.push(run);
+      } else if (isValidElement(child) && child.type === Footnote) {
+        runs.push(serializeFootnote(child));
       }
     }
     kind.runs = runs;
@@ -367,10 +410,25 @@ function serializeText(element: ReactElement): FormeNode {
     sourceLocation: extractSourceLocation(element),
   };
   if (props.bookmark) node.bookmark = props.bookmark;
+  if (props.id) node.id = props.id;
 
   return node;
 }
 
+/** A <Footnote> as a reference run, with its children as the footnote's body. */
+function serializeFootnote(element: ReactElement): TextRun {
+  const props = element.props as { style?: Style; children?: unknown };
+  const childElements = flattenChildren(props.children);
+  // Plain text makes one paragraph rather than one per string
+  const isPlainText = !childElements.some(c => isValidElement(c));
+  const footnote: FormeNode[] = isPlainText
+    ? [{ kind: { type: 'Text', content: flattenTextContent(props.children) }, style: {}, children: [] }]
+    : serializeChildren(childElements);
+  const run: TextRun = { content: '', footnote };
+  if (props.style) run.style = mapStyle(props.style);
+  return run;
+}
+
 function serializeImage(element: ReactElement): FormeNode {
   const props = element.props as { src: string; width?: number; height?: number; style?: Style; href?: string; alt?: string };
   const kind: FormeNodeKind = { type: 'Image', src: props.src };
@@ -405,14 +463,27 @@ function serializeTable(element: ReactElement, _parent: ParentContext = null): F
   };
 }
 
+function mapFooterMode(footer: RowProps['footer']): 'Repeat' | 'Continued' | 'Final' | undefined {
+  if (footer === true || footer === 'repeat') return 'Repeat';
+  if (footer === 'continued') return 'Continued';
+  if (footer === 'final') return 'Final';
+  return undefined;
+}
+
 function serializeRow(element: ReactElement): FormeNode {
-  const props = element.props as { header?: boolean; style?: Style; children?: unknown };
+  const props = element.props as { header?: boolean; footer?: RowProps['footer']; wrap?: boolean; style?: Style; children?: unknown };
+  const style = mapStyle(props.style);
+  if (props.wrap !== undefined) {
+    style.wrap = props.wrap;
+  }
   const childElements = flattenChildren(props.children);
   const children = serializeChildren(childElements, 'Row');
 
+  const footer = mapFooterMode(props.footer);
+
   return {
-    kind: { type: 'TableRow', is_header: props.header ?? false },
-    style: mapStyle(props.style),
+    kind: { type: 'TableRow', is_header: props.header ?? false, ...(footer ? { footer } : {}) },
+    style,
     children,
     sourceLocation: extractSourceLocation(element),
   };
@@ -556,6 +627,17 @@ function serializeWatermark(element: ReactElement): FormeNode {
   };
 }
 
+function serializeTableOfContents(element: ReactElement): FormeNode {
+  const props = element.props as TableOfContentsProps;
+  const childElements = flattenChildren(props.children);
+  return {
+    kind: { type: 'TableOfContents', leader: props.leader ?? '.' },
+    style: mapStyle(props.style),
+    children: serializeChildren(childElements, 'View'),
+    sourceLocation: extractSourceLocation(element),
+  };
+}
+
 // ─── Children helpers ────────────────────────────────────────────────
 
 function flattenChildren(children: unknown): unknown[] {
@@ -659,12 +741,61 @@ const ALIGN_CONTENT_MAP: Record<string, string> = {
   'stretch': 'Stretch',
 };
 
+const COLUMN_FILL_MAP: Record<string, string> = {
+  'auto': 'Auto',
+  'balance': 'Balance',
+};
+
+const COLUMN_SPAN_MAP: Record<string, string> = {
+  'none': 'None',
+  'all': 'All',
+};
+
+const FLOAT_MAP: Record<string, string> = {
+  'none': 'None',
+  'left': 'Left',
+  'right': 'Right',
+};
+
 const FONT_STYLE_MAP: Record<string, string> = {
   'normal': 'Normal',
   'italic': 'Italic',
   'oblique': 'Oblique',
 };
 
+const FONT_STRETCH_MAP: Record<string, number> = {
+  'ultra-condensed': 50,
+  'extra-condensed': 62,
+  'condensed': 75,
+  'semi-condensed': 87,
+  'normal': 100,
+  'semi-expanded': 112,
+  'expanded': 125,
+  'extra-expanded': 150,
+  'ultra-expanded': 200,
+};
+
+const FONT_VARIANT_NUMERIC_MAP: Record<string, string> = {
+  'lining-nums': 'LiningNums',
+  'oldstyle-nums': 'OldstyleNums',
+  'proportional-nums': 'ProportionalNums',
+  'tabular-nums': 'TabularNums',
+  'diagonal-fractions': 'DiagonalFractions',
+  'stacked-fractions': 'StackedFractions',
+  'ordinal': 'Ordinal',
+  'slashed-zero': 'SlashedZero',
+};
+
+const FONT_VARIANT_CAPS_MAP: Record<string, string> = {
+  'normal': 'Normal',
+  'small-caps': 'SmallCaps',
+  'all-small-caps': 'AllSmallCaps',
+  'petite-caps': 'PetiteCaps',
+  'all-petite-caps': 'AllPetiteCaps',
+  'unicase': 'Unicase',
+  'titling-caps': 'TitlingCaps',
+};
+
 const TEXT_ALIGN_MAP: Record<string, string> = {
   'left': 'Left',
   'right': 'Right',
@@ -678,6 +809,12 @@ const TEXT_DECORATION_MAP: Record<string, string> = {
   'line-through': 'LineThrough',
 };
 
+const VERTICAL_ALIGN_MAP: Record<string, string> = {
+  'baseline': 'Baseline',
+  'super': 'Super',
+  'sub': 'Sub',
+};
+
 const TEXT_TRANSFORM_MAP: Record<string, string> = {
   'none': 'None',
   'uppercase': 'Uppercase',
@@ -707,6 +844,21 @@ const OVERFLOW_MAP: Record<string, string> = {
   'hidden': 'Hidden',
 };
 
+const OBJECT_FIT_MAP: Record<string, string> = {
+  'fill': 'Fill',
+  'contain': 'Contain',
+  'cover': 'Cover',
+  'none': 'None',
+  'scale-down': 'ScaleDown',
+};
+
+const BACKGROUND_REPEAT_MAP: Record<string, string> = {
+  'repeat': 'Repeat',
+  'repeat-x': 'RepeatX',
+  'repeat-y': 'RepeatY',
+  'no-repeat': 'NoRepeat',
+};
+
 export function mapStyle(style?: Style): FormeStyle {
   if (!style) return {};
 
@@ -719,6 +871,10 @@ export function mapStyle(style?: Style): FormeStyle {
   if (style.minHeight !== undefined) result.minHeight = mapDimension(style.minHeight);
   if (style.maxWidth !== undefined) result.maxWidth = mapDimension(style.maxWidth);
   if (style.maxHeight !== undefined) result.maxHeight = mapDimension(style.maxHeight);
+  if (style.aspectRatio !== undefined) {
+    const ratio = parseAspectRatio(style.aspectRatio);
+    if (ratio !== undefined) result.aspectRatio = ratio;
+  }
 
   // Edges (individual > axis > base)
   if (style.padding !== undefined || style.paddingTop !== undefined || style.paddingRight !== undefined || style.paddingBottom !== undefined || style.paddingLeft !== undefined || style.paddingHorizontal !== undefined || style.paddingVertical !== undefined) {
@@ -801,21 +957,45 @@ export function mapStyle(style?: Style): FormeStyle {
     result.gridPlacement = placement;
   }
 
+  // Multi-column layout
+  if (style.columnCount !== undefined) result.columnCount = style.columnCount;
+  if (style.columnFill !== undefined) result.columnFill = COLUMN_FILL_MAP[style.columnFill];
+  if (style.columnRuleWidth !== undefined) result.columnRuleWidth = style.columnRuleWidth;
+  if (style.columnRuleColor !== undefined) result.columnRuleColor = parseColor(style.columnRuleColor);
+  if (style.columnSpan !== undefined) result.columnSpan = COLUMN_SPAN_MAP[style.columnSpan];
+
   // Typography
   if (style.fontFamily !== undefined) result.fontFamily = style.fontFamily;
   if (style.fontSize !== undefined) result.fontSize = style.fontSize;
   if (style.fontWeight !== undefined) {
     result.fontWeight = style.fontWeight === 'bold' ? 700 : style.fontWeight === 'normal' ? 400 : style.fontWeight;
   }
+  if (style.fontStretch !== undefined) {
+    result.fontStretch = typeof style.fontStretch === 'number'
+      ? style.fontStretch
+      : FONT_STRETCH_MAP[style.fontStretch] ?? Math.round(parseFloat(style.fontStretch));
+  }
   if (style.fontStyle !== undefined) result.fontStyle = FONT_STYLE_MAP[style.fontStyle];
+  if (style.fontFeatureSettings !== undefined) {
+    result.fontFeatureSettings = parseFontFeatureSettings(style.fontFeatureSettings);
+  }
+  if (style.fontVariantNumeric !== undefined) {
+    result.fontVariantNumeric = style.fontVariantNumeric
+      .split(/\s+/)
+      .map((v) => FONT_VARIANT_NUMERIC_MAP[v])
+      .filter((v): v is string => v !== undefined);
+  }
+  if (style.fontVariantCaps !== undefined) result.fontVariantCaps = FONT_VARIANT_CAPS_MAP[style.fontVariantCaps];
   if (style.lineHeight !== undefined) result.lineHeight = style.lineHeight;
   if (style.textAlign !== undefined) result.textAlign = TEXT_ALIGN_MAP[style.textAlign];
   if (style.letterSpacing !== undefined) result.letterSpacing = style.letterSpacing;
   if (style.textDecoration !== undefined) result.textDecoration = TEXT_DECORATION_MAP[style.textDecoration];
   if (style.textTransform !== undefined) result.textTransform = TEXT_TRANSFORM_MAP[style.textTransform];
+  if (style.verticalAlign !== undefined) result.verticalAlign = VERTICAL_ALIGN_MAP[style.verticalAlign];
   if (style.hyphens !== undefined) result.hyphens = HYPHENS_MAP[style.hyphens];
   if (style.lang !== undefined) result.lang = style.lang;
   if (style.direction !== undefined) result.direction = style.direction;
+  if (style.writingMode !== undefined) result.writingMode = style.writingMode;
   if (style.textOverflow !== undefined) result.textOverflow = TEXT_OVERFLOW_MAP[style.textOverflow];
   if (style.lineBreaking !== undefined) result.lineBreaking = LINE_BREAKING_MAP[style.lineBreaking];
   if (style.overflow !== undefined) result.overflow = OVERFLOW_MAP[style.overflow];
@@ -823,6 +1003,10 @@ export function mapStyle(style?: Style): FormeStyle {
   // Color
   if (style.color !== undefined) result.color = parseColor(style.color);
   if (style.backgroundColor !== undefined) result.backgroundColor = parseColor(style.backgroundColor);
+  if (style.backgroundImage !== undefined) result.backgroundImage = parseBackgroundImage(style.backgroundImage);
+  if (style.backgroundSize !== undefined) result.backgroundSize = parseBackgroundSize(style.backgroundSize);
+  if (style.backgroundRepeat !== undefined) result.backgroundRepeat = BACKGROUND_REPEAT_MAP[style.backgroundRepeat];
+  if (style.backgroundPosition !== undefined) result.backgroundPosition = parseObjectPosition(style.backgroundPosition);
   if (style.opacity !== undefined) result.opacity = style.opacity;
 
   // Border — cascade: border < borderTop/Right/Bottom/Left < borderWidth/borderColor < borderTopWidth/borderTopColor
@@ -911,6 +1095,11 @@ export function mapStyle(style?: Style): FormeStyle {
   if (style.right !== undefined) result.right = style.right;
   if (style.bottom !== undefined) result.bottom = style.bottom;
   if (style.left !== undefined) result.left = style.left;
+  if (style.float !== undefined) result.float = FLOAT_MAP[style.float];
+
+  // Images
+  if (style.objectFit !== undefined) result.objectFit = OBJECT_FIT_MAP[style.objectFit];
+  if (style.objectPosition !== undefined) result.objectPosition = parseObjectPosition(style.objectPosition);
 
   // Page behavior
   if (style.wrap !== undefined) result.wrap = style.wrap;
@@ -921,6 +1110,27 @@ export function mapStyle(style?: Style): FormeStyle {
   return result;
 }
 
+// ─── Font feature helpers ───────────────────────────────────────────
+
+/**
+ * Parse CSS `font-feature-settings` into tag/value pairs.
+ * E.g. `'"tnum", "ss01" on, "salt" 2'` → `[{tnum,1}, {ss01,1}, {salt,2}]`
+ * Settings that aren't a quoted four-letter tag are skipped.
+ */
+function parseFontFeatureSettings(value: string | Record<string, number | boolean>): FormeFontFeature[] {
+  if (typeof value !== 'string') {
+    return Object.entries(value).map(([tag, v]) => ({ tag, value: typeof v === 'boolean' ? Number(v) : v }));
+  }
+  const features: FormeFontFeature[] = [];
+  for (const setting of value.split(',')) {
+    const match = setting.trim().match(/^["']([\x20-\x7e]{4})["'](?:\s+(on|off|\d+))?$/);
+    if (!match) continue;
+    const v = match[2] === undefined || match[2] === 'on' ? 1 : match[2] === 'off' ? 0 : parseInt(match[2], 10);
+    features.push({ tag: match[1], value: v });
+  }
+  return features;
+}
+
 // ─── Grid helpers ───────────────────────────────────────────────────
 
 /** Convert a single GridTrackSize to the Forme JSON format. */
@@ -989,6 +1199,133 @@ export function mapDimension(val: number | string): FormeDimension {
   return 'Auto';
 }
 
+/** Parse an aspect ratio: a number, `'1.5'`, or `'16 / 9'`. */
+function parseAspectRatio(val: number | string): number | undefined {
+  if (typeof val === 'number') return val > 0 ? val : undefined;
+  const [w, h] = val.split('/').map(s => parseFloat(s.trim()));
+  const ratio = h === undefined ? w : w / h;
+  return isFinite(ratio) && ratio > 0 ? ratio : undefined;
+}
+
+const OBJECT_POSITION_KEYWORDS: Record<string, { axis?: 'x' | 'y'; percent: number }> = {
+  'left': { axis: 'x', percent: 0 },
+  'right': { axis: 'x', percent: 100 },
+  'top': { axis: 'y', percent: 0 },
+  'bottom': { axis: 'y', percent: 100 },
+  'center': { percent: 50 },
+};
+
+/**
+ * Parse an `object-position` value: one or two keywords (`left`, `center`,
+ * `right`, `top`, `bottom`, in either order), percentages, or offsets in
+ * points. A single value applies to its axis and centers the other.
+ */
+function parseObjectPosition(val: string): { x: FormeDimension; y: FormeDimension } {
+  const center: FormeDimension = { Percent: 50 };
+  const result = { x: center, y: center };
+  const tokens = val.trim().toLowerCase().split(/\s+/).slice(0, 2);
+  // Keywords name their own axis, so `top left` means the same as `left top`
+  if (tokens.length === 2 && (OBJECT_POSITION_KEYWORDS[tokens[0]]?.axis === 'y' || OBJECT_POSITION_KEYWORDS[tokens[1]]?.axis === 'x')) {
+    tokens.reverse();
+  }
+  tokens.forEach((token, i) => {
+    const keyword = OBJECT_POSITION_KEYWORDS[token];
+    const axis = keyword?.axis ?? (i === 0 ? 'x' : 'y');
+    result[axis] = keyword ? { Percent: keyword.percent } : mapDimension(token.replace(/px$/, ''));
+  });
+  return result;
+}
+
+/** Split CSS function arguments on the commas outside parentheses. */
+function splitArgs(val: string): string[] {
+  const args: string[] = [];
+  let depth = 0;
+  let start = 0;
+  for (let i = 0; i < val.length; i++) {
+    if (val[i] === '(') depth++;
+    else if (val[i] === ')') depth--;
+    else if (val[i] === ',' && depth === 0) {
+      args.push(val.slice(start, i).trim());
+      start = i + 1;
+    }
+  }
+  args.push(val.slice(start).trim());
+  return args.filter(Boolean);
+}
+
+const GRADIENT_SIDES: Record<string, number> = { top: 0, right: 90, bottom: 180, left: 270 };
+
+/**
+ * Parse a `linear-gradient` direction: an angle (`deg`, `turn`, `rad`,
+ * `grad`) or `to` a side or corner. Corners point diagonally, as in a
+ * square box.
+ */
+function parseGradientAngle(val: string): number | undefined {
+  const angle = val.match(/^(-?[0-9.]+)(deg|turn|rad|grad)?$/);
+  if (angle) {
+    const n = parseFloat(angle[1]);
+    switch (angle[2]) {
+      case 'turn': return n * 360;
+      case 'rad': return n * 180 / Math.PI;
+      case 'grad': return n * 0.9;
+      default: return n;
+    }
+  }
+  const sides = val.match(/^to\s+(\w+)(?:\s+(\w+))?$/);
+  if (!sides) return undefined;
+  let a = GRADIENT_SIDES[sides[1]];
+  let b = sides[2] === undefined ? a : GRADIENT_SIDES[sides[2]];
+  if (a === undefined || b === undefined) return undefined;
+  // `to top left` is halfway between 270 and 360, not 0 and 270
+  if (Math.abs(a - b) > 180) {
+    if (a < b) a += 360;
+    else b += 360;
+  }
+  return ((a + b) / 2) % 360;
+}
+
+/** Parse a gradient color stop: a color, optionally followed by a percentage. */
+function parseColorStop(val: string): FormeColorStop {
+  const match = val.match(/^(.*?)\s+(-?[0-9.]+)%$/);
+  if (!match) return { color: parseColor(val) };
+  return { color: parseColor(match[1]), offset: parseFloat(match[2]) / 100 };
+}
+
+/**
+ * Parse a `background-image` value: `linear-gradient(...)`,
+ * `radial-gradient(...)`, `url(...)`, or a bare image src. Radial gradients
+ * are always circles from the center to the farthest corner, so a shape or
+ * position given first is skipped.
+ */
+function parseBackgroundImage(val: string): FormeBackgroundImage {
+  const s = val.trim();
+  const gradient = s.match(/^(linear|radial)-gradient\(([\s\S]*)\)$/);
+  if (gradient) {
+    const args = splitArgs(gradient[2]);
+    if (gradient[1] === 'linear') {
+      const angle = args.length > 0 ? parseGradientAngle(args[0]) : undefined;
+      const stops = (angle === undefined ? args : args.slice(1)).map(parseColorStop);
+      return { LinearGradient: { angle: angle ?? 180, stops } };
+    }
+    const stops = args
+      .filter((arg, i) => i > 0 || !/^(circle|ellipse|closest|farthest|at)\b/.test(arg))
+      .map(parseColorStop);
+    return { RadialGradient: { stops } };
+  }
+  const url = s.match(/^url\(\s*(['"]?)([\s\S]*)\1\s*\)$/);
+  return { Url: url ? url[2] : s };
+}
+
+/** Parse a `background-size` value: `cover`, `contain`, or a width and optional height. */
+function parseBackgroundSize(val: string): FormeBackgroundSize {
+  const s = val.trim().toLowerCase();
+  if (s === 'cover') return 'Cover';
+  if (s === 'contain') return 'Contain';
+  const [width, height = 'auto'] = s.split(/\s+/).map(token => token.replace(/px$/, ''));
+  if (width === 'auto' && height === 'auto') return 'Auto';
+  return { Size: { width: mapDimension(width), height: mapDimension(height) } };
+}
+
 export function parseColor(hex: string): FormeColor {
   const s = hex.trim();
 
@@ -1146,7 +1483,13 @@ function mergeFonts(
     const weight = normalizeFontWeight(f.fontWeight);
     const italic = f.fontStyle === 'italic' || f.fontStyle === 'oblique';
     const key = fontKey(f.family, weight, italic);
-    map.set(key, { family: f.family, src: f.src, weight, italic });
+    map.set(key, {
+      family: f.family,
+      src: f.src,
+      weight,
+      italic,
+      ...(f.faceIndex !== undefined && { faceIndex: f.faceIndex }),
+    });
   }
 
   if (docFonts) {
@@ -1154,7 +1497,13 @@ function mergeFonts(
       const weight = normalizeFontWeight(f.fontWeight);
       const italic = f.fontStyle === 'italic' || f.fontStyle === 'oblique';
       const key = fontKey(f.family, weight, italic);
-      map.set(key, { family: f.family, src: f.src, weight, italic });
+      map.set(key, {
+      family: f.family,
+      src: f.src,
+      weight,
+      italic,
+      ...(f.faceIndex !== undefined && { faceIndex: f.faceIndex }),
+    });
     }
   }
 
@@ -1209,6 +1558,7 @@ export function serializeTemplate(element: ReactElement): Record<string, unknown
   if (props.subject !== undefined) metadata.subject = processTemplateValue(props.subject);
   if (props.creator !== undefined) metadata.creator = processTemplateValue(props.creator);
   if (props.lang !== undefined) metadata.lang = processTemplateValue(props.lang);
+  if (props.outlineOpenDepth !== undefined) metadata.outlineOpenDepth = props.outlineOpenDepth;
 
   const mergedFonts = mergeFonts(Font.getRegistered(), props.fonts);
 
@@ -1225,6 +1575,9 @@ export function serializeTemplate(element: ReactElement): Record<string, unknown
   if (props.style) result.defaultStyle = mapStyle(props.style);
   if (props.tagged !== undefined) result.tagged = props.tagged;
   if (props.pdfa !== undefined) result.pdfa = props.pdfa;
+  if (props.fontSynthesis !== undefined) result.fontSynthesis = props.fontSynthesis;
+  if (props.footnoteNumbering !== undefined) result.footnoteNumbering = FOOTNOTE_NUMBERING_MAP[props.footnoteNumbering];
+  if (props.imagePolicy !== undefined) result.imagePolicy = props.imagePolicy;
 
   if (mergedFonts.length > 0) {
     result.fonts = mergedFonts;
@@ -1234,7 +1587,13 @@ export function serializeTemplate(element: ReactElement): Record<string, unknown
 }
 
 function serializeTemplatePage(element: ReactElement): Record<string, unknown> {
-  const props = element.props as { size?: string | { width: number; height: number }; margin?: number | string | number[] | Edges; children?: unknown };
+  const props = element.props as {
+    size?: string | { width: number; height: number };
+    margin?: number | string | number[] | Edges;
+    pageNumberStart?: number;
+    pageNumberFormat?: PageNumberFormat;
+    children?: unknown;
+  };
 
   let size: FormePageSize = 'A4';
   if (props.size !== undefined) {
@@ -1251,6 +1610,12 @@ function serializeTemplatePage(element: ReactElement): Record<string, unknown> {
   }
 
   const config: FormePageConfig = { size, margin, wrap: true };
+  if (props.pageNumberStart !== undefined || props.pageNumberFormat !== undefined) {
+    config.numbering = {
+      start: props.pageNumberStart ?? 1,
+      format: PAGE_NUMBER_FORMAT_MAP[props.pageNumberFormat ?? 'decimal'],
+    };
+  }
   const childElements = flattenTemplateChildren(props.children);
   const children = serializeTemplateChildren(childElements, 'Page');
 
@@ -1326,6 +1691,15 @@ function serializeTemplateChild(child: unknown, parent: ParentContext = null): u
   if (element.type === QrCode) return serializeQrCode(element);
   if (element.type === Canvas) return serializeCanvas(element);
   if (element.type === Watermark) return serializeWatermark(element);
+  if (element.type === TableOfContents) {
+    const props = element.props as TableOfContentsProps;
+    const childElements = flattenTemplateChildren(props.children);
+    return {
+      kind: { type: 'TableOfContents', leader: props.leader ?? '.' },
+      style: mapTemplateStyle(props.style),
+      children: serializeTemplateChildren(childElements, 'View'),
+    };
+  }
   if (element.type === PageBreak) {
     return { kind: { type: 'PageBreak' }, style: {}, children: [] };
   }
@@ -1347,7 +1721,7 @@ function serializeTemplateChild(child: unknown, parent: ParentContext = null): u
 }
 
 function serializeTemplateView(element: ReactElement, _parent: ParentContext = null): Record<string, unknown> {
-  const props = element.props as { style?: Style; wrap?: boolean; bookmark?: string; href?: string; children?: unknown };
+  const props = element.props as { style?: Style; wrap?: boolean; bookmark?: string; href?: string; id?: string; children?: unknown };
   const style = mapTemplateStyle(props.style);
   if (props.wrap !== undefined) style.wrap = props.wrap;
   const childElements = flattenTemplateChildren(props.children);
@@ -1355,16 +1729,17 @@ function serializeTemplateView(element: ReactElement, _parent: ParentContext = n
 
   const node: Record<string, unknown> = { kind: { type: 'View' }, style, children };
   if (props.bookmark) node.bookmark = props.bookmark;
+  if (props.id) node.id = props.id;
   if (props.href) node.href = props.href;
   return node;
 }
 
 function serializeTemplateText(element: ReactElement): Record<string, unknown> {
-  const props = element.props as { style?: Style; href?: string; bookmark?: string; children?: unknown };
+  const props = element.props as { style?: Style; href?: string; bookmark?: string; id?: string; children?: unknown };
   const childElements = flattenTemplateChildren(props.children);
 
   const hasTextChild = childElements.some(
-    c => isValidElement(c) && c.type === Text
+    c => isValidElement(c) && (c.type === Text || c.type === Footnote)
   );
 
   const kind: Record<string, unknown> = { type: 'Text', content: '' };
@@ -1387,6 +1762,8 @@ function serializeTemplateText(element: ReactElement): Record<string, unknown> {
         if (childProps.style) run.style = mapTemplateStyle(childProps.style);
         if (childProps.href) run.href = childProps.href;
         runs.push(run);
+      } else if (isValidElement(child) && child.type === Footnote) {
+        runs.push(serializeTemplateFootnote(child));
       }
     }
     kind.runs = runs;
@@ -1402,9 +1779,22 @@ function serializeTemplateText(element: ReactElement): Record<string, unknown> {
     children: [],
   };
   if (props.bookmark) node.bookmark = props.bookmark;
+  if (props.id) node.id = props.id;
   return node;
 }
 
+function serializeTemplateFootnote(element: ReactElement): Record<string, unknown> {
+  const props = element.props as { style?: Style; children?: unknown };
+  const childElements = flattenTemplateChildren(props.children);
+  const isPlainText = !childElements.some(c => isValidElement(c));
+  const footnote = isPlainText
+    ? [{ kind: { type: 'Text', content: flattenTemplateTextContent(props.children) }, style: {}, children: [] }]
+    : serializeTemplateChildren(childElements);
+  const run: Record<string, unknown> = { content: '', footnote };
+  if (props.style) run.style = mapTemplateStyle(props.style);
+  return run;
+}
+
 function serializeTemplateImage(element: ReactElement): Record<string, unknown> {
   const props = element.props as { src: string | unknown; width?: number; height?: number; style?: Style };
   const kind: Record<string, unknown> = { type: 'Image', src: processTemplateValue(props.src) };
@@ -1424,10 +1814,15 @@ function serializeTemplateTable(element: ReactElement, _parent: ParentContext =
 }
 
 function serializeTemplateRow(element: ReactElement): Record<string, unknown> {
-  const props = element.props as { header?: boolean; style?: Style; children?: unknown };
+  const props = element.props as { header?: boolean; footer?: RowProps['footer']; wrap?: boolean; style?: Style; children?: unknown };
+  const style = mapTemplateStyle(props.style);
+  if (props.wrap !== undefined) {
+    style.wrap = props.wrap;
+  }
+  const footer = mapFooterMode(props.footer);
   const childElements = flattenTemplateChildren(props.children);
   const children = serializeTemplateChildren(childElements, 'Row');
-  return { kind: { type: 'TableRow', is_header: props.header ?? false }, style: mapTemplateStyle(props.style), children };
+  return { kind: { type: 'TableRow', is_header: props.header ?? false, ...(footer ? { footer } : {}) }, style, children };
 }
 
 function serializeTemplateCell(element: ReactElement): Record<string, unknown> {
@@ -44,6 +44,8 @@ export interface Style {
   minHeight?: number | string;
   maxWidth?: number | string;
   maxHeight?: number | string;
+  /** Width-to-height ratio, as a number or `'16 / 9'` */
+  aspectRatio?: number | string;
   flexDirection?: 'row' | 'column' | 'row-reverse' | 'column-reverse';
   flex?: number;
   flexGrow?: number;
@@ -80,6 +82,18 @@ export interface Style {
   /** Number of rows to span. */
   gridRowSpan?: number;
 
+  // Multi-column layout
+  /** Flow children into this many columns, continuing across pages. */
+  columnCount?: number;
+  /** `'balance'` evens out the columns on the last page. Default: `'auto'`. */
+  columnFill?: 'auto' | 'balance';
+  /** Width of the rule drawn between columns. */
+  columnRuleWidth?: number;
+  /** Color of the rule between columns. Defaults to the text color. */
+  columnRuleColor?: string;
+  /** `'all'` lets a child of a multi-column View span every column. */
+  columnSpan?: 'none' | 'all';
+
   // Box model
   padding?: number | string | number[] | Edges;
   paddingTop?: number;
@@ -100,17 +114,28 @@ export interface Style {
   fontSize?: number;
   fontFamily?: string;
   fontWeight?: number | 'normal' | 'bold';
+  /** Width as a keyword or a percentage of normal (`'75%'` or `75`). */
+  fontStretch?: number | `${number}%` | 'ultra-condensed' | 'extra-condensed' | 'condensed' | 'semi-condensed' | 'normal' | 'semi-expanded' | 'expanded' | 'extra-expanded' | 'ultra-expanded';
   fontStyle?: 'normal' | 'italic' | 'oblique';
+  /** OpenType features, as in CSS (`'"tnum", "ss01" on, "salt" 2'`) or as an object (`{ tnum: true, salt: 2 }`). */
+  fontFeatureSettings?: string | Record<string, number | boolean>;
+  /** Space-separated numeric variants, e.g. `'tabular-nums slashed-zero'`. */
+  fontVariantNumeric?: string;
+  fontVariantCaps?: 'normal' | 'small-caps' | 'all-small-caps' | 'petite-caps' | 'all-petite-caps' | 'unicase' | 'titling-caps';
   lineHeight?: number;
   textAlign?: 'left' | 'center' | 'right' | 'justify';
   letterSpacing?: number;
   textDecoration?: 'none' | 'underline' | 'line-through';
   textTransform?: 'none' | 'uppercase' | 'lowercase' | 'capitalize';
+  /** Raise or lower a run of text from the baseline, for superscripts and subscripts. */
+  verticalAlign?: 'baseline' | 'super' | 'sub';
   hyphens?: 'none' | 'manual' | 'auto';
   /** Language tag (BCP 47, e.g. "en-US", "de"). Controls hyphenation dictionary. */
   lang?: string;
   /** Text direction for BiDi support (Arabic, Hebrew). */
   direction?: 'ltr' | 'rtl' | 'auto';
+  /** Writing mode: horizontal lines, or vertical columns read right to left (Japanese, Chinese). */
+  writingMode?: 'horizontal-tb' | 'vertical-rl';
   /** Text overflow behavior: 'wrap' (default), 'ellipsis' (truncate with ...), 'clip' (truncate). */
   textOverflow?: 'wrap' | 'ellipsis' | 'clip';
   /** Line breaking algorithm: 'optimal' (Knuth-Plass, default) or 'greedy'. */
@@ -121,6 +146,17 @@ export interface Style {
   // Visual
   color?: string;
   backgroundColor?: string;
+  /**
+   * Drawn over the background color: an image src or `url(...)`, or a
+   * `linear-gradient(...)` or `radial-gradient(...)`, e.g.
+   * `'linear-gradient(90deg, #1e3a8a, #60a5fa 40%)'`
+   */
+  backgroundImage?: string;
+  /** `'auto'` (default), `'cover'`, `'contain'`, or a width and height, e.g. `'50% auto'` */
+  backgroundSize?: string;
+  backgroundRepeat?: 'repeat' | 'repeat-x' | 'repeat-y' | 'no-repeat';
+  /** Where the background image sits in the box, like `objectPosition`. Default: `'left top'` */
+  backgroundPosition?: string;
   opacity?: number;
   borderWidth?: number | Edges;
   borderTopWidth?: number;
@@ -156,6 +192,13 @@ export interface Style {
   right?: number;
   bottom?: number;
   left?: number;
+  /** Take the element out of flow against one side; text wraps around it */
+  float?: 'none' | 'left' | 'right';
+
+  // Images
+  objectFit?: 'fill' | 'contain' | 'cover' | 'none' | 'scale-down';
+  /** Where the image sits in its box, e.g. `'left top'`, `'25% 75%'`, `'10 20'` */
+  objectPosition?: string;
 
   // Page behavior
   wrap?: boolean;
@@ -173,19 +216,54 @@ export interface DocumentProps {
   creator?: string;
   /** Document language (BCP 47 tag, e.g. "en-US"). Emitted as /Lang in the PDF Catalog. */
   lang?: string;
+  /** Bookmark levels shown expanded when the PDF opens (0 = top level only). Default: all. */
+  outlineOpenDepth?: number;
   /** Default style applied to the entire document. Sets global fontFamily, fontSize, color, etc. */
   style?: Style;
   /** Whether to produce a tagged (accessible) PDF with structure tree. */
   tagged?: boolean;
   /** PDF/A conformance level. "2a" requires tagging, "2b" is visual-only compliance. */
   pdfa?: '2a' | '2b';
+  /**
+   * Whether bold and italic text may be faked when a font family has no face for them:
+   * bold by stroking the glyphs, italic by slanting them. Both default to true.
+   */
+  fontSynthesis?: FontSynthesis;
+  /** Number footnotes through the whole document (default) or from 1 on every page. */
+  footnoteNumbering?: 'document' | 'page';
+  /** How images are resampled and compressed. By default they're embedded at full resolution. */
+  imagePolicy?: ImagePolicy;
   fonts?: FontRegistration[];
   children?: ReactNode;
 }
 
+/** How images are resampled and compressed when they're embedded. */
+export interface ImagePolicy {
+  /** Highest resolution (pixels per inch at the size drawn) to embed an image at. */
+  maxDpi?: number;
+  /** JPEG quality (1-100) to recompress opaque photographs with. Images with transparency and flat graphics stay lossless. */
+  jpegQuality?: number;
+}
+
+/** Which styles may be faked for a font family without a face for them. */
+export interface FontSynthesis {
+  weight?: boolean;
+  style?: boolean;
+}
+
+export type PageNumberFormat = 'decimal' | 'lower-roman' | 'upper-roman' | 'lower-alpha' | 'upper-alpha';
+
 export interface PageProps {
   size?: 'A4' | 'A3' | 'A5' | 'Letter' | 'Legal' | 'Tabloid' | { width: number; height: number };
   margin?: number | string | number[] | Edges;
+  /**
+   * Restart page numbering on this page at the given number (defaults to 1
+   * when only `pageNumberFormat` is set). Later pages without it continue
+   * counting from here.
+   */
+  pageNumberStart?: number;
+  /** How `{{pageNumber}}` is printed from this page on. */
+  pageNumberFormat?: PageNumberFormat;
   children?: ReactNode;
 }
 
@@ -194,6 +272,8 @@ export interface ViewProps {
   wrap?: boolean;
   bookmark?: string;
   href?: string;
+  /** Target id for `{{pageRef:id}}` cross-references. */
+  id?: string;
   children?: ReactNode;
 }
 
@@ -201,6 +281,8 @@ export interface TextProps {
   style?: Style;
   href?: string;
   bookmark?: string;
+  /** Target id for `{{pageRef:id}}` cross-references. */
+  id?: string;
   children?: ReactNode;
 }
 
@@ -227,6 +309,14 @@ export interface TableProps {
 
 export interface RowProps {
   header?: boolean;
+  /**
+   * Marks the row as a table footer. `true` or `'repeat'` draws it below the
+   * rows of every page the table spans, `'continued'` on every page but the
+   * last, and `'final'` only once at the end.
+   */
+  footer?: boolean | 'repeat' | 'continued' | 'final';
+  /** Set to false to keep the row on one page instead of splitting it. */
+  wrap?: boolean;
   style?: Style;
   children?: ReactNode;
 }
@@ -337,6 +427,21 @@ export interface WatermarkProps {
   style?: Style;
 }
 
+export interface TableOfContentsProps {
+  /** Text repeated between each entry's title and page number. Default: ".". */
+  leader?: string;
+  style?: Style;
+  /** Content shown above the entries, such as a heading. */
+  children?: ReactNode;
+}
+
+export interface FootnoteProps {
+  /** Style for the reference mark. Defaults to a smaller superscript. */
+  style?: Style;
+  /** The footnote's body: text, or any elements. */
+  children?: ReactNode;
+}
+
 /** Canvas drawing context for the draw callback. */
 export interface CanvasContext {
   moveTo(x: number, y: number): void;
@@ -396,6 +501,8 @@ export interface TextRun {
   content: string;
   style?: FormeStyle;
   href?: string;
+  /** Makes the run a footnote reference, with these nodes as the footnote's body. */
+  footnote?: FormeNode[];
 }
 
 // ─── Forme JSON output types (match Rust serde format) ───────────────
@@ -405,6 +512,7 @@ export interface FormeFont {
   src: string | Uint8Array;
   weight: number;
   italic: boolean;
+  faceIndex?: number;
 }
 
 export interface FormeDocument {
@@ -415,6 +523,14 @@ export interface FormeDocument {
   fonts?: FormeFont[];
   tagged?: boolean;
   pdfa?: '2a' | '2b';
+  fontSynthesis?: FontSynthesis;
+  footnoteNumbering?: 'Document' | 'Page';
+  imagePolicy?: ImagePolicy;
+}
+
+export interface FormeFontFeature {
+  tag: string;
+  value: number;
 }
 
 export interface FormeMetadata {
@@ -423,12 +539,19 @@ export interface FormeMetadata {
   subject?: string;
   creator?: string;
   lang?: string;
+  outlineOpenDepth?: number;
 }
 
 export interface FormePageConfig {
   size: FormePageSize;
   margin: FormeEdges;
   wrap: boolean;
+  numbering?: FormePageNumbering;
+}
+
+export interface FormePageNumbering {
+  start: number;
+  format: 'Decimal' | 'LowerRoman' | 'UpperRoman' | 'LowerAlpha' | 'UpperAlpha';
 }
 
 export type FormePageSize =
@@ -446,6 +569,7 @@ export interface FormeNode {
   kind: FormeNodeKind;
   style: FormeStyle;
   children: FormeNode[];
+  id?: string;
   bookmark?: string;
   href?: string;
   alt?: string;
@@ -458,13 +582,14 @@ export type FormeNodeKind =
   | { type: 'Text'; content: string; href?: string; runs?: TextRun[] }
   | { type: 'Image'; src: string; width?: number; height?: number }
   | { type: 'Table'; columns: FormeColumnDef[] }
-  | { type: 'TableRow'; is_header: boolean }
+  | { type: 'TableRow'; is_header: boolean; footer?: 'Repeat' | 'Continued' | 'Final' }
   | { type: 'TableCell'; col_span: number; row_span: number }
   | { type: 'Fixed'; position: 'Header' | 'Footer' }
   | { type: 'Svg'; width: number; height: number; view_box?: string; content: string }
   | { type: 'QrCode'; data: string; size?: number }
   | { type: 'Canvas'; width: number; height: number; operations: CanvasOp[] }
   | { type: 'Watermark'; text: string; font_size: number; angle: number }
+  | { type: 'TableOfContents'; leader: string }
   | { type: 'PageBreak' };
 
 export interface FormeColumnDef {
@@ -503,6 +628,23 @@ export interface FormeCornerValues {
 }
 
 /** Grid track size in Forme JSON format (matches Rust GridTrackSize enum) */
+export interface FormeColorStop {
+  color: FormeColor;
+  /** From 0 to 1 along the gradient */
+  offset?: number;
+}
+
+export type FormeBackgroundImage =
+  | { Url: string }
+  | { LinearGradient: { angle: number; stops: FormeColorStop[] } }
+  | { RadialGradient: { stops: FormeColorStop[] } };
+
+export type FormeBackgroundSize =
+  | 'Auto'
+  | 'Cover'
+  | 'Contain'
+  | { Size: { width: FormeDimension; height: FormeDimension } };
+
 export type FormeGridTrackSize =
   | { Pt: number }
   | { Fr: number }
@@ -528,6 +670,7 @@ export interface FormeStyle {
   minHeight?: FormeDimension;
   maxWidth?: FormeDimension;
   maxHeight?: FormeDimension;
+  aspectRatio?: number;
   padding?: FormeEdges;
   margin?: FormeEdges;
   flexDirection?: string;
@@ -547,23 +690,38 @@ export interface FormeStyle {
   gridAutoRows?: FormeGridTrackSize;
   gridAutoColumns?: FormeGridTrackSize;
   gridPlacement?: FormeGridPlacement;
+  columnCount?: number;
+  columnFill?: string;
+  columnRuleWidth?: number;
+  columnRuleColor?: FormeColor;
+  columnSpan?: string;
   fontFamily?: string;
   fontSize?: number;
   fontWeight?: number;
+  fontStretch?: number;
   fontStyle?: string;
+  fontFeatureSettings?: FormeFontFeature[];
+  fontVariantNumeric?: string[];
+  fontVariantCaps?: string;
   lineHeight?: number;
   textAlign?: string;
   letterSpacing?: number;
   textDecoration?: string;
   textTransform?: string;
+  verticalAlign?: string;
   hyphens?: string;
   lang?: string;
   direction?: string;
+  writingMode?: string;
   textOverflow?: string;
   lineBreaking?: string;
   overflow?: string;
   color?: FormeColor;
   backgroundColor?: FormeColor;
+  backgroundImage?: FormeBackgroundImage;
+  backgroundSize?: FormeBackgroundSize;
+  backgroundRepeat?: string;
+  backgroundPosition?: { x: FormeDimension; y: FormeDimension };
   opacity?: number;
   borderWidth?: FormeEdgeValues<number>;
   borderColor?: FormeEdgeValues<FormeColor>;
@@ -573,6 +731,9 @@ export interface FormeStyle {
   right?: number;
   bottom?: number;
   left?: number;
+  float?: string;
+  objectFit?: string;
+  objectPosition?: { x: FormeDimension; y: FormeDimension };
   wrap?: boolean;
   breakBefore?: boolean;
   minWidowLines?: number;
@@ -13,6 +13,7 @@ import {
   PageBreak,
   Svg,
   QrCode,
+  Footnote,
   serialize,
   render,
   mapStyle,
@@ -155,6 +156,36 @@ describe('Style mapping', () => {
     expect(mapStyle({ fontWeight: 600 }).fontWeight).toBe(600);
   });
 
+  it('fontStretch mapping', () => {
+    expect(mapStyle({ fontStretch: 'condensed' }).fontStretch).toBe(75);
+    expect(mapStyle({ fontStretch: 'ultra-expanded' }).fontStretch).toBe(200);
+    expect(mapStyle({ fontStretch: '87.5%' }).fontStretch).toBe(88);
+    expect(mapStyle({ fontStretch: 110 }).fontStretch).toBe(110);
+  });
+
+  it('fontFeatureSettings mapping', () => {
+    expect(mapStyle({ fontFeatureSettings: '"tnum", "ss01" on, "liga" off, "salt" 2' }).fontFeatureSettings).toEqual([
+      { tag: 'tnum', value: 1 },
+      { tag: 'ss01', value: 1 },
+      { tag: 'liga', value: 0 },
+      { tag: 'salt', value: 2 },
+    ]);
+    expect(mapStyle({ fontFeatureSettings: { smcp: true, kern: false } }).fontFeatureSettings).toEqual([
+      { tag: 'smcp', value: 1 },
+      { tag: 'kern', value: 0 },
+    ]);
+    expect(mapStyle({ fontFeatureSettings: 'normal' }).fontFeatureSettings).toEqual([]);
+  });
+
+  it('fontVariant mapping', () => {
+    expect(mapStyle({ fontVariantNumeric: 'tabular-nums slashed-zero' }).fontVariantNumeric).toEqual([
+      'TabularNums',
+      'SlashedZero',
+    ]);
+    expect(mapStyle({ fontVariantNumeric: 'normal' }).fontVariantNumeric).toEqual([]);
+    expect(mapStyle({ fontVariantCaps: 'all-small-caps' }).fontVariantCaps).toBe('AllSmallCaps');
+  });
+
   it('fontStyle mapping', () => {
     expect(mapStyle({ fontStyle: 'italic' }).fontStyle).toBe('Italic');
     expect(mapStyle({ fontStyle: 'oblique' }).fontStyle).toBe('Oblique');
@@ -168,6 +199,72 @@ describe('Style mapping', () => {
     expect(mapStyle({ textAlign: 'justify' }).textAlign).toBe('Justify');
   });
 
+  it('writingMode passes through', () => {
+    expect(mapStyle({ writingMode: 'vertical-rl' }).writingMode).toBe('vertical-rl');
+  });
+
+  it('multi-column mapping', () => {
+    const style = mapStyle({
+      columnCount: 2,
+      columnFill: 'balance',
+      columnRuleWidth: 0.5,
+      columnRuleColor: '#ff0000',
+    });
+    expect(style.columnCount).toBe(2);
+    expect(style.columnFill).toBe('Balance');
+    expect(style.columnRuleWidth).toBe(0.5);
+    expect(style.columnRuleColor).toEqual({ r: 1, g: 0, b: 0, a: 1 });
+    expect(mapStyle({ columnSpan: 'all' }).columnSpan).toBe('All');
+  });
+
+  it('float mapping', () => {
+    expect(mapStyle({ float: 'left' }).float).toBe('Left');
+    expect(mapStyle({ float: 'right' }).float).toBe('Right');
+    expect(mapStyle({ float: 'none' }).float).toBe('None');
+  });
+
+  it('objectFit, objectPosition and aspectRatio mapping', () => {
+    expect(mapStyle({ objectFit: 'cover' }).objectFit).toBe('Cover');
+    expect(mapStyle({ objectFit: 'scale-down' }).objectFit).toBe('ScaleDown');
+    expect(mapStyle({ objectPosition: 'left top' }).objectPosition).toEqual({ x: { Percent: 0 }, y: { Percent: 0 } });
+    expect(mapStyle({ objectPosition: 'bottom right' }).objectPosition).toEqual({ x: { Percent: 100 }, y: { Percent: 100 } });
+    expect(mapStyle({ objectPosition: 'top' }).objectPosition).toEqual({ x: { Percent: 50 }, y: { Percent: 0 } });
+    expect(mapStyle({ objectPosition: '25% 10' }).objectPosition).toEqual({ x: { Percent: 25 }, y: { Pt: 10 } });
+    expect(mapStyle({ aspectRatio: 1.5 }).aspectRatio).toBe(1.5);
+    expect(mapStyle({ aspectRatio: '16 / 9' }).aspectRatio).toBeCloseTo(16 / 9);
+    expect(mapStyle({ aspectRatio: 0 }).aspectRatio).toBeUndefined();
+  });
+
+  it('backgroundImage, backgroundSize, backgroundRepeat and backgroundPosition mapping', () => {
+    expect(mapStyle({ backgroundImage: 'linear-gradient(90deg, #ff0000, rgb(0, 0, 255) 40%)' }).backgroundImage).toEqual({
+      LinearGradient: {
+        angle: 90,
+        stops: [
+          { color: { r: 1, g: 0, b: 0, a: 1 } },
+          { color: { r: 0, g: 0, b: 1, a: 1 }, offset: 0.4 },
+        ],
+      },
+    });
+    const toTopLeft = mapStyle({ backgroundImage: 'linear-gradient(to top left, #000, #fff)' }).backgroundImage;
+    expect(toTopLeft).toMatchObject({ LinearGradient: { angle: 315 } });
+    expect(mapStyle({ backgroundImage: 'linear-gradient(#000, #fff)' }).backgroundImage).toMatchObject({ LinearGradient: { angle: 180 } });
+    expect(mapStyle({ backgroundImage: 'radial-gradient(circle, #fff, #000 80%)' }).backgroundImage).toEqual({
+      RadialGradient: {
+        stops: [
+          { color: { r: 1, g: 1, b: 1, a: 1 } },
+          { color: { r: 0, g: 0, b: 0, a: 1 }, offset: 0.8 },
+        ],
+      },
+    });
+    expect(mapStyle({ backgroundImage: "url('logo.png')" }).backgroundImage).toEqual({ Url: 'logo.png' });
+    expect(mapStyle({ backgroundImage: 'logo.png' }).backgroundImage).toEqual({ Url: 'logo.png' });
+    expect(mapStyle({ backgroundSize: 'cover' }).backgroundSize).toBe('Cover');
+    expect(mapStyle({ backgroundSize: 'auto' }).backgroundSize).toBe('Auto');
+    expect(mapStyle({ backgroundSize: '50% auto' }).backgroundSize).toEqual({ Size: { width: { Percent: 50 }, height: 'Auto' } });
+    expect(mapStyle({ backgroundRepeat: 'no-repeat' }).backgroundRepeat).toBe('NoRepeat');
+    expect(mapStyle({ backgroundPosition: 'right bottom' }).backgroundPosition).toEqual({ x: { Percent: 100 }, y: { Percent: 100 } });
+  });
+
   it('textDecoration mapping', () => {
     expect(mapStyle({ textDecoration: 'underline' }).textDecoration).toBe('Underline');
     expect(mapStyle({ textDecoration: 'line-through' }).textDecoration).toBe('LineThrough');
@@ -181,6 +278,12 @@ describe('Style mapping', () => {
     expect(mapStyle({ textTransform: 'none' }).textTransform).toBe('None');
   });
 
+  it('verticalAlign mapping', () => {
+    expect(mapStyle({ verticalAlign: 'super' }).verticalAlign).toBe('Super');
+    expect(mapStyle({ verticalAlign: 'sub' }).verticalAlign).toBe('Sub');
+    expect(mapStyle({ verticalAlign: 'baseline' }).verticalAlign).toBe('Baseline');
+  });
+
   it('color hex parsing', () => {
     expect(mapStyle({ color: '#ff0000' }).color).toEqual({ r: 1, g: 0, b: 0, a: 1 });
     expect(mapStyle({ color: '#00ff00' }).color).toEqual({ r: 0, g: 1, b: 0, a: 1 });
@@ -550,6 +653,37 @@ describe('Edge cases', () => {
     });
   });
 
+  it('Footnote produces a reference run with its body', () => {
+    const doc = serialize(
+      <Document>
+        <Text>Claim<Footnote>Source, page {12}.</Footnote></Text>
+      </Document>
+    );
+    expect(doc.children[0].kind).toEqual({
+      type: 'Text',
+      content: '',
+      runs: [
+        { content: 'Claim' },
+        {
+          content: '',
+          footnote: [{ kind: { type: 'Text', content: 'Source, page 12.' }, style: {}, children: [] }],
+        },
+      ],
+    });
+  });
+
+  it('Footnote with elements serializes them as its body', () => {
+    const doc = serialize(
+      <Document>
+        <Text>Claim<Footnote><Text style={{ fontSize: 8 }}>Source.</Text></Footnote></Text>
+      </Document>
+    );
+    const kind = doc.children[0].kind as { runs: { footnote?: { kind: unknown; style: unknown }[] }[] };
+    expect(kind.runs[1].footnote).toHaveLength(1);
+    expect(kind.runs[1].footnote![0].kind).toEqual({ type: 'Text', content: 'Source.' });
+    expect(kind.runs[1].footnote![0].style).toEqual({ fontSize: 8 });
+  });
+
   it('Text without nested Text still flattens to content', () => {
     const doc = serialize(
       <Document>
@@ -1124,6 +1258,44 @@ describe('Document lang', () => {
   });
 });
 
+// ─── Document fontSynthesis ─────────────────────────────────────────
+
+describe('Document fontSynthesis', () => {
+  it('fontSynthesis is passed through', () => {
+    const doc = serialize(<Document fontSynthesis={{ style: false }}><Text>Hello</Text></Document>);
+    expect(doc.fontSynthesis).toEqual({ style: false });
+  });
+
+  it('fontSynthesis is omitted when not set', () => {
+    const doc = serialize(<Document><Text>Hello</Text></Document>);
+    expect(doc.fontSynthesis).toBeUndefined();
+  });
+});
+
+describe('Document imagePolicy', () => {
+  it('imagePolicy is passed through', () => {
+    const doc = serialize(<Document imagePolicy={{ maxDpi: 150, jpegQuality: 80 }}><Text>Hello</Text></Document>);
+    expect(doc.imagePolicy).toEqual({ maxDpi: 150, jpegQuality: 80 });
+  });
+
+  it('imagePolicy is omitted when not set', () => {
+    const doc = serialize(<Document><Text>Hello</Text></Document>);
+    expect(doc.imagePolicy).toBeUndefined();
+  });
+});
+
+describe('Document footnoteNumbering', () => {
+  it('footnoteNumbering mapping', () => {
+    const doc = serialize(<Document footnoteNumbering="page"><Text>Hello</Text></Document>);
+    expect(doc.footnoteNumbering).toBe('Page');
+  });
+
+  it('footnoteNumbering is omitted when not set', () => {
+    const doc = serialize(<Document><Text>Hello</Text></Document>);
+    expect(doc.footnoteNumbering).toBeUndefined();
+  });
+});
+
 describe('CSS Grid serialization', () => {
   it('maps display: grid', () => {
     const doc = serialize(
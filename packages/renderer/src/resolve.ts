@@ -35,6 +35,8 @@ const MIME_BY_EXT: Record<string, string> = {
   '.webp': 'image/webp',
   '.svg': 'image/svg+xml',
   '.bmp': 'image/bmp',
+  '.tif': 'image/tiff',
+  '.tiff': 'image/tiff',
   '.ico': 'image/x-icon',
   '.avif': 'image/avif',
 };